@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use rook_aoc_2023::problems::problem19::{Part, Workflow};
+
+// Workflows and parts share the input, so every line goes through both.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        for line in text.lines() {
+            let _ = Workflow::parse(line);
+            let _ = Part::parse(line);
+        }
+    }
+});
@@ -0,0 +1,15 @@
+#!/bin/sh
+# Seeds each fuzz target's corpus with the example input for its problem.
+# Run from the fuzz directory. The fuzzer adds what it finds next to these.
+
+set -e
+
+seed() {
+    mkdir -p "corpus/$1"
+    cp "../input/$2" "corpus/$1/example"
+}
+
+seed problem19_workflows input_19_test.txt
+seed problem20_modules input_20_test.txt
+seed problem22_pieces input_22_test.txt
+seed problem24_hail_balls input_24_test.txt
@@ -0,0 +1,399 @@
+// Shortest paths over any kind of state, so searches don't each need their
+// own priority queue loop. A state is anything hashable, successors gives the
+// states one step away from a state along with what each step costs, and
+// costs can't be negative.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::ops::Add;
+
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+// Every state seen is hashed, and puzzle states don't need the default
+// hasher's protection from keys picked to collide. This is the multiply and
+// rotate hash rustc uses, which is a lot quicker on small keys.
+#[derive(Default)]
+struct StateHasher {
+    hash: u64,
+}
+
+impl StateHasher {
+
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(0x517cc1b727220a95);
+    }
+}
+
+impl Hasher for StateHasher {
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[.. chunk.len()].copy_from_slice(chunk);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.add(i as u32 as u64);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.add(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.add(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+type StateMap<S> = HashMap<S, usize, BuildHasherDefault<StateHasher>>;
+
+#[derive(Debug, Clone)]
+pub struct ShortestPath<S, C> {
+    pub cost: C,
+
+    // From the start to the goal, both included.
+    pub path: Vec<S>,
+}
+
+impl<S, C> ShortestPath<S, C> {
+
+    pub fn goal(&self) -> &S {
+        self.path.last().unwrap()
+    }
+}
+
+pub fn dijkstra<S, C, FS, I, FG>(start: S, successors: FS, is_goal: FG) -> Option<ShortestPath<S, C>>
+    where S: Clone + Eq + Hash,
+          C: Cost,
+          FS: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>,
+          FG: FnMut(&S) -> bool
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+// Like dijkstra, with heuristic guessing the cost left from a state to the
+// nearest goal so the search heads that way first. The guess must never be
+// more than the real cost or a longer path can come back.
+pub fn astar<S, C, FS, I, FH, FG>(start: S, successors: FS, heuristic: FH, is_goal: FG) -> Option<ShortestPath<S, C>>
+    where S: Clone + Eq + Hash,
+          C: Cost,
+          FS: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>,
+          FH: FnMut(&S) -> C,
+          FG: FnMut(&S) -> bool
+{
+    astar_pruned(start, successors, heuristic, |_, _| false, is_goal)
+}
+
+// Like astar, but is_dominated is asked about each state and the cost to it
+// before it's expanded, so it can skip states that one it has already let
+// through reaches everything from for no more. States don't come out in cost
+// order, so it has to compare costs as well as keep track of those itself.
+pub fn astar_pruned<S, C, FS, I, FH, FD, FG>(start: S, mut successors: FS, mut heuristic: FH, mut is_dominated: FD, mut is_goal: FG) -> Option<ShortestPath<S, C>>
+    where S: Clone + Eq + Hash,
+          C: Cost,
+          FS: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>,
+          FH: FnMut(&S) -> C,
+          FD: FnMut(&S, C) -> bool,
+          FG: FnMut(&S) -> bool
+{
+    // States are numbered as they're found so the heap and the links back
+    // along the best paths only hold numbers.
+    let mut states: Vec<S> = vec![start.clone()];
+    let mut numbers: StateMap<S> = StateMap::default();
+    numbers.insert(start.clone(), 0);
+    let mut best_costs: Vec<C> = vec![C::default()];
+    let mut previous: Vec<Option<usize>> = vec![None];
+
+    let mut queue: BinaryHeap<Reverse<(C, C, usize)>> = BinaryHeap::new();
+    queue.push(Reverse((heuristic(&start), C::default(), 0)));
+
+    while let Some(Reverse((_, cost, number))) = queue.pop() {
+        // A better way here was found after this was queued.
+        if cost > best_costs[number] {
+            continue;
+        }
+
+        let state = states[number].clone();
+        if is_goal(&state) {
+            return Some(ShortestPath { cost, path: path_to(&states, &previous, number) });
+        }
+        if is_dominated(&state, cost) {
+            continue;
+        }
+
+        for (next, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+
+            let next_number = match numbers.entry(next) {
+                Entry::Occupied(entry) => {
+                    let next_number = *entry.get();
+                    if next_cost >= best_costs[next_number] {
+                        continue;
+                    }
+                    best_costs[next_number] = next_cost;
+                    previous[next_number] = Some(number);
+                    next_number
+                },
+                Entry::Vacant(entry) => {
+                    let next_number = states.len();
+                    states.push(entry.key().clone());
+                    entry.insert(next_number);
+                    best_costs.push(next_cost);
+                    previous.push(Some(number));
+                    next_number
+                },
+            };
+
+            queue.push(Reverse((next_cost + heuristic(&states[next_number]), next_cost, next_number)));
+        }
+    }
+
+    None
+}
+
+// Past this many buckets a heap is quicker than going through the buckets
+// one cost at a time, and the buckets would take a lot of memory.
+const MAX_DIAL_BUCKETS: usize = 1 << 16;
+
+// Like dijkstra, but with a bucket for each cost rather than a heap (Dial's
+// algorithm), for steps that cost small whole numbers. No step can cost more
+// than max_step_cost, so the buckets are a ring of max_step_cost + 1 that's
+// gone round as the cost goes up. When steps can cost more than that's
+// worth, it's the heap search after all. States are expanded cheapest first
+// either way, and is_dominated is asked about each one before it's expanded,
+// so it can skip states no better than one it has already let through. It
+// has to keep track of those itself.
+pub fn dial<S, C, FS, I, FD, FG>(start: S, max_step_cost: C, mut successors: FS, mut is_dominated: FD, mut is_goal: FG) -> Option<ShortestPath<S, C>>
+    where S: Clone + Eq + Hash,
+          C: Cost + TryInto<usize>,
+          FS: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>,
+          FD: FnMut(&S) -> bool,
+          FG: FnMut(&S) -> bool
+{
+    let to_usize = |cost: C| -> usize {
+        let Ok(cost) = cost.try_into() else { panic!("Dial's algorithm needs costs that fit in a usize.") };
+        cost
+    };
+
+    let ring_size = to_usize(max_step_cost).saturating_add(1);
+    if ring_size > MAX_DIAL_BUCKETS {
+        return astar_pruned(start, successors, |_| C::default(), |state, _| is_dominated(state), is_goal);
+    }
+
+    let mut states: Vec<S> = vec![start.clone()];
+    let mut numbers: StateMap<S> = StateMap::default();
+    numbers.insert(start, 0);
+    let mut best_costs: Vec<C> = vec![C::default()];
+    let mut previous: Vec<Option<usize>> = vec![None];
+
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); ring_size];
+    buckets[0].push(0);
+    let mut queued = 1;
+    let mut at = 0;
+
+    while queued > 0 {
+        let Some(number) = buckets[at % ring_size].pop() else {
+            at += 1;
+            continue;
+        };
+        queued -= 1;
+
+        // A better way here was found after this was queued.
+        let cost = best_costs[number];
+        if to_usize(cost) < at {
+            continue;
+        }
+
+        let state = states[number].clone();
+        if is_goal(&state) {
+            return Some(ShortestPath { cost, path: path_to(&states, &previous, number) });
+        }
+        if is_dominated(&state) {
+            continue;
+        }
+
+        for (next, step_cost) in successors(&state) {
+            assert!(step_cost <= max_step_cost, "A step cost more than the most Dial's algorithm was told about.");
+            let next_cost = cost + step_cost;
+
+            let next_number = match numbers.entry(next) {
+                Entry::Occupied(entry) => {
+                    let next_number = *entry.get();
+                    if next_cost >= best_costs[next_number] {
+                        continue;
+                    }
+                    best_costs[next_number] = next_cost;
+                    previous[next_number] = Some(number);
+                    next_number
+                },
+                Entry::Vacant(entry) => {
+                    let next_number = states.len();
+                    states.push(entry.key().clone());
+                    entry.insert(next_number);
+                    best_costs.push(next_cost);
+                    previous.push(Some(number));
+                    next_number
+                },
+            };
+
+            buckets[to_usize(next_cost) % ring_size].push(next_number);
+            queued += 1;
+        }
+    }
+
+    None
+}
+
+fn path_to<S: Clone>(states: &[S], previous: &[Option<usize>], number: usize) -> Vec<S> {
+    let mut path: Vec<S> = Vec::new();
+    let mut at = Some(number);
+    while let Some(n) = at {
+        path.push(states[n].clone());
+        at = previous[n];
+    }
+    path.reverse();
+    path
+}
+
+// A dijkstra search that can be asked for one goal after another without
+// starting over. Everything explored looking for one goal is kept, so a goal
+// that was already passed is answered straight away and the rest carry on
+// from where the search stopped.
+pub struct Search<S, C, FS> {
+    successors: FS,
+    states: Vec<S>,
+    numbers: StateMap<S>,
+    best_costs: Vec<C>,
+    previous: Vec<Option<usize>>,
+    queue: BinaryHeap<Reverse<(C, usize)>>,
+
+    // States whose best cost is known, cheapest first.
+    settled: Vec<usize>,
+}
+
+impl<S, C, FS, I> Search<S, C, FS>
+    where S: Clone + Eq + Hash,
+          C: Cost,
+          FS: FnMut(&S) -> I,
+          I: IntoIterator<Item = (S, C)>
+{
+
+    pub fn new(start: S, successors: FS) -> Self {
+        let mut numbers: StateMap<S> = StateMap::default();
+        numbers.insert(start.clone(), 0);
+
+        let mut queue: BinaryHeap<Reverse<(C, usize)>> = BinaryHeap::new();
+        queue.push(Reverse((C::default(), 0)));
+
+        Self {
+            successors,
+            states: vec![start],
+            numbers,
+            best_costs: vec![C::default()],
+            previous: vec![None],
+            queue,
+            settled: Vec::new(),
+        }
+    }
+
+    // The cheapest way to a state that's a goal, or None if there's no way
+    // to one.
+    pub fn find<FG>(&mut self, mut is_goal: FG) -> Option<ShortestPath<S, C>>
+        where FG: FnMut(&S) -> bool
+    {
+        if let Some(number) = self.settled.iter().copied().find(|n| is_goal(&self.states[*n])) {
+            return Some(self.shortest_path(number));
+        }
+
+        while let Some(number) = self.settle_next() {
+            if is_goal(&self.states[number]) {
+                return Some(self.shortest_path(number));
+            }
+        }
+
+        None
+    }
+
+    // The answer for each goal, in the same order, from the one search.
+    pub fn find_each<FG>(&mut self, goals: &mut [FG]) -> Vec<Option<ShortestPath<S, C>>>
+        where FG: FnMut(&S) -> bool
+    {
+        goals.iter_mut().map(|is_goal| self.find(is_goal)).collect()
+    }
+
+    fn shortest_path(&self, number: usize) -> ShortestPath<S, C> {
+        ShortestPath { cost: self.best_costs[number], path: path_to(&self.states, &self.previous, number) }
+    }
+
+    // Takes the cheapest state not yet settled off the queue and queues up
+    // where it leads.
+    fn settle_next(&mut self) -> Option<usize> {
+        while let Some(Reverse((cost, number))) = self.queue.pop() {
+            if cost > self.best_costs[number] {
+                continue;
+            }
+            self.settled.push(number);
+
+            let state = self.states[number].clone();
+            for (next, step_cost) in (self.successors)(&state) {
+                let next_cost = cost + step_cost;
+
+                let next_number = match self.numbers.entry(next) {
+                    Entry::Occupied(entry) => {
+                        let next_number = *entry.get();
+                        if next_cost >= self.best_costs[next_number] {
+                            continue;
+                        }
+                        self.best_costs[next_number] = next_cost;
+                        self.previous[next_number] = Some(number);
+                        next_number
+                    },
+                    Entry::Vacant(entry) => {
+                        let next_number = self.states.len();
+                        self.states.push(entry.key().clone());
+                        entry.insert(next_number);
+                        self.best_costs.push(next_cost);
+                        self.previous.push(Some(number));
+                        next_number
+                    },
+                };
+
+                self.queue.push(Reverse((next_cost, next_number)));
+            }
+
+            return Some(number);
+        }
+
+        None
+    }
+}
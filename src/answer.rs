@@ -0,0 +1,59 @@
+
+// Turning computed values into answer text. Answers are compared as strings
+// against earlier runs and expected answers, so the same value has to come
+// out the same way every time.
+
+use bigdecimal::num_bigint::BigInt;
+
+use crate::aocbase::{AOCError, AOCResult};
+
+// How float answers are written. One that is within epsilon of a whole
+// number is written as that integer, anything else as fixed point. Epsilon
+// grows with the size of the value since big floats can't land exactly on
+// a whole number anyway.
+#[derive(Debug, Clone, Copy)]
+pub struct FloatAnswerPolicy {
+    pub epsilon: f64,
+    pub decimals: usize,
+}
+
+impl Default for FloatAnswerPolicy {
+    fn default() -> Self {
+        Self { epsilon: 1e-9, decimals: 6 }
+    }
+}
+
+impl FloatAnswerPolicy {
+
+    pub fn format(&self, value: f64) -> AOCResult<String> {
+        if !value.is_finite() {
+            return Err(AOCError::ProcessingError(format!("Answer isn't a finite number: {}", value)));
+        }
+
+        let rounded = value.round();
+        if (value - rounded).abs() <= self.epsilon * value.abs().max(1.0) {
+            // {:.0} never switches to scientific notation and "-0" is no good as an answer.
+            Ok(format!("{:.0}", rounded + 0.0))
+        }
+        else {
+            Ok(format!("{:.*}", self.decimals, value))
+        }
+    }
+}
+
+pub trait Answer {
+    fn to_answer(&self) -> AOCResult<String>;
+}
+
+impl Answer for f64 {
+    fn to_answer(&self) -> AOCResult<String> {
+        FloatAnswerPolicy::default().format(*self)
+    }
+}
+
+// Exact answers are already whole, so there's nothing to round.
+impl Answer for BigInt {
+    fn to_answer(&self) -> AOCResult<String> {
+        Ok(self.to_string())
+    }
+}
@@ -19,7 +19,19 @@ pub enum AOCError {
     InvalidRegexOperation(String),
 
     #[error("Problem processing error: {0}")]
-    ProcessingError(String)
+    ProcessingError(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Unsupported input: {0}")]
+    Unsupported(String),
+
+    #[error("Not run: {0}")]
+    NotRun(String),
+
+    #[error("HTTP error: {0}")]
+    HttpError(String),
 }
 
 pub type AOCResult<T> = Result<T, AOCError>;
@@ -0,0 +1,191 @@
+// Downloads puzzle inputs from adventofcode.com when they aren't on disk yet.
+// Inputs are different for everyone, so it needs the session cookie of a
+// logged in browser, from --session or AOC_SESSION.
+//
+// The downloaded file is the cache, once it's there it's never fetched again.
+// Requests go through curl so there's no HTTP client to build, and are spaced
+// out so a run over every day doesn't hammer the site.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::settings;
+
+pub const SESSION_ENV_VAR: &str = "AOC_SESSION";
+
+// The site asks that automated requests say where they come from.
+const USER_AGENT: &str = "github.com/castle8080/rook_aoc_2023";
+
+// Time between the start of one request and the next.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(3);
+
+lazy_static! {
+    static ref LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+
+    // Days that already failed this run, so both parts don't ask.
+    static ref FAILED: Mutex<HashMap<i32, String>> = Mutex::new(HashMap::new());
+
+    // Held by whoever is getting a day's input, so parts run at once don't
+    // both download into the same file.
+    static ref DOWNLOADING: Mutex<HashMap<i32, Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+// --session wins over the environment.
+pub fn session_token() -> Option<String> {
+    settings::session()
+        .or_else(|| env::var(SESSION_ENV_VAR).ok())
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+// Session cookies are hex, anything else isn't sent, as it goes into a curl
+// config line where quotes and line breaks would change what curl does.
+pub fn is_hex_token(token: &str) -> bool {
+    !token.is_empty() && token.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// Makes sure the input for a day is at path, downloading it if there's a
+// session token. Without one nothing happens and opening the file fails
+// like it always did.
+pub fn ensure_input(day: i32, path: impl AsRef<Path>) -> AOCResult<()> {
+    let path = path.as_ref();
+    if path.is_file() {
+        return Ok(());
+    }
+
+    let token = match session_token() {
+        None => return Ok(()),
+        Some(token) => token,
+    };
+
+    let day_lock = DOWNLOADING.lock().unwrap().entry(day).or_default().clone();
+    let _downloading = day_lock.lock().unwrap();
+
+    // Another part may have got it while this one waited.
+    if path.is_file() {
+        return Ok(());
+    }
+
+    if let Some(reason) = FAILED.lock().unwrap().get(&day) {
+        return Err(AOCError::HttpError(reason.clone()));
+    }
+
+    let result = download_input(day, &token, path);
+    if let Err(AOCError::HttpError(reason)) = &result {
+        FAILED.lock().unwrap().insert(day, reason.clone());
+    }
+    result
+}
+
+fn wait_for_turn() {
+    let mut last_request = LAST_REQUEST.lock().unwrap();
+    if let Some(last) = *last_request {
+        let elapsed = last.elapsed();
+        if elapsed < MIN_REQUEST_INTERVAL {
+            thread::sleep(MIN_REQUEST_INTERVAL - elapsed);
+        }
+    }
+    *last_request = Some(Instant::now());
+}
+
+fn input_url(day: i32) -> String {
+    format!("https://adventofcode.com/2023/day/{}/input", day)
+}
+
+// Runs curl for url with the session cookie, giving back the HTTP status and
+// anything curl complained about. The cookie goes in on stdin so the token
+// doesn't show up in the process list.
+fn curl(token: &str, url: &str, args: &[&str], output: &Path, max_time: Duration) -> AOCResult<(String, String)> {
+    if !is_hex_token(token) {
+        return Err(AOCError::HttpError("The session token isn't all hex digits, so it wasn't sent.".to_string()));
+    }
+
+    let mut curl = Command::new("curl")
+        .args(["--silent", "--show-error", "--location", "--config", "-"])
+        .args(["--user-agent", USER_AGENT])
+        .arg("--max-time").arg(max_time.as_secs().to_string())
+        .args(["--write-out", "%{http_code}"])
+        .args(args)
+        .arg("--output").arg(output)
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AOCError::HttpError(format!("Couldn't run curl to get {}: {}", url, e)))?;
+
+    if let Some(mut stdin) = curl.stdin.take() {
+        writeln!(stdin, "cookie = \"session={}\"", token)?;
+    }
+    let output = curl.wait_with_output()?;
+
+    let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let errors = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !output.status.success() && errors.is_empty() {
+        return Ok((status, format!("curl exited with {}", output.status)));
+    }
+    Ok((status, errors))
+}
+
+// Written to a temporary file first so a failed download never leaves
+// something behind that looks like an input.
+fn download_input(day: i32, token: &str, path: &Path) -> AOCResult<()> {
+    let url = input_url(day);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = path.with_extension("download");
+
+    wait_for_turn();
+    tracing::debug!("Downloading: {} -> {}", url, path.display());
+
+    let (status, errors) = curl(token, &url, &[], &partial, Duration::from_secs(60))?;
+
+    if status != "200" {
+        let _ = fs::remove_file(&partial);
+        let reason = match status.as_str() {
+            "400" | "500" => format!("{} was refused, the session token is probably wrong or expired.", url),
+            "404" => format!("{} isn't available yet.", url),
+            _ => format!("Getting {} failed with status {}: {}", url, status, errors),
+        };
+        return Err(AOCError::HttpError(reason));
+    }
+
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionCheck {
+    Accepted,
+    // The site answers 400 or 500 to a session it doesn't know.
+    Refused(String),
+    // Nothing came back that says either way, like with no network.
+    Unknown(String),
+}
+
+// Asks for the headers of day 1's input, which only a logged in session gets.
+pub fn check_session(token: &str, max_time: Duration) -> SessionCheck {
+    let url = input_url(1);
+    let null_device = Path::new(if cfg!(windows) { "NUL" } else { "/dev/null" });
+
+    wait_for_turn();
+    match curl(token, &url, &["--head"], null_device, max_time) {
+        Err(AOCError::HttpError(reason)) => SessionCheck::Unknown(reason),
+        Err(e) => SessionCheck::Unknown(e.to_string()),
+        Ok((status, _)) if status == "200" => SessionCheck::Accepted,
+        Ok((status, _)) if status == "400" || status == "500" => SessionCheck::Refused(status),
+        Ok((status, errors)) => SessionCheck::Unknown(format!("status {} {}", status, errors).trim().to_string()),
+    }
+}
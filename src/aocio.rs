@@ -1,15 +1,100 @@
 
-use std::path::Path;
-use std::fs::File;
-use std::io::BufReader;
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, BufReader};
 use std::io::prelude::*;
 
-use crate::aocbase::AOCResult;
+use crate::aocbase::{AOCError, AOCResult};
+use crate::sha256::sha256_hex;
 
-pub fn each_line<F>(input: impl AsRef<Path>, mut f: F) -> AOCResult<()>
+// Anything a problem can read its input from. Paths, whether &str, String,
+// Path or PathBuf, are files. InputSource::Text is input that's already in
+// memory, so problems can be run from a library or a test without a file.
+pub trait ProblemInput {
+
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>>;
+
+    fn read_bytes(&self) -> AOCResult<Vec<u8>> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.open()?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_string(&self) -> AOCResult<String> {
+        String::from_utf8(self.read_bytes()?)
+            .map_err(|e| AOCError::ParseError(format!("Input isn't UTF-8: {}", e)))
+    }
+
+    // How big the input is, where that's known without reading it.
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+fn open_file(path: &Path) -> AOCResult<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(File::open(path)?)))
+}
+
+fn file_len(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+impl ProblemInput for Path {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        open_file(self)
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        file_len(self)
+    }
+}
+
+impl ProblemInput for PathBuf {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        open_file(self)
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        file_len(self)
+    }
+}
+
+impl ProblemInput for str {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        open_file(Path::new(self))
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        file_len(Path::new(self))
+    }
+}
+
+impl ProblemInput for String {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        open_file(Path::new(self))
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        file_len(Path::new(self))
+    }
+}
+
+impl<T: ProblemInput + ?Sized> ProblemInput for &T {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        (**self).open()
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        (**self).byte_len()
+    }
+}
+
+pub fn each_line<F>(input: impl ProblemInput, mut f: F) -> AOCResult<()>
     where F: FnMut(&String) -> AOCResult<()>
 {
-    let mut reader = BufReader::new(File::open(input)?);
+    let mut reader = input.open()?;
     let mut buffer = String::new();
 
     while reader.read_line(&mut buffer)? > 0 {
@@ -20,8 +105,97 @@ pub fn each_line<F>(input: impl AsRef<Path>, mut f: F) -> AOCResult<()>
     Ok(())
 }
 
-pub fn read_lines_as_bytes(input: impl AsRef<Path>) -> AOCResult<Vec<Vec<u8>>> {
-    let mut reader = BufReader::new(File::open(input)?);
+// Like each_line but hands over the raw bytes. It's cheaper because the
+// lines aren't checked to be UTF-8 and no String is made for each one.
+pub fn each_line_bytes<F>(input: impl ProblemInput, mut f: F) -> AOCResult<()>
+    where F: FnMut(&[u8]) -> AOCResult<()>
+{
+    let mut reader = input.open()?;
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        f(&buffer)?;
+        buffer.clear();
+    }
+
+    Ok(())
+}
+
+// For tokens that start with a single letter code.
+pub fn first_char(text: &str) -> AOCResult<char> {
+    text.chars()
+        .next()
+        .ok_or_else(|| AOCError::ParseError(format!("Expected a character: \"{}\"", text)))
+}
+
+// A line that was skipped and why.
+#[derive(Debug, Clone)]
+pub struct LineError {
+    pub line_number: usize,
+    pub reason: String,
+}
+
+// What to do with lines that fail to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParsePolicy {
+    // Stop at the first bad line.
+    #[default]
+    Strict,
+    // Skip bad lines, but still give up once there are more than max_errors.
+    SkipInvalid { max_errors: usize },
+}
+
+thread_local! {
+    static SKIPPED_LINES: RefCell<Vec<LineError>> = const { RefCell::new(Vec::new()) };
+}
+
+// Hands back the lines skipped on this thread since the last call. The runner
+// uses this to attach them to the problem's result.
+pub fn take_skipped_lines() -> Vec<LineError> {
+    SKIPPED_LINES.with(|skipped| skipped.take())
+}
+
+// Like each_line, but a line that fails to parse can be skipped instead of
+// stopping everything, depending on the policy. Other errors always stop.
+pub fn each_line_with_policy<F>(input: impl ProblemInput, policy: ParsePolicy, mut f: F) -> AOCResult<()>
+    where F: FnMut(&String) -> AOCResult<()>
+{
+    let max_errors = match policy {
+        ParsePolicy::Strict => return each_line(input, f),
+        ParsePolicy::SkipInvalid { max_errors } => max_errors,
+    };
+
+    let mut line_errors: Vec<LineError> = Vec::new();
+    let mut line_number = 0;
+
+    let result = each_line(input, |line| {
+        line_number += 1;
+        match f(line) {
+            Err(AOCError::ParseError(reason)) if line_errors.len() < max_errors => {
+                line_errors.push(LineError { line_number, reason });
+                Ok(())
+            },
+            Err(AOCError::ParseError(reason)) => {
+                Err(AOCError::ParseError(format!("More than {} invalid lines, line {}: {}", max_errors, line_number, reason)))
+            },
+            result => result,
+        }
+    });
+
+    SKIPPED_LINES.with(|skipped| skipped.borrow_mut().append(&mut line_errors));
+    result
+}
+
+pub fn line_errors_report(line_errors: &[LineError]) -> String {
+    let mut report = format!("Skipped {} invalid lines:\n", line_errors.len());
+    for line_error in line_errors {
+        report.push_str(&format!("  line {}: {}\n", line_error.line_number, line_error.reason.trim_end()));
+    }
+    report
+}
+
+pub fn read_lines_as_bytes(input: impl ProblemInput) -> AOCResult<Vec<Vec<u8>>> {
+    let mut reader = input.open()?;
     let mut buffer: Vec<u8> = Vec::new();
     buffer.reserve(1024);
 
@@ -38,4 +212,84 @@ pub fn read_lines_as_bytes(input: impl AsRef<Path>) -> AOCResult<Vec<Vec<u8>>> {
     }
 
     Ok(results)
-}
\ No newline at end of file
+}
+
+// Problems write files for looking at in other tools here.
+pub const ARTIFACTS_DIR: &str = "artifacts";
+
+// A path in the artifacts directory, which is made if it isn't there yet.
+pub fn artifact_path(name: impl AsRef<Path>) -> AOCResult<PathBuf> {
+    create_dir_all(ARTIFACTS_DIR)?;
+    Ok(Path::new(ARTIFACTS_DIR).join(name))
+}
+
+// Where a problem's input comes from. Problems read their input from a path,
+// so input that isn't in a file, like input piped in with --input -, is
+// written to one in artifacts/inputs first. The file is named by the input's
+// hash so the same input always ends up in the same file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    File(PathBuf),
+    Stdin,
+    Text(String),
+}
+
+impl InputSource {
+
+    // - is stdin, anything else a file.
+    pub fn parse(arg: &str) -> Self {
+        match arg {
+            "-" => InputSource::Stdin,
+            path => InputSource::File(PathBuf::from(path)),
+        }
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        InputSource::Text(text.into())
+    }
+
+    // Stdin can only be read once, so this should only be called once for it.
+    pub fn to_path(&self) -> AOCResult<PathBuf> {
+        if let InputSource::File(path) = self {
+            return Ok(path.clone());
+        }
+
+        let text = self.read_string()?;
+        if text.trim().is_empty() {
+            return Err(AOCError::ProcessingError(format!("No input given on {}.", self)));
+        }
+
+        let path = artifact_path("inputs")?.join(format!("{}.txt", &sha256_hex(text.as_bytes())[.. 16]));
+        create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, text)?;
+        Ok(path)
+    }
+}
+
+impl ProblemInput for InputSource {
+    fn open(&self) -> AOCResult<Box<dyn BufRead + '_>> {
+        match self {
+            InputSource::File(path) => open_file(path),
+            InputSource::Stdin => Ok(Box::new(io::stdin().lock())),
+            InputSource::Text(text) => Ok(Box::new(text.as_bytes())),
+        }
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        match self {
+            InputSource::File(path) => file_len(path),
+            InputSource::Stdin => None,
+            InputSource::Text(text) => Some(text.len() as u64),
+        }
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::File(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::Text(_) => write!(f, "text"),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Timing problems over many runs. A single run is too noisy to tell whether a
+// change made a problem faster, so each one is run a number of times after
+// some warmup runs that aren't counted.
+
+use std::fs::create_dir_all;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::aocbase::AOCResult;
+use crate::run::Problem;
+use crate::settings::{self, Settings};
+
+pub struct BenchResult {
+    pub name: String,
+    pub durations: Vec<Duration>,
+    // Why the runs stopped, if a run failed.
+    pub error: Option<String>,
+}
+
+impl BenchResult {
+
+    fn durations_ms(&self) -> Vec<f64> {
+        let mut durations: Vec<f64> = self.durations
+            .iter()
+            .map(|d| d.as_micros() as f64 / 1000.0)
+            .collect();
+        durations.sort_by(|d1, d2| d1.total_cmp(d2));
+        durations
+    }
+
+    pub fn min_ms(&self) -> f64 {
+        self.durations_ms().first().copied().unwrap_or(0.0)
+    }
+
+    pub fn median_ms(&self) -> f64 {
+        let durations = self.durations_ms();
+        match durations.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (durations[n / 2 - 1] + durations[n / 2]) / 2.0,
+            n => durations[n / 2],
+        }
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        let durations = self.durations_ms();
+        if durations.is_empty() {
+            return 0.0;
+        }
+        durations.iter().sum::<f64>() / durations.len() as f64
+    }
+
+    pub fn stddev_ms(&self) -> f64 {
+        let durations = self.durations_ms();
+        if durations.len() < 2 {
+            return 0.0;
+        }
+        let mean = self.mean_ms();
+        let variance = durations.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (durations.len() - 1) as f64;
+        variance.sqrt()
+    }
+}
+
+// Stops at the first run that fails, there's no point timing a failure. Each
+// run is held to the timeout and memory limit, so a slow part gives up once
+// instead of holding up every run after it. The cache is off for every run,
+// or the runs after the first would time cache hits instead of solves.
+pub fn bench(problem: &Problem, input: &String, warmup: usize, iterations: usize, timeout: Option<Duration>, memory_limit: Option<usize>) -> BenchResult {
+    let mut durations: Vec<Duration> = Vec::with_capacity(iterations);
+    let mut error = None;
+
+    let before = settings::current();
+    settings::set(Settings { no_cache: true, ..before.clone() });
+
+    for run in 0 .. warmup + iterations {
+        let result = problem.execute(input, timeout, memory_limit);
+        if let Err(e) = result.result {
+            error = Some(e.to_string());
+            break;
+        }
+        if run >= warmup {
+            durations.push(result.duration);
+        }
+    }
+    settings::set(before);
+
+    BenchResult { name: problem.name.clone(), durations, error }
+}
+
+// The problem timed with each strategy in turn, named like
+// problem17::part1[buckets]. A problem without a strategy of that name runs
+// its usual way, the same as with --strategy.
+pub fn bench_strategies(problem: &Problem, input: &String, strategies: &[String], warmup: usize, iterations: usize, timeout: Option<Duration>, memory_limit: Option<usize>) -> Vec<BenchResult> {
+    let before = settings::current();
+
+    let results = strategies
+        .iter()
+        .map(|strategy| {
+            settings::set(Settings { strategy: Some(strategy.clone()), ..before.clone() });
+            let mut result = bench(problem, input, warmup, iterations, timeout, memory_limit);
+            result.name = format!("{}[{}]", problem.name, strategy);
+            result
+        })
+        .collect();
+    settings::set(before);
+
+    results
+}
+
+pub fn to_table(results: &[BenchResult]) -> String {
+    // Wide enough for names with a strategy on the end.
+    let width = results.iter().map(|result| result.name.len()).max().unwrap_or(0).max(20);
+    let mut out = format!("{:<width$} {:>5} {:>12} {:>12} {:>12} {:>12}\n", "Problem", "Runs", "Min ms", "Median ms", "Mean ms", "Stddev ms");
+
+    for result in results {
+        out.push_str(format!("{:<width$} {:>5} {:>12.3} {:>12.3} {:>12.3} {:>12.3}\n",
+            result.name, result.durations.len(), result.min_ms(), result.median_ms(), result.mean_ms(), result.stddev_ms()).as_str());
+        if let Some(error) = &result.error {
+            out.push_str(format!("  Failed: {}\n", error).as_str());
+        }
+    }
+
+    out
+}
+
+pub fn write_csv(path: impl AsRef<Path>, results: &[BenchResult]) -> AOCResult<()> {
+    let path = path.as_ref();
+
+    // Make sure the parent directory exists.
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut csv_out = csv::Writer::from_path(path)?;
+
+    csv_out.write_record(["Problem", "Runs", "Min", "Median", "Mean", "Stddev", "Error"])?;
+
+    for result in results {
+        csv_out.write_record([
+            result.name.clone(),
+            result.durations.len().to_string(),
+            result.min_ms().to_string(),
+            result.median_ms().to_string(),
+            result.mean_ms().to_string(),
+            result.stddev_ms().to_string(),
+            result.error.clone().unwrap_or_default(),
+        ])?;
+    }
+
+    Ok(())
+}
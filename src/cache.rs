@@ -0,0 +1,104 @@
+// Results of slow preprocessing that only depend on the input, kept so they
+// don't have to be worked out again. They're kept in memory for the rest of
+// the run and in artifacts/cache for later runs, keyed by the SHA-256 of the
+// input file so an edited input never gets an old result, and by the
+// version of what's kept so a changed computation doesn't either.
+//
+// --no-cache turns it off, and bench always runs with it off.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::{artifact_path, ProblemInput};
+use crate::settings;
+use crate::sha256::sha256_hex;
+
+pub trait Cacheable: Sized {
+    // Goes up whenever the bytes or how the value is worked out change.
+    const VERSION: u32;
+
+    fn to_bytes(&self) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> AOCResult<Self>;
+}
+
+lazy_static! {
+    static ref IN_MEMORY: Mutex<HashMap<String, Vec<u8>>> = Mutex::new(HashMap::new());
+}
+
+pub fn input_hash(input: impl ProblemInput) -> AOCResult<String> {
+    Ok(sha256_hex(&input.read_bytes()?))
+}
+
+// name says what's being kept, like problem23_graph. A cached copy that
+// can't be read back is worked out again and replaced.
+pub fn get_or_compute<T, F>(name: &str, input: impl ProblemInput, compute: F) -> AOCResult<T>
+    where T: Cacheable, F: FnOnce() -> AOCResult<T>
+{
+    if settings::no_cache() {
+        return compute();
+    }
+
+    let key = format!("{}_v{}_{}", name, T::VERSION, input_hash(input)?);
+    let cache_file = artifact_path(Path::new("cache").join(format!("{}.bin", key)))?;
+
+    let cached = match IN_MEMORY.lock().unwrap().get(&key) {
+        Some(bytes) => Some(bytes.clone()),
+        None => fs::read(&cache_file).ok(),
+    };
+
+    if let Some(bytes) = cached {
+        match T::from_bytes(&bytes) {
+            Ok(value) => {
+                tracing::debug!("Cached: {}", key);
+                IN_MEMORY.lock().unwrap().insert(key, bytes);
+                return Ok(value);
+            },
+            Err(e) => tracing::debug!("Ignoring cached {}: {}", key, e),
+        }
+    }
+
+    let value = compute()?;
+    let bytes = value.to_bytes();
+
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&cache_file, &bytes)?;
+    IN_MEMORY.lock().unwrap().insert(key, bytes);
+
+    Ok(value)
+}
+
+// Reads little endian values back out of cached bytes.
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn read_u32(&mut self) -> AOCResult<u32> {
+        if self.bytes.len() < 4 {
+            return Err(AOCError::ParseError("Cached data is cut short.".into()));
+        }
+        let (value, rest) = self.bytes.split_at(4);
+        self.bytes = rest;
+        Ok(u32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+    }
+
+    pub fn read_i32(&mut self) -> AOCResult<i32> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
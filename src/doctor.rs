@@ -0,0 +1,174 @@
+// Checks for the usual reasons a fresh checkout doesn't run, each with what
+// to do about it. Nothing is changed except making the results directory if
+// it isn't there yet, the same as a run would, and a scratch file in it.
+
+use std::fs::{self, create_dir_all};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::aocfetch::{self, SessionCheck, SESSION_ENV_VAR};
+use crate::run::{parse_number, Problem};
+
+const SESSION_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    // Things still run, but maybe not how you'd want.
+    Warn,
+    Fail,
+}
+
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub fix: Option<String>,
+}
+
+impl Check {
+
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Ok, detail: detail.into(), fix: None }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Warn, detail: detail.into(), fix: Some(fix.into()) }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check { name: name.into(), status: CheckStatus::Fail, detail: detail.into(), fix: Some(fix.into()) }
+    }
+}
+
+// Each day only needs checking once even though it has two parts.
+pub fn check_inputs(problems: &[&Problem]) -> Check {
+    let name = "Inputs";
+
+    if !Path::new("input").is_dir() {
+        return Check::fail(name, "There is no input directory.",
+            "Run from the top of the repo, or make an input directory there and download the inputs into it.");
+    }
+
+    let mut days: Vec<i32> = problems.iter().filter_map(|p| parse_number(&p.name).ok()).collect();
+    days.dedup();
+
+    let missing: Vec<(i32, String)> = days
+        .iter()
+        .map(|day| (*day, format!("input/input_{:0>2}.txt", day)))
+        .filter(|(_, path)| !Path::new(path).is_file())
+        .collect();
+
+    match missing.first() {
+        None => Check::ok(name, format!("All {} days have an input file.", days.len())),
+        Some((day, path)) => {
+            let missing_days: Vec<String> = missing.iter().map(|(day, _)| day.to_string()).collect();
+            Check::fail(name,
+                format!("Missing input for days: {}", missing_days.join(", ")),
+                format!("Set a session token to have them downloaded, or download https://adventofcode.com/2023/day/{}/input to {} and the same for the others.",
+                    day, path))
+        },
+    }
+}
+
+pub fn check_results_writable(result_file: impl AsRef<Path>) -> Check {
+    let name = "Results";
+    let dir = match result_file.as_ref().parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+
+    if let Err(e) = create_dir_all(&dir) {
+        return Check::fail(name, format!("Can't make {}: {}", dir.display(), e),
+            "Fix the permissions or pass --result-file somewhere writable.");
+    }
+
+    let probe = dir.join(".doctor_probe");
+    match fs::write(&probe, b"") {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            Check::ok(name, format!("{} is writable.", dir.display()))
+        },
+        Err(e) => Check::fail(name, format!("Can't write to {}: {}", dir.display(), e),
+            "Fix the permissions or pass --result-file somewhere writable."),
+    }
+}
+
+// A token that looks like one, a long run of hex digits, is tried against
+// adventofcode.com with a HEAD request.
+pub fn check_session_token() -> Check {
+    let name = "Session token";
+    let fix = format!("Copy the session cookie from adventofcode.com in a browser into {} or pass --session.", SESSION_ENV_VAR);
+
+    let token = match aocfetch::session_token() {
+        None => return Check::warn(name, "No session token, missing inputs can't be downloaded.", fix),
+        Some(token) => token,
+    };
+
+    if token.len() < 64 || !aocfetch::is_hex_token(&token) {
+        return Check::warn(name, "The session token doesn't look like one.", fix);
+    }
+
+    match aocfetch::check_session(&token, SESSION_CHECK_TIMEOUT) {
+        SessionCheck::Accepted => Check::ok(name, "adventofcode.com accepted the session token."),
+        SessionCheck::Refused(status) => Check::fail(name,
+            format!("adventofcode.com refused the session token with status {}, it's probably wrong or expired.", status), fix),
+        SessionCheck::Unknown(reason) => Check::warn(name,
+            format!("Couldn't check the session token with adventofcode.com: {}", reason),
+            "Check that curl is installed and adventofcode.com can be reached."),
+    }
+}
+
+pub fn check_build_profile() -> Check {
+    let name = "Build";
+    if cfg!(debug_assertions) {
+        Check::warn(name, "This is a debug build, some problems take minutes instead of seconds.",
+            "Use cargo run --release.")
+    }
+    else {
+        Check::ok(name, "Release build.")
+    }
+}
+
+pub fn check_parallelism() -> Check {
+    let name = "Parallelism";
+    match thread::available_parallelism() {
+        Ok(n) if n.get() > 1 => Check::ok(name, format!("{} threads available for --jobs.", n)),
+        Ok(_) => Check::ok(name, "Only 1 thread is available, --jobs won't make runs any faster."),
+        Err(e) => Check::warn(name, format!("Couldn't tell how many threads are available: {}", e),
+            "Pass a number to --jobs instead of leaving it to be worked out."),
+    }
+}
+
+pub fn run_checks(problems: &[&Problem], result_file: impl AsRef<Path>) -> Vec<Check> {
+    vec![
+        check_inputs(problems),
+        check_results_writable(result_file),
+        check_session_token(),
+        check_build_profile(),
+        check_parallelism(),
+    ]
+}
+
+pub fn report(checks: &[Check]) -> String {
+    let mut out = String::new();
+
+    for check in checks {
+        let label = match check.status {
+            CheckStatus::Ok => "ok  ",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        };
+        out.push_str(&format!("[{}] {}: {}\n", label, check.name, check.detail));
+        if let Some(fix) = &check.fix {
+            out.push_str(&format!("       Fix: {}\n", fix));
+        }
+    }
+
+    out
+}
+
+pub fn failure_count(checks: &[Check]) -> usize {
+    checks.iter().filter(|check| check.status == CheckStatus::Fail).count()
+}
@@ -0,0 +1,151 @@
+// Poking at what a problem parsed its input into, a command at a time, for
+// the repl subcommand. Problems that can be explored implement Explorable for
+// their model, and explorers() has each day that can be with how to get it
+// from an input.
+//
+// Commands are a name followed by arguments separated by whitespace. Regions
+// are given as ranges like 3..7, 3..=6, 3.. or .., and a single number is a
+// range of one.
+
+use std::ops::Bound;
+use std::str::FromStr;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::ProblemInput;
+use crate::problems::{problem10, problem11, problem19, problem20, problem22};
+
+pub struct ExploreCommand {
+    pub name: &'static str,
+    // How the arguments go, like "<row> <column>".
+    pub args: &'static str,
+    pub help: &'static str,
+}
+
+impl ExploreCommand {
+
+    pub const fn new(name: &'static str, args: &'static str, help: &'static str) -> Self {
+        Self { name, args, help }
+    }
+}
+
+pub trait Explorable {
+    // A line or two about what was parsed, shown when exploring starts.
+    fn summary(&self) -> String;
+
+    fn commands(&self) -> Vec<ExploreCommand>;
+
+    // What the command has to say, or an error for commands or arguments that
+    // don't make sense, after which exploring carries on.
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String>;
+}
+
+// The commands and what they do, for help.
+pub fn command_help(commands: &[ExploreCommand]) -> String {
+    let usage = |command: &ExploreCommand| format!("{} {}", command.name, command.args).trim_end().to_string();
+    let width = commands.iter().map(|command| usage(command).len()).max().unwrap_or(0);
+
+    let mut help = String::new();
+    for command in commands {
+        help.push_str(&format!("  {:width$}  {}\n", usage(command), command.help, width = width));
+    }
+    help
+}
+
+pub fn unknown_command(name: &str) -> AOCError {
+    AOCError::ProcessingError(format!("Unknown command: {}, try help", name))
+}
+
+pub fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> AOCResult<T> {
+    let value = args
+        .get(index)
+        .ok_or_else(|| AOCError::ParseError(format!("Missing {}", name)))?;
+    value
+        .parse::<T>()
+        .map_err(|_| AOCError::ParseError(format!("Invalid {}: {}", name, value)))
+}
+
+// For arguments that can be left off.
+pub fn arg_or<T: FromStr>(args: &[&str], index: usize, name: &str, default: T) -> AOCResult<T> {
+    match args.get(index) {
+        None => Ok(default),
+        Some(_) => arg(args, index, name),
+    }
+}
+
+// A range of rows or columns, which works with RangeBounds.
+pub fn range_arg(args: &[&str], index: usize, name: &str) -> AOCResult<(Bound<usize>, Bound<usize>)> {
+    let text: &str = args
+        .get(index)
+        .ok_or_else(|| AOCError::ParseError(format!("Missing {}", name)))?;
+    let invalid = || AOCError::ParseError(format!("Invalid {}, expected a range like 3..7: {}", name, text));
+    let number = |n: &str| n.parse::<usize>().map_err(|_| invalid());
+
+    let Some((start, end)) = text.split_once("..") else {
+        let n = number(text)?;
+        return Ok((Bound::Included(n), Bound::Included(n)));
+    };
+
+    let start = if start.is_empty() { Bound::Unbounded } else { Bound::Included(number(start)?) };
+    let end = match end.strip_prefix('=') {
+        Some(end) => Bound::Included(number(end)?),
+        None if end.is_empty() => Bound::Unbounded,
+        None => Bound::Excluded(number(end)?),
+    };
+    Ok((start, end))
+}
+
+// Where a range starts and ends, within 0 to len.
+pub fn range_within((start, end): (Bound<usize>, Bound<usize>), len: usize) -> std::ops::Range<usize> {
+    let start = match start {
+        Bound::Included(n) => n,
+        Bound::Excluded(n) => n + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match end {
+        Bound::Included(n) => n + 1,
+        Bound::Excluded(n) => n,
+        Bound::Unbounded => len,
+    };
+    start.min(len) .. end.min(len).max(start.min(len))
+}
+
+pub struct Explorer {
+    pub day: u32,
+    pub explore: fn(&dyn ProblemInput) -> AOCResult<Box<dyn Explorable>>,
+}
+
+pub fn explorers() -> Vec<Explorer> {
+    vec![
+        Explorer {
+            day: 10,
+            explore: |input| {
+                let mut pipe_map = problem10::PipeMap::parse(input)?;
+                pipe_map.resolve_start()?;
+                Ok(Box::new(pipe_map))
+            },
+        },
+        Explorer {
+            day: 11,
+            explore: |input| Ok(Box::new(problem11::SpaceMap::parse(input)?)),
+        },
+        Explorer {
+            day: 19,
+            explore: |input| Ok(Box::new(problem19::parse_worksheet(input)?.0)),
+        },
+        Explorer {
+            day: 20,
+            explore: |input| Ok(Box::new(problem20::Modules::parse(input)?)),
+        },
+        Explorer {
+            day: 22,
+            explore: |input| Ok(Box::new(problem22::Pieces::parse(input)?)),
+        },
+    ]
+}
+
+pub fn explorer_for(day: u32) -> AOCResult<Explorer> {
+    explorers()
+        .into_iter()
+        .find(|explorer| explorer.day == day)
+        .ok_or_else(|| AOCError::ProcessingError(format!("problem{} can't be explored.", day)))
+}
@@ -0,0 +1,201 @@
+// Random puzzle inputs, for checking solvers against each other on more than
+// the examples and the one real input. They're small so the slow ways of
+// solving still finish quickly, and they're made from an rng so the same seed
+// always gives the same input.
+
+use std::collections::HashSet;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// Seeds and the chain of maps from seed to location. Each map's source
+// ranges are cut from 0..100 so they never overlap.
+pub fn problem5(rng: &mut StdRng) -> String {
+    const CHAIN: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+    let seeds: Vec<String> = (0 .. 2 * rng.gen_range(1 ..= 4)).map(|_| rng.gen_range(0 .. 100).to_string()).collect();
+    let mut text = format!("seeds: {}\n", seeds.join(" "));
+
+    for names in CHAIN.windows(2) {
+        text.push_str(&format!("\n{}-to-{} map:\n", names[0], names[1]));
+
+        let mut cuts: Vec<u32> = (0 .. 2 * rng.gen_range(1 ..= 4)).map(|_| rng.gen_range(0 .. 100)).collect();
+        cuts.sort();
+        cuts.dedup();
+        for range in cuts.chunks_exact(2) {
+            let len = range[1] - range[0];
+            text.push_str(&format!("{} {} {}\n", rng.gen_range(0 .. 100), range[0], len));
+        }
+    }
+    text
+}
+
+// Hands that are all different, since the same hand twice with different
+// bids could be ranked either way round.
+pub fn problem7(rng: &mut StdRng) -> String {
+    const CARDS: &[u8] = b"23456789TJQKA";
+
+    let mut hands: HashSet<Vec<u8>> = HashSet::new();
+    let count = rng.gen_range(1 ..= 50);
+    let mut text = String::new();
+    while hands.len() < count {
+        // Few kinds of card so there are plenty of pairs and jokers.
+        let kind_count = rng.gen_range(1 ..= 5);
+        let kinds: Vec<u8> = CARDS.choose_multiple(rng, kind_count).copied().collect();
+        let hand: Vec<u8> = (0 .. 5).map(|_| *kinds.choose(rng).unwrap()).collect();
+        if hands.insert(hand.clone()) {
+            text.push_str(&format!("{} {}\n", String::from_utf8(hand).unwrap(), rng.gen_range(1 ..= 1000)));
+        }
+    }
+    text
+}
+
+// Commands and a network of nodes that AAA may or may not find ZZZ in.
+pub fn problem8(rng: &mut StdRng) -> String {
+    let mut names: Vec<String> = vec!["AAA".into(), "ZZZ".into()];
+    while names.len() < rng.gen_range(2 ..= 30) {
+        let name: String = (0 .. 3).map(|_| rng.gen_range(b'B' ..= b'Y') as char).collect();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let commands: String = (0 .. rng.gen_range(1 ..= 6)).map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' }).collect();
+    let mut text = format!("{}\n\n", commands);
+    for name in &names {
+        text.push_str(&format!("{} = ({}, {})\n", name, names.choose(rng).unwrap(), names.choose(rng).unwrap()));
+    }
+    text
+}
+
+// Rounded and cube rocks scattered over a platform.
+pub fn problem14(rng: &mut StdRng) -> String {
+    let (width, height) = (rng.gen_range(1 ..= 12), rng.gen_range(1 ..= 12));
+    let mut text = String::new();
+    for _ in 0 .. height {
+        let row: String = (0 .. width)
+            .map(|_| match rng.gen_range(0 .. 10) {
+                0 ..= 2 => 'O',
+                3 ..= 4 => '#',
+                _ => '.',
+            })
+            .collect();
+        text.push_str(&row);
+        text.push('\n');
+    }
+    text
+}
+
+// Mostly empty space with mirrors and splitters scattered about.
+pub fn problem16(rng: &mut StdRng) -> String {
+    const CELLS: &[u8] = b"|-/\\";
+
+    let (width, height) = (rng.gen_range(1 ..= 20), rng.gen_range(1 ..= 20));
+    let mut text = String::new();
+    for _ in 0 .. height {
+        let row: String = (0 .. width)
+            .map(|_| if rng.gen_bool(0.2) { *CELLS.choose(rng).unwrap() as char } else { '.' })
+            .collect();
+        text.push_str(&row);
+        text.push('\n');
+    }
+    text
+}
+
+// Heat loss from 1 to 9 in each block.
+pub fn problem17(rng: &mut StdRng) -> String {
+    let (width, height) = (rng.gen_range(1 ..= 20), rng.gen_range(1 ..= 20));
+    let mut text = String::new();
+    for _ in 0 .. height {
+        let row: String = (0 .. width).map(|_| char::from(b'0' + rng.gen_range(1 ..= 9))).collect();
+        text.push_str(&row);
+        text.push('\n');
+    }
+    text
+}
+
+// Bricks in a small area so they pile on each other, none of them sharing a
+// cube.
+pub fn problem22(rng: &mut StdRng) -> String {
+    let mut taken: HashSet<(i32, i32, i32)> = HashSet::new();
+    let mut text = String::new();
+
+    for _ in 0 .. rng.gen_range(1 ..= 30) {
+        let start = (rng.gen_range(0 .. 4), rng.gen_range(0 .. 4), rng.gen_range(1 .. 30));
+        let len = rng.gen_range(0 .. 3);
+        let end = match rng.gen_range(0 .. 3) {
+            0 => (start.0 + len, start.1, start.2),
+            1 => (start.0, start.1 + len, start.2),
+            _ => (start.0, start.1, start.2 + len),
+        };
+
+        let cubes: Vec<(i32, i32, i32)> = (start.0 ..= end.0)
+            .flat_map(|x| (start.1 ..= end.1).flat_map(move |y| (start.2 ..= end.2).map(move |z| (x, y, z))))
+            .collect();
+        if cubes.iter().any(|cube| taken.contains(cube)) {
+            continue;
+        }
+        taken.extend(cubes);
+        text.push_str(&format!("{},{},{}~{},{},{}\n", start.0, start.1, start.2, end.0, end.1, end.2));
+    }
+
+    // There has to be at least one.
+    if text.is_empty() {
+        text.push_str("0,0,1~0,0,1\n");
+    }
+    text
+}
+
+// A maze with some extra walls knocked through so there's more than one way
+// from the gap in the top row to the one in the bottom row. Slopes point
+// down or right, the way the puzzle's mostly do.
+pub fn problem23(rng: &mut StdRng) -> String {
+    let (cells_wide, cells_high) = (rng.gen_range(2 ..= 6), rng.gen_range(2 ..= 6));
+    let (width, height) = (2 * cells_wide + 1, 2 * cells_high + 1);
+    let mut map: Vec<Vec<u8>> = vec![vec![b'#'; width]; height];
+
+    // Depth first carving from the top left cell.
+    let mut stack: Vec<(usize, usize)> = vec![(1, 1)];
+    map[1][1] = b'.';
+    while let Some(&(y, x)) = stack.last() {
+        let mut next: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .map(|(dy, dx): &(i32, i32)| ((y as i32 + dy) as usize, (x as i32 + dx) as usize))
+            .filter(|(ny, nx)| *ny > 0 && *ny < height - 1 && *nx > 0 && *nx < width - 1 && map[*ny][*nx] == b'#')
+            .collect();
+        next.shuffle(rng);
+        match next.first() {
+            Some(&(ny, nx)) => {
+                map[(y + ny) / 2][(x + nx) / 2] = b'.';
+                map[ny][nx] = b'.';
+                stack.push((ny, nx));
+            },
+            None => {
+                stack.pop();
+            },
+        }
+    }
+
+    // Between two cells, where walls can go and slopes can be.
+    let between: Vec<(usize, usize)> = (1 .. height - 1)
+        .flat_map(|y| (1 .. width - 1).map(move |x| (y, x)))
+        .filter(|(y, x)| y % 2 != x % 2)
+        .collect();
+
+    for &(y, x) in &between {
+        if map[y][x] == b'#' && rng.gen_bool(0.15) {
+            map[y][x] = b'.';
+        }
+    }
+
+    for &(y, x) in &between {
+        if map[y][x] == b'.' && rng.gen_bool(0.1) {
+            map[y][x] = if y % 2 == 0 { b'v' } else { b'>' };
+        }
+    }
+
+    map[0][1] = b'.';
+    map[height - 1][width - 2] = b'.';
+    map.into_iter().map(|row| String::from_utf8(row).unwrap() + "\n").collect()
+}
@@ -0,0 +1,185 @@
+// A 2d vector for the float geometry in the problems. With the simd
+// feature its lanes are added and multiplied with SSE2 on x86_64, otherwise
+// (or on other targets) it's plain scalar code. Both give the same answers.
+// Also the four ways to move on a grid.
+
+use std::ops::{Add, Mul, Sub};
+
+use crate::aocbase::{AOCError, AOCResult};
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use std::arch::x86_64::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[repr(C, align(16))]
+pub struct Vec2 {
+    lanes: [f64; 2],
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod lanes {
+    use super::*;
+
+    // SSE2 is always there on x86_64 so these are safe to call.
+    #[inline]
+    pub fn add2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        let mut out = [0.0; 2];
+        unsafe { _mm_storeu_pd(out.as_mut_ptr(), _mm_add_pd(_mm_loadu_pd(a.as_ptr()), _mm_loadu_pd(b.as_ptr()))) };
+        out
+    }
+
+    #[inline]
+    pub fn sub2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        let mut out = [0.0; 2];
+        unsafe { _mm_storeu_pd(out.as_mut_ptr(), _mm_sub_pd(_mm_loadu_pd(a.as_ptr()), _mm_loadu_pd(b.as_ptr()))) };
+        out
+    }
+
+    #[inline]
+    pub fn mul2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        let mut out = [0.0; 2];
+        unsafe { _mm_storeu_pd(out.as_mut_ptr(), _mm_mul_pd(_mm_loadu_pd(a.as_ptr()), _mm_loadu_pd(b.as_ptr()))) };
+        out
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod lanes {
+
+    #[inline]
+    pub fn add2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        [a[0] + b[0], a[1] + b[1]]
+    }
+
+    #[inline]
+    pub fn sub2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        [a[0] - b[0], a[1] - b[1]]
+    }
+
+    #[inline]
+    pub fn mul2(a: &[f64; 2], b: &[f64; 2]) -> [f64; 2] {
+        [a[0] * b[0], a[1] * b[1]]
+    }
+}
+
+impl Vec2 {
+
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { lanes: [x, y] }
+    }
+
+    pub fn x(&self) -> f64 {
+        self.lanes[0]
+    }
+
+    pub fn y(&self) -> f64 {
+        self.lanes[1]
+    }
+
+    pub fn dot(&self, other: Vec2) -> f64 {
+        let p = lanes::mul2(&self.lanes, &other.lanes);
+        p[0] + p[1]
+    }
+
+    // The z of the 3d cross product, zero when the two are parallel.
+    pub fn cross(&self, other: Vec2) -> f64 {
+        let p = lanes::mul2(&self.lanes, &[other.lanes[1], other.lanes[0]]);
+        p[0] - p[1]
+    }
+
+    pub fn within(&self, start: f64, end: f64) -> bool {
+        self.lanes.iter().all(|v| *v >= start && *v <= end)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2 { lanes: lanes::add2(&self.lanes, &other.lanes) }
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2 { lanes: lanes::sub2(&self.lanes, &other.lanes) }
+    }
+}
+
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, scale: f64) -> Vec2 {
+        Vec2 { lanes: lanes::mul2(&self.lanes, &[scale, scale]) }
+    }
+}
+
+// North is up, so towards lower rows. They go round clockwise.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    North = 0,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    // 0-3, for keeping something per direction in an array or bits.
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    // (y, x) change to move one step this way.
+    pub fn delta(&self) -> (i64, i64) {
+        use Direction::*;
+        match self {
+            North => (-1, 0),
+            East => (0, 1),
+            South => (1, 0),
+            West => (0, -1),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        Self::ALL[(self.index() + 2) % 4]
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        Self::ALL[(self.index() + 3) % 4]
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        Self::ALL[(self.index() + 1) % 4]
+    }
+
+    // East or west.
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Direction::East | Direction::West)
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        !self.is_horizontal()
+    }
+}
+
+// Either UDLR or arrows.
+impl TryFrom<char> for Direction {
+    type Error = AOCError;
+
+    fn try_from(value: char) -> AOCResult<Direction> {
+        use Direction::*;
+        Ok(match value {
+            'U' | '^' => North,
+            'R' | '>' => East,
+            'D' | 'v' => South,
+            'L' | '<' => West,
+            _ => {
+                return Err(AOCError::ParseError(format!("Invalid direction: {}", value)));
+            }
+        })
+    }
+}
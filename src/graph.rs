@@ -0,0 +1,336 @@
+// Directed graphs where nodes are numbered as they're added, with whatever a
+// problem wants to keep with each node and each edge. Nodes can also be
+// interned, added once by value and found again by it, for inputs that name
+// them. Searches, orderings and components are worked out here once so
+// problems with graphs of their own don't each need their own copy.
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::aocbase::{AOCError, AOCResult};
+
+#[derive(Debug, Clone)]
+pub struct Graph<N, E = ()> {
+    nodes: Vec<N>,
+    edges: Vec<Vec<usize>>,
+    // What goes with each edge, in the same order as edges.
+    edge_data: Vec<Vec<E>>,
+    // Nodes added by intern.
+    interned: HashMap<N, usize>,
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Self { nodes: Vec::new(), edges: Vec::new(), edge_data: Vec::new(), interned: HashMap::new() }
+    }
+}
+
+impl<N, E> Graph<N, E> {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Gives back the new node's number.
+    pub fn add_node(&mut self, node: N) -> usize {
+        self.nodes.push(node);
+        self.edges.push(Vec::new());
+        self.edge_data.push(Vec::new());
+        self.nodes.len() - 1
+    }
+
+    pub fn add_edge_with(&mut self, from: usize, to: usize, data: E) {
+        self.edges[from].push(to);
+        self.edge_data[from].push(data);
+    }
+
+    pub fn has_edge(&self, from: usize, to: usize) -> bool {
+        self.edges[from].contains(&to)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(|edges| edges.len()).sum()
+    }
+
+    pub fn node(&self, node: usize) -> &N {
+        &self.nodes[node]
+    }
+
+    pub fn node_mut(&mut self, node: usize) -> &mut N {
+        &mut self.nodes[node]
+    }
+
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn successors(&self, node: usize) -> &[usize] {
+        &self.edges[node]
+    }
+
+    // Where each of a node's edges go, with what goes with them.
+    pub fn edges_from(&self, node: usize) -> impl Iterator<Item = (usize, &E)> + '_ {
+        self.edges[node].iter().copied().zip(&self.edge_data[node])
+    }
+
+    // How many edges come into each node.
+    pub fn in_degrees(&self) -> Vec<usize> {
+        let mut in_degrees = vec![0; self.nodes.len()];
+        for edges in &self.edges {
+            for &to in edges {
+                in_degrees[to] += 1;
+            }
+        }
+        in_degrees
+    }
+}
+
+impl<N, E: Default> Graph<N, E> {
+
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.add_edge_with(from, to, E::default());
+    }
+}
+
+impl<N: Eq + Hash + Clone, E> Graph<N, E> {
+
+    // The node's number, adding it the first time it's seen.
+    pub fn intern(&mut self, node: N) -> usize {
+        if let Some(&existing) = self.interned.get(&node) {
+            return existing;
+        }
+        let added = self.add_node(node.clone());
+        self.interned.insert(node, added);
+        added
+    }
+
+    // Only finds nodes that were interned.
+    pub fn find(&self, node: &N) -> Option<usize> {
+        self.interned.get(node).copied()
+    }
+}
+
+// Nodes in the order a breadth first search from start reaches them,
+// following each node's edges in the order they were added.
+pub fn bfs_order<N, E>(graph: &Graph<N, E>, start: usize) -> Vec<usize> {
+    bfs(graph, start).0
+}
+
+// How many edges it takes to get to each node from start, if it can be got to.
+pub fn bfs_distances<N, E>(graph: &Graph<N, E>, start: usize) -> Vec<Option<usize>> {
+    bfs(graph, start).1
+}
+
+fn bfs<N, E>(graph: &Graph<N, E>, start: usize) -> (Vec<usize>, Vec<Option<usize>>) {
+    let mut distances: Vec<Option<usize>> = vec![None; graph.node_count()];
+    distances[start] = Some(0);
+    let mut order: Vec<usize> = Vec::new();
+    let mut queue: VecDeque<usize> = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        let distance = distances[node].unwrap() + 1;
+        for &next in graph.successors(node) {
+            if distances[next].is_none() {
+                distances[next] = Some(distance);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    (order, distances)
+}
+
+// Nodes in the order a depth first search from start first gets to them,
+// following each node's edges in the order they were added.
+pub fn dfs_order<N, E>(graph: &Graph<N, E>, start: usize) -> Vec<usize> {
+    let mut seen = vec![false; graph.node_count()];
+    let mut order: Vec<usize> = Vec::new();
+    let mut stack: Vec<usize> = vec![start];
+
+    while let Some(node) = stack.pop() {
+        if seen[node] {
+            continue;
+        }
+        seen[node] = true;
+        order.push(node);
+        // Reversed so the first edge is followed first.
+        stack.extend(graph.successors(node).iter().rev().filter(|next| !seen[**next]));
+    }
+
+    order
+}
+
+// Nodes joined by edges going either way. Each component's nodes are in
+// order, and components are in order of their lowest node.
+pub fn connected_components<N, E>(graph: &Graph<N, E>) -> Vec<Vec<usize>> {
+    let count = graph.node_count();
+    let mut either_way: Vec<Vec<usize>> = vec![Vec::new(); count];
+    for from in 0 .. count {
+        for &to in graph.successors(from) {
+            either_way[from].push(to);
+            either_way[to].push(from);
+        }
+    }
+
+    let mut component_of: Vec<Option<usize>> = vec![None; count];
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    for root in 0 .. count {
+        if component_of[root].is_some() {
+            continue;
+        }
+
+        let component = components.len();
+        let mut members: Vec<usize> = vec![root];
+        component_of[root] = Some(component);
+        let mut at = 0;
+        while at < members.len() {
+            let node = members[at];
+            at += 1;
+            for &next in &either_way[node] {
+                if component_of[next].is_none() {
+                    component_of[next] = Some(component);
+                    members.push(next);
+                }
+            }
+        }
+
+        members.sort_unstable();
+        components.push(members);
+    }
+
+    components
+}
+
+// Kahn's algorithm. Every node comes before the nodes its edges go to, and
+// nodes that could go in either order keep the order they were added in. A
+// graph with a cycle has no such order.
+pub fn topological_sort<N, E>(graph: &Graph<N, E>) -> AOCResult<Vec<usize>> {
+    let mut in_degrees = graph.in_degrees();
+    let mut ready: Vec<usize> = (0 .. graph.node_count()).filter(|n| in_degrees[*n] == 0).rev().collect();
+    let mut order: Vec<usize> = Vec::with_capacity(graph.node_count());
+
+    while let Some(node) = ready.pop() {
+        order.push(node);
+        let start = ready.len();
+        for &next in graph.successors(node) {
+            in_degrees[next] -= 1;
+            if in_degrees[next] == 0 {
+                ready.push(next);
+            }
+        }
+        // Lowest numbered first, as they're taken off the end.
+        ready[start ..].sort_by(|a, b| b.cmp(a));
+    }
+
+    if order.len() != graph.node_count() {
+        return Err(AOCError::ProcessingError(format!(
+            "Graph has a cycle, only {} of {} nodes could be ordered.", order.len(), graph.node_count())));
+    }
+
+    Ok(order)
+}
+
+// Tarjan's algorithm, without recursion so deep graphs don't run out of
+// stack. A component is only finished after every component it has edges to,
+// so later components can build on the ones before them.
+pub fn strongly_connected_components<N, E>(graph: &Graph<N, E>) -> Vec<Vec<usize>> {
+    let count = graph.node_count();
+    let mut index: Vec<Option<usize>> = vec![None; count];
+    let mut low_link: Vec<usize> = vec![0; count];
+    let mut on_stack: Vec<bool> = vec![false; count];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut next_index = 0;
+    let mut components: Vec<Vec<usize>> = Vec::new();
+
+    // Each node being visited with how many of its edges have been followed.
+    let mut visiting: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0 .. count {
+        if index[root].is_some() {
+            continue;
+        }
+        visiting.push((root, 0));
+
+        while let Some((node, edge)) = visiting.pop() {
+            if edge == 0 {
+                index[node] = Some(next_index);
+                low_link[node] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+
+            if let Some(&next) = graph.successors(node).get(edge) {
+                visiting.push((node, edge + 1));
+                match index[next] {
+                    None => visiting.push((next, 0)),
+                    Some(next_index) if on_stack[next] => low_link[node] = low_link[node].min(next_index),
+                    Some(_) => {},
+                }
+                continue;
+            }
+
+            // All of node's edges are done.
+            if Some(low_link[node]) == index[node] {
+                let mut component: Vec<usize> = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+
+            if let Some((parent, _)) = visiting.last() {
+                low_link[*parent] = low_link[*parent].min(low_link[node]);
+            }
+        }
+    }
+
+    components
+}
+
+// The components as a graph of their own, which never has a cycle. Each
+// component node holds its members and the component each original node
+// went into is given back too. Components are numbered in the order
+// strongly_connected_components gives them.
+pub fn condensation<N, E>(graph: &Graph<N, E>) -> (Graph<Vec<usize>>, Vec<usize>) {
+    let components = strongly_connected_components(graph);
+
+    let mut component_of: Vec<usize> = vec![0; graph.node_count()];
+    for (component, members) in components.iter().enumerate() {
+        for &member in members {
+            component_of[member] = component;
+        }
+    }
+
+    let mut condensed: Graph<Vec<usize>> = Graph::new();
+    for members in &components {
+        condensed.add_node(members.clone());
+    }
+
+    for (component, members) in components.iter().enumerate() {
+        let mut targets: Vec<usize> = members
+            .iter()
+            .flat_map(|member| graph.successors(*member))
+            .map(|next| component_of[*next])
+            .filter(|target| *target != component)
+            .collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        for target in targets {
+            condensed.add_edge(component, target);
+        }
+    }
+
+    (condensed, component_of)
+}
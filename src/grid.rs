@@ -0,0 +1,281 @@
+
+// Grid helpers shared between problems.
+
+use std::ops::{Index, IndexMut};
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
+
+// Cells that can be read from one character of a puzzle map.
+pub trait FromChar: Sized {
+    fn from_char(c: u8) -> AOCResult<Self>;
+}
+
+impl FromChar for u8 {
+    fn from_char(c: u8) -> AOCResult<Self> {
+        Ok(c)
+    }
+}
+
+// Digits, for maps of numbers.
+impl FromChar for i32 {
+    fn from_char(c: u8) -> AOCResult<Self> {
+        if c.is_ascii_digit() {
+            Ok((c - b'0') as i32)
+        }
+        else {
+            Err(AOCError::ParseError(format!("Invalid number character: {}", c as char)))
+        }
+    }
+}
+
+const NEIGHBOR_OFFSETS_4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+const NEIGHBOR_OFFSETS_8: [(isize, isize); 8] = [
+    (-1, -1), (-1, 0), (-1, 1),
+    (0, -1), (0, 1),
+    (1, -1), (1, 0), (1, 1),
+];
+
+// A width x height grid stored row-major. Positions are (y, x) like the
+// problems use, with y going down the rows.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+
+    pub fn new(width: usize, height: usize, value: T) -> Self
+        where T: Clone
+    {
+        Self { width, height, cells: vec![value; width * height] }
+    }
+
+    // Every row has to be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> AOCResult<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+
+        if let Some(y) = rows.iter().position(|row| row.len() != width) {
+            return Err(AOCError::ParseError(format!("Row {} is {} wide, expected {}.", y + 1, rows[y].len(), width)));
+        }
+
+        Ok(Self { width, height, cells: rows.into_iter().flatten().collect() })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, y: usize, x: usize) -> bool {
+        y < self.height && x < self.width
+    }
+
+    pub fn get(&self, y: usize, x: usize) -> Option<&T> {
+        if self.contains(y, x) {
+            self.cells.get(y * self.width + x)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, y: usize, x: usize) -> Option<&mut T> {
+        if self.contains(y, x) {
+            self.cells.get_mut(y * self.width + x)
+        }
+        else {
+            None
+        }
+    }
+
+    // The position moved by (dy, dx), if that's still on the grid.
+    pub fn offset(&self, y: usize, x: usize, dy: isize, dx: isize) -> Option<(usize, usize)> {
+        let y = y.checked_add_signed(dy)?;
+        let x = x.checked_add_signed(dx)?;
+        if self.contains(y, x) { Some((y, x)) } else { None }
+    }
+
+    // Up, right, down and left of a position, leaving out any off the grid.
+    pub fn neighbors4(&self, y: usize, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOR_OFFSETS_4.iter().filter_map(move |(dy, dx)| self.offset(y, x, *dy, *dx))
+    }
+
+    // The 4 neighbors and the diagonals.
+    pub fn neighbors8(&self, y: usize, x: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        NEIGHBOR_OFFSETS_8.iter().filter_map(move |(dy, dx)| self.offset(y, x, *dy, *dx))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width .. (y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks_exact(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0 .. self.width).map(move |x| self.column(x))
+    }
+
+    // Every cell with its position, a row at a time.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, value)| ((i / self.width, i % self.width), value))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0 .. self.height).flat_map(move |y| (0 .. width).map(move |x| (y, x)))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+        where F: FnMut(&T) -> U
+    {
+        Grid { width: self.width, height: self.height, cells: self.cells.iter().map(f).collect() }
+    }
+
+    pub fn render<F>(&self, f: F) -> String
+        where F: Fn(&T) -> char
+    {
+        let mut output = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            output.extend(row.iter().map(&f));
+            output.push('\n');
+        }
+        output
+    }
+}
+
+impl<T: FromChar> Grid<T> {
+
+    pub fn parse_lines<L: AsRef<[u8]>>(lines: &[L]) -> AOCResult<Self> {
+        let rows = lines
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                line.as_ref()
+                    .iter()
+                    .map(|c| T::from_char(*c))
+                    .collect::<AOCResult<Vec<T>>>()
+                    .map_err(|e| match e {
+                        AOCError::ParseError(reason) => AOCError::ParseError(format!("line {}: {}", y + 1, reason)),
+                        e => e,
+                    })
+            })
+            .collect::<AOCResult<Vec<Vec<T>>>>()?;
+
+        if rows.is_empty() {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+
+        Self::from_rows(rows)
+    }
+
+    // The whole file is one map. A blank line at the end is left off.
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
+        let mut lines = read_lines_as_bytes(input)?;
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        Self::parse_lines(&lines)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (y, x): (usize, usize)) -> &T {
+        self.get(y, x).unwrap_or_else(|| panic!("({}, {}) outside of {}x{}", y, x, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+
+    fn index_mut(&mut self, (y, x): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(y, x).unwrap_or_else(|| panic!("({}, {}) outside of {}x{}", y, x, width, height))
+    }
+}
+
+// A width x height grid of bits stored row-major, one bit per cell. Much
+// cheaper than a HashSet of positions for tracking visited/occupied cells.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, bits: vec![0; (width * height).div_ceil(64)] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, y: usize, x: usize) -> (usize, u64) {
+        debug_assert!(y < self.height && x < self.width, "({}, {}) outside of {}x{}", y, x, self.width, self.height);
+        let bit = y * self.width + x;
+        (bit / 64, 1 << (bit % 64))
+    }
+
+    pub fn get(&self, y: usize, x: usize) -> bool {
+        let (word, mask) = self.index(y, x);
+        self.bits[word] & mask != 0
+    }
+
+    pub fn set(&mut self, y: usize, x: usize, value: bool) {
+        let (word, mask) = self.index(y, x);
+        if value {
+            self.bits[word] |= mask;
+        }
+        else {
+            self.bits[word] &= !mask;
+        }
+    }
+
+    // Sets the bit and returns true if it wasn't already set, like HashSet::insert.
+    pub fn insert(&mut self, y: usize, x: usize) -> bool {
+        let (word, mask) = self.index(y, x);
+        let was_set = self.bits[word] & mask != 0;
+        self.bits[word] |= mask;
+        !was_set
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    // Sets every bit that's set in other, which must be the same size.
+    pub fn union_with(&mut self, other: &BitGrid) {
+        debug_assert!(self.width == other.width && self.height == other.height);
+        for (word, other_word) in self.bits.iter_mut().zip(&other.bits) {
+            *word |= other_word;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+}
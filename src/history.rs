@@ -0,0 +1,169 @@
+// Remembers when each problem's answer was first known to be right, so we can
+// see when each star was earned.
+
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::run::parse_number;
+
+pub const DEFAULT_HISTORY_FILE: &str = "results/history.csv";
+
+pub struct AnswerHistory {
+    path: PathBuf,
+    // Problem name to seconds since the epoch.
+    first_correct: BTreeMap<String, u64>,
+}
+
+impl AnswerHistory {
+
+    pub fn load(path: impl AsRef<Path>) -> AOCResult<Self> {
+        let path = path.as_ref();
+        let mut first_correct: BTreeMap<String, u64> = BTreeMap::new();
+
+        if path.is_file() {
+            let mut csv_in = csv::Reader::from_path(path)?;
+            for record in csv_in.records() {
+                let record = record?;
+
+                let problem = record
+                    .get(0)
+                    .ok_or(AOCError::ParseError("Problem field not present.".into()))?;
+
+                let timestamp = record
+                    .get(1)
+                    .ok_or(AOCError::ParseError("FirstCorrect field not present.".into()))?
+                    .parse::<u64>()?;
+
+                first_correct.insert(problem.into(), timestamp);
+            }
+        }
+
+        Ok(AnswerHistory { path: path.into(), first_correct })
+    }
+
+    // Only the first time counts. Returns true if this is it.
+    pub fn record_correct(&mut self, problem: impl AsRef<str>, timestamp: u64) -> bool {
+        if self.first_correct.contains_key(problem.as_ref()) {
+            return false;
+        }
+        self.first_correct.insert(problem.as_ref().into(), timestamp);
+        true
+    }
+
+    pub fn save(&self) -> AOCResult<()> {
+        if let Some(parent) = self.path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut csv_out = csv::Writer::from_path(&self.path)?;
+        csv_out.write_record(["Problem", "FirstCorrect"])?;
+
+        for (problem, timestamp) in &self.first_correct {
+            csv_out.write_record([problem.clone(), timestamp.to_string()])?;
+        }
+
+        Ok(())
+    }
+
+    // One line per day of the 2023 calendar with its stars and when they were earned.
+    pub fn stars_calendar(&self) -> String {
+        let mut stars_by_day: BTreeMap<i32, [Option<u64>; 2]> = BTreeMap::new();
+        for (problem, timestamp) in &self.first_correct {
+            let day = match parse_number(problem) {
+                Ok(day) => day,
+                Err(_) => continue,
+            };
+            let part = if problem.ends_with("part2") { 1 } else { 0 };
+            stars_by_day.entry(day).or_insert([None, None])[part] = Some(*timestamp);
+        }
+
+        let star_count: usize = stars_by_day
+            .values()
+            .map(|parts| parts.iter().filter(|p| p.is_some()).count())
+            .sum();
+
+        let mut calendar = format!("2023 stars: {}/50\n", star_count);
+
+        for day in 1..=25 {
+            let parts = stars_by_day.get(&day).copied().unwrap_or([None, None]);
+            let stars: String = parts.iter().map(|p| if p.is_some() { '*' } else { ' ' }).collect();
+
+            calendar.push_str(&format!("Day {:>2} {}", day, stars));
+            for (part, timestamp) in parts.iter().enumerate() {
+                if let Some(timestamp) = timestamp {
+                    calendar.push_str(&format!("  part{}: {}", part + 1, format_timestamp(*timestamp)));
+                }
+            }
+            calendar.push('\n');
+        }
+
+        calendar
+    }
+}
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Formats as "YYYY-MM-DD HH:MM UTC". Days to a civil date is Howard Hinnant's
+// algorithm, which saves pulling in a date crate for one line of output.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds_of_day = timestamp % 86400;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year, month, day, seconds_of_day / 3600, (seconds_of_day % 3600) / 60)
+}
+
+// The start of a "YYYY-MM-DD" day in UTC, the other way round from
+// format_timestamp.
+pub fn parse_date(date: &str) -> AOCResult<u64> {
+    let invalid = || AOCError::ParseError(format!("Invalid date, expected YYYY-MM-DD: {}", date));
+
+    let parts: Vec<i64> = date
+        .trim()
+        .split('-')
+        .map(|part| part.parse::<i64>().map_err(|_| invalid()))
+        .collect::<AOCResult<Vec<i64>>>()?;
+
+    let (year, month, day) = match parts[..] {
+        [year, month, day] if year >= 1970 && (1 ..= 12).contains(&month) && (1 ..= days_in_month(year, month)).contains(&day) => (year, month, day),
+        _ => return Err(invalid()),
+    };
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Ok(days as u64 * 86400)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
@@ -0,0 +1,43 @@
+pub mod problems;
+pub mod registry;
+pub mod aocbase;
+pub mod answer;
+pub mod aocio;
+pub mod aocfetch;
+pub mod regex_ext;
+pub mod mathx;
+pub mod linalg;
+pub mod ranges;
+pub mod geometry;
+pub mod grid;
+pub mod algorithms;
+pub mod graph;
+pub mod pool;
+pub mod parallel;
+pub mod memory;
+pub mod settings;
+pub mod log;
+pub mod progress;
+pub mod strategy;
+pub mod history;
+pub mod run_history;
+pub mod verification;
+pub mod samples;
+pub mod snapshots;
+pub mod report;
+pub mod visualize;
+pub mod explore;
+pub mod bench;
+pub mod gen;
+pub mod stress;
+pub mod cache;
+pub mod sha256;
+pub mod doctor;
+
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "repl")]
+pub mod repl;
+
+pub mod run;
@@ -0,0 +1,189 @@
+// Exact linear algebra, for systems where floating point, or even BigDecimal,
+// rounds its way to an answer that's close but wrong. Numbers are fractions
+// of big integers, so nothing is ever rounded and a solution either checks
+// out exactly or there isn't one.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use bigdecimal::num_bigint::BigInt;
+use bigdecimal::{One, Signed, Zero};
+
+use crate::aocbase::{AOCError, AOCResult};
+
+// Always in lowest terms with a positive denominator, so equal values have
+// equal parts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: BigInt,
+    denom: BigInt,
+}
+
+fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while !b.is_zero() {
+        let r = &a % &b;
+        (a, b) = (b, r);
+    }
+    a
+}
+
+impl Rational {
+
+    pub fn new(numer: impl Into<BigInt>, denom: impl Into<BigInt>) -> AOCResult<Self> {
+        let denom = denom.into();
+        if denom.is_zero() {
+            return Err(AOCError::ProcessingError("Division by zero.".into()));
+        }
+        Ok(Self::reduced(numer.into(), denom))
+    }
+
+    pub fn zero() -> Self {
+        Self::from(0)
+    }
+
+    fn reduced(numer: BigInt, denom: BigInt) -> Self {
+        let divisor = gcd(&numer, &denom);
+        let (numer, denom) = if divisor.is_zero() || divisor.is_one() { (numer, denom) } else { (numer / &divisor, denom / &divisor) };
+        if denom.is_negative() { Self { numer: -numer, denom: -denom } } else { Self { numer, denom } }
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom.is_one()
+    }
+
+    pub fn to_integer(&self) -> Option<BigInt> {
+        self.is_integer().then(|| self.numer.clone())
+    }
+
+    pub fn recip(&self) -> AOCResult<Self> {
+        Self::new(self.denom.clone(), self.numer.clone())
+    }
+
+    // Division that says so when it's by zero.
+    pub fn checked_div(&self, other: &Rational) -> AOCResult<Self> {
+        Ok(self * &other.recip()?)
+    }
+}
+
+impl<T: Into<BigInt>> From<T> for Rational {
+    fn from(n: T) -> Self {
+        Self { numer: n.into(), denom: BigInt::one() }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        }
+        else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.numer * &other.denom).cmp(&(&other.numer * &self.denom))
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+    fn add(self, other: &Rational) -> Rational {
+        Rational::reduced(&self.numer * &other.denom + &other.numer * &self.denom, &self.denom * &other.denom)
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+    fn sub(self, other: &Rational) -> Rational {
+        Rational::reduced(&self.numer * &other.denom - &other.numer * &self.denom, &self.denom * &other.denom)
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::reduced(&self.numer * &other.numer, &self.denom * &other.denom)
+    }
+}
+
+// Panics on zero like integer division does, checked_div doesn't.
+impl Div for &Rational {
+    type Output = Rational;
+    fn div(self, other: &Rational) -> Rational {
+        self.checked_div(other).expect("Rational division by zero")
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational { numer: -&self.numer, denom: self.denom.clone() }
+    }
+}
+
+// Solves equations in the given number of unknowns, as rows of coefficients
+// with the right hand side last, by Gaussian elimination. There can be more
+// equations than unknowns, as long as they all agree, and it's an error if
+// they don't or if there aren't enough independent ones to pin every unknown
+// down, no equations at all included.
+pub fn solve(mut rows: Vec<Vec<Rational>>, unknowns: usize) -> AOCResult<Vec<Rational>> {
+    if rows.iter().any(|row| row.len() != unknowns + 1) {
+        return Err(AOCError::ProcessingError(format!("Equations should each have {} unknowns.", unknowns)));
+    }
+    if rows.len() < unknowns {
+        return Err(AOCError::ProcessingError(format!("{} equations can't solve for {} unknowns.", rows.len(), unknowns)));
+    }
+
+    for col in 0 .. unknowns {
+        let pivot = (col .. rows.len())
+            .find(|row| !rows[*row][col].is_zero())
+            .ok_or_else(|| AOCError::ProcessingError(format!("Not enough independent equations to solve for unknown {}.", col)))?;
+        rows.swap(col, pivot);
+
+        let pivot_value = rows[col][col].clone();
+        rows[col] = rows[col].iter().map(|value| value / &pivot_value).collect();
+
+        for row in 0 .. rows.len() {
+            if row == col || rows[row][col].is_zero() {
+                continue;
+            }
+            let factor = rows[row][col].clone();
+            let reduced: Vec<Rational> = rows[row]
+                .iter()
+                .zip(&rows[col])
+                .map(|(value, pivot_row_value)| value - &(&factor * pivot_row_value))
+                .collect();
+            rows[row] = reduced;
+        }
+    }
+
+    // Anything left over is down to 0 = something, which had better be 0.
+    if rows[unknowns ..].iter().any(|row| !row[unknowns].is_zero()) {
+        return Err(AOCError::ProcessingError("Equations are inconsistent.".into()));
+    }
+
+    Ok(rows.into_iter().take(unknowns).map(|mut row| row.pop().unwrap()).collect())
+}
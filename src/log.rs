@@ -0,0 +1,173 @@
+// Diagnostics from inside problems, at a level picked on the command line
+// instead of by editing code. -v shows info, -vv debug and -vvv trace, and
+// --log problem21=debug turns one problem up without the rest.
+//
+// Problems log with the tracing macros. Each problem runs in a span of its
+// own, so messages are tagged with the problem they came from. The problem
+// being solved is also kept per thread for the filter, since problems can
+// run on threads of their own.
+
+use std::cell::RefCell;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use tracing::span::EnteredSpan;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::prelude::*;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+
+    // From the number of times -v was given.
+    pub fn from_verbosity(count: u8) -> Self {
+        match count {
+            0 => Level::Warn,
+            1 => Level::Info,
+            2 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    // Errors are always shown, so they count as warnings.
+    fn from_tracing(level: &tracing::Level) -> Self {
+        match *level {
+            tracing::Level::ERROR | tracing::Level::WARN => Level::Warn,
+            tracing::Level::INFO => Level::Info,
+            tracing::Level::DEBUG => Level::Debug,
+            tracing::Level::TRACE => Level::Trace,
+        }
+    }
+}
+
+impl FromStr for Level {
+    type Err = AOCError;
+
+    fn from_str(s: &str) -> AOCResult<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(AOCError::ParseError(format!("Unknown log level: {}", s))),
+        }
+    }
+}
+
+// The level for everything, and levels for problems given with --log. An
+// entry like problem21 covers both parts, problem21::part2 just the one.
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    level: Level,
+    problems: Vec<(String, Level)>,
+}
+
+impl LogFilter {
+
+    pub fn parse(level: Level, entries: &[String]) -> AOCResult<Self> {
+        let mut problems: Vec<(String, Level)> = Vec::new();
+
+        for entry in entries.iter().flat_map(|entry| entry.split(',')).filter(|entry| !entry.trim().is_empty()) {
+            let (name, level) = entry
+                .split_once('=')
+                .ok_or_else(|| AOCError::ParseError(format!("Invalid log setting, expected problem=level: {}", entry)))?;
+            problems.push((name.trim().to_string(), level.parse()?));
+        }
+
+        Ok(Self { level, problems })
+    }
+
+    // The most specific entry wins, so problem21::part2 beats problem21.
+    pub fn level_for(&self, problem: Option<&str>) -> Level {
+        let problem = match problem {
+            None => return self.level,
+            Some(problem) => problem,
+        };
+
+        self.problems
+            .iter()
+            .filter(|(name, _)| problem == name || problem.strip_prefix(name.as_str()).is_some_and(|rest| rest.starts_with("::")))
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.level, |(_, level)| *level)
+    }
+
+    // The most that's on for any problem.
+    pub fn max_level(&self) -> Level {
+        self.problems.iter().map(|(_, level)| *level).fold(self.level, Level::max)
+    }
+}
+
+// Kept apart from the settings so messages that are off cost next to nothing.
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Warn as u8);
+
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+thread_local! {
+    static CURRENT_PROBLEM: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// Messages on this thread are for the problem until the guard is dropped.
+pub struct ProblemSpan {
+    previous: Option<String>,
+    _entered: EnteredSpan,
+}
+
+impl Drop for ProblemSpan {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT_PROBLEM.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+pub fn enter_problem(name: &str) -> ProblemSpan {
+    let previous = CURRENT_PROBLEM.with(|current| current.borrow_mut().replace(name.to_string()));
+    let _entered = tracing::info_span!("problem", name).entered();
+    ProblemSpan { previous, _entered }
+}
+
+pub fn current_problem() -> Option<String> {
+    CURRENT_PROBLEM.with(|current| current.borrow().clone())
+}
+
+pub fn enabled(level: Level) -> bool {
+    if level as u8 > MAX_LEVEL.load(Ordering::Relaxed) {
+        return false;
+    }
+    let filter = settings::log_filter();
+    CURRENT_PROBLEM.with(|current| level <= filter.level_for(current.borrow().as_deref()))
+}
+
+// Sends tracing events to stderr, filtered by the settings, so they never
+// mix with answers on stdout. Spans are always on so messages keep the
+// problem they came from.
+pub fn init() {
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_target(false)
+        .without_time()
+        .with_filter(filter_fn(|metadata| metadata.is_span() || enabled(Level::from_tracing(metadata.level()))));
+    let _ = tracing_subscriber::registry().with(layer).try_init();
+}
@@ -1,88 +1,280 @@
-mod problems;
-mod aocbase;
-mod aocio;
-mod regex_ext;
-mod mathx;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[macro_use]
-mod run;
+use clap::{ArgAction, Parser, Subcommand};
 
-use clap::Parser;
+use rook_aoc_2023::settings;
+use rook_aoc_2023::history;
+use rook_aoc_2023::verification::{self, ExpectedAnswers};
+use rook_aoc_2023::bench::{self, BenchResult};
+use rook_aoc_2023::doctor;
+use rook_aoc_2023::samples;
+use rook_aoc_2023::snapshots;
+use rook_aoc_2023::report;
+use rook_aoc_2023::stress;
+use rook_aoc_2023::visualize;
+#[cfg(feature = "tui")]
+use rook_aoc_2023::tui;
+#[cfg(feature = "repl")]
+use rook_aoc_2023::{explore, repl};
 
-use run::{Problem, ProblemResult, ProblemResults};
-use aocbase::AOCResult;
+use rook_aoc_2023::run::{self, AnswerStatus, Problem, ProblemResult, ProblemResults, ResultRecord, RunBudget};
+use rook_aoc_2023::aocbase::{AOCResult, AOCError};
+use rook_aoc_2023::settings::{ProblemParams, Settings};
+use rook_aoc_2023::aocio::{artifact_path, InputSource, ParsePolicy};
+use rook_aoc_2023::log::{self, Level, LogFilter};
+#[cfg(any(feature = "memory-limit", feature = "peak-memory"))]
+use rook_aoc_2023::memory::CountingAllocator;
+use rook_aoc_2023::history::{AnswerHistory, DEFAULT_HISTORY_FILE};
+use rook_aoc_2023::run_history::{self, PrunePolicy, RunHistory, DEFAULT_RUN_HISTORY_DIR, DEFAULT_TREND_RUNS};
+
+#[cfg(any(feature = "memory-limit", feature = "peak-memory"))]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 const DEFAULT_RESULT_FILE: &str = "results/latest.csv";
 const DEFAULT_LAST_RESULT_FILE: &str = "results/last.csv";
 
-fn get_problems() -> Vec<Problem> {
-    problems![
-        problem1::part1,
-        problem1::part2,
-        problem2::part1,
-        problem2::part2,
-        problem3::part1,
-        problem3::part2,
-        problem4::part1,
-        problem4::part2,
-        problem5::part1,
-        problem5::part2,
-        problem6::part1,
-        problem6::part2,
-        problem7::part1,
-        problem7::part2,
-        problem8::part1,
-        problem8::part2,
-        problem9::part1,
-        problem9::part2,
-        problem10::part1,
-        problem10::part2,
-        problem11::part1,
-        problem11::part2,
-        problem12::part1,
-        problem12::part2,
-        problem13::part1,
-        problem13::part2,
-        problem14::part1,
-        problem14::part2,
-        problem15::part1,
-        problem15::part2,
-        problem16::part1,
-        problem16::part2,
-        problem17::part1,
-        problem17::part2,
-        problem18::part1,
-        problem18::part2,
-        problem19::part1,
-        problem19::part2,
-        problem20::part1,
-        problem20::part2,
-        problem21::part1,
-        problem21::part2,
-        problem22::part1,
-        problem22::part2,
-        problem23::part1,
-        problem23::part2,
-        problem24::part1,
-        problem24::part2,
-        problem25::part1,
-    ]
-}
-
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Which problems to run: names like problem17::part2, days like 17 or
+    /// problem17, day ranges like 5..10 and patterns like 'problem1*::part2',
+    /// separated by commas.
     #[arg(long, short)]
     problem: Option<String>,
 
+    /// Input file to run instead of each problem's own, or - to read it from stdin.
     #[arg(long, short)]
     input: Option<String>,
 
+    // Where --input ended up, since stdin can only be read once.
+    #[arg(skip)]
+    input_path: OnceLock<String>,
+
     #[arg(long, short)]
     result_file: Option<String>,
 
+    /// Results to compare the run with, results/last.csv by default. Answers
+    /// marked accepted-by-aoc here are marked the same in the result file as
+    /// long as they come out the same, or don't finish. Copy the result file
+    /// over this one after a run where every problem finished to keep it, and
+    /// its statuses, as the one to compare with.
     #[arg(long, short)]
     last_result_file: Option<String>,
+
+    /// Check answers against a CSV of known answers and exit with an error
+    /// status if any are different.
+    #[arg(long, value_name = "FILE")]
+    expected: Option<String>,
+
+    /// Run each problem against the puzzle's example in input/sample_NN.txt
+    /// and check the answer, instead of running the real input.
+    #[arg(long, alias = "example")]
+    samples: bool,
+
+    /// Where to keep when each answer was first known to be right.
+    #[arg(long)]
+    history_file: Option<String>,
+
+    /// Show how long each problem took over the last N recorded runs, 5 if no
+    /// number is given, and how that changed, instead of running anything.
+    #[arg(long, value_name = "N")]
+    show_trend: Option<Option<usize>>,
+
+    /// Seconds to wait for each problem before giving up on it. A problem
+    /// given up on can't be stopped, so unless it checks its deadline it keeps
+    /// running in the background until the run ends, and problems timed after
+    /// it share the CPU with it.
+    #[arg(long, short)]
+    timeout: Option<f64>,
+
+    /// Run this many problems at once, or one per core if no number is given.
+    /// Can't be used with --memory-limit, and --tui always runs one at a time.
+    #[arg(long, value_name = "N")]
+    jobs: Option<Option<usize>>,
+
+    /// Megabytes each problem can allocate before it's given up on.
+    #[cfg(feature = "memory-limit")]
+    #[arg(long, value_name = "MB")]
+    memory_limit: Option<usize>,
+
+    /// Seconds for the whole run. Problems are run fastest first and any left
+    /// when time is up are marked as not run. The one cut off keeps running in
+    /// the background, the same as with --timeout.
+    #[arg(long)]
+    time_limit: Option<f64>,
+
+    /// Print extra information about how problems are being solved. Give it
+    /// twice for debug messages and three times for trace messages.
+    #[arg(long, short, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Log level for a problem or part, like problem21=debug or
+    /// problem25::part1=trace. Can be repeated or comma separated.
+    #[arg(long = "log", value_name = "PROBLEM=LEVEL")]
+    log: Vec<String>,
+
+    /// Use the solving strategy with this name for problems that have one.
+    #[arg(long)]
+    strategy: Option<String>,
+
+    /// Cross check answers with slower brute force solutions where problems have them.
+    #[arg(long)]
+    verify: bool,
+
+    /// Don't use or save cached preprocessing of inputs.
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Before solving, check problems that work answers out in pieces against
+    /// a direct simulation of a small case, for problems that have one.
+    #[arg(long)]
+    self_test: bool,
+
+    /// Only print name<TAB>answer for each problem, for scripts to read.
+    #[arg(long)]
+    print_answer_only: bool,
+
+    /// Show how answers are worked out, for problems that can.
+    #[arg(long)]
+    explain: bool,
+
+    /// Skip input lines that fail to parse instead of failing, for days 2, 4, 7,
+    /// 9, 12, 18 and 24 where each line stands on its own. Gives up after the
+    /// optional maximum number of bad lines.
+    #[arg(long, value_name = "MAX_ERRORS")]
+    skip_invalid: Option<Option<usize>>,
+
+    /// Session cookie from adventofcode.com, used to download missing inputs.
+    /// AOC_SESSION is used if this isn't given.
+    #[arg(long)]
+    session: Option<String>,
+
+    /// Show how far along slow problems are on stderr.
+    #[arg(long)]
+    progress: bool,
+
+    /// Draw problems that simulate something as they go, into a GIF per problem
+    /// named after FILE, or on the terminal with -. Problems 14, 16 and 22 can.
+    #[arg(long, value_name = "FILE")]
+    animate: Option<String>,
+
+    /// Set a named value for problems that take one, like problem8.start=AAA. Can be repeated.
+    #[arg(long = "param", value_name = "NAME=VALUE")]
+    params: Vec<String>,
+
+    /// Show a live table of the problems as they run.
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    tui: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show which 2023 stars have been earned and when.
+    Stars,
+
+    /// Run each selected problem many times and show timing statistics.
+    /// Cached preprocessing is never used, so every run does all the work.
+    Bench {
+        /// Timed runs of each problem.
+        #[arg(long, default_value_t = 10)]
+        iterations: usize,
+
+        /// Runs before the timed ones that aren't counted.
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+
+        /// Time each problem once per strategy, like astar,buckets, to compare them.
+        #[arg(long, value_delimiter = ',')]
+        strategies: Vec<String>,
+    },
+
+    /// Check for common setup problems, like missing inputs, and how to fix them.
+    Doctor,
+
+    /// Check what problems draw of their example inputs against the copies
+    /// in tests/snapshots.
+    Snapshots {
+        /// Write the snapshots out again instead of checking them.
+        #[arg(long)]
+        update: bool,
+    },
+
+    /// Write up the results in the result file as Markdown, with a table of
+    /// answers and a chart of how long each problem took.
+    Report {
+        /// Write HTML with the chart drawn as an SVG instead.
+        #[arg(long)]
+        html: bool,
+
+        /// Where to write the report, instead of stdout.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<String>,
+    },
+
+    /// Remove runs from the run history. Runs older than --older-than or
+    /// before the last --keep-last go, and with --keep-best-per-commit only
+    /// the fastest run of each commit is kept, whatever else says.
+    Prune {
+        /// Keep this many of the most recent runs.
+        #[arg(long, value_name = "N")]
+        keep_last: Option<usize>,
+
+        /// Keep only the fastest run of each commit, for each set of problems run.
+        #[arg(long)]
+        keep_best_per_commit: bool,
+
+        /// Remove runs from before this day, as YYYY-MM-DD.
+        #[arg(long, value_name = "DATE")]
+        older_than: Option<String>,
+
+        /// Show what would be removed without removing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Parse a day's input and look around it with commands typed in, for
+    /// days that can be explored.
+    #[cfg(feature = "repl")]
+    Repl {
+        /// The day, like 19.
+        day: u32,
+    },
+
+    /// Solve random inputs with each of a day's strategies until they
+    /// disagree or the time's up. The input they disagree on is saved in
+    /// artifacts/stress.
+    Stress {
+        /// The day, like 23.
+        day: u32,
+
+        /// How long to keep making inputs for.
+        #[arg(long, default_value_t = 10.0)]
+        seconds: f64,
+
+        /// Where to start making inputs from, to repeat an earlier run.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// Draw what the selected problems work on, for those that can be drawn,
+    /// as SVG or PNG pictures.
+    Visualize {
+        /// Where to write the picture, as PNG if it ends in .png and SVG
+        /// otherwise. With more than one problem each gets its name added.
+        /// Goes in artifacts/visualize if not given.
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<String>,
+    },
 }
 
 impl Args {
@@ -101,6 +293,94 @@ impl Args {
         }
     }
 
+    pub fn get_history_file(&self) -> &str {
+        match &self.history_file {
+            None => DEFAULT_HISTORY_FILE,
+            Some(history_file) => history_file.as_str(),
+        }
+    }
+
+    // Every run is kept in a history directory next to the result file.
+    pub fn get_run_history_dir(&self) -> PathBuf {
+        match &self.result_file {
+            None => PathBuf::from(DEFAULT_RUN_HISTORY_DIR),
+            Some(result_file) => Path::new(result_file).with_file_name("history"),
+        }
+    }
+
+    pub fn get_timeout(&self) -> AOCResult<Option<Duration>> {
+        self.timeout.map(|seconds| seconds_to_duration("--timeout", seconds)).transpose()
+    }
+
+    #[cfg(feature = "memory-limit")]
+    pub fn get_memory_limit(&self) -> Option<usize> {
+        self.memory_limit.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    #[cfg(not(feature = "memory-limit"))]
+    pub fn get_memory_limit(&self) -> Option<usize> {
+        None
+    }
+
+    // More than one job runs problems in parallel.
+    pub fn get_jobs(&self) -> usize {
+        match self.jobs {
+            None => 1,
+            Some(None) => thread::available_parallelism().map_or(1, |n| n.get()),
+            Some(Some(jobs)) => jobs.max(1),
+        }
+    }
+
+    pub fn get_time_limit(&self) -> AOCResult<Option<Duration>> {
+        self.time_limit.map(|seconds| seconds_to_duration("--time-limit", seconds)).transpose()
+    }
+
+    pub fn get_settings(&self) -> AOCResult<Settings> {
+        Ok(Settings {
+            log_filter: LogFilter::parse(Level::from_verbosity(self.verbose), &self.log)?,
+            strategy: self.strategy.clone(),
+            verify: self.verify,
+            no_cache: self.no_cache,
+            self_test: self.self_test,
+            explain: self.explain,
+            parse_policy: self.get_parse_policy(),
+            params: ProblemParams::parse(&self.params)?,
+            session: self.session.clone(),
+            progress: self.progress,
+            animate: self.animate.clone(),
+        })
+    }
+
+    pub fn get_parse_policy(&self) -> ParsePolicy {
+        match self.skip_invalid {
+            None => ParsePolicy::Strict,
+            Some(max_errors) => ParsePolicy::SkipInvalid { max_errors: max_errors.unwrap_or(usize::MAX) },
+        }
+    }
+
+    // AOC doesn't change its mind, so an accepted answer stays accepted as long
+    // as we keep getting it, and anything else we get instead is wrong. A
+    // problem that didn't finish this time hasn't changed its answer, so it
+    // keeps the status too. Statuses are only ever marked by hand in the last
+    // result file, and come back to it when the result file is copied over.
+    pub fn carry_forward_statuses(&self, results: &mut Vec<ProblemResult>) -> AOCResult<()> {
+        let last_answers = ProblemResults::load_answers(self.get_last_result_file())?;
+        let last_statuses = ProblemResults::load_statuses(self.get_last_result_file())?;
+
+        for result in results {
+            if last_statuses.get(&result.name) != Some(&AnswerStatus::AcceptedByAoc) {
+                continue;
+            }
+            result.status = match (&result.result, last_answers.get(&result.name)) {
+                (Ok(answer), Some(last_answer)) if answer == last_answer => AnswerStatus::AcceptedByAoc,
+                (Ok(_), _) => AnswerStatus::Wrong,
+                (Err(_), _) => AnswerStatus::AcceptedByAoc,
+            };
+        }
+
+        Ok(())
+    }
+
     pub fn compare_with_last(&self, results: &Vec<ProblemResult>) -> AOCResult<()> {
         let last_results = ProblemResults::load_answers(self.get_last_result_file())?;
 
@@ -112,6 +392,16 @@ impl Args {
                 (Ok(answer), Some(last_answer)) if answer != last_answer => {
                     println!("Mismatch: [{}] {} != {}", &result.name, last_answer, answer);
                 },
+                // These aren't wrong answers, the problem just didn't get to finish.
+                (Err(AOCError::Timeout(msg)), _) => {
+                    println!("Timed out: [{}] {}", &result.name, msg);
+                },
+                (Err(AOCError::Unsupported(msg)), _) => {
+                    println!("Unsupported: [{}] {}", &result.name, msg);
+                },
+                (Err(AOCError::NotRun(msg)), _) => {
+                    println!("Not run: [{}] {}", &result.name, msg);
+                },
                 (Err(e), Some(last_answer)) if last_answer != "" => {
                     println!("Mismatch: [{}] {} != {}", &result.name, last_answer, e);
                 },
@@ -123,45 +413,466 @@ impl Args {
     }
 
     pub fn run(&self) -> AOCResult<()> {
-        let problems = get_problems();
+        settings::set(self.get_settings()?);
+
+        if let Some(Command::Stars) = &self.command {
+            print!("{}", AnswerHistory::load(self.get_history_file())?.stars_calendar());
+            return Ok(());
+        }
+
+        if let Some(Command::Prune { keep_last, keep_best_per_commit, older_than, dry_run }) = &self.command {
+            let policy = PrunePolicy {
+                keep_last: *keep_last,
+                keep_best_per_commit: *keep_best_per_commit,
+                older_than: older_than.as_deref().map(history::parse_date).transpose()?,
+            };
+            return self.run_prune(&policy, *dry_run);
+        }
+
+        if let Some(Command::Stress { day, seconds, seed }) = &self.command {
+            return self.run_stress(*day, *seconds, *seed);
+        }
+
+        let problems = run::registered_problems();
+
+        #[cfg(feature = "repl")]
+        if let Some(Command::Repl { day }) = &self.command {
+            return self.run_repl(&problems, *day);
+        }
     
-        let to_run: Vec<&Problem> = match &self.problem {
+        let mut to_run: Vec<&Problem> = match &self.problem {
             None => problems.iter().collect(),
-            Some(problem) => problems.iter().filter(|p| &p.name == problem).collect(),
+            Some(selection) => run::select_problems(&problems, selection)?,
         };
     
         if to_run.len() == 0 {
             panic!("There were no matching problems found to run!");
         }
+
+        // Rather than have the flag quietly do nothing.
+        if self.skip_invalid.is_some() {
+            let unsupported: Vec<&str> = to_run.iter().filter(|p| !p.skips_invalid).map(|p| p.name.as_str()).collect();
+            if !unsupported.is_empty() {
+                return Err(AOCError::Unsupported(format!("--skip-invalid isn't supported by: {}", unsupported.join(", "))));
+            }
+        }
+
+        if let Some(runs) = self.show_trend {
+            let runs = RunHistory::new(self.get_run_history_dir()).last_runs(runs.unwrap_or(DEFAULT_TREND_RUNS))?;
+            let names: Vec<&str> = to_run.iter().map(|p| p.name.as_str()).collect();
+            print!("{}", run_history::trend_report(&runs, &names));
+            return Ok(());
+        }
+
+        if let Some(Command::Doctor) = &self.command {
+            return self.run_doctor(&to_run);
+        }
+
+        if let Some(Command::Snapshots { update }) = &self.command {
+            return self.run_snapshots(&to_run, *update);
+        }
+
+        if let Some(Command::Report { html, output }) = &self.command {
+            return self.run_report(&to_run, *html, output.as_deref());
+        }
+
+        if let Some(Command::Visualize { output }) = &self.command {
+            return self.run_visualize(&to_run, output.as_deref());
+        }
+
+        if let Some(Command::Bench { iterations, warmup, strategies }) = &self.command {
+            return self.run_bench(&to_run, *warmup, *iterations, strategies);
+        }
+
+        if self.samples {
+            return self.run_samples(&to_run);
+        }
+
+        let budget = RunBudget::new(self.get_time_limit()?);
+        if budget.is_limited() {
+            let last_durations = ProblemResults::load_durations(self.get_last_result_file())?;
+            run::order_slowest_last(&mut to_run, &last_durations);
+        }
     
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return self.run_tui(&to_run, &budget, &problems);
+        }
+
+        if self.get_jobs() > 1 {
+            return self.run_parallel(&to_run, &budget, &problems);
+        }
+
         let mut results: Vec<ProblemResult> = Vec::new();
 
         for p in to_run {
-            let result = match &self.input {
-                None => {
-                    let input = p.get_default_input()?;
-                    p.run(&input)
-                },
-                Some(input) => {
-                    p.run(input)
+            if budget.is_spent() {
+                let result = ProblemResult::not_run(&p.name, "Out of time for the run.");
+                self.show_result(&result);
+                results.push(result);
+                continue;
+            }
+
+            let input = self.get_input(p)?;
+            let timeout = budget.limit_timeout(self.get_timeout()?);
+            let result = if self.print_answer_only {
+                p.execute(&input, timeout, self.get_memory_limit())
+            }
+            else {
+                p.run(&input, timeout, self.get_memory_limit())
+            };
+
+            self.show_result(&result);
+            results.push(result);
+        }
+
+        Self::sort_results(&mut results, &problems);
+        self.finish(results)
+    }
+
+    fn run_parallel(&self, to_run: &[&Problem], budget: &RunBudget, problems: &[Problem]) -> AOCResult<()> {
+        // The memory count is for the whole process so it can't tell
+        // problems running at the same time apart.
+        if self.get_memory_limit().is_some() {
+            return Err(AOCError::Unsupported("--memory-limit can't be used with --jobs.".into()));
+        }
+
+        let with_inputs = to_run
+            .iter()
+            .map(|p| Ok((*p, self.get_input(p)?)))
+            .collect::<AOCResult<Vec<(&Problem, String)>>>()?;
+
+        let mut results: Vec<ProblemResult> = Vec::new();
+
+        run::run_in_parallel(&with_inputs, self.get_jobs(), budget, self.get_timeout()?, |result| {
+            if !self.print_answer_only {
+                println!("--------------------------------------");
+            }
+            self.show_result(&result);
+            results.push(result);
+        });
+
+        Self::sort_results(&mut results, problems);
+        self.finish(results)
+    }
+
+    // Statistics go next to the results file as bench.csv.
+    fn run_bench(&self, to_run: &[&Problem], warmup: usize, iterations: usize, strategies: &[String]) -> AOCResult<()> {
+        let mut results: Vec<BenchResult> = Vec::new();
+
+        for p in to_run {
+            println!("Benchmarking: {}", p.name);
+            let input = self.get_input(p)?;
+            if strategies.is_empty() {
+                results.push(bench::bench(p, &input, warmup, iterations, self.get_timeout()?, self.get_memory_limit()));
+            }
+            else {
+                results.extend(bench::bench_strategies(p, &input, strategies, warmup, iterations, self.get_timeout()?, self.get_memory_limit()));
+            }
+        }
+
+        print!("{}", bench::to_table(&results));
+        bench::write_csv(Path::new(self.get_result_file()).with_file_name("bench.csv"), &results)
+    }
+
+    fn run_doctor(&self, to_run: &[&Problem]) -> AOCResult<()> {
+        let checks = doctor::run_checks(to_run, self.get_result_file());
+        print!("{}", doctor::report(&checks));
+
+        let failures = doctor::failure_count(&checks);
+        if failures > 0 {
+            eprintln!("{} checks failed.", failures);
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    // Only the snapshots of the selected problems' days.
+    fn run_snapshots(&self, to_run: &[&Problem], update: bool) -> AOCResult<()> {
+        let days: Vec<u32> = to_run
+            .iter()
+            .map(|p| run::parse_number(&p.name).map(|day| day as u32))
+            .collect::<AOCResult<Vec<u32>>>()?;
+
+        let selected: Vec<snapshots::Snapshot> = snapshots::snapshots()
+            .into_iter()
+            .filter(|snapshot| days.contains(&snapshot.day))
+            .collect();
+
+        let results = snapshots::check_all(&selected, update);
+        print!("{}", snapshots::report(&results));
+
+        let failures = snapshots::failure_count(&results);
+        if failures > 0 {
+            eprintln!("{} snapshots didn't match.", failures);
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    fn run_report(&self, to_run: &[&Problem], html: bool, output: Option<&str>) -> AOCResult<()> {
+        let records: Vec<ResultRecord> = ProblemResults::load(self.get_result_file())?
+            .into_iter()
+            .filter(|record| to_run.iter().any(|p| p.name == record.name))
+            .collect();
+
+        let title = format!("Advent of Code 2023 results from {}", self.get_result_file());
+        let document = if html {
+            report::html(&title, &records)
+        }
+        else {
+            report::markdown(&title, &records)
+        };
+
+        match output {
+            None => print!("{}", document),
+            Some(path) => {
+                if let Some(parent) = Path::new(path).parent() {
+                    fs::create_dir_all(parent)?;
                 }
+                fs::write(path, document)?;
+                println!("Wrote: {}", path);
+            },
+        }
+        Ok(())
+    }
+
+    fn run_prune(&self, policy: &PrunePolicy, dry_run: bool) -> AOCResult<()> {
+        if policy.is_empty() {
+            return Err(AOCError::ProcessingError(
+                "Nothing to prune by, give --keep-last, --keep-best-per-commit or --older-than.".into()));
+        }
+
+        let run_history = RunHistory::new(self.get_run_history_dir());
+        let (removed, bytes) = run_history.prune(policy, dry_run)?;
+        print!("{}", run_history::prune_report(&removed, bytes, dry_run));
+        println!("{}", run_history.usage()?.line(run_history.dir()));
+        Ok(())
+    }
+
+    // One picture per day, from the input the first selected part of it would use.
+    fn run_visualize(&self, to_run: &[&Problem], output: Option<&str>) -> AOCResult<()> {
+        let mut days: Vec<(u32, &Problem)> = Vec::new();
+        for p in to_run {
+            let day = run::parse_number(&p.name)? as u32;
+            if !days.iter().any(|(seen, _)| *seen == day) {
+                days.push((day, p));
+            }
+        }
+
+        let drawable: Vec<(u32, &Problem, visualize::Visualization)> = days
+            .into_iter()
+            .filter_map(|(day, p)| visualize::visualization_for(day).ok().map(|visualization| (day, p, visualization)))
+            .collect();
+
+        if drawable.is_empty() {
+            return Err(AOCError::ProcessingError("None of the selected problems can be drawn.".into()));
+        }
+
+        for (day, p, visualization) in &drawable {
+            let path = match output {
+                None => artifact_path("visualize")?.join(format!("problem{}.svg", day)),
+                Some(output) if drawable.len() == 1 => PathBuf::from(output),
+                Some(output) => {
+                    let output = Path::new(output);
+                    let stem = output.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+                    let extension = output.extension().map(|ext| ext.to_string_lossy().into_owned()).unwrap_or("svg".into());
+                    output.with_file_name(format!("{}_problem{}.{}", stem, day, extension))
+                },
+            };
+
+            let input = self.get_input(p)?;
+            (visualization.picture)(&input)?.write(&path)?;
+            println!("Wrote: {}", path.display());
+        }
+        Ok(())
+    }
+
+    // Uses --input if given and the day's own input otherwise.
+    #[cfg(feature = "repl")]
+    fn run_repl(&self, problems: &[Problem], day: u32) -> AOCResult<()> {
+        let explorer = explore::explorer_for(day)?;
+        let p = problems
+            .iter()
+            .find(|p| run::parse_number(&p.name).ok() == Some(day as i32))
+            .ok_or_else(|| AOCError::ProcessingError(format!("No problem{} to explore.", day)))?;
+
+        let input = self.get_input(p)?;
+        let mut explorable = (explorer.explore)(&input)?;
+        repl::run(explorable.as_mut(), std::io::stdin().lock(), std::io::stdout())
+    }
+
+    fn run_stress(&self, day: u32, seconds: f64, seed: Option<u64>) -> AOCResult<()> {
+        let stress_test = stress::stress_test_for(day)?;
+        let seed = seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64));
+        let duration = Duration::try_from_secs_f64(seconds)
+            .map_err(|_| AOCError::ParseError(format!("Invalid seconds: {}", seconds)))?;
+
+        let report = stress_test.run(seed, duration)?;
+        let Some(disagreement) = report.disagreement else {
+            println!("problem{}: {} inputs from seed {}, strategies all agreed.", day, report.inputs, seed);
+            return Ok(());
+        };
+
+        println!("problem{}::part{} strategies disagree on the input from seed {}:", day, disagreement.part, disagreement.seed);
+        for (strategy, answer) in &disagreement.answers {
+            match answer {
+                Ok(answer) => println!("  {}: {}", strategy, answer),
+                Err(e) => println!("  {}: Error: {}", strategy, e),
+            }
+        }
+        let path = disagreement.save(day)?;
+        eprintln!("Strategies disagree after {} inputs, input saved to {}", report.inputs, path.display());
+        process::exit(1);
+    }
+
+    // Nothing is written out, sample answers aren't results.
+    fn run_samples(&self, to_run: &[&Problem]) -> AOCResult<()> {
+        let expected = samples::load_expected(to_run, samples::SAMPLE_ANSWERS_FILE)?;
+        let mut results: Vec<ProblemResult> = Vec::new();
+
+        for p in to_run {
+            let result = match samples::sample_input_for(p)? {
+                None => ProblemResult::not_run(&p.name, "No sample input."),
+                Some(input) => p.execute(&input.to_string_lossy().into_owned(), self.get_timeout()?, self.get_memory_limit()),
             };
+            results.push(result);
+        }
 
+        let verifications = verification::verify(&results, &expected);
+        print!("{}", verification::report(&verifications));
+
+        let failures = verification::failure_count(&verifications);
+        if failures > 0 {
+            eprintln!("{} problems didn't match their sample answers.", failures);
+            process::exit(1);
+        }
+        Ok(())
+    }
+
+    fn show_result(&self, result: &ProblemResult) {
+        if self.print_answer_only {
+            result.to_stdout_answer_only();
+        }
+        else {
             result.to_stdout();
+        }
+    }
+
+    // Back in the usual problem order in case they were run in a different one.
+    fn sort_results(results: &mut [ProblemResult], problems: &[Problem]) {
+        results.sort_by_key(|result| problems.iter().position(|p| p.name == result.name));
+    }
+
+    #[cfg(feature = "tui")]
+    fn run_tui(&self, to_run: &[&Problem], budget: &RunBudget, problems: &[Problem]) -> AOCResult<()> {
+        let names: Vec<&str> = to_run.iter().map(|p| p.name.as_str()).collect();
+        let dashboard = tui::Dashboard::start(&names);
+
+        let mut results: Vec<ProblemResult> = Vec::new();
+
+        for (idx, p) in to_run.iter().enumerate() {
+            let result = if budget.is_spent() {
+                ProblemResult::not_run(&p.name, "Out of time for the run.")
+            }
+            else {
+                dashboard.started(idx);
+                let input = self.get_input(p)?;
+                p.execute(&input, budget.limit_timeout(self.get_timeout()?), self.get_memory_limit())
+            };
+            dashboard.finished(idx, &result);
             results.push(result);
         }
-    
+
+        dashboard.stop();
+        Self::sort_results(&mut results, problems);
+        self.finish(results)
+    }
+
+    fn get_input(&self, p: &Problem) -> AOCResult<String> {
+        let input = match &self.input {
+            None => return p.get_default_input(),
+            Some(input) => input,
+        };
+
+        if let Some(path) = self.input_path.get() {
+            return Ok(path.clone());
+        }
+        let path = InputSource::parse(input).to_path()?.to_string_lossy().into_owned();
+        Ok(self.input_path.get_or_init(|| path).clone())
+    }
+
+    // Answers known to be right count as a star the first time we see them.
+    fn record_history(&self, results: &[ProblemResult]) -> AOCResult<()> {
+        let mut history = AnswerHistory::load(self.get_history_file())?;
+        let now = history::now_timestamp();
+        let mut changed = false;
+
+        for result in results {
+            if let AnswerStatus::AcceptedByAoc | AnswerStatus::MatchesExpected = result.status {
+                changed |= history.record_correct(&result.name, now);
+            }
+        }
+
+        if changed {
+            history.save()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&self, mut results: Vec<ProblemResult>) -> AOCResult<()> {
+        self.carry_forward_statuses(&mut results)?;
+
+        let failures = match &self.expected {
+            None => 0,
+            Some(expected_file) => {
+                let expected = ExpectedAnswers::load(expected_file)?;
+                let verifications = verification::verify(&results, &expected);
+                verification::apply_statuses(&mut results, &verifications);
+                if !self.print_answer_only {
+                    print!("{}", verification::report(&verifications));
+                }
+                verification::failure_count(&verifications)
+            }
+        };
+
+        self.record_history(&results)?;
+
         // Write results to file
         ProblemResults::write_csv(self.get_result_file(), &results)?;
+        let run_history = RunHistory::new(self.get_run_history_dir());
+        let run_id = run_history.append(&results)?;
+        if !self.print_answer_only {
+            println!("Run: {}", run_id);
+            println!("{}", run_history.usage()?.line(run_history.dir()));
+        }
 
         // Show if there are any differences from a previous run.
-        self.compare_with_last(&results)?;
+        if !self.print_answer_only {
+            self.compare_with_last(&results)?;
+        }
+
+        // Everything is written out first so the failures can be looked at.
+        if failures > 0 {
+            eprintln!("{} problems didn't match the expected answers.", failures);
+            process::exit(1);
+        }
         Ok(())
     }
 
 }
 
+// Negative, NaN and infinite seconds are errors rather than a panic.
+fn seconds_to_duration(option: &str, seconds: f64) -> AOCResult<Duration> {
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| AOCError::ParseError(format!("Invalid {} seconds: {}", option, seconds)))
+}
+
 fn main() {
+    log::init();
     let args = Args::parse();
-    args.run().unwrap();
+    if let Err(e) = args.run() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
 }
\ No newline at end of file
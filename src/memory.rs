@@ -0,0 +1,168 @@
+// Keeps count of how much memory is allocated so a run can put a ceiling on
+// a problem. The binary only installs CountingAllocator as the global
+// allocator with the memory-limit or peak-memory feature, as counting costs
+// an atomic add on every allocation. Without it nothing is counted and
+// limits never trip.
+//
+// Each limited problem is its own run, and only what the threads of the run
+// going on now allocate counts against its limit. A problem that was given
+// up on keeps running in the background, but nothing it does is counted
+// against the problems after it.
+//
+// With the peak-memory feature the most allocated at once is kept too, so a
+// run can say how much memory each problem needed.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "peak-memory")]
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+// The limited run going on now, 0 when there isn't one.
+static RUN: AtomicUsize = AtomicUsize::new(0);
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(1);
+// What the run's threads have allocated less what they've freed. They can
+// free what was allocated before the run, so it can go below 0.
+static RUN_ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+static LIMIT: AtomicIsize = AtomicIsize::new(isize::MAX);
+// The last run that went over its limit.
+static EXCEEDED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    // The run this thread allocates for, 0 for none.
+    static THREAD_RUN: Cell<usize> = const { Cell::new(0) };
+}
+
+pub struct CountingAllocator;
+
+// While a thread is being torn down its run can't be looked up any more,
+// and what it frees then isn't counted.
+fn counted_run() -> Option<usize> {
+    let run = THREAD_RUN.try_with(|run| run.get()).unwrap_or(0);
+    (run != 0 && run == RUN.load(Ordering::Relaxed)).then_some(run)
+}
+
+// Allocations are never refused. Going over the limit is only flagged, for
+// the runner to fail the problem.
+fn add_allocated(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    #[cfg(feature = "peak-memory")]
+    PEAK.fetch_max(allocated, Ordering::Relaxed);
+    #[cfg(not(feature = "peak-memory"))]
+    let _ = allocated;
+    if let Some(run) = counted_run() {
+        let run_allocated = RUN_ALLOCATED.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        if run_allocated > LIMIT.load(Ordering::Relaxed) {
+            EXCEEDED.store(run, Ordering::Relaxed);
+        }
+    }
+}
+
+fn remove_allocated(size: usize) {
+    ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    if counted_run().is_some() {
+        RUN_ALLOCATED.fetch_sub(size as isize, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        add_allocated(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        add_allocated(layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        remove_allocated(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            add_allocated(new_size - layout.size());
+        }
+        else {
+            remove_allocated(layout.size() - new_size);
+        }
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+pub fn allocated() -> usize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+// Has the current thread allocate for a run started with set_limit.
+pub fn join_run(run: usize) {
+    THREAD_RUN.with(|cell| cell.set(run));
+}
+
+// Threads a problem starts pass this on with join_run so they're held to
+// the limit too.
+pub fn current_run() -> usize {
+    THREAD_RUN.with(|run| run.get())
+}
+
+// Starts a new run allowed this many bytes, for the threads that join it.
+// Whatever earlier runs went over or still have allocated is left behind.
+pub fn set_limit(limit: usize) -> usize {
+    let run = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+    RUN.store(0, Ordering::Relaxed);
+    RUN_ALLOCATED.store(0, Ordering::Relaxed);
+    LIMIT.store(limit.min(isize::MAX as usize) as isize, Ordering::Relaxed);
+    RUN.store(run, Ordering::Relaxed);
+    run
+}
+
+pub fn clear_limit() {
+    RUN.store(0, Ordering::Relaxed);
+    LIMIT.store(isize::MAX, Ordering::Relaxed);
+}
+
+// Whether the run going on now has gone over its limit.
+pub fn limit_exceeded() -> bool {
+    let run = RUN.load(Ordering::Relaxed);
+    run != 0 && EXCEEDED.load(Ordering::Relaxed) == run
+}
+
+// The most allocated at once since it was started, over what was allocated
+// then. It can't tell apart problems running at the same time.
+pub struct PeakMemory {
+    #[cfg(feature = "peak-memory")]
+    baseline: usize,
+}
+
+impl PeakMemory {
+
+    #[cfg(feature = "peak-memory")]
+    pub fn start() -> Self {
+        let baseline = allocated();
+        PEAK.store(baseline, Ordering::Relaxed);
+        Self { baseline }
+    }
+
+    #[cfg(not(feature = "peak-memory"))]
+    pub fn start() -> Self {
+        Self {}
+    }
+
+    // Nothing without the peak-memory feature, or when CountingAllocator
+    // isn't the global allocator and nothing has been counted.
+    #[cfg(feature = "peak-memory")]
+    pub fn bytes(&self) -> Option<usize> {
+        let peak = PEAK.load(Ordering::Relaxed);
+        (peak > 0).then(|| peak.saturating_sub(self.baseline))
+    }
+
+    #[cfg(not(feature = "peak-memory"))]
+    pub fn bytes(&self) -> Option<usize> {
+        None
+    }
+}
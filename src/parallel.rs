@@ -0,0 +1,94 @@
+// Mapping over things that don't depend on each other on as many threads as
+// there are cores. They're split into contiguous chunks, one per thread, and
+// the results come back in the same order as what went in, so nothing
+// depends on how the work was split up.
+
+use std::thread;
+
+use crate::memory;
+
+pub fn threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+pub fn map_in_order<T, R, F>(items: &[T], f: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync
+{
+    let threads = threads().min(items.len());
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    let run = memory::current_run();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                memory::join_run(run);
+                chunk.iter().map(f).collect::<Vec<R>>()
+            }))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+// For work that's only worth the threads when there's enough of it.
+pub fn map_in_order_if<T, R, F>(parallel: bool, items: &[T], f: F) -> Vec<R>
+    where T: Sync, R: Send, F: Fn(&T) -> R + Sync
+{
+    if parallel {
+        map_in_order(items, f)
+    }
+    else {
+        items.iter().map(f).collect()
+    }
+}
+
+// Runs f on each piece of items, piece_len long, in place. Pieces stay
+// whole: each thread gets a run of them.
+pub fn for_each_piece_mut<T, F>(items: &mut [T], piece_len: usize, f: F)
+    where T: Send, F: Fn(&mut [T]) + Sync
+{
+    if piece_len == 0 {
+        return;
+    }
+
+    let pieces = items.len() / piece_len;
+    let threads = threads().min(pieces);
+    if threads <= 1 {
+        items.chunks_mut(piece_len).for_each(f);
+        return;
+    }
+
+    let chunk_size = pieces.div_ceil(threads) * piece_len;
+    let f = &f;
+    let run = memory::current_run();
+
+    thread::scope(|scope| {
+        for chunk in items.chunks_mut(chunk_size) {
+            scope.spawn(move || {
+                memory::join_run(run);
+                chunk.chunks_mut(piece_len).for_each(f);
+            });
+        }
+    });
+}
+
+// For work that's only worth the threads when there's enough of it.
+pub fn for_each_piece_mut_if<T, F>(parallel: bool, items: &mut [T], piece_len: usize, f: F)
+    where T: Send, F: Fn(&mut [T]) + Sync
+{
+    if parallel {
+        for_each_piece_mut(items, piece_len, f);
+    }
+    else if piece_len > 0 {
+        items.chunks_mut(piece_len).for_each(f);
+    }
+}
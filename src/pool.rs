@@ -0,0 +1,79 @@
+
+// A simple pool of reusable objects. Hot recursive solvers can take scratch
+// buffers from here and give them back instead of allocating fresh ones on
+// every call. Buffers keep their capacity so after warming up there are
+// hardly any allocations at all.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// Something that can be emptied out and used again.
+pub trait Reusable {
+    fn reset(&mut self);
+}
+
+impl<T> Reusable for Vec<T> {
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K: Eq + Hash, V> Reusable for HashMap<K, V> {
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Eq + Hash> Reusable for HashSet<T> {
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PoolStats {
+    // How many times something was taken from the pool.
+    pub taken: usize,
+    // How many of those had to be newly created.
+    pub created: usize,
+}
+
+impl PoolStats {
+    pub fn reused(&self) -> usize {
+        self.taken - self.created
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Pool<T: Reusable + Default> {
+    free: Vec<T>,
+    stats: PoolStats,
+}
+
+impl<T: Reusable + Default> Pool<T> {
+
+    pub fn new() -> Self {
+        Self { free: Vec::new(), stats: PoolStats::default() }
+    }
+
+    pub fn take(&mut self) -> T {
+        self.stats.taken += 1;
+        match self.free.pop() {
+            Some(item) => item,
+            None => {
+                self.stats.created += 1;
+                T::default()
+            }
+        }
+    }
+
+    pub fn give(&mut self, mut item: T) {
+        item.reset();
+        self.free.push(item);
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
@@ -1,25 +1,42 @@
-pub mod problem1;
-pub mod problem2;
-pub mod problem3;
-pub mod problem4;
-pub mod problem5;
-pub mod problem6;
-pub mod problem7;
-pub mod problem8;
-pub mod problem9;
-pub mod problem10;
-pub mod problem11;
-pub mod problem12;
-pub mod problem13;
-pub mod problem14;
-pub mod problem15;
-pub mod problem16;
-pub mod problem17;
-pub mod problem18;
-pub mod problem19;
-pub mod problem20;
-pub mod problem21;
-pub mod problem22;
-pub mod problem23;
-pub mod problem24;
-pub mod problem25;
\ No newline at end of file
+use crate::registry::AocProblem;
+
+macro_rules! days {
+    [$($day:ident,)*] => {
+        $(pub mod $day;)*
+
+        // Every part of every day, in day and part order.
+        pub fn registered() -> Vec<&'static dyn AocProblem> {
+            let mut problems: Vec<&'static dyn AocProblem> = Vec::new();
+            $(problems.extend($day::SOLUTIONS.iter().map(|s| s as &'static dyn AocProblem));)*
+            problems
+        }
+    }
+}
+
+days![
+    problem1,
+    problem2,
+    problem3,
+    problem4,
+    problem5,
+    problem6,
+    problem7,
+    problem8,
+    problem9,
+    problem10,
+    problem11,
+    problem12,
+    problem13,
+    problem14,
+    problem15,
+    problem16,
+    problem17,
+    problem18,
+    problem19,
+    problem20,
+    problem21,
+    problem22,
+    problem23,
+    problem24,
+    problem25,
+];
@@ -1,10 +1,14 @@
-use std::path::Path;
 
 use crate::aocbase::AOCResult;
-use crate::aocio::each_line;
+use crate::aocio::{each_line_bytes, ProblemInput};
 
 pub trait LineNumberExtractor {
-    fn get_number(&self, line: &String) -> Option<i32>;
+    fn get_number(&self, line: &str) -> Option<i32>;
+
+    // Extractors that only care about ascii can look at the bytes directly.
+    fn get_number_bytes(&self, line: &[u8]) -> Option<i32> {
+        self.get_number(&String::from_utf8_lossy(line))
+    }
 }
 
 fn combine_digits(first_digit: Option<i32>, last_digit: Option<i32>) -> Option<i32> {
@@ -19,13 +23,19 @@ fn combine_digits(first_digit: Option<i32>, last_digit: Option<i32>) -> Option<i
 pub struct BasicExtractor {}
 
 impl LineNumberExtractor for BasicExtractor {
-    fn get_number(&self, line: &String) -> Option<i32> {
+    fn get_number(&self, line: &str) -> Option<i32> {
+        self.get_number_bytes(line.as_bytes())
+    }
+
+    // Digits are ascii and no byte of a multi-byte character is in the ascii
+    // range, so the bytes give the same answer as the characters.
+    fn get_number_bytes(&self, line: &[u8]) -> Option<i32> {
         let mut first_digit: Option<i32> = None;
         let mut last_digit: Option<i32> = None;
     
-        for c in line.chars() {
-            if c >= '0' && c <= '9' {
-                let n = c as i32 - '0' as i32;
+        for c in line {
+            if c.is_ascii_digit() {
+                let n = (c - b'0') as i32;
                 if let None = first_digit {
                     first_digit = Some(n);
                 }
@@ -93,7 +103,7 @@ impl NumMatchers {
 
 impl LineNumberExtractor for NumMatchers {
 
-    fn get_number(&self, line: &String) -> Option<i32> {
+    fn get_number(&self, line: &str) -> Option<i32> {
         let mut first_digit: Option<i32> = None;
         let mut last_digit: Option<i32> = None;
 
@@ -116,13 +126,12 @@ impl LineNumberExtractor for NumMatchers {
     }
 }
 
-pub fn run_part(input: impl AsRef<Path>, extractor: impl LineNumberExtractor) -> AOCResult<String> {
-    let mut result = 0;
+pub fn run_part(input: impl ProblemInput, extractor: impl LineNumberExtractor) -> AOCResult<String> {
+    let mut result: i64 = 0;
 
-    each_line(input, |line| {
-        match extractor.get_number(line) {
-            Some(v) => result += v,
-            None => {}
+    each_line_bytes(input, |line| {
+        if let Some(v) = extractor.get_number_bytes(line) {
+            result += v as i64;
         }
         Ok(())
     })?;
@@ -130,10 +139,12 @@ pub fn run_part(input: impl AsRef<Path>, extractor: impl LineNumberExtractor) ->
     Ok(format!("{result}"))
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, BasicExtractor {})
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, NumMatchers::default())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 1, title: "Trebuchet?!", parts: [1 => part1 (example = "142"), 2 => part2]);
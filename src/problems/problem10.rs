@@ -1,8 +1,13 @@
-use std::path::Path;
+use std::fs;
 use std::collections::HashSet;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{artifact_path, read_lines_as_bytes, ProblemInput};
+use crate::explore::{self, Explorable, ExploreCommand};
+use crate::geometry::{Direction, Vec2};
+use crate::settings;
+use crate::strategy::{Strategies, StrategyFn};
+use crate::visualize::{self, Picture, Visualizable};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
 pub enum Pipe {
@@ -16,18 +21,38 @@ pub enum Pipe {
     Ground,
 }
 
-macro_rules! make_has_dir_method {
-    ($method:ident => $d1:ident|$d2:ident|$d3:ident) => {
-        pub fn $method(&self) -> bool {
-            use Pipe::*;
-            match self {
-                Start|$d1|$d2|$d3 => true,
-                _ => false,
-            }
-        }
-    }
+fn mask(direction: Direction) -> u8 {
+    1 << direction.index()
 }
 
+const NORTH: u8 = 1 << Direction::North as u8;
+const SOUTH: u8 = 1 << Direction::South as u8;
+const EAST: u8 = 1 << Direction::East as u8;
+const WEST: u8 = 1 << Direction::West as u8;
+
+// The directions each pipe opens to, in the same order as the Pipe enum.
+// Start is treated as connecting everywhere since we don't know what's under it.
+const PIPE_CONNECTIONS: [u8; 8] = [
+    NORTH | SOUTH | EAST | WEST, // Start
+    NORTH | SOUTH,               // NorthSouth
+    EAST | WEST,                 // EastWest
+    NORTH | EAST,                // NorthEast
+    NORTH | WEST,                // NorthWest
+    SOUTH | WEST,                // SouthWest
+    SOUTH | EAST,                // SouthEast
+    0,                           // Ground
+];
+
+// The pipes that can stand in for the start, as in PIPE_CONNECTIONS.
+const START_PIPES: [Pipe; 6] = [
+    Pipe::NorthSouth,
+    Pipe::EastWest,
+    Pipe::NorthEast,
+    Pipe::NorthWest,
+    Pipe::SouthWest,
+    Pipe::SouthEast,
+];
+
 impl Pipe {
 
     pub fn is_start(&self) -> bool {
@@ -52,12 +77,19 @@ impl Pipe {
         })
     }
 
-    make_has_dir_method!(has_north => NorthSouth|NorthEast|NorthWest);
-    make_has_dir_method!(has_south => NorthSouth|SouthEast|SouthWest);
-    make_has_dir_method!(has_east => EastWest|NorthEast|SouthEast);
-    make_has_dir_method!(has_west => EastWest|NorthWest|SouthWest);
+    // The pipe opening to exactly these directions, if there is one.
+    pub fn from_connections(connections: u8) -> Option<Pipe> {
+        START_PIPES.iter().find(|pipe| pipe.connections() == connections).copied()
+    }
+
+    pub fn connections(&self) -> u8 {
+        PIPE_CONNECTIONS[*self as usize]
+    }
+
+    pub fn connects(&self, direction: Direction) -> bool {
+        self.connections() & mask(direction) != 0
+    }
 
-    #[allow(dead_code)]
     pub fn render_unicode(&self) -> &str {
         use Pipe::*;
         match self {
@@ -74,9 +106,11 @@ impl Pipe {
 
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PipeMap {
     pub map: Vec<Vec<Pipe>>,
+    // Where the start was once resolve_start has put a pipe over it.
+    start: Option<(usize, usize)>,
 }
 
 impl PipeMap {
@@ -93,7 +127,7 @@ impl PipeMap {
             }
         }
 
-        Ok(PipeMap { map })
+        Ok(PipeMap { map, start: None })
     }
 
     #[allow(dead_code)]
@@ -120,6 +154,9 @@ impl PipeMap {
     }
 
     pub fn get_start(&self) -> AOCResult<(usize, usize)> {
+        if let Some(start) = self.start {
+            return Ok(start);
+        }
         for (h, row) in self.map.iter().enumerate() {
             for (w, p) in row.iter().enumerate() {
                 if *p == Pipe::Start {
@@ -130,177 +167,193 @@ impl PipeMap {
         Err(AOCError::ProcessingError("No start position found.".into()))
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<PipeMap> {
-        let lines = read_lines_as_bytes(input)?;
-
-        let map = lines
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|c| Pipe::from_char(*c as char))
-                    .collect::<AOCResult<Vec<Pipe>>>()
-            })
-            .collect::<AOCResult<Vec<Vec<Pipe>>>>()?;
-
-        Ok(PipeMap::new(map)?)
-    }
-}
-
-#[derive(Clone, Debug)]
-struct SearchPath {
-    pub path: Vec<(usize, usize)>,
-    pub visited: HashSet<(usize, usize)>,
-}
-
-impl SearchPath {
-    pub fn new() -> Self {
-        SearchPath { path: Vec::new(), visited: HashSet::new() }
-    }
-
-    pub fn add(&mut self, pos: (usize, usize)) {
-        self.path.push(pos);
-        self.visited.insert(pos);
-    }
-
-    pub fn has_visited(&self, pos: &(usize, usize)) -> bool {
-        self.visited.contains(&pos)
+    fn neighbor(&self, (h, w): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let (dh, dw) = direction.delta();
+        let next_h = h.checked_add_signed(dh as isize)?;
+        let next_w = w.checked_add_signed(dw as isize)?;
+        (next_h < self.height() && next_w < self.width()).then_some((next_h, next_w))
     }
-}
 
-struct PipeMapSolver<'a> {
-    pipe_map: &'a PipeMap,
-}
-
-impl<'a> PipeMapSolver<'a> {
+    // Puts the pipe the start must be under it, going by which of its
+    // neighbors connect back to it, and returns where the start is.
+    pub fn resolve_start(&mut self) -> AOCResult<(usize, usize)> {
+        let start = self.get_start()?;
+        if self.start.is_some() {
+            return Ok(start);
+        }
 
-    pub fn new(pipe_map: &'a PipeMap) -> Self {
-        PipeMapSolver { pipe_map }
-    }
+        let connections = Direction::ALL
+            .iter()
+            .filter(|direction| {
+                self.neighbor(start, **direction)
+                    .is_some_and(|(h, w)| self.map[h][w].connects(direction.opposite()))
+            })
+            .fold(0, |connections, direction| connections | mask(*direction));
 
-    fn width(&self) -> usize {
-        self.pipe_map.width()
-    }
+        let pipe = Pipe::from_connections(connections)
+            .ok_or_else(|| AOCError::ProcessingError(format!(
+                "Can't tell the pipe under the start, {} neighbors connect to it.", connections.count_ones())))?;
 
-    fn height(&self) -> usize {
-        self.pipe_map.height()
+        self.map[start.0][start.1] = pipe;
+        self.start = Some(start);
+        Ok(start)
     }
 
-    fn get_connected_positions(&self, (h, w): (usize, usize)) -> Vec<(usize, usize)> {
-        let mut connections: Vec<(usize, usize)> = Vec::new();
-
-        let max_h = self.height();
-        let max_w = self.width();
-
-        if h > 0  && self.is_connected((h, w), (h-1, w)) {
-            connections.push((h-1, w));
-        }
-        if h < max_h - 1 && self.is_connected((h, w), (h+1, w)) {
-            connections.push((h+1, w));
-        }
-        if w > 0 && self.is_connected((h, w), (h, w-1)) {
-            connections.push((h, w-1));
+    // Follows the pipes from the start until they come back round to it. The
+    // path ends with the start again.
+    pub fn enclosure_path(&self) -> AOCResult<Vec<(usize, usize)>> {
+        let start = self.get_start()?;
+        let pipe = self.map[start.0][start.1];
+        if self.start.is_none() {
+            return Err(AOCError::ProcessingError("The start has to be resolved before following the loop.".into()));
         }
-        if w < max_w - 1 && self.is_connected((h, w), (h, w+1)) {
-            connections.push((h, w+1));
-        }
-
-        connections
-    }
 
-    pub fn get_enclosure_path(&self, (start_h, start_w): (usize, usize)) -> Option<Vec<(usize, usize)>> {
-        let start_pos = (start_h, start_w);
-        let paths = self.search_paths(start_pos, start_pos);
+        let mut direction = Direction::ALL
+            .into_iter()
+            .find(|direction| pipe.connects(*direction))
+            .ok_or_else(|| AOCError::ProcessingError("No Enclosure Found!".into()))?;
+        let mut path = vec![start];
+        let mut pos = start;
+
+        loop {
+            pos = self.neighbor(pos, direction)
+                .ok_or_else(|| AOCError::ProcessingError(format!("The loop runs off the map at {:?}.", pos)))?;
+            path.push(pos);
+            if pos == start {
+                return Ok(path);
+            }
 
-        for path in paths {
-            // You need more than 3 nodes in the path for a loop.
-            // this would be starting at one, going 1, and going back.
-            if path.len() > 3 {
-                // Just return the first path enclosuer.
-                // I suppose there could be more than 1?
-                return Some(path);
+            let pipe = self.map[pos.0][pos.1];
+            let came_from = direction.opposite();
+            if !pipe.connects(came_from) {
+                return Err(AOCError::ProcessingError(format!("The loop is broken at {:?}.", pos)));
             }
+            direction = Direction::ALL
+                .into_iter()
+                .find(|direction| *direction != came_from && pipe.connects(*direction))
+                .ok_or_else(|| AOCError::ProcessingError(format!("The loop is broken at {:?}.", pos)))?;
         }
-
-        None
     }
 
-    pub fn search_paths(&self, (start_h, start_w): (usize, usize), (end_h, end_w): (usize, usize))
-        -> Vec<Vec<(usize, usize)>> 
-    {
-        // This would probably be easier to read with recursion.
-        let mut search_path = SearchPath::new();
-        search_path.add((start_h, start_w));
-
-        let mut search_stack: Vec<SearchPath> = vec![search_path];
-        let mut wanted_paths: Vec<Vec<(usize, usize)>> = Vec::new();
-        let mut branches: Vec<(usize, usize)> = Vec::new();
-
-        while let Some(mut search_path) = search_stack.pop() {
-            let (cur_h, cur_w) = search_path.path.last().unwrap();
+    pub fn parse(input: impl ProblemInput) -> AOCResult<PipeMap> {
+        let lines = read_lines_as_bytes(input)?;
 
-            branches.clear();
+        let map = lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|c| Pipe::from_char(*c as char))
+                    .collect::<AOCResult<Vec<Pipe>>>()
+            })
+            .collect::<AOCResult<Vec<Vec<Pipe>>>>()?;
 
-            for (next_h, next_w) in self.get_connected_positions((*cur_h, *cur_w)) {
-                //println!("connected: {next_h}, {next_w}");
-                // Is this a target!
-                if next_h == end_h && next_w == end_w {
-                    let mut path = search_path.path.clone();
-                    path.push((next_h, next_w));
-                    wanted_paths.push(path);
-                }
+        Ok(PipeMap::new(map)?)
+    }
+}
 
-                // One we want to visit
-                else if !search_path.has_visited(&(next_h as usize, next_w as usize)) {
-                    branches.push((next_h, next_w));
+// Pipes in grey with the loop drawn through them and the tiles it encloses
+// filled in.
+impl Visualizable for PipeMap {
+    fn picture(&self) -> AOCResult<Picture> {
+        let mut picture = Picture::new(self.width(), self.height());
+        for (h, row) in self.map.iter().enumerate() {
+            for (w, pipe) in row.iter().enumerate() {
+                if *pipe != Pipe::Ground {
+                    picture.set_cell(h, w, visualize::STONE);
                 }
             }
+        }
 
-            // For more than 1 branch we need to clone.
-            for branch in branches.iter().skip(1) {
-                let mut branch_search_path = search_path.clone();
-                branch_search_path.add(*branch);
-                search_stack.push(branch_search_path);
-            }
+        let start_pos = self.get_start()?;
+        picture.set_cell(start_pos.0, start_pos.1, visualize::WARM);
 
-            // Don't clone if we don't have to
-            if let Some(branch) = branches.get(0) {
-                search_path.add(*branch);
-                search_stack.push(search_path);
+        let mut resolved = self.clone();
+        if let Ok(enclosing_path) = resolved.resolve_start().and_then(|_| resolved.enclosure_path()) {
+            for (h, w) in InnerSpaceSolver::new(&resolved, &enclosing_path).solve() {
+                picture.set_cell(h, w, visualize::ACCENT);
             }
+            picture.add_cell_path(&enclosing_path, visualize::HIGHLIGHT, false);
         }
 
-        wanted_paths
+        Ok(picture)
+    }
+}
+
+impl Explorable for PipeMap {
+
+    fn summary(&self) -> String {
+        let start = self.get_start().map_or("no start".to_string(), |(h, w)| format!("start at row {}, column {}", h, w));
+        format!("{}x{} pipe map, {}.", self.width(), self.height(), start)
     }
 
-    pub fn is_connected(&self, (h1, w1): (usize, usize), (h2, w2): (usize, usize)) -> bool {
-        let p1 = self.pipe_map.map[h1][w1];
-        let p2 = self.pipe_map.map[h2][w2];
+    fn commands(&self) -> Vec<ExploreCommand> {
+        vec![
+            ExploreCommand::new("tile", "<row> <column>", "The pipe at a tile and where it is on the loop"),
+            ExploreCommand::new("loop", "", "How long the loop is and how much it encloses"),
+            ExploreCommand::new("region", "<rows> <columns>", "Draw part of the map, the loop in pipes and enclosed tiles as I"),
+        ]
+    }
 
-        match ((h2 as i64 - h1 as i64), (w2 as i64 - w1 as i64)) {
-            // 2 above 1
-            (-1, 0) => p1.has_north() && p2.has_south(),
-            // 2 below 1
-            (1, 0)  => p1.has_south() && p2.has_north(),
-            // 2 left of 1
-            (0, -1) => p1.has_west() && p2.has_east(),
-            // 2 right of 1
-            (0, 1)  => p1.has_east() && p2.has_west(),
-            _ => false
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String> {
+        match name {
+            "tile" => {
+                let (h, w): (usize, usize) = (explore::arg(args, 0, "row")?, explore::arg(args, 1, "column")?);
+                let pipe = self.map
+                    .get(h)
+                    .and_then(|row| row.get(w))
+                    .ok_or_else(|| AOCError::ProcessingError(format!("No tile at row {}, column {}", h, w)))?;
+
+                let connects: Vec<String> = Direction::ALL
+                    .iter()
+                    .filter(|direction| pipe.connects(**direction))
+                    .map(|direction| format!("{:?}", direction))
+                    .collect();
+                let on_loop = match self.enclosure_path()?.iter().position(|tile| *tile == (h, w)) {
+                    Some(step) => format!("step {} of the loop", step),
+                    None => "not on the loop".to_string(),
+                };
+                Ok(format!("{:?} '{}', connects {}, {}", pipe, pipe.render_unicode(), connects.join(" "), on_loop))
+            },
+            "loop" => {
+                let path = self.enclosure_path()?;
+                let enclosed = InnerSpaceSolver::new(self, &path).solve();
+                Ok(format!("{} tiles, the farthest is {} steps from the start, {} tiles enclosed",
+                    path.len() - 1, path.len() / 2, enclosed.len()))
+            },
+            "region" => {
+                let rows = explore::range_within(explore::range_arg(args, 0, "rows")?, self.height());
+                let columns = explore::range_within(explore::range_arg(args, 1, "columns")?, self.width());
+                let path = self.enclosure_path()?;
+                let on_loop: HashSet<(usize, usize)> = path.iter().copied().collect();
+                let enclosed: HashSet<(usize, usize)> = InnerSpaceSolver::new(self, &path).solve().into_iter().collect();
+
+                let mut output = String::new();
+                for h in rows {
+                    for w in columns.clone() {
+                        if on_loop.contains(&(h, w)) {
+                            output.push_str(self.map[h][w].render_unicode());
+                        }
+                        else {
+                            output.push(if enclosed.contains(&(h, w)) { 'I' } else { '.' });
+                        }
+                    }
+                    output.push('\n');
+                }
+                Ok(output)
+            },
+            _ => Err(explore::unknown_command(name)),
         }
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let pipe_map = PipeMap::parse(input)?;
-    let start_pos = pipe_map.get_start()?;
-    let pipe_map_solver = PipeMapSolver::new(&pipe_map);
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let mut pipe_map = PipeMap::parse(input)?;
+    pipe_map.resolve_start()?;
 
-    //println!("Solving for map:\n{}", pipe_map.render());
+    tracing::trace!("Solving for map:\n{}", pipe_map.render());
 
-    pipe_map_solver
-        .get_enclosure_path(start_pos)
-        .map(|path| (path.len() / 2).to_string())
-        .ok_or_else(|| AOCError::ProcessingError("No Enclosure Found!".into()))
+    let path = pipe_map.enclosure_path()?;
+    Ok((path.len() / 2).to_string())
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -328,7 +381,7 @@ struct InnerSpaceSolver<'a> {
 
 impl<'a> InnerSpaceSolver<'a> {
 
-    pub fn new(pipe_map: &'a PipeMap, enclosure_path: &Vec<(usize, usize)>) -> Self {
+    pub fn new(pipe_map: &'a PipeMap, enclosure_path: &[(usize, usize)]) -> Self {
         let mut enclosure_locations: HashSet<(usize, usize)> = HashSet::new();
         for pos in enclosure_path {
             enclosure_locations.insert(*pos);
@@ -343,31 +396,31 @@ impl<'a> InnerSpaceSolver<'a> {
         }
     }
 
-    pub fn solve(&mut self) -> i32 {
+    pub fn solve(&mut self) -> Vec<(usize, usize)> {
         self.clear();
         self.seed_search_stack();
         self.run_search();
-        self.get_inner_tile_counts()
+        self.get_inner_tiles()
 
     }
 
-    fn get_inner_tile_counts(&self) -> i32 {
+    fn get_inner_tiles(&self) -> Vec<(usize, usize)> {
         let max_h = self.pipe_map.height();
         let max_w = self.pipe_map.width();
 
-        let mut inner_tile_count = 0;
+        let mut inner_tiles = Vec::new();
 
         for h in 0..max_h {
             for w in 0..max_w {
                 if !self.outside_locations.contains(&(h, w)) &&
                     !self.enclosure_locations.contains(&(h, w))
                 {
-                    inner_tile_count += 1;
+                    inner_tiles.push((h, w));
                 }
             }
         }
 
-        inner_tile_count
+        inner_tiles
     }
 
     fn run_search(&mut self) {
@@ -397,73 +450,73 @@ impl<'a> InnerSpaceSolver<'a> {
         match pos.corner {
             Corner::UpperLeft => {
                 // Check upwards
-                if pos.h > 0 && !self.pipe_map.map[pos.h - 1][pos.w].is_start() {
+                if pos.h > 0 {
                     self.add_to_visit(SpaceCorner { h: pos.h - 1, w: pos.w, corner: Corner::LowerLeft });
                 }
                 // Check downwards
-                if !pipe.has_west() {
+                if !pipe.connects(Direction::West) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::LowerLeft });
                 }
                 // Check left
-                if pos.w > 0 && !self.pipe_map.map[pos.h][pos.w - 1].is_start() {
+                if pos.w > 0 {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w - 1, corner: Corner::UpperRight });
                 }
                 // Check right
-                if !pipe.has_north() {
+                if !pipe.connects(Direction::North) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::UpperRight });
                 }
             },
             Corner::UpperRight => {
                 // Check upwards
-                if pos.h > 0 && !self.pipe_map.map[pos.h - 1][pos.w].is_start() {
+                if pos.h > 0 {
                     self.add_to_visit(SpaceCorner { h: pos.h - 1, w: pos.w, corner: Corner::LowerRight });
                 }
                 // Check downwards
-                if !pipe.has_east() {
+                if !pipe.connects(Direction::East) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::LowerRight });
                 }
                 // Check left
-                if !pipe.has_north() {
+                if !pipe.connects(Direction::North) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::UpperLeft });
                 }
                 // Check right
-                if pos.w < max_w - 1 && !self.pipe_map.map[pos.h][pos.w + 1].is_start() {
+                if pos.w < max_w - 1 {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w + 1, corner: Corner::UpperLeft });
                 }
             },
             Corner::LowerLeft => {
                 // Check upwards
-                if !pipe.has_west() {
+                if !pipe.connects(Direction::West) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::UpperLeft });
                 }
                 // Check downwards
-                if pos.h < max_h - 1 && !self.pipe_map.map[pos.h + 1][pos.w].is_start() {
+                if pos.h < max_h - 1 {
                     self.add_to_visit(SpaceCorner { h: pos.h + 1, w: pos.w, corner: Corner::UpperLeft });
                 }
                 // Check left
-                if pos.w > 0 && !self.pipe_map.map[pos.h][pos.w - 1].is_start() {
+                if pos.w > 0 {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w - 1, corner: Corner::LowerRight });
                 }
                 // Check right
-                if !pipe.has_south() {
+                if !pipe.connects(Direction::South) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::LowerRight });
                 }
             },
             Corner::LowerRight => {
                 // Check upwards
-                if !pipe.has_east() {
+                if !pipe.connects(Direction::East) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::UpperRight });
                 }
                 // Check downwards
-                if pos.h < max_h - 1 && !self.pipe_map.map[pos.h + 1][pos.w].is_start() {
+                if pos.h < max_h - 1 {
                     self.add_to_visit(SpaceCorner { h: pos.h + 1, w: pos.w, corner: Corner::UpperRight });
                 }
                 // Check left
-                if !pipe.has_south() {
+                if !pipe.connects(Direction::South) {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w, corner: Corner::LowerLeft });
                 }
                 // Check right
-                if pos.w < max_w - 1 && !self.pipe_map.map[pos.h][pos.w + 1].is_start() {
+                if pos.w < max_w - 1 {
                     self.add_to_visit(SpaceCorner { h: pos.h, w: pos.w + 1, corner: Corner::LowerLeft });
                 }
             }
@@ -478,13 +531,6 @@ impl<'a> InnerSpaceSolver<'a> {
     }
 
     fn seed_search_stack_single(&mut self, h: usize, w: usize) {
-        let pipe = self.pipe_map.map[h][w];
-
-        if pipe.is_start() {
-            // Skip start for now, we don't know what it really is
-            return;
-        }
-
         let max_h = self.pipe_map.height();
         let max_w = self.pipe_map.width();
 
@@ -523,19 +569,199 @@ impl<'a> InnerSpaceSolver<'a> {
     }
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let pipe_map = PipeMap::parse(input)?;
-    let start_pos = pipe_map.get_start()?;
-    let pipe_map_solver = PipeMapSolver::new(&pipe_map);
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let mut pipe_map = PipeMap::parse(input)?;
+    pipe_map.resolve_start()?;
+
+    tracing::trace!("Solving for map:\n{}", pipe_map.render());
+
+    let enclosing_path = pipe_map.enclosure_path()?;
+    let pipe_loop: PipeLoop = (pipe_map, enclosing_path);
+
+    let mut strategies = Strategies::new("problem10");
+    for (name, counter) in ENCLOSED_COUNTERS {
+        strategies = strategies.add(name, counter);
+    }
+    let enclosed = strategies.run(&pipe_loop, |_| "flood")?;
+
+    if settings::verify() {
+        check_enclosed_counts(&pipe_loop)?;
+    }
+
+    export_loop(&pipe_loop.0, &pipe_loop.1)?;
+
+    Ok(enclosed.to_string())
+}
+
+// A resolved map and the loop through it.
+type PipeLoop = (PipeMap, Vec<(usize, usize)>);
+
+const ENCLOSED_COUNTERS: [(&str, StrategyFn<PipeLoop, usize>); 3] = [
+    ("flood", count_by_flood),
+    ("rays", count_by_rays),
+    ("area", count_by_area),
+];
+
+fn count_by_flood((pipe_map, enclosing_path): &PipeLoop) -> AOCResult<usize> {
+    Ok(enclosed_by_flood(pipe_map, enclosing_path))
+}
+
+fn count_by_rays((pipe_map, enclosing_path): &PipeLoop) -> AOCResult<usize> {
+    Ok(enclosed_by_rays(pipe_map, enclosing_path))
+}
+
+fn count_by_area((_, enclosing_path): &PipeLoop) -> AOCResult<usize> {
+    Ok(enclosed_by_area(enclosing_path))
+}
+
+// Runs every way of counting and fails if any of them come out different.
+fn check_enclosed_counts(pipe_loop: &PipeLoop) -> AOCResult<()> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for (name, counter) in ENCLOSED_COUNTERS {
+        counts.push((name, counter(pipe_loop)?));
+    }
+
+    if counts.iter().any(|(_, count)| *count != counts[0].1) {
+        let counts: Vec<String> = counts.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+        return Err(AOCError::ProcessingError(format!("Enclosed tile counts disagree: {}", counts.join(", "))));
+    }
+    Ok(())
+}
+
+// Floods the gaps between pipes in from the edge of the map. Whatever isn't
+// reached or on the loop is enclosed.
+pub fn enclosed_by_flood(pipe_map: &PipeMap, enclosing_path: &[(usize, usize)]) -> usize {
+    InnerSpaceSolver::new(pipe_map, enclosing_path).solve().len()
+}
+
+// Goes along each row, going in or out of the loop at every loop pipe that
+// opens north. A run along the loop like L--7 crosses it once and L--J not
+// at all, which counting only the ends opening north gets right.
+pub fn enclosed_by_rays(pipe_map: &PipeMap, enclosing_path: &[(usize, usize)]) -> usize {
+    let mut on_loop = vec![vec![false; pipe_map.width()]; pipe_map.height()];
+    for (h, w) in enclosing_path {
+        on_loop[*h][*w] = true;
+    }
+
+    let mut enclosed = 0;
+    for (h, row) in pipe_map.map.iter().enumerate() {
+        let mut inside = false;
+        for (w, pipe) in row.iter().enumerate() {
+            if on_loop[h][w] {
+                if pipe.connects(Direction::North) {
+                    inside = !inside;
+                }
+            }
+            else if inside {
+                enclosed += 1;
+            }
+        }
+    }
+    enclosed
+}
+
+// The shoelace formula gives the area inside the loop drawn through the
+// middle of its tiles. By Pick's theorem that's the enclosed tiles plus half
+// the loop, less one.
+pub fn enclosed_by_area(enclosing_path: &[(usize, usize)]) -> usize {
+    let twice_area: i64 = enclosing_path
+        .windows(2)
+        .map(|pair| {
+            let ((h1, w1), (h2, w2)) = (pair[0], pair[1]);
+            w1 as i64 * h2 as i64 - w2 as i64 * h1 as i64
+        })
+        .sum::<i64>()
+        .abs();
+    let loop_tiles = enclosing_path.len() as i64 - 1;
+    ((twice_area - loop_tiles) / 2 + 1) as usize
+}
+
+// The loop as a polygon through the middle of its tiles, x going across and
+// y going down like the map. The path already ends back at the start.
+fn loop_polygon(enclosing_path: &[(usize, usize)]) -> Vec<Vec2> {
+    enclosing_path
+        .iter()
+        .map(|(h, w)| Vec2::new(*w as f64 + 0.5, *h as f64 + 0.5))
+        .collect()
+}
+
+// Corners of each enclosed tile, as a closed ring.
+fn tile_squares(inner_tiles: &[(usize, usize)]) -> Vec<Vec<Vec2>> {
+    inner_tiles
+        .iter()
+        .map(|(h, w)| {
+            let corner = Vec2::new(*w as f64, *h as f64);
+            [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)]
+                .iter()
+                .map(|(dx, dy)| corner + Vec2::new(*dx, *dy))
+                .collect()
+        })
+        .collect()
+}
+
+fn to_svg(polygon: &[Vec2], squares: &[Vec<Vec2>]) -> String {
+    let (width, height) = polygon
+        .iter()
+        .fold((0.0f64, 0.0f64), |(width, height), p| (width.max(p.x() + 0.5), height.max(p.y() + 0.5)));
+
+    let path_data = |ring: &[Vec2]| -> String {
+        let points: Vec<String> = ring.iter().map(|p| format!("{} {}", p.x(), p.y())).collect();
+        format!("M {} Z", points.join(" L "))
+    };
+
+    let squares_data: Vec<String> = squares.iter().map(|square| path_data(square)).collect();
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\">\n", width, height);
+    svg.push_str(&format!("  <path id=\"interior\" fill=\"#8c8\" stroke=\"none\" d=\"{}\"/>\n", squares_data.join(" ")));
+    svg.push_str(&format!("  <path id=\"loop\" fill=\"none\" stroke=\"#333\" stroke-width=\"0.2\" d=\"{}\"/>\n", path_data(polygon)));
+    svg.push_str("</svg>\n");
+    svg
+}
 
-    //println!("Solving for map:\n{}", pipe_map.render());
+fn to_geojson(polygon: &[Vec2], squares: &[Vec<Vec2>]) -> String {
+    let ring_json = |ring: &[Vec2]| -> String {
+        let points: Vec<String> = ring.iter().map(|p| format!("[{},{}]", p.x(), p.y())).collect();
+        format!("[{}]", points.join(","))
+    };
+
+    let squares_json: Vec<String> = squares.iter().map(|square| format!("[{}]", ring_json(square))).collect();
+
+    format!(concat!(
+        "{{\"type\":\"FeatureCollection\",\"features\":[\n",
+        "{{\"type\":\"Feature\",\"properties\":{{\"name\":\"loop\",\"tiles\":{}}},",
+        "\"geometry\":{{\"type\":\"Polygon\",\"coordinates\":[{}]}}}},\n",
+        "{{\"type\":\"Feature\",\"properties\":{{\"name\":\"interior\",\"tiles\":{}}},",
+        "\"geometry\":{{\"type\":\"MultiPolygon\",\"coordinates\":[{}]}}}}\n",
+        "]}}\n"),
+        polygon.len() - 1, ring_json(polygon), squares.len(), squares_json.join(","))
+}
 
-    let enclosing_path = pipe_map_solver
-        .get_enclosure_path(start_pos)
-        .ok_or_else(|| AOCError::ProcessingError("Could not find enclosing path.".into()))?;
+// Set problem10.export to svg, geojson or both separated by a comma to
+// write the loop and what it encloses to the artifacts directory.
+fn export_loop(pipe_map: &PipeMap, enclosing_path: &[(usize, usize)]) -> AOCResult<()> {
+    let params = settings::params();
+    let formats = match params.get("problem10.export") {
+        None => return Ok(()),
+        Some(formats) => formats,
+    };
+
+    let inner_tiles = InnerSpaceSolver::new(pipe_map, enclosing_path).solve();
+    let polygon = loop_polygon(enclosing_path);
+    let squares = tile_squares(&inner_tiles);
+
+    for format in formats.split(',').map(|format| format.trim()) {
+        let (file_name, contents) = match format {
+            "svg" => ("problem10_loop.svg", to_svg(&polygon, &squares)),
+            "geojson" => ("problem10_loop.geojson", to_geojson(&polygon, &squares)),
+            _ => return Err(AOCError::ProcessingError(format!("Unknown export format for problem10: {}", format))),
+        };
+
+        let path = artifact_path(file_name)?;
+        fs::write(&path, contents)?;
+        tracing::debug!("Wrote: {}", path.display());
+    }
 
-    let mut ispace_solver = InnerSpaceSolver::new(&pipe_map, &enclosing_path);
-    let result = ispace_solver.solve();
+    Ok(())
+}
 
-    Ok(result.to_string())
-}
\ No newline at end of file
+crate::register_day!(day: 10, title: "Pipe Maze", parts: [1 => part1, 2 => part2 (example = "10", input = "input/input_10_test3.txt")]);
@@ -1,8 +1,9 @@
-use std::path::Path;
 use std::collections::HashMap;
+use std::ops::RangeBounds;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
+use crate::explore::{self, Explorable, ExploreCommand};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum SpaceArea {
@@ -32,7 +33,6 @@ impl SpaceMap {
         }
     }
 
-    #[allow(dead_code)]
     fn get_reverse_galaxy_index(&self) -> HashMap<(usize, usize), u32> {
         let mut r_index: HashMap<(usize, usize), u32> = HashMap::new();
         for (id, (h, w)) in &self.galaxy_index {
@@ -97,11 +97,10 @@ impl SpaceMap {
     pub fn calculate_galaxy_distances(&self) -> Vec<(u32, u32, usize)> {
         let mut distances: Vec<(u32, u32, usize)> = Vec::new();
 
-        for (id1, (h1, w1)) in &self.galaxy_index {
-            for (id2, (h2, w2)) in &self.galaxy_index {
+        for (id1, p1) in &self.galaxy_index {
+            for (id2, p2) in &self.galaxy_index {
                 if id1 < id2 {
-                    let dist = h1.abs_diff(*h2) + w1.abs_diff(*w2);
-                    distances.push((*id1, *id2, dist));
+                    distances.push((*id1, *id2, Self::distance(*p1, *p2)));
                 }
             }
         }
@@ -109,6 +108,38 @@ impl SpaceMap {
         distances
     }
 
+    fn distance((h1, w1): (usize, usize), (h2, w2): (usize, usize)) -> usize {
+        h1.abs_diff(h2) + w1.abs_diff(w2)
+    }
+
+    // Galaxies are numbered from 0 in reading order, and keep their ids when
+    // the map is expanded. Positions are (row, column).
+    pub fn position_of(&self, id: u32) -> AOCResult<(usize, usize)> {
+        self.galaxy_index
+            .get(&id)
+            .copied()
+            .ok_or_else(|| AOCError::ProcessingError(format!("No galaxy with id: {}", id)))
+    }
+
+    pub fn distance_between(&self, id1: u32, id2: u32) -> AOCResult<usize> {
+        Ok(Self::distance(self.position_of(id1)?, self.position_of(id2)?))
+    }
+
+    // The ids of the galaxies inside the rows and columns given, in id order.
+    pub fn galaxies_in(&self, rows: impl RangeBounds<usize>, columns: impl RangeBounds<usize>) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.galaxy_index
+            .iter()
+            .filter(|(_, (h, w))| rows.contains(h) && columns.contains(w))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn galaxy_count(&self) -> usize {
+        self.galaxy_index.len()
+    }
+
     pub fn expand(&self, expand_amount: usize) -> SpaceMap {
         let empty_rows = self.get_empty_rows();
         let empty_columns = self.get_empty_columns();
@@ -184,7 +215,7 @@ impl SpaceMap {
             .collect()
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<SpaceMap> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<SpaceMap> {
         let lines = read_lines_as_bytes(input)?;
         let mut map: Vec<Vec<SpaceArea>> = Vec::new();
         let mut id = 0;
@@ -206,11 +237,69 @@ impl SpaceMap {
             map.push(row);
         }
 
+        let width = map.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+        if let Some(y) = map.iter().position(|row| row.len() != width) {
+            return Err(AOCError::ParseError(format!("Row {} is {} wide, expected {}.", y + 1, map[y].len(), width)));
+        }
+
         Ok(SpaceMap::from(&map))
     }
 }
 
-fn run_part(input: impl AsRef<Path>, expansion_amount: usize) -> AOCResult<String> {
+impl Explorable for SpaceMap {
+
+    fn summary(&self) -> String {
+        format!("{} galaxies on a {}x{} map.", self.galaxy_count(), self.width, self.height)
+    }
+
+    fn commands(&self) -> Vec<ExploreCommand> {
+        vec![
+            ExploreCommand::new("galaxy", "<id>", "Where a galaxy is, as row and column"),
+            ExploreCommand::new("distance", "<id> <id>", "Steps between two galaxies"),
+            ExploreCommand::new("region", "<rows> <columns>", "Draw part of the map with the galaxies in it"),
+            ExploreCommand::new("expand", "<amount>", "Add amount rows and columns after each empty one, as the map is now"),
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String> {
+        match name {
+            "galaxy" => {
+                let (row, column) = self.position_of(explore::arg(args, 0, "id")?)?;
+                Ok(format!("row {}, column {}", row, column))
+            },
+            "distance" => {
+                let distance = self.distance_between(explore::arg(args, 0, "id")?, explore::arg(args, 1, "id")?)?;
+                Ok(distance.to_string())
+            },
+            "region" => {
+                let rows = explore::range_within(explore::range_arg(args, 0, "rows")?, self.height);
+                let columns = explore::range_within(explore::range_arg(args, 1, "columns")?, self.width);
+                let ids = self.galaxies_in(rows.clone(), columns.clone());
+                let r_index = self.get_reverse_galaxy_index();
+
+                let mut output = String::new();
+                for h in rows {
+                    for w in columns.clone() {
+                        output.push(if r_index.contains_key(&(h, w)) { '#' } else { '.' });
+                    }
+                    output.push('\n');
+                }
+                output.push_str(&format!("Galaxies: {:?}", ids));
+                Ok(output)
+            },
+            "expand" => {
+                *self = self.expand(explore::arg(args, 0, "amount")?);
+                Ok(self.summary())
+            },
+            _ => Err(explore::unknown_command(name)),
+        }
+    }
+}
+
+fn run_part(input: impl ProblemInput, expansion_amount: usize) -> AOCResult<String> {
     let space_map = SpaceMap::parse(input)?;
     let expanded_space_map = space_map.expand(expansion_amount);
 
@@ -220,10 +309,12 @@ fn run_part(input: impl AsRef<Path>, expansion_amount: usize) -> AOCResult<Strin
     Ok(result.to_string())
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, 1)
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, 1_000_000 - 1)
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 11, title: "Cosmic Expansion", parts: [1 => part1 (example = "374"), 2 => part2]);
@@ -1,9 +1,14 @@
-use std::path::Path;
 use std::num::ParseIntError;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::each_line;
+use crate::aocio::{each_line_with_policy, ProblemInput};
+use crate::settings;
+use crate::strategy::Strategies;
+
+// Rows with more unknowns than this are too slow to brute force.
+const MAX_BRUTE_FORCE_UNKNOWNS: usize = 20;
 
 #[derive(Debug, Copy, Clone)]
 pub enum SpringCondition {
@@ -13,6 +18,14 @@ pub enum SpringCondition {
 }
 
 impl SpringCondition {
+    pub fn to_char(&self) -> char {
+        match self {
+            SpringCondition::Operational => '.',
+            SpringCondition::Damaged => '#',
+            SpringCondition::Unknown => '?',
+        }
+    }
+
     pub fn parse(c: char) -> AOCResult<SpringCondition> {
         use SpringCondition::*;
         Ok(match c {
@@ -52,6 +65,52 @@ impl SpringsCondition {
         SpringsCondition { springs, damaged_sequences }
     }
 
+    pub fn unknown_count(&self) -> usize {
+        self.springs.iter().filter(|s| matches!(s, SpringCondition::Unknown)).count()
+    }
+
+    // Tries every damaged/operational assignment of the unknowns.
+    pub fn brute_force_count(&self) -> u64 {
+        let unknown_count = self.unknown_count();
+        let mut match_count: u64 = 0;
+        let mut sequences: Vec<u32> = Vec::with_capacity(self.damaged_sequences.len() + 1);
+
+        for assignment in 0u64 .. 1 << unknown_count {
+            sequences.clear();
+            let mut unknown_pos = 0;
+            let mut run: u32 = 0;
+
+            for spring in &self.springs {
+                let damaged = match spring {
+                    SpringCondition::Operational => false,
+                    SpringCondition::Damaged => true,
+                    SpringCondition::Unknown => {
+                        unknown_pos += 1;
+                        assignment & (1 << (unknown_pos - 1)) != 0
+                    }
+                };
+
+                if damaged {
+                    run += 1;
+                }
+                else if run > 0 {
+                    sequences.push(run);
+                    run = 0;
+                }
+            }
+
+            if run > 0 {
+                sequences.push(run);
+            }
+
+            if sequences == self.damaged_sequences {
+                match_count += 1;
+            }
+        }
+
+        match_count
+    }
+
     pub fn parse(line: impl AsRef<str>) -> AOCResult<SpringsCondition> {
         let parts: Vec<&str> = line.as_ref().trim().split_ascii_whitespace().collect();
 
@@ -75,6 +134,42 @@ impl SpringsCondition {
     }
 }
 
+// Everything the solver worked out for a row. Each entry is how many ways
+// the rest of the row can be finished from a spring position once some
+// number of the damaged sequences have been placed.
+#[derive(Debug)]
+pub struct MatchCountTable {
+    pub match_count: u64,
+    // (pos, ds_pos) -> match count from there
+    pub counts: BTreeMap<(usize, usize), u64>,
+}
+
+impl MatchCountTable {
+
+    // A line per spring position listing sequences placed:count, leaving
+    // out the dead ends.
+    pub fn render(&self, springs_condition: &SpringsCondition) -> String {
+        let springs: String = springs_condition.springs.iter().map(|s| s.to_char()).collect();
+        let sequences: Vec<String> = springs_condition.damaged_sequences.iter().map(|ds| ds.to_string()).collect();
+
+        let mut out = format!("{} {} -> {} arrangements\n", springs, sequences.join(","), self.match_count);
+
+        for (pos, spring) in springs_condition.springs.iter().enumerate() {
+            let counts: Vec<String> = self.counts
+                .range((pos, 0) .. (pos + 1, 0))
+                .filter(|(_, count)| **count > 0)
+                .map(|((_, ds_pos), count)| format!("{}:{}", ds_pos, count))
+                .collect();
+
+            if !counts.is_empty() {
+                out.push_str(&format!("  {:>4} {}  {}\n", pos, spring.to_char(), counts.join(" ")));
+            }
+        }
+
+        out
+    }
+}
+
 pub struct SpringsConditionsSolver<'a> {
     pub springs_condition: &'a SpringsCondition,
     pub match_count_cache: HashMap<(usize, usize), u64>,
@@ -91,6 +186,15 @@ impl<'a> SpringsConditionsSolver<'a> {
         self.search_for_matches(0, 0)
     }
     
+    // Solves and hands back the memoized counts along with the answer.
+    pub fn solve_with_table(&mut self) -> MatchCountTable {
+        let match_count = self.solve();
+        MatchCountTable {
+            match_count,
+            counts: self.match_count_cache.iter().map(|(k, v)| (*k, *v)).collect(),
+        }
+    }
+
     fn set_match_count(&mut self, pos: usize, ds_pos: usize, match_count: u64) -> u64 {
         self.match_count_cache.insert((pos, ds_pos), match_count);
         match_count
@@ -100,6 +204,55 @@ impl<'a> SpringsConditionsSolver<'a> {
         pos >= self.springs_condition.springs.len() && ds_pos >= self.springs_condition.damaged_sequences.len()
     }
 
+    // The same counts as the recursive search, filled in from the end of the
+    // row back to the start so there's no recursion to run out of stack on
+    // long rows. counts[pos][ds_pos] is how many ways to finish the row from
+    // pos once ds_pos sequences are placed. A sequence ending at the last
+    // spring carries on from one past the end, so there's a row for that.
+    pub fn solve_bottom_up(&self) -> u64 {
+        let springs = &self.springs_condition.springs;
+        let damaged_sequences = &self.springs_condition.damaged_sequences;
+        let (len, ds_len) = (springs.len(), damaged_sequences.len());
+
+        let mut counts: Vec<Vec<u64>> = vec![vec![0; ds_len + 1]; len + 2];
+        counts[len][ds_len] = 1;
+        counts[len + 1][ds_len] = 1;
+
+        // Runs of springs that aren't operational ending at each position, so
+        // whether a sequence fits is a lookup.
+        let mut not_operational_from: Vec<usize> = vec![0; len + 1];
+        for pos in (0 .. len).rev() {
+            if !matches!(springs[pos], SpringCondition::Operational) {
+                not_operational_from[pos] = not_operational_from[pos + 1] + 1;
+            }
+        }
+
+        for pos in (0 .. len).rev() {
+            for ds_pos in 0 ..= ds_len {
+                let mut match_count = 0;
+
+                if !matches!(springs[pos], SpringCondition::Damaged) {
+                    match_count += counts[pos + 1][ds_pos];
+                }
+
+                if !matches!(springs[pos], SpringCondition::Operational) && ds_pos < ds_len {
+                    let end = pos + damaged_sequences[ds_pos] as usize;
+                    if not_operational_from[pos] >= end - pos {
+                        match springs.get(end) {
+                            None => match_count += counts[end][ds_pos + 1],
+                            Some(SpringCondition::Damaged) => {},
+                            Some(_) => match_count += counts[end + 1][ds_pos + 1],
+                        }
+                    }
+                }
+
+                counts[pos][ds_pos] = match_count;
+            }
+        }
+
+        counts[0][0]
+    }
+
     fn search_for_matches(&mut self, pos: usize, ds_pos: usize)
         -> u64
     {
@@ -176,29 +329,92 @@ impl<'a> SpringsConditionsSolver<'a> {
 
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut total: u64 = 0;
+// Explain mode shows the table for every row, or only the row given by
+// --param problem12.row=N (counting from 1).
+fn should_explain(row: usize) -> AOCResult<bool> {
+    if !settings::explain() {
+        return Ok(false);
+    }
+    Ok(match settings::params().get_or("problem12.row", 0usize)? {
+        0 => true,
+        explain_row => explain_row == row,
+    })
+}
 
-    each_line(input, |line| {
-        let springs_condition = SpringsCondition::parse(line)?;
-        let mut solver = SpringsConditionsSolver::new(&springs_condition);
-        total += solver.solve();
-        Ok(())
-    })?;
+// Solves and, in verify mode, checks small rows against brute force. The
+// explained table always comes from the recursive search, whatever solve is.
+fn count_matches(springs_condition: &SpringsCondition, row: usize, solve: fn(&SpringsCondition) -> u64) -> AOCResult<u64> {
+    let match_count = if should_explain(row)? {
+        let table = SpringsConditionsSolver::new(springs_condition).solve_with_table();
+        print!("Row {}: {}", row, table.render(springs_condition));
+        table.match_count
+    }
+    else {
+        solve(springs_condition)
+    };
+
+    if settings::verify() && springs_condition.unknown_count() <= MAX_BRUTE_FORCE_UNKNOWNS {
+        let brute_force_count = springs_condition.brute_force_count();
+        if brute_force_count != match_count {
+            return Err(AOCError::ProcessingError(format!(
+                "Verify failed for {:?}: solver found {} but brute force found {}",
+                springs_condition, match_count, brute_force_count)));
+        }
+    }
 
-    Ok(total.to_string())
+    Ok(match_count)
+}
+
+fn solve_recursive(springs_condition: &SpringsCondition) -> u64 {
+    SpringsConditionsSolver::new(springs_condition).solve()
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+fn solve_bottom_up(springs_condition: &SpringsCondition) -> u64 {
+    SpringsConditionsSolver::new(springs_condition).solve_bottom_up()
+}
+
+fn total_matches_with(springs_conditions: &[SpringsCondition], solve: fn(&SpringsCondition) -> u64) -> AOCResult<u64> {
     let mut total: u64 = 0;
+    for (idx, springs_condition) in springs_conditions.iter().enumerate() {
+        total += count_matches(springs_condition, idx + 1, solve)?;
+    }
+    Ok(total)
+}
 
-    each_line(input, |line| {
-        let springs_condition = SpringsCondition::parse(line)?;
-        let x_springs_condition = springs_condition.expand(4);
-        let mut solver = SpringsConditionsSolver::new(&x_springs_condition);
-        total += solver.solve();
+fn total_matches(springs_conditions: &Vec<SpringsCondition>) -> AOCResult<u64> {
+    Strategies::new("problem12")
+        .add("recursive", |springs_conditions: &Vec<SpringsCondition>| total_matches_with(springs_conditions, solve_recursive))
+        .add("bottom-up", |springs_conditions: &Vec<SpringsCondition>| total_matches_with(springs_conditions, solve_bottom_up))
+        .run(springs_conditions, |_| "bottom-up")
+}
+
+// Each row repeated this many times over in part 2, set with
+// problem12.copies. The puzzle's is 5.
+fn copies() -> AOCResult<u32> {
+    let copies = settings::params().get_or("problem12.copies", 5u32)?;
+    if copies < 1 {
+        return Err(AOCError::ProcessingError("problem12.copies must be at least 1.".into()));
+    }
+    Ok(copies)
+}
+
+fn parse_rows(input: impl ProblemInput, copies: u32) -> AOCResult<Vec<SpringsCondition>> {
+    let mut springs_conditions: Vec<SpringsCondition> = Vec::new();
+    each_line_with_policy(input, settings::parse_policy(), |line| {
+        springs_conditions.push(SpringsCondition::parse(line)?.expand(copies - 1));
         Ok(())
     })?;
+    Ok(springs_conditions)
+}
 
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let total = total_matches(&parse_rows(input, 1)?)?;
     Ok(total.to_string())
 }
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let total = total_matches(&parse_rows(input, copies()?)?)?;
+    Ok(total.to_string())
+}
+
+crate::register_day!(day: 12, title: "Hot Springs", parts: [1 => part1 (example = "21"), 2 => part2 (example = "525152")], skips_invalid: true);
@@ -1,22 +1,25 @@
-use std::path::Path;
-use std::mem::replace;
+use std::mem::take;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::each_line;
+use crate::aocio::{each_line, ProblemInput};
+use crate::geometry::Vec2;
+use crate::grid::{FromChar, Grid};
+use crate::parallel;
+use crate::visualize::{self, Picture, Visualizable};
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum GroundCover {
     Rock = 0,
     Ash,
 }
 
-impl GroundCover {
-    pub fn parse(c: char) -> AOCResult<Self> {
+impl FromChar for GroundCover {
+    fn from_char(c: u8) -> AOCResult<Self> {
         Ok(match c {
-            '.' => GroundCover::Ash,
-            '#' => GroundCover::Rock,
+            b'.' => GroundCover::Ash,
+            b'#' => GroundCover::Rock,
             _ => {
-                return Err(AOCError::ParseError(format!("Invalid ground cover: ({})", c)));
+                return Err(AOCError::ParseError(format!("Invalid ground cover: ({})", c as char)));
             }
         })
     }
@@ -24,96 +27,146 @@ impl GroundCover {
 
 #[derive(Debug)]
 pub struct IslandMap {
-    map: Vec<Vec<GroundCover>>,
+    map: Grid<GroundCover>,
 }
 
 impl IslandMap {
 
-    pub fn new(map: Vec<Vec<GroundCover>>) -> Self {
+    pub fn new(map: Grid<GroundCover>) -> Self {
         Self { map }
     }
 
     #[allow(dead_code)]
     pub fn render(&self) -> String {
-        let mut output = String::new();
-
-        for row in &self.map {
-            for val in row {
-                output.push(match val {
-                    GroundCover::Ash => '.',
-                    GroundCover::Rock => '#',
-                });
-            }
-            output.push('\n');
-        }
-
-        output
+        self.map.render(|val| match val {
+            GroundCover::Ash => '.',
+            GroundCover::Rock => '#',
+        })
     }
 
     pub fn columns_equal(&self, c1: usize, c2: usize) -> bool {
-        self.map.iter().all(|row| row[c1] == row[c2])
+        self.map.column(c1).eq(self.map.column(c2))
     }
 
     pub fn find_column_diffs(&self, c1: usize, c2: usize) -> Vec<usize> {
-        self.map.iter()
+        self.column_diffs(c1, c2).collect()
+    }
+
+    fn column_diffs(&self, c1: usize, c2: usize) -> impl Iterator<Item = usize> + '_ {
+        self.map.column(c1)
+            .zip(self.map.column(c2))
             .enumerate()
-            .filter(|(_, row)| row[c1] != row[c2])
+            .filter(|(_, (v1, v2))| v1 != v2)
             .map(|(r, _)| r)
-            .collect()
     }
 
     pub fn rows_equal(&self, r1: usize, r2: usize) -> bool {
-        self.map[r1] == self.map[r2]
+        self.map.row(r1) == self.map.row(r2)
     }
 
     pub fn find_row_diffs(&self, r1: usize, r2: usize) -> Vec<usize> {
-        self.map[r1]
+        self.row_diffs(r1, r2).collect()
+    }
+
+    fn row_diffs(&self, r1: usize, r2: usize) -> impl Iterator<Item = usize> + '_ {
+        self.map.row(r1)
             .iter()
-            .zip(&self.map[r2])
+            .zip(self.map.row(r2))
             .enumerate()
             .filter(|(_, (v1, v2))| v1 != v2)
             .map(|(c, _)| c)
-            .collect()
     }
 
     pub fn height(&self) -> usize {
-        self.map.len()
+        self.map.height()
     }
 
     pub fn width(&self) -> usize {
-        self.map[0].len()
+        self.map.width()
     }
 
-    pub fn parse_each<F>(input: impl AsRef<Path>, mut f: F) -> AOCResult<()>
+    pub fn parse_each<F>(input: impl ProblemInput, mut f: F) -> AOCResult<()>
         where F: FnMut(IslandMap) -> AOCResult<()>
     {
-        let mut map: Vec<Vec<GroundCover>> = Vec::new();
+        let mut lines: Vec<Vec<u8>> = Vec::new();
 
         each_line(input, |line| {
             let line = line.trim();
-            if line.len() == 0 {
-                if map.len() > 0 {
-                    f(IslandMap::new(replace(&mut map, Vec::new())))?;
+            if line.is_empty() {
+                if !lines.is_empty() {
+                    f(IslandMap::new(Grid::parse_lines(&take(&mut lines))?))?;
                 }
             }
             else {
-                let map_row = line
-                    .chars()
-                    .map(|c| GroundCover::parse(c))
-                    .collect::<AOCResult<Vec<GroundCover>>>()?;
-                map.push(map_row);
+                lines.push(line.as_bytes().to_vec());
             }
             Ok(())
         })?;
 
-        if map.len() > 0 {
-            f(IslandMap::new(map))?;
+        if !lines.is_empty() {
+            f(IslandMap::new(Grid::parse_lines(&lines)?))?;
         }
 
         Ok(())
     }
+
+    pub fn parse_all(input: impl ProblemInput) -> AOCResult<Vec<IslandMap>> {
+        let mut maps: Vec<IslandMap> = Vec::new();
+        Self::parse_each(input, |island_map| {
+            maps.push(island_map);
+            Ok(())
+        })?;
+        Ok(maps)
+    }
+}
+
+// Each map is independent, so they're scored on their own threads. The
+// scores come back in the same order as the maps.
+pub fn score_in_parallel<F>(island_maps: &[IslandMap], f: F) -> Vec<usize>
+    where F: Fn(&IslandMap) -> usize + Sync
+{
+    parallel::map_in_order(island_maps, f)
+}
+
+// Rocks in grey with the mirror lines across them, the part 1 ones in yellow
+// and the ones the smudges give in blue, with the smudge itself marked.
+impl Visualizable for IslandMap {
+    fn picture(&self) -> AOCResult<Picture> {
+        let mut picture = Picture::new(self.width(), self.height());
+        for ((y, x), cover) in self.map.cells() {
+            if *cover == GroundCover::Rock {
+                picture.set_cell(y, x, visualize::STONE);
+            }
+        }
+
+        let (width, height) = (self.width() as f64, self.height() as f64);
+        let horizontal = |row: usize| vec![Vec2::new(0.0, row as f64 + 1.0), Vec2::new(width, row as f64 + 1.0)];
+        let vertical = |col: usize| vec![Vec2::new(col as f64 + 1.0, 0.0), Vec2::new(col as f64 + 1.0, height)];
+
+        let mirror_finder = MirrorFinder::new(self);
+        for row in mirror_finder.find_horizontals() {
+            picture.add_path(horizontal(row), visualize::HIGHLIGHT, false);
+        }
+        for col in mirror_finder.find_verticals() {
+            picture.add_path(vertical(col), visualize::HIGHLIGHT, false);
+        }
+
+        for (y, x, row) in mirror_finder.find_row_mirror_smudges() {
+            picture.set_cell(y, x, visualize::WARM);
+            picture.add_path(horizontal(row), visualize::ACCENT, false);
+        }
+        for (y, x, col) in mirror_finder.find_column_mirror_smudges() {
+            picture.set_cell(y, x, visualize::WARM);
+            picture.add_path(vertical(col), visualize::ACCENT, false);
+        }
+
+        Ok(picture)
+    }
 }
 
+// About a 1000x1000 map.
+const PARALLEL_MIN_CELLS: usize = 1_000_000;
+
 pub struct MirrorFinder<'a> {
     island_map: &'a IslandMap,
 }
@@ -154,21 +207,31 @@ impl<'a> MirrorFinder<'a> {
         (0 .. dist).all(|delta| f(lower - delta, lower + 1 + delta))
     }
 
-    fn find_row_mirror_smudges(&self) -> Vec<(usize, usize, usize)> {
-        (0 .. self.island_map.height() - 1)
-            .flat_map(|row| self.find_row_mirror_smudge(row))
+    // Each candidate line is checked on its own, so big maps split them
+    // across threads.
+    pub fn find_row_mirror_smudges(&self) -> Vec<(usize, usize, usize)> {
+        let rows: Vec<usize> = (0 .. self.island_map.height() - 1).collect();
+        parallel::map_in_order_if(self.is_big(), &rows, |row| self.find_row_mirror_smudge(*row))
+            .into_iter()
+            .flatten()
             .collect()
     }
 
-    fn find_column_mirror_smudges(&self) -> Vec<(usize, usize, usize)> {
-        (0 .. self.island_map.width() - 1)
-            .flat_map(|col| self.find_column_mirror_smudge(col))
+    pub fn find_column_mirror_smudges(&self) -> Vec<(usize, usize, usize)> {
+        let cols: Vec<usize> = (0 .. self.island_map.width() - 1).collect();
+        parallel::map_in_order_if(self.is_big(), &cols, |col| self.find_column_mirror_smudge(*col))
+            .into_iter()
+            .flatten()
             .collect()
     }
 
+    fn is_big(&self) -> bool {
+        self.island_map.width() * self.island_map.height() >= PARALLEL_MIN_CELLS
+    }
+
     fn find_row_mirror_smudge(&self, row: usize) -> Option<(usize, usize, usize)> {
         let smudge = Self::find_smudge(row, self.island_map.height(), |r1, r2| {
-            self.island_map.find_row_diffs(r1, r2)
+            self.island_map.row_diffs(r1, r2)
         });
 
         smudge.map(|(r1, _r2, c)| (r1, c, row))
@@ -176,14 +239,16 @@ impl<'a> MirrorFinder<'a> {
 
     fn find_column_mirror_smudge(&self, col: usize) -> Option<(usize, usize, usize)> {
         let smudge = Self::find_smudge(col, self.island_map.width(), |c1, c2| {
-            self.island_map.find_column_diffs(c1, c2)
+            self.island_map.column_diffs(c1, c2)
         });
 
         smudge.map(|(c1, _c2, r)| (r, c1, col))
     }
 
-    fn find_smudge<F>(lower: usize, max: usize, f: F) -> Option<(usize, usize, usize)>
-        where F: Fn(usize, usize) -> Vec<usize>
+    // Gives up on a line at its second diff, which for wide maps is usually
+    // long before the lines have been compared all the way.
+    fn find_smudge<F, I>(lower: usize, max: usize, f: F) -> Option<(usize, usize, usize)>
+        where F: Fn(usize, usize) -> I, I: Iterator<Item = usize>
     {
         let dist = (lower + 1).min(max - lower - 1);
         let mut smudge: Option<(usize, usize, usize)> = None;
@@ -191,7 +256,7 @@ impl<'a> MirrorFinder<'a> {
         for delta in 0 .. dist {
             let lower_pos = lower - delta;
             let upper_pos = lower + 1 + delta;
-            let diff_positions = f(lower_pos, upper_pos);
+            let diff_positions: Vec<usize> = f(lower_pos, upper_pos).take(2).collect();
             if diff_positions.len() > 1 {
                 return None;
             }
@@ -214,27 +279,27 @@ pub fn score(verticals: &Vec<usize>, horizontals: &Vec<usize>) -> usize {
         100 * horizontals.iter().map(|h| h + 1).sum::<usize>()
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut result: usize = 0;
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let island_maps = IslandMap::parse_all(input)?;
 
-    IslandMap::parse_each(input, |island_map| {
-        let mirror_finder = MirrorFinder::new(&island_map);
+    let scores = score_in_parallel(&island_maps, |island_map| {
+        let mirror_finder = MirrorFinder::new(island_map);
 
         let verticals = mirror_finder.find_verticals();
         let horizontals = mirror_finder.find_horizontals();
 
-        result += score(&verticals, &horizontals);
-        Ok(())
-    })?;
+        score(&verticals, &horizontals)
+    });
 
+    let result: usize = scores.iter().sum();
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut result: usize = 0;
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let island_maps = IslandMap::parse_all(input)?;
 
-    IslandMap::parse_each(input, |island_map| {
-        let mirror_finder = MirrorFinder::new(&island_map);
+    let scores = score_in_parallel(&island_maps, |island_map| {
+        let mirror_finder = MirrorFinder::new(island_map);
 
         let row_smudges = mirror_finder.find_row_mirror_smudges();
         let col_smudges = mirror_finder.find_column_mirror_smudges();
@@ -242,10 +307,11 @@ pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
         let verticals: Vec<usize> = col_smudges.iter().map(|t| t.2).collect();
         let horizontals: Vec<usize> = row_smudges.iter().map(|t| t.2).collect();
 
-        result += score(&verticals, &horizontals);
-
-        Ok(())
-    })?;
+        score(&verticals, &horizontals)
+    });
 
+    let result: usize = scores.iter().sum();
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 13, title: "Point of Incidence", parts: [1 => part1 (example = "405"), 2 => part2 (example = "400")]);
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
-use std::path::Path;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
+use crate::geometry::Direction;
+use crate::grid::BitGrid;
+use crate::parallel;
+use crate::settings;
+use crate::strategy::Strategies;
+use crate::visualize::{self, Animation, Picture, Visualizable};
 
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum RockType {
@@ -33,40 +38,44 @@ impl RockType {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum Direction {
-    North = 0,
-    East,
-    South,
-    West,
-}
+// About a 1000x1000 platform.
+const PARALLEL_MIN_CELLS: usize = 1_000_000;
 
 #[derive(Debug, Clone)]
 pub struct MirrorPlatform {
     pub width: usize,
     pub height: usize,
-    pub rocks: Vec<Vec<RockType>>,
+
+    // The rocks are kept both a row at a time and a column at a time so every
+    // slide runs over contiguous memory. A slide works on one copy and then
+    // copies the result over into the other.
+    pub rows: Vec<RockType>,
+    pub columns: Vec<RockType>,
 }
 
 impl MirrorPlatform {
 
     pub fn new(width: usize, height: usize) -> MirrorPlatform {
-        let mut rocks: Vec<Vec<RockType>> = Vec::new();
-        for _ in 0..height {
-            rocks.push(vec![RockType::Space; width]);
+        Self {
+            width,
+            height,
+            rows: vec![RockType::Space; width * height],
+            columns: vec![RockType::Space; width * height],
         }
-        Self { width, height, rocks }
     }
 
     pub fn get(&self, y: usize, x: usize) -> Option<&RockType> {
-        match self.rocks.get(y) {
-            Some(row) => row.get(x),
-            None => None,
+        if y < self.height && x < self.width {
+            self.rows.get(y * self.width + x)
+        }
+        else {
+            None
         }
     }
 
     pub fn set(&mut self, y: usize, x: usize, rock_type: RockType) {
-        self.rocks[y][x] = rock_type;
+        self.rows[y * self.width + x] = rock_type;
+        self.columns[x * self.height + y] = rock_type;
     }
 
     #[allow(dead_code)]
@@ -88,10 +97,17 @@ impl MirrorPlatform {
         output
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<MirrorPlatform> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<MirrorPlatform> {
         let map_data = read_lines_as_bytes(input)?;
 
-        let width = map_data[0].len();
+        let width = map_data.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+        if let Some(y) = map_data.iter().position(|row| row.len() != width) {
+            return Err(AOCError::ParseError(format!("Row {} is {} wide, expected {}.", y + 1, map_data[y].len(), width)));
+        }
+
         let height = map_data.len();
         let mut mirror_platform = MirrorPlatform::new(width, height);
 
@@ -105,130 +121,115 @@ impl MirrorPlatform {
     }
 
     pub fn slide(&mut self, direction: Direction) {
-        let slider = MirrorPlatformSlider::new(self, direction);
-        slider.slide(self);
-    }
+        let (width, height) = (self.width, self.height);
 
-    pub fn calculate_load(&self) -> usize {
-        let mut load: usize = 0;
-        for (y, row) in self.rocks.iter().enumerate() {
-            for rock in row {
-                if rock.is_rounded() {
-                    load += self.height - y;
-                }
-            }
-        }
-        load
-    }
-}
-
-pub struct MirrorPlatformSlider {
-    start: i64,
-    end: i64,
-    delta: i64,
-
-    outer_start: i64,
-    outer_end: i64,
-
-    getter: fn(this: &MirrorPlatform, i64, i64) -> &RockType,
-    setter: fn(this: &mut MirrorPlatform, i64, i64, RockType),
-}
-
-impl MirrorPlatformSlider {
-
-    pub fn new(mirror_platform: &MirrorPlatform, direction: Direction) -> Self {
+        // Each line only moves rocks along itself, so the lines are slid on
+        // their own and the other copy is rebuilt from them afterwards.
         match direction {
-            Direction::North => MirrorPlatformSlider {
-                start: 0,
-                end: mirror_platform.height as i64,
-                delta: 1,
-                outer_start: 0,
-                outer_end: mirror_platform.width as i64,
-                getter: Self::get_xy,
-                setter: Self::set_xy,
+            Direction::North => {
+                Self::slide_lines(&mut self.columns, height, true);
+                Self::transpose(&self.columns, height, &mut self.rows);
             },
-            Direction::East => MirrorPlatformSlider {
-                start: mirror_platform.width as i64 - 1,
-                end: -1,
-                delta: -1,
-                outer_start: 0,
-                outer_end: mirror_platform.height as i64,
-                getter: Self::get_yx,
-                setter: Self::set_yx,
+            Direction::South => {
+                Self::slide_lines(&mut self.columns, height, false);
+                Self::transpose(&self.columns, height, &mut self.rows);
             },
-            Direction::South => MirrorPlatformSlider {
-                start: mirror_platform.height as i64 - 1,
-                end: -1,
-                delta: -1,
-                outer_start: 0,
-                outer_end: mirror_platform.width as i64,
-                getter: Self::get_xy,
-                setter: Self::set_xy,
+            Direction::West => {
+                Self::slide_lines(&mut self.rows, width, true);
+                Self::transpose(&self.rows, width, &mut self.columns);
+            },
+            Direction::East => {
+                Self::slide_lines(&mut self.rows, width, false);
+                Self::transpose(&self.rows, width, &mut self.columns);
             },
-            Direction::West => MirrorPlatformSlider {
-                start: 0,
-                end: mirror_platform.width as i64,
-                delta: 1,
-                outer_start: 0,
-                outer_end: mirror_platform.height as i64,
-                getter: Self::get_yx,
-                setter: Self::set_yx,
-            }
         }
     }
 
-    fn slide(&self, mirror_platform: &mut MirrorPlatform) {
-        // Perform the actual slide
-        for outer_pos in self.outer_start..self.outer_end {
-            // The inner loop does the slide for a row or column.
-            let mut inner_pos = self.start;
-            let mut move_to: Option<i64> = None;
-            
-            while inner_pos != self.end {
-                match (self.getter)(mirror_platform, outer_pos, inner_pos) {
-                    RockType::Space => {
-                        if let None = move_to {
-                            move_to = Some(inner_pos);
-                        }
-                    },
-                    RockType::Rounded => {
-                        if let Some(move_pos) = move_to {
-                            (self.setter)(mirror_platform, outer_pos, move_pos, RockType::Rounded);
-                            (self.setter)(mirror_platform, outer_pos, inner_pos, RockType::Space);
-                            move_to = Some(move_pos + self.delta);
-                        }
-                    },
-                    RockType::Cube => {
-                        move_to = None;
-                    }
-                }
-                inner_pos += self.delta;
+    // Big platforms split the lines across threads. Below this the threads
+    // cost more than they save.
+    fn slide_lines(lines: &mut [RockType], line_len: usize, to_start: bool) {
+        parallel::for_each_piece_mut_if(lines.len() >= PARALLEL_MIN_CELLS, lines, line_len, |line| {
+            Self::slide_line(line, to_start);
+        });
+    }
+
+    fn transpose(lines: &[RockType], line_len: usize, other: &mut [RockType]) {
+        let line_count = lines.len() / line_len.max(1);
+        for (i, line) in lines.chunks(line_len).enumerate() {
+            for (j, rock) in line.iter().enumerate() {
+                other[j * line_count + i] = *rock;
             }
         }
     }
 
-    fn get_yx(mirror_platform: &MirrorPlatform, y: i64, x: i64) -> &RockType {
-        mirror_platform.get(y as usize, x as usize).unwrap()
+    // Rounded rocks pile up at one end of each run between cube rocks, so count
+    // them and rewrite the run. Writing every cell was faster than branching on
+    // which rocks moved.
+    fn fill_run(line: &mut [RockType], start: usize, end: usize, rounded_start: usize, rounded_end: usize) {
+        for (pos, rock) in (start..end).zip(line[start..end].iter_mut()) {
+            *rock = if pos >= rounded_start && pos < rounded_end { RockType::Rounded } else { RockType::Space };
+        }
     }
 
-    fn get_xy(mirror_platform: &MirrorPlatform, x: i64, y: i64) -> &RockType {
-        Self::get_yx(mirror_platform, y, x)
+    fn count_rounded(run: &[RockType]) -> usize {
+        run.iter().filter(|rock| **rock == RockType::Rounded).count()
     }
 
-    fn set_yx(mirror_platform: &mut MirrorPlatform, y: i64, x: i64, rock_type: RockType) {
-        mirror_platform.set(y as usize, x as usize, rock_type);
+    fn slide_line(line: &mut [RockType], to_start: bool) {
+        let mut start = 0;
+        while start < line.len() {
+            let end = line[start..].iter().position(|rock| *rock == RockType::Cube).map_or(line.len(), |p| start + p);
+            let rounded = Self::count_rounded(&line[start..end]);
+            if to_start {
+                Self::fill_run(line, start, end, start, start + rounded);
+            }
+            else {
+                Self::fill_run(line, start, end, end - rounded, end);
+            }
+            start = end + 1;
+        }
+    }
+
+    pub fn calculate_load(&self) -> usize {
+        let mut load: usize = 0;
+        for (y, row) in self.rows.chunks(self.width).enumerate() {
+            for rock in row {
+                if rock.is_rounded() {
+                    load += self.height - y;
+                }
+            }
+        }
+        load
     }
+}
 
-    fn set_xy(mirror_platform: &mut MirrorPlatform, x: i64, y: i64, rock_type: RockType) {
-        Self::set_yx(mirror_platform, y, x, rock_type)
+// Rounded rocks light and cube rocks grey, as the platform is now.
+impl Visualizable for MirrorPlatform {
+    fn picture(&self) -> AOCResult<Picture> {
+        let mut picture = Picture::new(self.width, self.height);
+        for y in 0 .. self.height {
+            for x in 0 .. self.width {
+                match self.get(y, x) {
+                    Some(RockType::Rounded) => picture.set_cell(y, x, visualize::LIGHT),
+                    Some(RockType::Cube) => picture.set_cell(y, x, visualize::STONE),
+                    _ => {},
+                }
+            }
+        }
+        Ok(picture)
     }
 }
 
+// Spin cycles in part 2.
+const SPINS: i64 = 1_000_000;
+
+const SPIN_CYCLE: [Direction; 4] = [Direction::North, Direction::West, Direction::South, Direction::East];
+
 pub struct SpinTiltSolver {
     pub mirror_platform: MirrorPlatform,
     cycle_start: Option<i64>,
     cycle_end: Option<i64>,
-    map_steps: HashMap<Vec<Vec<RockType>>, (i64, usize)>,
+    map_steps: HashMap<Vec<RockType>, (i64, usize)>,
 }
 
 impl SpinTiltSolver {
@@ -241,20 +242,24 @@ impl SpinTiltSolver {
         }
     }
 
-    pub fn find_cycle(&mut self) {
+    // With --animate every slide is a frame.
+    pub fn find_cycle(&mut self) -> AOCResult<()> {
+        let mut animation = Animation::new("problem14", None);
         let mut cycle = 1;
 
         self.cycle_start = None;
         self.cycle_end = None;
         self.map_steps = HashMap::new();
 
+        animation.frame(|| self.mirror_platform.picture())?;
+
         while self.cycle_start.is_none() {
-            self.run_cycle();
+            self.run_cycle_animated(&mut animation)?;
     
-            match self.map_steps.get(&self.mirror_platform.rocks) {
+            match self.map_steps.get(&self.mirror_platform.rows) {
                 None => {
                     self.map_steps.insert(
-                        self.mirror_platform.rocks.clone(),
+                        self.mirror_platform.rows.clone(),
                         (cycle, self.mirror_platform.calculate_load())
                     );
                 },
@@ -266,6 +271,8 @@ impl SpinTiltSolver {
     
             cycle += 1;
         }
+
+        animation.finish(|| self.mirror_platform.picture())
     }
 
     pub fn get_load(&self, cycle: i64) -> AOCResult<usize> {
@@ -287,14 +294,171 @@ impl SpinTiltSolver {
     }
 
     pub fn run_cycle(&mut self) {
-        self.mirror_platform.slide(Direction::North);
-        self.mirror_platform.slide(Direction::West);
-        self.mirror_platform.slide(Direction::South);
-        self.mirror_platform.slide(Direction::East);
+        for direction in SPIN_CYCLE {
+            self.mirror_platform.slide(direction);
+        }
+    }
+
+    pub fn run_cycle_animated(&mut self, animation: &mut Animation) -> AOCResult<()> {
+        for direction in SPIN_CYCLE {
+            self.mirror_platform.slide(direction);
+            animation.frame(|| self.mirror_platform.picture())?;
+        }
+        Ok(())
+    }
+}
+
+// A stretch of a row or column between cube rocks, or the edge. Cells are
+// start, start + step and so on, length of them.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: usize,
+    step: usize,
+    length: usize,
+}
+
+impl Segment {
+
+    // The cells the rounded rocks fill, piled at the start or the end.
+    fn piled(&self, rounded: usize, at_start: bool) -> impl Iterator<Item = usize> + '_ {
+        let first = if at_start { 0 } else { self.length - rounded };
+        (first .. first + rounded).map(move |i| self.start + i * self.step)
+    }
+}
+
+// Spins without looking at empty space. Cube rocks never move, so rows and
+// columns split into the same segments every time, and after a tilt all a
+// segment needs to say is how many rounded rocks are piled at one end of it.
+// Tilting the other way round the platform just hands those rocks to the
+// segments they're in the other way, so a tilt costs as much as there are
+// rounded rocks, however big the platform.
+pub struct SegmentSpinner {
+    width: usize,
+    height: usize,
+    columns: Vec<Segment>,
+    rows: Vec<Segment>,
+    // Cell, by y * width + x, to the column or row segment it's in.
+    column_of: Vec<usize>,
+    row_of: Vec<usize>,
+    // Rounded rocks in each column segment, ready for the next tilt north.
+    counts: Vec<usize>,
+}
+
+impl SegmentSpinner {
+
+    pub fn new(mirror_platform: &MirrorPlatform) -> Self {
+        let (width, height) = (mirror_platform.width, mirror_platform.height);
+        let is_cube = |cell: usize| mirror_platform.rows[cell] == RockType::Cube;
+
+        let segments = |lines: usize, line_len: usize, cell: &dyn Fn(usize, usize) -> usize| {
+            let mut segments: Vec<Segment> = Vec::new();
+            let mut segment_of: Vec<usize> = vec![usize::MAX; width * height];
+            for line in 0 .. lines {
+                let mut pos = 0;
+                while pos < line_len {
+                    if is_cube(cell(line, pos)) {
+                        pos += 1;
+                        continue;
+                    }
+                    let length = (pos .. line_len).take_while(|p| !is_cube(cell(line, *p))).count();
+                    let step = if line_len > 1 { cell(line, 1) - cell(line, 0) } else { 1 };
+                    for p in pos .. pos + length {
+                        segment_of[cell(line, p)] = segments.len();
+                    }
+                    segments.push(Segment { start: cell(line, pos), step, length });
+                    pos += length;
+                }
+            }
+            (segments, segment_of)
+        };
+
+        let (columns, column_of) = segments(width, height, &|x, y| y * width + x);
+        let (rows, row_of) = segments(height, width, &|y, x| y * width + x);
+
+        let mut counts = vec![0; columns.len()];
+        for (cell, rock) in mirror_platform.rows.iter().enumerate() {
+            if rock.is_rounded() {
+                counts[column_of[cell]] += 1;
+            }
+        }
+
+        Self { width, height, columns, rows, column_of, row_of, counts }
+    }
+
+    // Piles each segment's rocks at one end and counts them into the
+    // segments going the other way.
+    fn tilt(counts: &[usize], segments: &[Segment], at_start: bool, other_of: &[usize], other_count: usize) -> Vec<usize> {
+        let mut other_counts = vec![0; other_count];
+        for (segment, rounded) in segments.iter().zip(counts) {
+            for cell in segment.piled(*rounded, at_start) {
+                other_counts[other_of[cell]] += 1;
+            }
+        }
+        other_counts
+    }
+
+    // North, west, south and east. Afterwards the rocks are piled at the east
+    // end of each row segment, with their counts in row_counts.
+    pub fn spin(&mut self) -> Vec<usize> {
+        let after_north = Self::tilt(&self.counts, &self.columns, true, &self.row_of, self.rows.len());
+        let after_west = Self::tilt(&after_north, &self.rows, true, &self.column_of, self.columns.len());
+        let row_counts = Self::tilt(&after_west, &self.columns, false, &self.row_of, self.rows.len());
+        self.counts = Self::tilt(&row_counts, &self.rows, false, &self.column_of, self.columns.len());
+        row_counts
+    }
+
+    // The rocks piled east after a spin, packed a bit per cell.
+    fn rounded_bits(&self, row_counts: &[usize]) -> BitGrid {
+        let mut bits = BitGrid::new(self.width, self.height);
+        for (segment, rounded) in self.rows.iter().zip(row_counts) {
+            for cell in segment.piled(*rounded, false) {
+                bits.insert(cell / self.width, cell % self.width);
+            }
+        }
+        bits
+    }
+
+    fn north_load(&self, row_counts: &[usize]) -> usize {
+        self.rows
+            .iter()
+            .zip(row_counts)
+            .map(|(segment, rounded)| rounded * (self.height - segment.start / self.width))
+            .sum()
+    }
+
+    // The load after this many spins, spinning until a platform repeats.
+    // Spins are counted from 1 the same way as SpinTiltSolver.
+    pub fn load_after(&mut self, spins: i64) -> AOCResult<usize> {
+        let mut seen: HashMap<BitGrid, i64> = HashMap::new();
+        let mut loads: Vec<usize> = vec![0];
+
+        for spin in 1 ..= spins {
+            let row_counts = self.spin();
+            loads.push(self.north_load(&row_counts));
+
+            if let Some(first) = seen.insert(self.rounded_bits(&row_counts), spin) {
+                let target = (spins - first) % (spin - first) + first;
+                return Ok(loads[target as usize]);
+            }
+        }
+
+        loads.last().copied().ok_or_else(|| AOCError::ProcessingError("No spins to load.".into()))
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+// Slides every cell of the platform. It's used with --animate, to draw each
+// slide.
+fn load_after_spins_grid(mirror_platform: &MirrorPlatform) -> AOCResult<usize> {
+    let mut solver = SpinTiltSolver::new(mirror_platform.clone());
+    solver.find_cycle()?;
+    solver.get_load(SPINS)
+}
+
+fn load_after_spins_segments(mirror_platform: &MirrorPlatform) -> AOCResult<usize> {
+    SegmentSpinner::new(mirror_platform).load_after(SPINS)
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let mut mirror_platform = MirrorPlatform::parse(input)?;
     mirror_platform.slide(Direction::North);
 
@@ -302,13 +466,15 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
     Ok(load.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let mirror_platform = MirrorPlatform::parse(input)?;
-    let mut solver = SpinTiltSolver::new(mirror_platform.clone());
 
-    solver.find_cycle();
-
-    let result = solver.get_load(1_000_000)?;
+    let result = Strategies::new("problem14")
+        .add("grid", load_after_spins_grid)
+        .add("segments", load_after_spins_segments)
+        .run(&mirror_platform, |_| if settings::animate().is_some() { "grid" } else { "segments" })?;
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 14, title: "Parabolic Reflector Dish", parts: [1 => part1, 2 => part2]);
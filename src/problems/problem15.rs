@@ -1,12 +1,10 @@
-use std::path::Path;
-use std::fs::File;
-use std::io::BufReader;
 use std::io::prelude::*;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::ProblemInput;
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
 
@@ -28,8 +26,8 @@ pub struct InitializationStep {
 
 impl InitializationStep {
 
-    pub fn load(input: impl AsRef<Path>) -> AOCResult<Vec<Self>> {
-        get_strings(input.as_ref())?
+    pub fn load(input: impl ProblemInput) -> AOCResult<Vec<Self>> {
+        get_strings(input)?
             .iter()
             .map(Self::parse)
             .collect::<AOCResult<Vec<Self>>>()
@@ -57,8 +55,8 @@ impl InitializationStep {
     }
 }
 
-fn get_strings(input: impl AsRef<Path>) -> AOCResult<Vec<String>> {
-    let reader = BufReader::new(File::open(input)?);
+fn get_strings(input: impl ProblemInput) -> AOCResult<Vec<String>> {
+    let reader = input.open()?;
     Ok(reader
         .lines()
         .nth(0)
@@ -76,11 +74,37 @@ fn get_strings(input: impl AsRef<Path>) -> AOCResult<Vec<String>> {
     Set the current value to the remainder of dividing itself by 256.
 */
 pub fn string_hash(input: impl AsRef<str>) -> i32 {
-    input
+    let hash = input
         .as_ref()
         .as_bytes()
         .iter()
-        .fold(0, |current, b| ((current + *b as i32) * 17) % 256)
+        .fold(0, |current, b| ((current + *b as i32) * 17) % 256);
+    debug_assert_box_id(hash);
+    hash
+}
+
+// The checks below are what the box operations promise. They only run in
+// debug builds, where they're cheap enough next to everything else.
+
+// Every hash has to name one of the 256 boxes.
+fn debug_assert_box_id(hash: i32) {
+    debug_assert!((0 .. 256).contains(&hash), "Hash outside of the boxes: {}", hash);
+}
+
+// Taking a lens out leaves the others in the order they were in.
+fn debug_assert_removed(before: &[String], after: &LightBox, label: &str) {
+    if cfg!(debug_assertions) {
+        let expected: Vec<&str> = before.iter().map(|l| l.as_str()).filter(|l| *l != label).collect();
+        assert_eq!(after.labels(), expected, "Removing {} moved other lenses", label);
+    }
+}
+
+// Adding a lens that's already there changes its focal length where it is.
+fn debug_assert_replaced(before: &[String], after: &LightBox, slot_id: usize, focal_length: i32) {
+    if cfg!(debug_assertions) {
+        assert_eq!(after.labels(), before, "Replacing a lens moved lenses");
+        assert_eq!(after.slots[slot_id].focal_length, focal_length, "Replacing a lens kept its focal length");
+    }
 }
 
 #[derive(Debug)]
@@ -118,19 +142,45 @@ impl LightBox {
             .map(|(idx, _)| idx)
     }
 
+    pub fn labels(&self) -> Vec<&str> {
+        self.slots.iter().map(|lens| lens.label.as_str()).collect()
+    }
+
+    pub fn lenses(&self) -> &[Lens] {
+        &self.slots
+    }
+
+    // Only worked out when the debug checks will look at it.
+    fn debug_labels(&self) -> Vec<String> {
+        if cfg!(debug_assertions) {
+            self.slots.iter().map(|lens| lens.label.clone()).collect()
+        }
+        else {
+            Vec::new()
+        }
+    }
+
     pub fn remove(&mut self, label: impl AsRef<str>) {
+        let label = label.as_ref();
         if let Some(id) = self.find_slot_id(label) {
+            let before = self.debug_labels();
+
             // This should bubble the item up to the end.
             for pos in id..(self.slots.len() -1) {
                 self.slots.swap(pos, pos+1);
             }
             self.slots.pop();
+
+            debug_assert_removed(&before, self, label);
         }
     }
 
     pub fn add(&mut self, lens: Lens) {
         if let Some(id) = self.find_slot_id(&lens.label) {
+            let before = self.debug_labels();
+            let focal_length = lens.focal_length;
             self.slots[id] = lens;
+            debug_assert_replaced(&before, self, id, focal_length);
         }
         else {
             self.slots.push(lens);
@@ -159,6 +209,10 @@ impl LightBoxes {
         }
     }
 
+    pub fn get_box(&self, box_id: usize) -> &LightBox {
+        &self.boxes[box_id]
+    }
+
     pub fn remove(&mut self, label: impl AsRef<str>) {
         let label = label.as_ref();
         let box_id = string_hash(label);
@@ -202,7 +256,7 @@ impl LightBoxes {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     Ok(get_strings(input)?
         .iter()
         .map(string_hash)
@@ -210,7 +264,7 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
         .to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let mut light_boxes = LightBoxes::new();
 
     let init_steps = InitializationStep::load(input)?;
@@ -221,4 +275,6 @@ pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
     let result = light_boxes.get_focussing_power();
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 15, title: "Lens Library", parts: [1 => part1 (example = "1320"), 2 => part2 (example = "145")]);
@@ -1,8 +1,15 @@
-use std::collections::HashSet;
-use std::path::Path;
 
-use crate::aocio::read_lines_as_bytes;
+use std::collections::HashMap;
+
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
 use crate::aocbase::{AOCResult, AOCError};
+use crate::geometry::Direction;
+use crate::graph::{self, Graph};
+use crate::grid::BitGrid;
+use crate::parallel;
+use crate::settings;
+use crate::strategy::Strategies;
+use crate::visualize::{self, Animation, Color, Picture, Visualizable};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
@@ -19,29 +26,25 @@ impl Position {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Photon {
     position: Position,
-    vector: Position,
+    direction: Direction,
 }
 
 impl Photon {
-    pub fn new(y: i64, x: i64, yv: i64, xv: i64) -> Self {
+    pub fn new(y: i64, x: i64, direction: Direction) -> Self {
         Self {
             position: Position::new(y, x),
-            vector: Position::new(yv, xv),
+            direction,
         }
     }
 
-    pub fn with_vector(&self, y: i64, x: i64) -> Photon {
-        Photon { position: self.position.clone(), vector: Position { y, x } }
-    }
-
-    pub fn set_vector(&mut self, y: i64, x: i64) {
-        self.vector.y = y;
-        self.vector.x = x;
+    pub fn with_direction(&self, direction: Direction) -> Photon {
+        Photon { position: self.position, direction }
     }
 
     pub fn move_step(&mut self) {
-        self.position.y += self.vector.y;
-        self.position.x += self.vector.x;
+        let (dy, dx) = self.direction.delta();
+        self.position.y += dy;
+        self.position.x += dx;
     }
 }
 
@@ -59,29 +62,41 @@ impl Reflector {
     pub fn apply(&self, mut photon: Photon) -> Vec<Photon> {
         match self {
             Reflector::Vertical => {
-                if photon.vector.x != 0 {
-                    photon.set_vector(1, 0);
-                    vec![photon, photon.with_vector(-1, 0)]
+                if photon.direction.is_horizontal() {
+                    photon.direction = Direction::South;
+                    vec![photon, photon.with_direction(Direction::North)]
                 }
                 else {
                     vec![photon]
                 }
             },
             Reflector::Horizontal => {
-                if photon.vector.y != 0 {
-                    photon.set_vector(0, 1);
-                    vec![photon, photon.with_vector(0, -1)]
+                if photon.direction.is_vertical() {
+                    photon.direction = Direction::East;
+                    vec![photon, photon.with_direction(Direction::West)]
                 }
                 else {
                     vec![photon]
                 }
             },
+            // Going across a \ turns right, going up or down it turns left.
             Reflector::DiagonallLeft => {
-                photon.set_vector(photon.vector.x, photon.vector.y);
+                photon.direction = if photon.direction.is_horizontal() {
+                    photon.direction.turn_right()
+                }
+                else {
+                    photon.direction.turn_left()
+                };
                 vec![photon]
             },
+            // And the other way round for /.
             Reflector::DiagonalRight => {
-                photon.set_vector(photon.vector.x * -1, photon.vector.y * -1);
+                photon.direction = if photon.direction.is_horizontal() {
+                    photon.direction.turn_left()
+                }
+                else {
+                    photon.direction.turn_right()
+                };
                 vec![photon]
             },
             Reflector::Space => {
@@ -105,12 +120,11 @@ impl Reflector {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Tile {
     pub reflector: Reflector,
-    pub energized: i32,
 }
 
 impl Tile {
     pub fn new(reflector: Reflector) -> Self {
-        Tile { reflector, energized: 0 }
+        Tile { reflector }
     }
 }
 
@@ -133,7 +147,7 @@ impl ReflectionGrid {
         self.tiles[0].len() as i64
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<ReflectionGrid> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<ReflectionGrid> {
         let lines = read_lines_as_bytes(input)?;
         let mut tiles: Vec<Vec<Tile>> = Vec::new();
 
@@ -147,109 +161,468 @@ impl ReflectionGrid {
             );
         }
 
+        let width = tiles.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+        if let Some(y) = tiles.iter().position(|row| row.len() != width) {
+            return Err(AOCError::ParseError(format!("Row {} is {} wide, expected {}.", y + 1, tiles[y].len(), width)));
+        }
+
         Ok(ReflectionGrid::new(tiles))
     }
 
-    pub fn send_photon(&mut self, photon: &Photon) {
+    pub fn contains(&self, photon: &Photon) -> bool {
+        photon.position.y >= 0 && photon.position.y < self.height() &&
+            photon.position.x >= 0 && photon.position.x < self.width()
+    }
+
+    fn reflector_at(&self, position: &Position) -> Reflector {
+        self.tiles[position.y as usize][position.x as usize].reflector
+    }
+
+    // The grid isn't changed, what the beams lit up is in the visitor, so
+    // any number of photons can be sent through the same grid at once.
+    pub fn send_photon(&self, photon: &Photon) -> PhotonVisitor<'_> {
         let mut visitor = PhotonVisitor::new(self);
         visitor.visit(photon);
+        visitor
     }
 
-    pub fn get_energized_count(&self) -> i64 {
-        let mut count: i64 = 0;
-        for row in &self.tiles {
-            for tile in row {
-                if tile.energized > 0 {
-                    count += 1;
+    // With a frame for each photon taken off the stack.
+    pub fn send_photon_animated(&self, photon: &Photon, animation: &mut Animation) -> AOCResult<PhotonVisitor<'_>> {
+        let mut visitor = PhotonVisitor::new(self);
+        visitor.visit_animated(photon, animation)?;
+        Ok(visitor)
+    }
+}
+
+// Mirrors and splitters in grey, with the tiles the part 1 beam lights up
+// shaded by how many times beams went through them.
+impl Visualizable for ReflectionGrid {
+    fn picture(&self) -> AOCResult<Picture> {
+        let lit = self.send_photon(&Photon::new(0, 0, Direction::East));
+        let most = lit.passes.iter().copied().max().unwrap_or(0).max(1);
+
+        let mut picture = Picture::new(self.width() as usize, self.height() as usize);
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let passes = lit.passes(y, x);
+                if tile.reflector != Reflector::Space {
+                    picture.set_cell(y, x, visualize::STONE);
+                }
+                else if passes > 0 {
+                    let fraction = passes as f64 / most as f64;
+                    picture.set_cell(y, x, Color::blend(visualize::WARM, visualize::HIGHLIGHT, fraction));
                 }
             }
         }
-        count
+        Ok(picture)
+    }
+}
+
+// What happened while a photon's beams were followed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VisitStats {
+    // Photons taken off the stack and moved through their tile.
+    pub photons_processed: usize,
+    // Position and direction pairs seen.
+    pub unique_states: usize,
+    // Beams that ran into a state already seen and were dropped.
+    pub loop_closures: usize,
+    // Beams that left the grid.
+    pub exits: usize,
+    // Most steps any beam took from the starting photon.
+    pub max_depth: usize,
+    // Tiles with at least one state, worked out from the visited bits
+    // rather than the pass counts.
+    pub energized_tiles: usize,
+}
+
+impl VisitStats {
+
+    // For totaling up many visits, keeping the deepest.
+    pub fn add(&mut self, other: &VisitStats) {
+        self.photons_processed += other.photons_processed;
+        self.unique_states += other.unique_states;
+        self.loop_closures += other.loop_closures;
+        self.exits += other.exits;
+        self.max_depth = self.max_depth.max(other.max_depth);
+        self.energized_tiles += other.energized_tiles;
+    }
+
+    // Every energized tile has to have been reached in at least one
+    // direction, and the bits have to agree with the pass counts.
+    pub fn check_energized(&self, energized_count: i64) -> AOCResult<()> {
+        if energized_count as usize != self.energized_tiles || self.energized_tiles > self.unique_states {
+            return Err(AOCError::ProcessingError(format!(
+                "Energized count {} doesn't match visit stats: {:?}", energized_count, self)));
+        }
+        Ok(())
+    }
+
+    pub fn pretty_print(&self) -> String {
+        let mut out = String::new();
+        out.push_str(format!("Photons processed: {}\n", self.photons_processed).as_str());
+        out.push_str(format!("Unique states:     {}\n", self.unique_states).as_str());
+        out.push_str(format!("Loop closures:     {}\n", self.loop_closures).as_str());
+        out.push_str(format!("Exits:             {}\n", self.exits).as_str());
+        out.push_str(format!("Max beam depth:    {}\n", self.max_depth).as_str());
+        out.push_str(format!("Energized tiles:   {}\n", self.energized_tiles).as_str());
+        out
     }
 }
 
 pub struct PhotonVisitor<'a> {
-    pub reflection_grid: &'a mut ReflectionGrid,
-    visited: HashSet<Photon>,
-    photons: Vec<Photon>,
+    pub reflection_grid: &'a ReflectionGrid,
+    // 4 bits per tile, one for each direction a photon has passed through it going.
+    // Swapping this in for a HashSet<Photon> took part 2 from ~325ms to ~48ms.
+    visited: BitGrid,
+    // How many photons went through each tile, by y * width + x. These used
+    // to be kept in the grid's tiles, so every beam needed a copy of the grid.
+    passes: Vec<u32>,
+    // Each photon with how many steps it is from the start.
+    photons: Vec<(Photon, usize)>,
+    stats: VisitStats,
 }
 
 impl<'a> PhotonVisitor<'a> {
-    pub fn new(reflection_grid: &'a mut ReflectionGrid) -> Self {
+    pub fn new(reflection_grid: &'a ReflectionGrid) -> Self {
+        let (width, height) = (reflection_grid.width() as usize, reflection_grid.height() as usize);
+
         Self {
             reflection_grid,
-            visited: HashSet::new(),
+            visited: BitGrid::new(width * 4, height),
+            passes: vec![0; width * height],
             photons: Vec::new(),
+            stats: VisitStats::default(),
         }
     }
 
+    pub fn stats(&self) -> VisitStats {
+        let energized_tiles = (0 .. self.visited.height())
+            .map(|y| (0 .. self.visited.width() / 4)
+                .filter(|x| (0 .. 4).any(|bit| self.visited.get(y, x * 4 + bit)))
+                .count())
+            .sum();
+
+        VisitStats { energized_tiles, ..self.stats }
+    }
+
+    pub fn passes(&self, y: usize, x: usize) -> u32 {
+        self.passes[y * self.reflection_grid.width() as usize + x]
+    }
+
+    // Tiles at least one photon went through.
+    pub fn energized_count(&self) -> i64 {
+        self.passes.iter().filter(|passes| **passes > 0).count() as i64
+    }
+
+    fn mark_visited(&mut self, photon: &Photon) -> bool {
+        self.visited.insert(
+            photon.position.y as usize,
+            photon.position.x as usize * 4 + photon.direction.index())
+    }
+
     pub fn visit(&mut self, photon: &Photon) {
-        if !self.visited.contains(photon) {
-            self.photons.push(photon.clone());
+        // Nothing is drawn, so there's nothing to fail.
+        let _ = self.visit_animated(photon, &mut Animation::off());
+    }
+
+    pub fn visit_animated(&mut self, photon: &Photon, animation: &mut Animation) -> AOCResult<()> {
+        if self.mark_visited(photon) {
+            self.stats.unique_states += 1;
+            self.photons.push((*photon, 0));
         }
 
-        let height = self.reflection_grid.height();
         let width = self.reflection_grid.width();
 
-        while let Some(photon) = self.photons.pop() {
+        while let Some((photon, depth)) = self.photons.pop() {
+            animation.frame(|| self.picture())?;
+            self.stats.photons_processed += 1;
+            self.stats.max_depth = self.stats.max_depth.max(depth);
 
-            let tile = &mut (self.reflection_grid
-                .tiles
-                [photon.position.y as usize]
-                [photon.position.x as usize]);
+            self.passes[(photon.position.y * width + photon.position.x) as usize] += 1;
 
-            tile.energized += 1;
-
-            let mut photons = tile.reflector.apply(photon);
+            let mut photons = self.reflection_grid.reflector_at(&photon.position).apply(photon);
             for photon in photons.iter_mut() {
                 photon.move_step();
 
-                if photon.position.x >= 0 && photon.position.x < width &&
-                    photon.position.y >= 0 && photon.position.y < height &&
-                    !self.visited.contains(photon)
-                {
-                    self.visited.insert(photon.clone());
-                    self.photons.push(*photon);
+                if !self.reflection_grid.contains(photon) {
+                    self.stats.exits += 1;
+                }
+                else if self.mark_visited(photon) {
+                    self.stats.unique_states += 1;
+                    self.photons.push((*photon, depth + 1));
+                }
+                else {
+                    self.stats.loop_closures += 1;
                 }
             }
         }
+
+        animation.finish(|| self.picture())
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut reflection_grid = ReflectionGrid::parse(input)?;
-    let initial_photon = Photon::new(0, 0, 0, 1);
+// The tiles lit up so far, with the photons still to be followed on top.
+impl Visualizable for PhotonVisitor<'_> {
+    fn picture(&self) -> AOCResult<Picture> {
+        let grid = &self.reflection_grid;
+        let mut picture = Picture::new(grid.width() as usize, grid.height() as usize);
+        for (y, row) in grid.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if self.passes(y, x) > 0 {
+                    picture.set_cell(y, x, visualize::WARM);
+                }
+                else if tile.reflector != Reflector::Space {
+                    picture.set_cell(y, x, visualize::STONE);
+                }
+            }
+        }
+        for (photon, _) in &self.photons {
+            picture.set_cell(photon.position.y as usize, photon.position.x as usize, visualize::HIGHLIGHT);
+        }
+        Ok(picture)
+    }
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let reflection_grid = ReflectionGrid::parse(input)?;
+    let initial_photon = Photon::new(0, 0, Direction::East);
+
+    // Beams don't usually go through a tile more than twice.
+    let tiles = (reflection_grid.width() * reflection_grid.height()) as u64;
+    let mut animation = Animation::new("problem16", Some(tiles * 2));
+    let visitor = reflection_grid.send_photon_animated(&initial_photon, &mut animation)?;
+    let stats = visitor.stats();
+    let result = visitor.energized_count();
+
+    tracing::debug!("{}", stats.pretty_print().trim_end());
 
-    reflection_grid.send_photon(&initial_photon);
-    let result = reflection_grid.get_energized_count();
+    if settings::verify() {
+        stats.check_energized(result)?;
+    }
 
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let reflection_grid = ReflectionGrid::parse(input)?;
+// Beams only branch at a splitter hit side on, and between splitters a beam
+// always goes the same way from a given tile and direction. So every splitter
+// is a node, with the tiles its two beams light up on their way to the next
+// splitters as its own. Beams entering from different edges soon run into the
+// same splitters, and what a splitter lights up is worked out once and shared
+// instead of followed again for every entry.
+pub struct BeamGraph<'a> {
+    reflection_grid: &'a ReflectionGrid,
+    // Splitter position to its node.
+    nodes: HashMap<Position, usize>,
+    // Everything lit up from each node on, both beams and everything they
+    // lead to.
+    energized: Vec<BitGrid>,
+}
+
+impl<'a> BeamGraph<'a> {
+
+    pub fn new(reflection_grid: &'a ReflectionGrid) -> Self {
+        let mut graph = Self { reflection_grid, nodes: HashMap::new(), energized: Vec::new() };
+
+        for (y, row) in reflection_grid.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                if let Reflector::Vertical | Reflector::Horizontal = tile.reflector {
+                    let node = graph.nodes.len();
+                    graph.nodes.insert(Position::new(y as i64, x as i64), node);
+                }
+            }
+        }
+
+        // Each splitter's own tiles and the splitters its beams run into.
+        let mut splitters: Graph<BitGrid> = Graph::new();
+        for _ in 0 .. graph.nodes.len() {
+            splitters.add_node(graph.empty_tiles());
+        }
+
+        for (position, node) in &graph.nodes {
+            let tile = &reflection_grid.tiles[position.y as usize][position.x as usize];
+            // Side on is the same both ways, either splits into the same two beams.
+            let side_on = match tile.reflector {
+                Reflector::Vertical => Photon::new(position.y, position.x, Direction::East),
+                _ => Photon::new(position.y, position.x, Direction::South),
+            };
+
+            let mut tiles = graph.empty_tiles();
+            tiles.insert(position.y as usize, position.x as usize);
+            for mut photon in tile.reflector.apply(side_on) {
+                photon.move_step();
+                if let Some(end) = graph.trace(photon, &mut tiles) {
+                    splitters.add_edge(*node, end);
+                }
+            }
+            *splitters.node_mut(*node) = tiles;
+        }
+
+        // Splitters that beams go around between light up the same tiles.
+        // Components come after the ones they lead to, so those are done.
+        let mut energized: Vec<Option<BitGrid>> = vec![None; splitters.node_count()];
+        for members in graph::strongly_connected_components(&splitters) {
+            let mut component_energized = graph.empty_tiles();
+            for &member in &members {
+                component_energized.union_with(splitters.node(member));
+                for &next in splitters.successors(member) {
+                    if let Some(next_energized) = &energized[next] {
+                        component_energized.union_with(next_energized);
+                    }
+                }
+            }
+            for &member in &members {
+                energized[member] = Some(component_energized.clone());
+            }
+        }
+
+        graph.energized = energized.into_iter().map(|energized| energized.unwrap()).collect();
+        graph
+    }
+
+    fn empty_tiles(&self) -> BitGrid {
+        BitGrid::new(self.reflection_grid.width() as usize, self.reflection_grid.height() as usize)
+    }
+
+    // Follows a beam until it leaves the grid or reaches a splitter side on,
+    // lighting up the tiles it passes. Gives the splitter, if it got to one.
+    fn trace(&self, mut photon: Photon, tiles: &mut BitGrid) -> Option<usize> {
+        // Mirrors alone can send a beam round in a circle, which it can't
+        // go around more than once without visiting a state twice.
+        let max_steps = 4 * self.reflection_grid.width() * self.reflection_grid.height();
+
+        for _ in 0 .. max_steps {
+            if !self.reflection_grid.contains(&photon) {
+                return None;
+            }
+
+            let reflector = self.reflection_grid.reflector_at(&photon.position);
+            tiles.insert(photon.position.y as usize, photon.position.x as usize);
+
+            let mut photons = reflector.apply(photon);
+            if photons.len() > 1 {
+                return self.nodes.get(&photon.position).copied();
+            }
+            photon = photons.pop().unwrap();
+            photon.move_step();
+        }
+
+        None
+    }
+
+    // How many tiles a beam starting with this photon lights up.
+    pub fn energized_count(&self, photon: &Photon) -> usize {
+        let mut tiles = self.empty_tiles();
+        if let Some(node) = self.trace(*photon, &mut tiles) {
+            tiles.union_with(&self.energized[node]);
+        }
+        tiles.count()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+// Every beam that can come in from an edge.
+fn edge_photons(reflection_grid: &ReflectionGrid) -> Vec<Photon> {
+    let (height, width) = (reflection_grid.height(), reflection_grid.width());
+    let mut photons: Vec<Photon> = Vec::new();
 
-    let mut energized_counts: Vec<i64> = Vec::new();
+    for x in 0 .. width {
+        photons.push(Photon::new(0, x, Direction::South));
+        photons.push(Photon::new(height - 1, x, Direction::North));
+    }
+
+    for y in 0 .. height {
+        photons.push(Photon::new(y, 0, Direction::East));
+        photons.push(Photon::new(y, width - 1, Direction::West));
+    }
+
+    photons
+}
 
-    let mut send_and_record = |photon: Photon| {
-        let mut rg = reflection_grid.clone();
-        rg.send_photon(&photon);
-        energized_counts.push(rg.get_energized_count());
-    };
+// Follows one entry's beams from scratch.
+fn simulate_entry(reflection_grid: &ReflectionGrid, photon: &Photon) -> AOCResult<(i64, VisitStats)> {
+    let visitor = reflection_grid.send_photon(photon);
+    let stats = visitor.stats();
+    let energized_count = visitor.energized_count();
 
-    for x in 0..reflection_grid.width() {
-        send_and_record(Photon::new(0, x, 1, 0));
-        send_and_record(Photon::new(reflection_grid.height()-1, x, -1, 0));
+    if settings::verify() {
+        stats.check_energized(energized_count)?;
     }
 
-    for y in 0..reflection_grid.height() {
-        send_and_record(Photon::new(y, 0, 0, 1));
-        send_and_record(Photon::new(0, reflection_grid.width() - 1, 0, -1));
+    Ok((energized_count, stats))
+}
+
+// Follows each entry's beams from scratch, on as many threads as there are
+// cores when parallel. Entries don't share anything, so they can go in any
+// order.
+fn max_energized_simulated_with(reflection_grid: &ReflectionGrid, parallel: bool) -> AOCResult<i64> {
+    let photons = edge_photons(reflection_grid);
+    let entries = parallel::map_in_order_if(parallel, &photons, |photon| simulate_entry(reflection_grid, photon));
+
+    let mut max_energized: Option<i64> = None;
+    let mut total_stats = VisitStats::default();
+
+    for entry in entries {
+        let (energized_count, stats) = entry?;
+        total_stats.add(&stats);
+        max_energized = max_energized.max(Some(energized_count));
     }
 
-    let max_ec = energized_counts.iter().max();
-    
-    Ok(max_ec
-        .ok_or_else(|| AOCError::ProcessingError("No maximum value found.".into()))?
-        .to_string())
-}
\ No newline at end of file
+    tracing::debug!("{}", total_stats.pretty_print().trim_end());
+
+    max_energized.ok_or_else(|| AOCError::ProcessingError("No maximum value found.".into()))
+}
+
+fn max_energized_simulated(reflection_grid: &ReflectionGrid) -> AOCResult<i64> {
+    max_energized_simulated_with(reflection_grid, false)
+}
+
+fn max_energized_parallel(reflection_grid: &ReflectionGrid) -> AOCResult<i64> {
+    max_energized_simulated_with(reflection_grid, true)
+}
+
+// Shares what each splitter lights up between entries. With --verify every
+// entry is simulated too and they have to agree.
+fn max_energized_graph(reflection_grid: &ReflectionGrid) -> AOCResult<i64> {
+    let graph = BeamGraph::new(reflection_grid);
+
+    tracing::debug!("Splitters: {}", graph.node_count());
+
+    let mut max_energized: Option<i64> = None;
+
+    for photon in edge_photons(reflection_grid) {
+        let energized_count = graph.energized_count(&photon) as i64;
+
+        if settings::verify() {
+            let simulated = reflection_grid.send_photon(&photon).energized_count();
+            if simulated != energized_count {
+                return Err(AOCError::ProcessingError(format!(
+                    "Beam graph lit up {} tiles for {:?}, simulating lit up {}.", energized_count, photon, simulated)));
+            }
+        }
+
+        max_energized = max_energized.max(Some(energized_count));
+    }
+
+    max_energized.ok_or_else(|| AOCError::ProcessingError("No maximum value found.".into()))
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let reflection_grid = ReflectionGrid::parse(input)?;
+
+    let result = Strategies::new("problem16")
+        .add("graph", max_energized_graph)
+        .add("simulate", max_energized_simulated)
+        .add("parallel", max_energized_parallel)
+        .run(&reflection_grid, |_| "graph")?;
+
+    Ok(result.to_string())
+}
+
+crate::register_day!(day: 16, title: "The Floor Will Be Lava", parts: [1 => part1 (example = "46"), 2 => part2 (example = "51")]);
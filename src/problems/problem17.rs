@@ -1,74 +1,69 @@
-use std::collections::BinaryHeap;
-use std::collections::HashMap;
-use std::path::Path;
 
-use crate::aocio::read_lines_as_bytes;
+use crate::algorithms::{self, Search, ShortestPath};
 use crate::aocbase::{AOCResult, AOCError};
-
-fn num_from_char(c: char) -> AOCResult<i32> {
-    if c >= '0' && c <= '9' {
-        Ok(c as i32 - '0' as i32)
-    }
-    else {
-        Err(AOCError::ParseError(format!("Invalid number character: {}", c)))
-    }
-}
+use crate::aocio::ProblemInput;
+use crate::geometry::Direction;
+use crate::grid::Grid;
+use crate::settings;
+use crate::strategy::Strategies;
+use crate::visualize::{self, Color, Picture, Visualizable};
 
 #[derive(Debug, Clone)]
 pub struct HeatLossMap {
-    map: Vec<Vec<i32>>,
+    map: Grid<i32>,
 }
 
 impl HeatLossMap {
 
     pub fn width(&self) -> i32 {
-        self.map[0].len() as i32
+        self.map.width() as i32
     }
 
     pub fn height(&self) -> i32 {
-        self.map.len() as i32
+        self.map.height() as i32
     }
 
     pub fn get_value(&self, y: i32, x: i32) -> i32 {
-        self.map[y as usize][x as usize]
+        self.map[(y as usize, x as usize)]
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> {
-        let lines = read_lines_as_bytes(input)?;
-        let mut map: Vec<Vec<i32>> = Vec::new();
-
-        for line in lines {
-            map.push(line
-                .iter()
-                .map(|c| num_from_char(*c as char))
-                .collect::<AOCResult<Vec<i32>>>()?);
-        }
+    pub fn min_value(&self) -> i32 {
+        self.map.cells().map(|(_, value)| *value).min().unwrap_or(0)
+    }
 
-        Ok(HeatLossMap { map })
+    pub fn max_value(&self) -> i32 {
+        self.map.cells().map(|(_, value)| *value).max().unwrap_or(0)
     }
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub enum Direction {
-    Up = 0,
-    Down,
-    Left,
-    Right,
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
+        Ok(HeatLossMap { map: Grid::parse(input)? })
+    }
 }
 
-impl Direction {
-    pub fn opposite(&self, other: &Direction) -> bool {
-        use Direction::*;
-        match (self, other) {
-            (Up, Down)|(Down, Up)|(Left, Right)|(Right, Left) => true,
-            _ => false,
+// Blocks shaded from dark for the least heat loss to light for the most,
+// with the best part 1 path drawn over them.
+impl Visualizable for HeatLossMap {
+    fn picture(&self) -> AOCResult<Picture> {
+        let (least, most) = (self.min_value(), self.map.cells().map(|(_, value)| *value).max().unwrap_or(0));
+        let mut picture = Picture::new(self.width() as usize, self.height() as usize);
+        for ((y, x), value) in self.map.cells() {
+            let fraction = (*value - least) as f64 / (most - least).max(1) as f64;
+            picture.set_cell(y, x, Color::blend(visualize::BACKGROUND, visualize::STONE, 0.25 + fraction * 0.75));
         }
+
+        let path_finder = HLPathFinder::new(self, (self.height() - 1, self.width() - 1));
+        let shortest = path_finder.find((0, 0), &Part1PathFinderRules::new())?;
+        let cells: Vec<(usize, usize)> = shortest.path.iter().map(|pf_st| (pf_st.y as usize, pf_st.x as usize)).collect();
+        picture.add_cell_path(&cells, visualize::HIGHLIGHT, false);
+
+        Ok(picture)
     }
 }
 
+// Where the crucible is and how it got there. The heat lost so far is kept
+// by the search, not in the state.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PathFindState {
-    pub heat_loss: i32,
     pub direction: Direction,
     pub direction_count: i32,
     pub y: i32,
@@ -77,6 +72,10 @@ pub struct PathFindState {
 
 impl PathFindState {
 
+    pub fn new(direction: Direction, direction_count: i32, y: i32, x: i32) -> Self {
+        Self { direction, direction_count, y, x }
+    }
+
     pub fn apply(&self, direction: &Direction) -> PathFindState {
         let mut new_st = self.clone();
 
@@ -87,46 +86,16 @@ impl PathFindState {
             new_st.direction_count = 1;
         };
 
-        match direction {
-            Direction::Up => {
-                new_st.y -= 1;
-            },
-            Direction::Down => {
-                new_st.y += 1;
-            },
-            Direction::Right => {
-                new_st.x += 1;
-            },
-            Direction::Left => {
-                new_st.x -= 1;
-            }
-        }
+        let (dy, dx) = direction.delta();
+        new_st.y += dy as i32;
+        new_st.x += dx as i32;
 
-        new_st.direction = direction.clone();
+        new_st.direction = *direction;
         new_st
     }
 
 }
 
-impl PartialOrd for PathFindState {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for PathFindState {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (self.heat_loss * -1, &self.direction, self.direction_count)
-            .cmp(&(other.heat_loss * -1, &other.direction, other.direction_count))
-    }
-}
-
-impl PathFindState {
-    pub fn new(heat_loss: i32, direction: Direction, direction_count: i32, y: i32, x: i32) -> Self {
-        Self { heat_loss, direction, direction_count, y, x }
-    }
-}
-
 pub trait HLPathFinderRules {
 
     fn is_endable(&self,
@@ -138,100 +107,207 @@ pub trait HLPathFinderRules {
         pf_st: &PathFindState,
         d: &Direction) -> bool;
 
-    fn check_prune(&self,
-        path_finder: &HLPathFinder,
+    // Whether a crucible that's gone run blocks in a straight line can do
+    // everything one that's gone other_run blocks the same way can, so the
+    // searches needn't carry on from the second when it's lost no less heat.
+    fn run_dominates(&self, run: i32, other_run: i32) -> bool {
+        run == other_run
+    }
+}
+
+// What moving from one state to the next costs. The search needs costs that
+// are never negative, and its guess at the cost left assumes every move
+// costs at least the heat lost in the block moved into.
+pub trait HLCostModel {
+
+    fn cost(&self,
+        heat_loss_map: &HeatLossMap,
+        pf_st: &PathFindState,
+        next_pf_st: &PathFindState) -> i32;
+
+    // The most any one move can cost, for the bucket search.
+    fn max_cost(&self, heat_loss_map: &HeatLossMap) -> i32;
+}
+
+// The puzzle's model, the heat lost in the block moved into.
+pub struct StandardCostModel {
+}
+
+impl HLCostModel for StandardCostModel {
+
+    fn cost(&self,
+        heat_loss_map: &HeatLossMap,
+        _pf_st: &PathFindState,
+        next_pf_st: &PathFindState) -> i32
+    {
+        heat_loss_map.get_value(next_pf_st.y, next_pf_st.x)
+    }
+
+    fn max_cost(&self, heat_loss_map: &HeatLossMap) -> i32 {
+        heat_loss_map.max_value()
+    }
+}
+
+// The standard cost plus a penalty every time the crucible turns.
+pub struct TurnPenaltyCostModel {
+    pub penalty: i32,
+}
+
+impl HLCostModel for TurnPenaltyCostModel {
+
+    fn cost(&self,
+        heat_loss_map: &HeatLossMap,
         pf_st: &PathFindState,
-        direction_tracking: &HashMap<i32, i32>) -> bool;
+        next_pf_st: &PathFindState) -> i32
+    {
+        let heat_loss = heat_loss_map.get_value(next_pf_st.y, next_pf_st.x);
+
+        // The first move from the start isn't a turn.
+        if pf_st.direction_count > 0 && pf_st.direction != next_pf_st.direction {
+            heat_loss + self.penalty
+        }
+        else {
+            heat_loss
+        }
+    }
+
+    fn max_cost(&self, heat_loss_map: &HeatLossMap) -> i32 {
+        heat_loss_map.max_value() + self.penalty
+    }
 }
 
 pub struct HLPathFinder<'a>
 {
     heat_loss_map: &'a HeatLossMap,
     end: (i32, i32),
-    path_find_states: BinaryHeap<PathFindState>,
-    known_states: HashMap<(i32, i32, Direction), HashMap<i32, i32>>,
-} 
+}
 
 impl<'a> HLPathFinder<'a> {
 
     pub fn new(heat_loss_map: &'a HeatLossMap, end: (i32, i32)) -> Self {
-        Self {
-            heat_loss_map,
-            end,
-            path_find_states: BinaryHeap::new(),
-            known_states: HashMap::new(),
-        }
+        Self { heat_loss_map, end }
     }
 
-    fn add_state(&mut self, pf_st: PathFindState, rules: &impl HLPathFinderRules) {
-        let key = (pf_st.y, pf_st.x, pf_st.direction.clone());
+    pub fn find(&self, start: (i32, i32), rules: &impl HLPathFinderRules) -> AOCResult<ShortestPath<PathFindState, i32>> {
+        self.find_with_cost(start, rules, &StandardCostModel {})
+    }
 
-        match self.known_states.get(&key) {
-            None => {
-                self.known_states.insert(key.clone(), HashMap::new());
-            },
-            Some(direction_tracking) => {
-                if rules.check_prune(self, &pf_st, direction_tracking) {
-                    return;
-                }
+    // An A* search where the guess at what's left is the distance to the end
+    // at the least heat loss of any block. States that a shorter run to the
+    // same block in the same direction, for no more heat, was already
+    // searched from are skipped.
+    pub fn find_with_cost(&self,
+        (y, x): (i32, i32),
+        rules: &impl HLPathFinderRules,
+        cost_model: &impl HLCostModel) -> AOCResult<ShortestPath<PathFindState, i32>>
+    {
+        let min_heat_loss = self.heat_loss_map.min_value();
+        let width = self.heat_loss_map.width();
+
+        // Runs and the heat lost getting there searched from for each block
+        // and direction.
+        let mut searched: Vec<Vec<(i32, i32)>> = vec![Vec::new(); (width * self.heat_loss_map.height()) as usize * 4];
+
+        let is_dominated = |pf_st: &PathFindState, heat_loss: i32| {
+            let runs = &mut searched[(pf_st.y * width + pf_st.x) as usize * 4 + pf_st.direction.index()];
+            if runs.iter().any(|(run, run_heat_loss)| rules.run_dominates(*run, pf_st.direction_count) && *run_heat_loss <= heat_loss) {
+                return true;
             }
+            runs.push((pf_st.direction_count, heat_loss));
+            false
         };
 
-        // The code above should guarantee key exists.
-        self.known_states
-            .get_mut(&key)
-            .unwrap()
-            .insert(pf_st.direction_count, pf_st.heat_loss);
-
-        self.path_find_states.push(pf_st);
-    }
+        let heuristic = |pf_st: &PathFindState| {
+            min_heat_loss * ((self.end.0 - pf_st.y).abs() + (self.end.1 - pf_st.x).abs())
+        };
 
-    pub fn find(&mut self, (y, x): (i32, i32), rules: &impl HLPathFinderRules) -> AOCResult<PathFindState> {
-        self.add_state(PathFindState::new(0, Direction::Down, 0, y, x), rules);
+        let is_end = |pf_st: &PathFindState| {
+            pf_st.y == self.end.0 && pf_st.x == self.end.1 && rules.is_endable(self, pf_st)
+        };
 
-        let directions = vec![
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ];
+        algorithms::astar_pruned(PathFindState::new(Direction::South, 0, y, x), self.successors(rules, cost_model), heuristic, is_dominated, is_end)
+            .ok_or_else(|| AOCError::ProcessingError("Could not find path.".into()))
+    }
 
+    // The same search as find_with_cost without a guess at what's left,
+    // using Dial's buckets for the cheapest state next and skipping states
+    // that a shorter run to the same block in the same direction, already
+    // searched from, beats.
+    pub fn find_with_buckets(&self,
+        (y, x): (i32, i32),
+        rules: &impl HLPathFinderRules,
+        cost_model: &impl HLCostModel) -> AOCResult<ShortestPath<PathFindState, i32>>
+    {
         let width = self.heat_loss_map.width();
-        let height = self.heat_loss_map.height();
 
-        while let Some(pf_st) = self.path_find_states.pop() {
-            // Found end state
-            if pf_st.y == self.end.0 && pf_st.x == self.end.1 &&
-                rules.is_endable(self, &pf_st)
-            {
-                return Ok(pf_st);
+        // Runs searched from for each block and direction.
+        let mut searched: Vec<Vec<i32>> = vec![Vec::new(); (width * self.heat_loss_map.height()) as usize * 4];
+
+        let is_dominated = |pf_st: &PathFindState| {
+            let runs = &mut searched[(pf_st.y * width + pf_st.x) as usize * 4 + pf_st.direction.index()];
+            if runs.iter().any(|run| rules.run_dominates(*run, pf_st.direction_count)) {
+                return true;
             }
+            runs.push(pf_st.direction_count);
+            false
+        };
 
-            for d in &directions {
-                if !rules.check_direction(self, &pf_st, &d) {
-                    continue;
-                }
+        let is_end = |pf_st: &PathFindState| {
+            pf_st.y == self.end.0 && pf_st.x == self.end.1 && rules.is_endable(self, pf_st)
+        };
 
-                let mut next_pf_st = pf_st.apply(d);
+        let start = PathFindState::new(Direction::South, 0, y, x);
+        algorithms::dial(start, cost_model.max_cost(self.heat_loss_map), self.successors(rules, cost_model), is_dominated, is_end)
+            .ok_or_else(|| AOCError::ProcessingError("Could not find path.".into()))
+    }
 
-                if next_pf_st.y >= 0 &&
-                    next_pf_st.y < height &&
-                    next_pf_st.x >= 0 &&
-                    next_pf_st.x < width
-                {
-                    let hl = self.heat_loss_map.get_value(next_pf_st.y, next_pf_st.x);
+    // A search from start that can be asked for any number of goals, like the
+    // cheapest way to each edge block, without exploring the map again for
+    // each one. There's no guess at what's left, as that depends on the goal.
+    pub fn search<'r>(&'r self,
+        (y, x): (i32, i32),
+        rules: &'r impl HLPathFinderRules,
+        cost_model: &'r impl HLCostModel) -> Search<PathFindState, i32, impl FnMut(&PathFindState) -> Vec<(PathFindState, i32)> + 'r>
+    {
+        Search::new(PathFindState::new(Direction::South, 0, y, x), self.successors(rules, cost_model))
+    }
 
-                    // Add up heat loss
-                    next_pf_st.heat_loss += hl;
+    // The best path to each goal from the one search, in the same order as
+    // the goals, with None for any that can't be reached. A goal is only met
+    // where the rules allow the crucible to stop.
+    pub fn find_each_with_cost<FG>(&self,
+        start: (i32, i32),
+        rules: &impl HLPathFinderRules,
+        cost_model: &impl HLCostModel,
+        goals: &mut [FG]) -> Vec<Option<ShortestPath<PathFindState, i32>>>
+        where FG: FnMut(&PathFindState) -> bool
+    {
+        let mut search = self.search(start, rules, cost_model);
+        goals
+            .iter_mut()
+            .map(|is_goal| search.find(|pf_st: &PathFindState| is_goal(pf_st) && rules.is_endable(self, pf_st)))
+            .collect()
+    }
 
-                    // Push onto heap search states.
-                    self.add_state(next_pf_st, rules);
-                }
-            }
+    fn successors<'r>(&'r self,
+        rules: &'r impl HLPathFinderRules,
+        cost_model: &'r impl HLCostModel) -> impl FnMut(&PathFindState) -> Vec<(PathFindState, i32)> + 'r
+    {
+        let width = self.heat_loss_map.width();
+        let height = self.heat_loss_map.height();
 
+        move |pf_st: &PathFindState| -> Vec<(PathFindState, i32)> {
+            Direction::ALL
+                .iter()
+                .filter(|d| rules.check_direction(self, pf_st, d))
+                .map(|d| pf_st.apply(d))
+                .filter(|next_pf_st| next_pf_st.y >= 0 && next_pf_st.y < height && next_pf_st.x >= 0 && next_pf_st.x < width)
+                .map(|next_pf_st| {
+                    let heat_loss = cost_model.cost(self.heat_loss_map, pf_st, &next_pf_st);
+                    (next_pf_st, heat_loss)
+                })
+                .collect()
         }
-
-        Err(AOCError::ProcessingError("Could not find path.".into()))
     }
 }
 
@@ -258,21 +334,13 @@ impl HLPathFinderRules for Part1PathFinderRules {
         pf_st: &PathFindState,
         d: &Direction) -> bool
     {
-        !pf_st.direction.opposite(d) &&
+        pf_st.direction.opposite() != *d &&
             (pf_st.direction_count < 3 || pf_st.direction != *d)
     }
 
-    fn check_prune(&self,
-        _path_finder: &HLPathFinder,
-        pf_st: &PathFindState,
-        direction_tracking: &HashMap<i32, i32>) -> bool
-    {
-        for (k_d_count, k_hl) in direction_tracking {
-            if *k_d_count <= pf_st.direction_count && *k_hl <= pf_st.heat_loss {
-                return true;
-            }
-        }
-        false
+    // Fewer blocks straight only means more choice.
+    fn run_dominates(&self, run: i32, other_run: i32) -> bool {
+        run <= other_run
     }
 }
 
@@ -292,7 +360,7 @@ impl HLPathFinderRules for Part2PathFinderRules {
     }
 
     fn check_direction(&self, _path_finder: &HLPathFinder, pf_st: &PathFindState, d: &Direction) -> bool {
-        if pf_st.direction.opposite(d) {
+        if pf_st.direction.opposite() == *d {
             false
         }
         else if pf_st.direction_count < 4 {
@@ -306,39 +374,68 @@ impl HLPathFinderRules for Part2PathFinderRules {
         }
     }
 
-    fn check_prune(&self,
-        _path_finder: &HLPathFinder,
-        pf_st: &PathFindState,
-        direction_tracking: &HashMap<i32, i32>) -> bool
-    {
-        for (k_d_count, k_hl) in direction_tracking {
-            // If it has gone at least 4 it had some choice.
-            if *k_d_count >= 4 {
-                if *k_d_count <= pf_st.direction_count && *k_hl <= pf_st.heat_loss {
-                    return true;
-                }
-            }
-            // Otherwise just use current count
-            else if *k_d_count == pf_st.direction_count && *k_hl <= pf_st.heat_loss {
-                return true;
-            }
-        }
-        false
+    // Once it can turn, fewer blocks straight only means more choice.
+    fn run_dominates(&self, run: i32, other_run: i32) -> bool {
+        run == other_run || (4 <= run && run <= other_run)
     }
 }
 
-pub fn run_part(input: impl AsRef<Path>, rules: impl HLPathFinderRules) -> AOCResult<String> {
+// Costs are i32s. A staircase from corner to corner of a map up to 1000
+// blocks a side turns at most 2000 times, so it costs under i32::MAX at this
+// penalty, and the searches never look at paths much dearer than the best.
+pub const MAX_TURN_PENALTY: i32 = 1_000_000;
+
+// The map, the part's rules and problem17.turn_penalty, for the strategies.
+type Crucible<R> = (HeatLossMap, R, i32);
+
+fn least_heat_loss_astar<R: HLPathFinderRules>((hl_map, rules, turn_penalty): &Crucible<R>) -> AOCResult<i32> {
+    let path_finder = HLPathFinder::new(hl_map, (hl_map.height() - 1, hl_map.width() - 1));
+
+    let result = if *turn_penalty > 0 {
+        path_finder.find_with_cost((0, 0), rules, &TurnPenaltyCostModel { penalty: *turn_penalty })?
+    }
+    else {
+        path_finder.find((0, 0), rules)?
+    };
+
+    Ok(result.cost)
+}
+
+fn least_heat_loss_buckets<R: HLPathFinderRules>((hl_map, rules, turn_penalty): &Crucible<R>) -> AOCResult<i32> {
+    let path_finder = HLPathFinder::new(hl_map, (hl_map.height() - 1, hl_map.width() - 1));
+
+    let result = if *turn_penalty > 0 {
+        path_finder.find_with_buckets((0, 0), rules, &TurnPenaltyCostModel { penalty: *turn_penalty })?
+    }
+    else {
+        path_finder.find_with_buckets((0, 0), rules, &StandardCostModel {})?
+    };
+
+    Ok(result.cost)
+}
+
+pub fn run_part<R: HLPathFinderRules>(input: impl ProblemInput, rules: R) -> AOCResult<String> {
     let hl_map = HeatLossMap::parse(input)?;
-    let mut path_finder = HLPathFinder::new(&hl_map, (hl_map.height() - 1, hl_map.width() - 1));
-    let result = path_finder.find((0, 0), &rules)?;
 
-    Ok(result.heat_loss.to_string())
+    let turn_penalty = settings::params().get_or("problem17.turn_penalty", 0)?;
+    if !(0 ..= MAX_TURN_PENALTY).contains(&turn_penalty) {
+        return Err(AOCError::ParseError(format!("Turn penalty must be from 0 to {}: {}", MAX_TURN_PENALTY, turn_penalty)));
+    }
+
+    let result = Strategies::new("problem17")
+        .add("astar", least_heat_loss_astar)
+        .add("buckets", least_heat_loss_buckets)
+        .run(&(hl_map, rules, turn_penalty), |_| "buckets")?;
+
+    Ok(result.to_string())
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, Part1PathFinderRules::new())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, Part2PathFinderRules::new())
 }
+
+crate::register_day!(day: 17, title: "Clumsy Crucible", parts: [1 => part1 (example = "102"), 2 => part2]);
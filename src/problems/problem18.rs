@@ -1,17 +1,20 @@
 use std::collections::HashSet;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::io::prelude::*;
-use std::path::Path;
 
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::{each_line_with_policy, ProblemInput};
+use crate::geometry::{Direction, Vec2};
+use crate::visualize::{self, Picture, Visualizable};
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
+use crate::settings;
+
+// Longest side of the picture, in cells.
+const MAX_PICTURE_SIZE: usize = 400;
 
 lazy_static! {
     static ref DIG_OPERATION_REGEX: Regex = Regex::new(
@@ -19,43 +22,6 @@ lazy_static! {
     ).unwrap();
 }
 
-#[derive(Debug, Copy, Clone)]
-pub enum Direction {
-    Up = 0,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    pub fn apply_vertex(&self, amount: i32, vertex: &Vertex) -> Vertex {
-        use Direction::*;
-        match self {
-            Up => Vertex::new(vertex.y - amount, vertex.x),
-            Down => Vertex::new(vertex.y + amount, vertex.x),
-            Left => Vertex::new(vertex.y, vertex.x - amount),
-            Right => Vertex::new(vertex.y, vertex.x + amount),
-        }
-    }
-}
-
-impl TryFrom<char> for Direction {
-    type Error = AOCError;
-
-    fn try_from(value: char) -> AOCResult<Direction> {
-        use Direction::*;
-        Ok(match value {
-            'U' => Up,
-            'D' => Down,
-            'L' => Left,
-            'R' => Right,
-            _ => {
-                return Err(AOCError::ParseError(format!("Invalid character: {}", value)));
-            }
-        })
-    }
-}
-
 #[derive(Debug)]
 pub struct DigOperation {
     pub direction: Direction,
@@ -68,22 +34,26 @@ impl DigOperation {
     pub fn get_fix_from_color(&self) -> AOCResult<DigOperation> {
         use Direction::*;
 
-        let s_len = self.color.len();
+        // The last character is the direction and the rest is the amount.
+        let mut chars = self.color.chars();
+        let d_l_char = chars
+            .next_back()
+            .ok_or_else(|| AOCError::ParseError(format!("Invalid color fix: {}", self.color)))?;
 
-        if s_len < 2 {
+        let amount_hex = chars.as_str();
+        if amount_hex.is_empty() {
             return Err(AOCError::ParseError(format!("Invalid color fix: {}", self.color)));
         }
 
-        let n_amount = i32::from_str_radix(&self.color[0..s_len-1], 16)?;
-        let d_l_char = self.color[s_len-1..s_len].bytes().nth(0).unwrap() as char;
+        let n_amount = i32::from_str_radix(amount_hex, 16)?;
 
         // 0 means R, 1 means D, 2 means L, and 3 means U.
         let n_direction = match d_l_char {
-            '0' => Right,
-            '1' => Down,
-            '2' => Left,
-            '3' => Up,
-            _ => return Err(AOCError::InvalidRegexOperation(format!("Invalid direction number: {}", d_l_char))),
+            '0' => East,
+            '1' => South,
+            '2' => West,
+            '3' => North,
+            _ => return Err(AOCError::ParseError(format!("Invalid direction number: {} in color: {}", d_l_char, self.color))),
         };
 
         Ok(Self { direction: n_direction, amount: n_amount, color: self.color.clone() })
@@ -94,11 +64,7 @@ impl DigOperation {
 
         let cap = DIG_OPERATION_REGEX.captures_must(line)?;
 
-        let direction: Direction = cap
-            .get_group(1)?
-            .chars()
-            .nth(0).unwrap()
-            .try_into()?;
+        let direction: Direction = cap.get_char(1)?.try_into()?;
 
         let amount = cap.get_group(2)?.parse::<i32>()?;
         let color = cap.get_group(3)?.to_string();
@@ -118,6 +84,11 @@ impl Vertex {
     pub fn new(y: i32, x: i32) -> Self {
         Self { y, x }
     }
+
+    pub fn moved(&self, direction: Direction, amount: i32) -> Vertex {
+        let (dy, dx) = direction.delta();
+        Vertex::new(self.y + dy as i32 * amount, self.x + dx as i32 * amount)
+    }
 }
 
 // Assumes lines are horizontal or vertical only.
@@ -163,8 +134,30 @@ impl DigSite {
         Self { position: Vertex::new(0, 0), lines: Vec::new() }
     }
 
+    // With fix_from_color the real instructions are read out of the colors.
+    pub fn parse(input: impl ProblemInput, fix_from_color: bool) -> AOCResult<Self> {
+        let mut dig_site = DigSite::new();
+
+        each_line_with_policy(input, settings::parse_policy(), |line| {
+            let dig_op = DigOperation::parse(line)?;
+            if fix_from_color {
+                dig_site.dig(&dig_op.get_fix_from_color()?);
+            }
+            else {
+                dig_site.dig(&dig_op);
+            }
+            Ok(())
+        })?;
+
+        if dig_site.lines.is_empty() {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+
+        Ok(dig_site)
+    }
+
     pub fn dig(&mut self, dig_operation: &DigOperation) {
-        let new_pos = dig_operation.direction.apply_vertex(dig_operation.amount, &self.position);
+        let new_pos = self.position.moved(dig_operation.direction, dig_operation.amount);
         self.lines.push(Line::new(self.position, new_pos));
         self.position = new_pos;
     }
@@ -236,13 +229,13 @@ impl DigSite {
         let mut inside_boxes: HashMap<(i32, i32), BoxInfo> = HashMap::new();
 
         // First find all the boxes and index them to find ones next to each other.
-        for y_idx in 0 .. y_vals.len() - 1 {
+        for y_idx in 0 .. y_vals.len().saturating_sub(1) {
             let y_start = y_vals[y_idx];
             let y_end = y_vals[y_idx + 1];
 
             let mut inside = false;
 
-            for x_idx in 0 .. x_vals.len() - 1 {
+            for x_idx in 0 .. x_vals.len().saturating_sub(1) {
                 let x_start = x_vals[x_idx];
                 let x_end = x_vals[x_idx + 1];
 
@@ -337,37 +330,140 @@ impl DigSite {
 
         s
     }
+
+    // How many plan cells go in one cell to fit the plan in width x height.
+    // The same scale is used on both axes to keep the shape, and small plans
+    // aren't blown up.
+    fn scale_to_fit(&self, width: usize, height: usize) -> f64 {
+        let y_span = (self.get_y_max() - self.get_y_min()) as f64;
+        let x_span = (self.get_x_max() - self.get_x_min()) as f64;
+
+        (y_span / (height.max(2) - 1) as f64)
+            .max(x_span / (width.max(2) - 1) as f64)
+            .max(1.0)
+    }
+
+    // Like render, but squeezes the plan into a width x height canvas so the
+    // part 2 plans (millions wide) can still be looked at.
+    pub fn render_scaled(&self, width: usize, height: usize) -> String {
+        let y_min = self.get_y_min();
+        let x_min = self.get_x_min();
+
+        let y_span = (self.get_y_max() - y_min) as f64;
+        let x_span = (self.get_x_max() - x_min) as f64;
+
+        let scale = self.scale_to_fit(width, height);
+
+        let to_canvas = |v: &Vertex| -> (usize, usize) {
+            (
+                ((v.y - y_min) as f64 / scale).round() as usize,
+                ((v.x - x_min) as f64 / scale).round() as usize,
+            )
+        };
+
+        let rows = (y_span / scale).round() as usize + 1;
+        let cols = (x_span / scale).round() as usize + 1;
+        let mut output: Vec<Vec<char>> = vec![vec![' '; cols]; rows];
+
+        // Lines stay horizontal or vertical after scaling, so just fill between the ends.
+        for line in &self.lines {
+            let (y1, x1) = to_canvas(&line.start);
+            let (y2, x2) = to_canvas(&line.end);
+            for row in &mut output[y1.min(y2) ..= y1.max(y2)] {
+                for c in &mut row[x1.min(x2) ..= x1.max(x2)] {
+                    *c = '#';
+                }
+            }
+        }
+
+        let mut s = String::new();
+
+        for row in output {
+            s.extend(row);
+            s.push('\n');
+        }
+
+        s
+    }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let reader = BufReader::new(File::open(input)?);
+// The lagoon filled in with the trench drawn round it, squeezed at the same
+// scale as render_scaled so the part 2 plans fit too.
+impl Visualizable for DigSite {
+    fn picture(&self) -> AOCResult<Picture> {
+        let (y_min, x_min) = (self.get_y_min(), self.get_x_min());
+        let y_span = (self.get_y_max() - y_min) as f64;
+        let x_span = (self.get_x_max() - x_min) as f64;
+        let scale = self.scale_to_fit(MAX_PICTURE_SIZE, MAX_PICTURE_SIZE);
+
+        // Where a vertex is, in cells, through the middle of the cell.
+        let to_picture = |v: &Vertex| Vec2::new((v.x - x_min) as f64 / scale + 0.5, (v.y - y_min) as f64 / scale + 0.5);
+
+        let width = (x_span / scale).ceil() as usize + 1;
+        let height = (y_span / scale).ceil() as usize + 1;
+        let mut picture = Picture::new(width, height);
+
+        // A cell is inside when a line going right from its middle crosses
+        // the trench an odd number of times. Only the vertical lines count.
+        let verticals: Vec<(f64, f64, f64)> = self.lines
+            .iter()
+            .filter(|line| line.is_vertical())
+            .map(|line| {
+                let (start, end) = (to_picture(&line.start), to_picture(&line.end));
+                (start.x(), start.y().min(end.y()), start.y().max(end.y()))
+            })
+            .collect();
+
+        for y in 0 .. height {
+            let middle = y as f64 + 0.5;
+            let mut crossings: Vec<f64> = verticals
+                .iter()
+                .filter(|(_, top, bottom)| *top <= middle && middle < *bottom)
+                .map(|(x, _, _)| *x)
+                .collect();
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for pair in crossings.chunks(2) {
+                if let [from, to] = pair {
+                    for x in (from.floor() as usize) .. (to.ceil() as usize).min(width) {
+                        picture.set_cell(y, x, visualize::ACCENT);
+                    }
+                }
+            }
+        }
+
+        // The bottom of each part of the lagoon is left out above, so the
+        // trench itself is filled in too.
+        for line in &self.lines {
+            let (start, end) = (to_picture(&line.start), to_picture(&line.end));
+            for y in (start.y().min(end.y()) as usize) ..= (start.y().max(end.y()) as usize) {
+                for x in (start.x().min(end.x()) as usize) ..= (start.x().max(end.x()) as usize) {
+                    picture.set_cell(y, x, visualize::ACCENT);
+                }
+            }
+        }
 
-    let mut dig_site = DigSite::new();
+        let outline: Vec<Vec2> = self.lines.iter().map(|line| to_picture(&line.start)).collect();
+        picture.add_path(outline, visualize::HIGHLIGHT, true);
 
-    for line in reader.lines() {
-        let line = line?;
-        let dig_op = DigOperation::parse(line)?;
-        dig_site.dig(&dig_op);
+        Ok(picture)
     }
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let dig_site = DigSite::parse(input, false)?;
 
     let result = dig_site.area();
 
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let reader = BufReader::new(File::open(input)?);
-
-    let mut dig_site = DigSite::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        let dig_op = DigOperation::parse(line)?;
-        let dig_op_fixed = dig_op.get_fix_from_color()?;
-        dig_site.dig(&dig_op_fixed);
-    }
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let dig_site = DigSite::parse(input, true)?;
 
     let result = dig_site.area();
 
     Ok(result.to_string())
 }
+
+crate::register_day!(day: 18, title: "Lavaduct Lagoon", parts: [1 => part1 (example = "62"), 2 => part2 (example = "952408144115")], skips_invalid: true);
@@ -1,16 +1,18 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufReader;
+use std::fmt;
 use std::io::prelude::*;
-use std::path::Path;
+use std::time::Instant;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::{first_char, ProblemInput};
+use crate::explore::{self, Explorable, ExploreCommand};
+use crate::ranges::{Interval, IntervalSet};
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
+use crate::settings;
 
 lazy_static! {
     static ref WORKFLOW_REGEX: Regex = Regex::new(
@@ -53,6 +55,16 @@ impl PartAttribute {
             _ => return Err(AOCError::ParseError(format!("Invalid part attribute: {c}")))
         })
     }
+
+    pub fn to_char(&self) -> char {
+        use PartAttribute::*;
+        match self {
+            Cool => 'x',
+            Musical => 'm',
+            Aerodynamic => 'a',
+            Shiny => 's',
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -94,11 +106,11 @@ impl Part {
 
         for attr in attr_parts {
             let s_parts: Vec<&str> = attr.split('=').collect();
-            if s_parts.len() != 2 {
+            if s_parts.len() != 2 || s_parts[0].is_empty() {
                 return Err(AOCError::ParseError(format!("Invalid part attribute: {}", attr)));
             }
 
-            let attr_type = PartAttribute::from_char(s_parts[0].chars().nth(0).unwrap())?;
+            let attr_type = PartAttribute::from_char(first_char(s_parts[0])?)?;
             let attr_num = s_parts[1].parse::<i32>()?;
 
             attrs.insert(attr_type, attr_num);
@@ -145,6 +157,20 @@ pub enum WorkflowResult {
     Proceed(String),
 }
 
+// Results for building workflows in code, like
+//   Workflow::builder("in").lt(Shiny, 1351, goto("px")).otherwise(goto("qqz"))
+pub fn accept() -> WorkflowResult {
+    WorkflowResult::Accept
+}
+
+pub fn reject() -> WorkflowResult {
+    WorkflowResult::Reject
+}
+
+pub fn goto(name: impl Into<String>) -> WorkflowResult {
+    WorkflowResult::Proceed(name.into())
+}
+
 #[derive(Debug, Clone)]
 pub struct WorkflowStep {
     condition: WorkflowStepCondition,
@@ -164,20 +190,14 @@ impl WorkflowStep {
                 (self.result.clone(), part_combinations.clone(), PartAttributeCombination::new_empty())
             },
             GreaterThan(attr, num) => {
-                let (parts_in, parts_out): (HashSet<i32>, HashSet<i32>) = part_combinations
-                    .get(&attr)
-                    .iter()
-                    .partition(|v| *v > &num);
+                let (parts_out, parts_in) = part_combinations.get(attr).split_at(*num as i64 + 1);
 
                 (self.result.clone(),
                     part_combinations.with_attributes(attr, parts_in),
                     part_combinations.with_attributes(attr, parts_out))
             },
             LessThan(attr, num) => {
-                let (parts_in, parts_out): (HashSet<i32>, HashSet<i32>) = part_combinations
-                    .get(&attr)
-                    .iter()
-                    .partition(|v| *v < &num);
+                let (parts_in, parts_out) = part_combinations.get(attr).split_at(*num as i64);
 
                 (self.result.clone(),
                     part_combinations.with_attributes(attr, parts_in),
@@ -204,11 +224,7 @@ impl WorkflowStep {
         let condition =
             if let Some(part_attribute_group) = cap.get(2) {
 
-                let part_attribute = PartAttribute::from_char(part_attribute_group
-                    .as_str()
-                    .chars()
-                    .nth(0).unwrap()
-                )?;
+                let part_attribute = PartAttribute::from_char(first_char(part_attribute_group.as_str())?)?;
 
                 let operation = cap.get_group(3)?;
                 let op_num = cap.get_group(4)?.parse::<i32>()?;
@@ -236,19 +252,69 @@ impl WorkflowStep {
     }
 }
 
+// As they're written in the input, like a<2006:qkq.
+impl fmt::Display for WorkflowStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.condition {
+            WorkflowStepCondition::LessThan(attr, num) => write!(f, "{}<{}:", attr.to_char(), num)?,
+            WorkflowStepCondition::GreaterThan(attr, num) => write!(f, "{}>{}:", attr.to_char(), num)?,
+            WorkflowStepCondition::True => {},
+        }
+        match &self.result {
+            WorkflowResult::Accept => write!(f, "A"),
+            WorkflowResult::Reject => write!(f, "R"),
+            WorkflowResult::Proceed(name) => write!(f, "{}", name),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Workflow {
     pub name: String,
     pub steps: Vec<WorkflowStep>,
 }
 
+// Steps are checked in the order they're added. A workflow always ends with a
+// step that takes everything left, so otherwise is what finishes it.
+#[derive(Debug, Clone)]
+pub struct WorkflowBuilder {
+    name: String,
+    steps: Vec<WorkflowStep>,
+}
+
+impl WorkflowBuilder {
+
+    fn step(mut self, condition: WorkflowStepCondition, result: WorkflowResult) -> Self {
+        self.steps.push(WorkflowStep { condition, result });
+        self
+    }
+
+    pub fn lt(self, attr: PartAttribute, num: i32, result: WorkflowResult) -> Self {
+        self.step(WorkflowStepCondition::LessThan(attr, num), result)
+    }
+
+    pub fn gt(self, attr: PartAttribute, num: i32, result: WorkflowResult) -> Self {
+        self.step(WorkflowStepCondition::GreaterThan(attr, num), result)
+    }
+
+    pub fn otherwise(self, result: WorkflowResult) -> Workflow {
+        let workflow = self.step(WorkflowStepCondition::True, result);
+        Workflow { name: workflow.name, steps: workflow.steps }
+    }
+}
+
 impl Workflow {
 
-    // Splits the part attribute combination into the different set of results it could have.
+    pub fn builder(name: impl Into<String>) -> WorkflowBuilder {
+        WorkflowBuilder { name: name.into(), steps: Vec::new() }
+    }
+
+    // Splits the part attribute combination into the different set of results it could have,
+    // along with the index of the step that gave each result.
     pub fn process_combinations(&self, part_combinations: &PartAttributeCombination)
-        -> AOCResult<Vec<(WorkflowResult, PartAttributeCombination)>>
+        -> AOCResult<Vec<(usize, WorkflowResult, PartAttributeCombination)>>
     {
-        let mut result: Vec<(WorkflowResult, PartAttributeCombination)> = Vec::new();
+        let mut result: Vec<(usize, WorkflowResult, PartAttributeCombination)> = Vec::new();
         self.process_combinations_recur(0, part_combinations, &mut result)?;
         Ok(result)
     }
@@ -257,7 +323,7 @@ impl Workflow {
     fn process_combinations_recur(&self,
         step_idx: usize,
         remaining_part_combinations: &PartAttributeCombination,
-        result: &mut Vec<(WorkflowResult, PartAttributeCombination)>)
+        result: &mut Vec<(usize, WorkflowResult, PartAttributeCombination)>)
         -> AOCResult<()>
     {
         if step_idx >= self.steps.len() {
@@ -267,7 +333,7 @@ impl Workflow {
         let (step_result, step_in, step_out) = step.process_combinations(&remaining_part_combinations)?;
 
         if !step_in.is_empty() {
-            result.push((step_result.clone(), step_in));
+            result.push((step_idx, step_result.clone(), step_in));
         }
 
         if !step_out.is_empty() {
@@ -323,6 +389,12 @@ impl Workflows {
         self.workflows.insert(workflow.name.clone(), workflow);
     }
 
+    // For chaining built workflows together.
+    pub fn with(mut self, workflow: Workflow) -> Self {
+        self.add(workflow);
+        self
+    }
+
     pub fn get_workflow<'a>(&'a self, name: impl AsRef<str>) -> AOCResult<&'a Workflow> {
         Ok(self.workflows.get(name.as_ref())
             .ok_or_else(|| AOCError::ProcessingError(format!("Missing workflow: {}", name.as_ref())))?)
@@ -345,19 +417,26 @@ impl Workflows {
         }
     }
 
+    // Going through more workflows than there are means going round a loop,
+    // which a part would never get out of.
     fn get_accepted_combinations_recur(
         &self,
         part_combinations: &PartAttributeCombination,
         name: impl AsRef<str>,
-        result_combinations: &mut Vec<PartAttributeCombination>) -> AOCResult<()>
+        depth: usize,
+        result_combinations: &mut Vec<(AcceptStep, PartAttributeCombination)>) -> AOCResult<()>
     {
+        if depth > self.workflows.len() {
+            return Err(AOCError::ProcessingError("Workflows loop back on themselves.".into()));
+        }
         let workflow = self.get_workflow(name)?;
 
-        for (wf_result, sub_part_combinations) in workflow.process_combinations(part_combinations)? {
+        for (step_idx, wf_result, sub_part_combinations) in workflow.process_combinations(part_combinations)? {
             if !sub_part_combinations.is_empty() {
                 match wf_result {
                     WorkflowResult::Accept => {
-                        result_combinations.push(sub_part_combinations);
+                        let accept_step = AcceptStep { workflow: workflow.name.clone(), step_idx };
+                        result_combinations.push((accept_step, sub_part_combinations));
                     },
                     WorkflowResult::Reject => {
                         // skip
@@ -366,6 +445,7 @@ impl Workflows {
                         self.get_accepted_combinations_recur(
                             &sub_part_combinations,
                             next_wf_name,
+                            depth + 1,
                             result_combinations)?;
                     }
                 }
@@ -378,20 +458,221 @@ impl Workflows {
     pub fn get_accepted_combinations(&self, part_combinations: &PartAttributeCombination)
         -> AOCResult<Vec<PartAttributeCombination> >
     {
-        let mut accepted_part_combos: Vec<PartAttributeCombination> = Vec::new();
+        Ok(self.get_accepted_combinations_by_step(part_combinations)?
+            .into_iter()
+            .map(|(_, combination)| combination)
+            .collect())
+    }
+
+    // The accepted combinations with the step that accepted each of them.
+    pub fn get_accepted_combinations_by_step(&self, part_combinations: &PartAttributeCombination)
+        -> AOCResult<Vec<(AcceptStep, PartAttributeCombination)>>
+    {
+        let mut accepted_part_combos: Vec<(AcceptStep, PartAttributeCombination)> = Vec::new();
 
         self.get_accepted_combinations_recur(
             part_combinations,
             "in",
+            0,
             &mut accepted_part_combos
         )?;
-        
+
         Ok(accepted_part_combos)
     }
+
+    // How many combinations each accepting step is responsible for, biggest first.
+    pub fn get_accept_step_counts(&self, part_combinations: &PartAttributeCombination)
+        -> AOCResult<Vec<(AcceptStep, i64)>>
+    {
+        let mut counts: HashMap<AcceptStep, i64> = HashMap::new();
+
+        for (accept_step, combination) in self.get_accepted_combinations_by_step(part_combinations)? {
+            *counts.entry(accept_step).or_default() += combination.get_combination_size();
+        }
+
+        let mut counts: Vec<(AcceptStep, i64)> = counts.into_iter().collect();
+        counts.sort_by(|(s1, c1), (s2, c2)| c2.cmp(c1).then_with(|| s1.cmp(s2)));
+
+        Ok(counts)
+    }
+}
+
+impl Explorable for Workflows {
+
+    fn summary(&self) -> String {
+        format!("{} workflows.", self.workflows.len())
+    }
+
+    fn commands(&self) -> Vec<ExploreCommand> {
+        vec![
+            ExploreCommand::new("workflow", "<name>", "The steps of a workflow"),
+            ExploreCommand::new("rate", "<part>", "The workflows a part like {x=787,m=2655,a=1222,s=2876} goes through"),
+            ExploreCommand::new("accepted", "[limit]", "How many combinations from 1 to 4000 each step accepts"),
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String> {
+        match name {
+            "workflow" => {
+                let workflow = self.get_workflow(explore::arg::<String>(args, 0, "name")?)?;
+                let steps: Vec<String> = workflow.steps.iter().map(|step| step.to_string()).collect();
+                Ok(format!("{}{{{}}}", workflow.name, steps.join(",")))
+            },
+            "rate" => {
+                let part = Part::parse(args.concat())?;
+                let mut route: Vec<String> = vec!["in".to_string()];
+                let mut workflow = self.get_workflow("in")?;
+                loop {
+                    match workflow.process(&part)? {
+                        WorkflowResult::Proceed(next) => {
+                            workflow = self.get_workflow(&next)?;
+                            route.push(next);
+                        },
+                        WorkflowResult::Accept => {
+                            route.push(format!("A, rating {}", part.rating()));
+                            break;
+                        },
+                        WorkflowResult::Reject => {
+                            route.push("R".to_string());
+                            break;
+                        },
+                    }
+                }
+                Ok(route.join(" -> "))
+            },
+            "accepted" => {
+                let counts = self.get_accept_step_counts(&PartAttributeCombination::new(1, 4000))?;
+                Ok(accept_step_report(&counts, explore::arg_or(args, 0, "limit", 10)?))
+            },
+            _ => Err(explore::unknown_command(name)),
+        }
+    }
+}
+
+// A step in a workflow that sends parts to accept.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AcceptStep {
+    pub workflow: String,
+    pub step_idx: usize,
+}
+
+pub fn accept_step_report(counts: &[(AcceptStep, i64)], limit: usize) -> String {
+    let total: i64 = counts.iter().map(|(_, count)| count).sum();
+
+    let mut report = format!("Accepted combinations by step ({} steps, {} total):\n", counts.len(), total);
+    for (accept_step, count) in counts.iter().take(limit) {
+        report.push_str(&format!("  {}[{}]: {} ({:.1}%)\n",
+            accept_step.workflow,
+            accept_step.step_idx,
+            count,
+            100.0 * *count as f64 / total.max(1) as f64));
+    }
+    report
+}
+
+// The workflows as an index over each attribute's values, for rating lots of
+// parts. Every condition cuts an attribute's values at a threshold, so the
+// accepted parts are a set of boxes, the same ones part 2 counts. Each
+// attribute keeps the sorted thresholds where a box starts or ends, and for
+// each gap between two of them a bit for every box over that gap. A part is
+// accepted when the gaps its four values fall in share a box, which is four
+// binary searches and an and of the bits, however deep the workflows go.
+#[derive(Debug, Clone)]
+pub struct CompiledWorkflows {
+    thresholds: [Vec<i64>; 4],
+    // box_words words of bits for each gap, gap after gap.
+    gap_boxes: [Vec<u64>; 4],
+    box_words: usize,
+    pub box_count: usize,
+}
+
+impl CompiledWorkflows {
+    const ATTRIBUTES: [PartAttribute; 4] = [PartAttribute::Cool, PartAttribute::Musical, PartAttribute::Aerodynamic, PartAttribute::Shiny];
+
+    pub fn compile(workflows: &Workflows) -> AOCResult<Self> {
+        // Every value a part could have, so parts outside 1 to 4000 go the
+        // same way they would through the workflows.
+        let boxes = workflows.get_accepted_combinations(&PartAttributeCombination::new(i32::MIN, i32::MAX))?;
+        let box_words = boxes.len().div_ceil(64);
+
+        let thresholds = Self::ATTRIBUTES.map(|attr| {
+            let mut thresholds: Vec<i64> = boxes
+                .iter()
+                .flat_map(|accepted| accepted.get(&attr).intervals())
+                .flat_map(|interval| [interval.start(), interval.end()])
+                .collect();
+            thresholds.sort();
+            thresholds.dedup();
+            thresholds
+        });
+
+        let gap_boxes = Self::ATTRIBUTES.map(|attr| {
+            let attr_thresholds = &thresholds[attr as usize];
+            let mut bits = vec![0u64; (attr_thresholds.len() + 1) * box_words];
+            for (box_idx, accepted) in boxes.iter().enumerate() {
+                for interval in accepted.get(&attr).intervals() {
+                    for gap in Self::gap(attr_thresholds, interval.start()) ..= Self::gap(attr_thresholds, interval.end() - 1) {
+                        bits[gap * box_words + box_idx / 64] |= 1 << (box_idx % 64);
+                    }
+                }
+            }
+            bits
+        });
+
+        Ok(Self { thresholds, gap_boxes, box_words, box_count: boxes.len() })
+    }
+
+    // Gap 0 is below the first threshold, gap n from the nth on.
+    fn gap(thresholds: &[i64], value: i64) -> usize {
+        thresholds.partition_point(|threshold| *threshold <= value)
+    }
+
+    pub fn is_accepted(&self, part: &Part) -> bool {
+        let rows = Self::ATTRIBUTES.map(|attr| {
+            let gap = Self::gap(&self.thresholds[attr as usize], part.get_attribute(&attr) as i64);
+            &self.gap_boxes[attr as usize][gap * self.box_words .. (gap + 1) * self.box_words]
+        });
+
+        (0 .. self.box_words).any(|word| rows[0][word] & rows[1][word] & rows[2][word] & rows[3][word] != 0)
+    }
 }
 
-pub fn parse_worksheet(input: impl AsRef<Path>) -> AOCResult<(Workflows, Vec<Part>)> {
-    let reader = BufReader::new(File::open(input)?);
+// Times the workflow walk against the compiled workflows on the same parts.
+fn compare_compiled_speed(workflows: &Workflows, compiled: &CompiledWorkflows, parts: &[Part]) -> AOCResult<()> {
+    const EVALUATIONS: usize = 1_000_000;
+    let rounds = EVALUATIONS / parts.len().max(1) + 1;
+
+    let start = Instant::now();
+    let mut naive_accepted = 0;
+    for _ in 0 .. rounds {
+        for part in parts {
+            if let WorkflowResult::Accept = workflows.process(part)? {
+                naive_accepted += 1;
+            }
+        }
+    }
+    let naive_duration = start.elapsed();
+
+    let start = Instant::now();
+    let mut compiled_accepted = 0;
+    for _ in 0 .. rounds {
+        for part in parts {
+            if compiled.is_accepted(part) {
+                compiled_accepted += 1;
+            }
+        }
+    }
+    let compiled_duration = start.elapsed();
+
+    tracing::info!("Evaluated {} parts {} times ({} accepted boxes)", parts.len(), rounds, compiled.box_count);
+    tracing::info!("  Workflows: {:?} ({} accepted)", naive_duration, naive_accepted);
+    tracing::info!("  Compiled:  {:?} ({} accepted)", compiled_duration, compiled_accepted);
+
+    Ok(())
+}
+
+pub fn parse_worksheet(input: impl ProblemInput) -> AOCResult<(Workflows, Vec<Part>)> {
+    let reader = input.open()?;
 
     let mut workflows = Workflows::new();
     let mut parts: Vec<Part> = Vec::new();
@@ -420,24 +701,23 @@ pub fn parse_worksheet(input: impl AsRef<Path>) -> AOCResult<(Workflows, Vec<Par
     Ok((workflows, parts))
 }
 
-// I realize now that I could have based this completely off of range specs and not
-// have to expand the whole HashSet. So it could have been 4 i32 pairs to represent
-// the combos. This is because the conditions are only greater/less than operations.
+// The values each attribute can still have. The conditions are only ever
+// greater or less than, so each attribute's values are ranges.
 #[derive(Debug, Clone)]
 pub struct PartAttributeCombination {
-    pub cool: HashSet<i32>,
-    pub musical: HashSet<i32>,
-    pub aerodynamic: HashSet<i32>,
-    pub shiny: HashSet<i32>,
+    pub cool: IntervalSet,
+    pub musical: IntervalSet,
+    pub aerodynamic: IntervalSet,
+    pub shiny: IntervalSet,
 }
 
 impl PartAttributeCombination {
 
     pub fn get_combination_size(&self) -> i64 {
-        self.cool.len() as i64 *
-            self.musical.len() as i64 *
-            self.aerodynamic.len() as i64 *
-            self.shiny.len() as i64
+        self.cool.len() *
+            self.musical.len() *
+            self.aerodynamic.len() *
+            self.shiny.len()
     }
 
     // If the combination is empty.
@@ -451,66 +731,41 @@ impl PartAttributeCombination {
 
     pub fn new_empty() -> Self {
         Self {
-            cool: HashSet::new(),
-            musical: HashSet::new(),
-            aerodynamic: HashSet::new(),
-            shiny: HashSet::new(),
+            cool: IntervalSet::new(),
+            musical: IntervalSet::new(),
+            aerodynamic: IntervalSet::new(),
+            shiny: IntervalSet::new(),
         }
     }
 
     pub fn new(min: i32, max: i32) -> Self {
-        let starting_vals: HashSet<i32> = (min ..= max).collect();
+        let starting_vals = IntervalSet::from(Interval::inclusive(min as i64, max as i64));
         Self {
             cool: starting_vals.clone(),
             musical: starting_vals.clone(),
             aerodynamic: starting_vals.clone(),
-            shiny: starting_vals.clone(),
+            shiny: starting_vals,
         }
     }
 
-    pub fn with_attributes(&self, attr: &PartAttribute, vals: HashSet<i32>) -> Self {
+    pub fn with_attributes(&self, attr: &PartAttribute, vals: IntervalSet) -> Self {
         use PartAttribute::*;
 
-        if vals.len() == 0 {
-            Self {
-                cool: HashSet::new(),
-                musical: HashSet::new(),
-                aerodynamic: HashSet::new(),
-                shiny: HashSet::new(),
-            }
+        if vals.is_empty() {
+            return Self::new_empty();
         }
-        else {
-            // I want to move this to a macro
-            match attr {
-                Cool => Self {
-                    cool: vals,
-                    musical: self.musical.clone(),
-                    aerodynamic: self.aerodynamic.clone(),
-                    shiny: self.shiny.clone(),
-                },
-                Musical => Self {
-                    cool: self.cool.clone(),
-                    musical: vals,
-                    aerodynamic: self.aerodynamic.clone(),
-                    shiny: self.shiny.clone(),
-                },
-                Aerodynamic => Self {
-                    cool: self.cool.clone(),
-                    musical: self.musical.clone(),
-                    aerodynamic: vals,
-                    shiny: self.shiny.clone(),
-                },
-                Shiny => Self {
-                    cool: self.cool.clone(),
-                    musical: self.musical.clone(),
-                    aerodynamic: self.aerodynamic.clone(),
-                    shiny: vals,
-                },
-            }
+
+        let mut combination = self.clone();
+        match attr {
+            Cool => combination.cool = vals,
+            Musical => combination.musical = vals,
+            Aerodynamic => combination.aerodynamic = vals,
+            Shiny => combination.shiny = vals,
         }
+        combination
     }
 
-    pub fn get<'a>(&'a self, attr: &PartAttribute) -> &'a HashSet<i32> {
+    pub fn get<'a>(&'a self, attr: &PartAttribute) -> &'a IntervalSet {
         use PartAttribute::*;
 
         match attr {
@@ -522,14 +777,21 @@ impl PartAttributeCombination {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let (workflows, parts) = parse_worksheet(input)?;
+    let compiled = CompiledWorkflows::compile(&workflows)?;
+
+    // A million evaluations, so only when asked for with
+    // --param problem19.compare_compiled=true. The timings are logged at
+    // info, so -v shows them.
+    if settings::params().get_or("problem19.compare_compiled", false)? {
+        compare_compiled_speed(&workflows, &compiled, &parts)?;
+    }
 
     let mut total_ratings = 0;
 
     for part in &parts {
-        let part_result = workflows.process(part)?;
-        if let WorkflowResult::Accept = part_result {
+        if compiled.is_accepted(part) {
             total_ratings += part.rating();
         }
     }
@@ -537,18 +799,17 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
     Ok(total_ratings.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let (workflows, _parts) = parse_worksheet(input)?;
 
     let combinations = PartAttributeCombination::new(1, 4000);
-    let accepted_combinations = workflows.get_accepted_combinations(&combinations)?;
+    let accept_step_counts = workflows.get_accept_step_counts(&combinations)?;
 
-    let mut total_combos: i64 = 0;
+    tracing::debug!("{}", accept_step_report(&accept_step_counts, 10).trim_end());
 
-    for accepted_combination in &accepted_combinations {
-        let size = accepted_combination.get_combination_size();
-        total_combos += size;
-    }
+    let total_combos: i64 = accept_step_counts.iter().map(|(_, count)| count).sum();
 
     Ok(total_combos.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 19, title: "Aplenty", parts: [1 => part1 (example = "19114"), 2 => part2 (example = "167409079868000")]);
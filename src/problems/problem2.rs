@@ -1,12 +1,12 @@
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCError, AOCResult};
-use crate::aocio::each_line;
+use crate::aocio::{each_line_with_policy, ProblemInput};
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
+use crate::settings;
 
 lazy_static! {
     static ref GAME_REGEX: Regex = Regex::new(r"^Game (\d+): (.*)").unwrap();
@@ -99,7 +99,17 @@ impl CubeCountGame {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+// Bad game lines are skipped or stop everything depending on --skip-invalid.
+fn each_game<F>(input: impl ProblemInput, mut f: F) -> AOCResult<()>
+    where F: FnMut(CubeCountGame)
+{
+    each_line_with_policy(input, settings::parse_policy(), |line| {
+        f(CubeCountGame::parse(line)?);
+        Ok(())
+    })
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let possible_counts = CubeCounts {
         red: 12,
         green: 13,
@@ -108,25 +118,23 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
 
     let mut result = 0;
 
-    each_line(input, |line| {
-        let game = CubeCountGame::parse(line)?;
+    each_game(input, |game| {
         if game.are_total_counts_possible(&possible_counts) {
             result += game.id;
         }
-        Ok(())
     })?;
 
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let mut result = 0;
 
-    each_line(input, |line| {
-        let game = CubeCountGame::parse(line)?;
+    each_game(input, |game| {
         result += game.get_max_counts().power_set();
-        Ok(())
     })?;
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 2, title: "Cube Conundrum", parts: [1 => part1, 2 => part2], skips_invalid: true);
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::BufReader;
+use std::fmt;
+use std::fs;
 use std::io::prelude::*;
 use std::path::Path;
 
@@ -9,9 +9,13 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::{artifact_path, ProblemInput};
+use crate::explore::{self, Explorable, ExploreCommand};
+use crate::log;
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
 use crate::mathx::lcm;
+use crate::settings;
 
 lazy_static! {
     static ref MODULE_REGEX: Regex = Regex::new(
@@ -19,6 +23,14 @@ lazy_static! {
     ).unwrap();
 }
 
+// How many presses analyze_cycles tries before giving up on a sub-circuit
+// ever repeating.
+const MAX_CYCLE_PRESSES: usize = 100_000;
+
+// How many presses find_button_pushes_until simulates one at a time before
+// giving up.
+const MAX_SIMULATED_PRESSES: i64 = 1_000_000;
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Pulse {
     Low = 0,
@@ -222,15 +234,201 @@ impl Module {
     }
 }
 
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PulseCounts {
+    pub high: i32,
+    pub low: i32,
+}
+
+impl PulseCounts {
+
+    pub fn add(&mut self, pulse: Pulse) {
+        match pulse {
+            Pulse::High => self.high += 1,
+            Pulse::Low => self.low += 1,
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.high + self.low
+    }
+}
+
+// Pulses sent by each module and in each button press, over every push_button.
+#[derive(Debug, Default, Clone)]
+pub struct PulseStats {
+    pub per_module: HashMap<String, PulseCounts>,
+    pub per_press: Vec<PulseCounts>,
+}
+
+impl PulseStats {
+
+    // The module that sent the most pulses, and how many.
+    pub fn busiest_module(&self) -> Option<(&String, PulseCounts)> {
+        self.per_module
+            .iter()
+            .max_by_key(|(name, counts)| (counts.total(), std::cmp::Reverse(*name)))
+            .map(|(name, counts)| (name, *counts))
+    }
+}
+
+// The state of a network packed into bits, a bit for each flip-flop and a bit
+// for each input a conjunction remembers, going through modules and inputs
+// in name order. Snapshots of the same network in the same state are always
+// equal, so they can be hashed to spot states seen before.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModulesSnapshot {
+    pub bit_count: usize,
+    pub bits: Vec<u64>,
+}
+
+impl ModulesSnapshot {
+
+    const MAGIC: &'static [u8; 4] = b"P20S";
+
+    fn new() -> Self {
+        Self { bit_count: 0, bits: Vec::new() }
+    }
+
+    fn push(&mut self, on: bool) {
+        if self.bit_count.is_multiple_of(64) {
+            self.bits.push(0);
+        }
+        if on {
+            self.bits[self.bit_count / 64] |= 1 << (self.bit_count % 64);
+        }
+        self.bit_count += 1;
+    }
+
+    fn get(&self, idx: usize) -> bool {
+        self.bits[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    // P20S, the bit count as a little endian u32, then the bits a byte at a time.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.bit_count.div_ceil(8));
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.extend_from_slice(&(self.bit_count as u32).to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes.truncate(8 + self.bit_count.div_ceil(8));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> AOCResult<Self> {
+        if bytes.len() < 8 || &bytes[0..4] != Self::MAGIC {
+            return Err(AOCError::ParseError("Not a module snapshot.".into()));
+        }
+
+        let bit_count = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        let data = &bytes[8..];
+        if data.len() != bit_count.div_ceil(8) {
+            return Err(AOCError::ParseError(format!(
+                "Snapshot has {} bytes of state for {} bits.", data.len(), bit_count)));
+        }
+
+        let bits = data
+            .chunks(8)
+            .map(|chunk| {
+                let mut word = [0u8; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u64::from_le_bytes(word)
+            })
+            .collect::<Vec<u64>>();
+
+        // Anything set past the last bit would make the snapshot unequal to
+        // one taken of the same state.
+        if !bit_count.is_multiple_of(64) && bits.last().is_some_and(|word| word >> (bit_count % 64) != 0) {
+            return Err(AOCError::ParseError("Snapshot has bits set past its bit count.".into()));
+        }
+
+        Ok(Self { bit_count, bits })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> AOCResult<()> {
+        std::fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> AOCResult<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+// One of the sub-circuits feeding a collector, the modules upstream of one
+// of its inputs. After offset presses it goes round the same length presses
+// over and over. high_presses are the presses it sends the collector a high
+// pulse in, counted from the start, up to the end of its first time round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubCircuitCycle {
+    pub output: String,
+    pub modules: Vec<String>,
+    pub offset: usize,
+    pub length: usize,
+    pub high_presses: Vec<usize>,
+}
+
+// How the sub-circuits feeding the conjunction in front of sink cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleReport {
+    pub sink: String,
+    pub collector: String,
+    pub sub_circuits: Vec<SubCircuitCycle>,
+    pub lcm: i64,
+}
+
+impl CycleReport {
+
+    // The first press where every sub-circuit sends the collector a high
+    // pulse, so it sends sink a low one. That's only the lcm when each
+    // sub-circuit fires just once a cycle, on a multiple of its length, which
+    // is how the puzzle inputs are made. Anything else isn't worked out. It
+    // also takes the highs in a press to overlap, which they do in the
+    // puzzle inputs.
+    pub fn first_low_press(&self) -> AOCResult<i64> {
+        for sub_circuit in &self.sub_circuits {
+            let fires_on_multiples = match sub_circuit.high_presses[..] {
+                [high] => high > sub_circuit.offset && high % sub_circuit.length == 0,
+                _ => false,
+            };
+            if !fires_on_multiples {
+                return Err(AOCError::Unsupported(format!(
+                    "{} fires at {:?} in a cycle of {} after {} presses, only firing once on a multiple of the cycle is supported.",
+                    sub_circuit.output, sub_circuit.high_presses, sub_circuit.length, sub_circuit.offset)));
+            }
+        }
+        Ok(self.lcm)
+    }
+}
+
+impl fmt::Display for CycleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} is fed by &{} from {} sub-circuits:", self.sink, self.collector, self.sub_circuits.len())?;
+        for sub_circuit in &self.sub_circuits {
+            writeln!(f, "  {}: {} modules, cycle of {} after {} presses, high at {:?}",
+                sub_circuit.output, sub_circuit.modules.len(), sub_circuit.length, sub_circuit.offset, sub_circuit.high_presses)?;
+        }
+        write!(f, "lcm {}", self.lcm)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Modules {
     pub modules: HashMap<String, Module>,
+    // Only kept after collect_stats, counting by module slows every pulse.
+    pub pulse_stats: Option<PulseStats>,
 }
 
 impl Modules {
 
     pub fn new() -> Self {
-        Self { modules: HashMap::new() }
+        Self { modules: HashMap::new(), pulse_stats: None }
+    }
+
+    // Start keeping pulse_stats from the next push_button on.
+    pub fn collect_stats(&mut self) {
+        self.pulse_stats.get_or_insert_with(PulseStats::default);
     }
 
     pub fn add(&mut self, module: Module) {
@@ -264,8 +462,8 @@ impl Modules {
         Ok(())
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Modules> {
-        let reader = BufReader::new(File::open(input)?);
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Modules> {
+        let reader = input.open()?;
         let mut modules = Modules::new();
 
         for line in reader.lines() {
@@ -278,91 +476,304 @@ impl Modules {
         Ok(modules)
     }
 
-    fn find_rx_input(&self) -> AOCResult<Conjunction> {
-        let rx_name = String::from("rx");
+    fn names_in_order(&self) -> Vec<&String> {
+        let mut names: Vec<&String> = self.modules.keys().collect();
+        names.sort();
+        names
+    }
 
-        for m in self.modules.values() {
-            if m.get_destinations().contains(&rx_name) {
-                match m {
-                    Module::ConjunctionType(c) => {
-                        return Ok(c.clone());
-                    },
-                    _ => {}
-                }
+    pub fn snapshot(&self) -> ModulesSnapshot {
+        self.snapshot_of(self.names_in_order())
+    }
+
+    // The bits for just these modules, which have to be given in name order
+    // for snapshots to compare equal.
+    fn snapshot_of<S: AsRef<str>>(&self, names: impl IntoIterator<Item = S>) -> ModulesSnapshot {
+        let mut snapshot = ModulesSnapshot::new();
+
+        for name in names {
+            match self.modules.get(name.as_ref()) {
+                Some(Module::FlipFlopType(f)) => snapshot.push(f.on),
+                Some(Module::ConjunctionType(c)) => {
+                    let mut inputs: Vec<(&String, &Pulse)> = c.inputs.iter().collect();
+                    inputs.sort_by_key(|(input_name, _)| *input_name);
+                    for (_, pulse) in inputs {
+                        snapshot.push(*pulse == Pulse::High);
+                    }
+                },
+                _ => {},
             }
         }
 
-        Err(AOCError::ProcessingError(format!("Not able to find the expected input type.")))
+        snapshot
     }
 
-    pub fn find_button_pushes_into_rx_single_low(&mut self) -> AOCResult<i64> {
+    // Puts the network back in a state from snapshot. It has to be from the
+    // same network, which is checked only as far as the bit count goes.
+    pub fn restore(&mut self, snapshot: &ModulesSnapshot) -> AOCResult<()> {
+        let expected_bits = self.snapshot().bit_count;
+        if snapshot.bit_count != expected_bits {
+            return Err(AOCError::ProcessingError(format!(
+                "Snapshot has {} bits but the network has {}.", snapshot.bit_count, expected_bits)));
+        }
 
-        // This is such a hack and works based on some assumptions about the data.
-        // Assumption 1: the input to rx is a Conjunction node.
-        // Assumption 2: there is a pattern to the cycles of each input coming into
-        //                the conjunction node. We can use these cycles to figure out
-        //                when they match.
+        let names: Vec<String> = self.names_in_order().into_iter().cloned().collect();
+        let mut idx = 0;
 
+        for name in names {
+            match self.modules.get_mut(&name) {
+                Some(Module::FlipFlopType(f)) => {
+                    f.on = snapshot.get(idx);
+                    idx += 1;
+                },
+                Some(Module::ConjunctionType(c)) => {
+                    let mut input_names: Vec<String> = c.inputs.keys().cloned().collect();
+                    input_names.sort();
+                    for input_name in input_names {
+                        let pulse = if snapshot.get(idx) { Pulse::High } else { Pulse::Low };
+                        c.inputs.insert(input_name, pulse);
+                        idx += 1;
+                    }
+                },
+                _ => {},
+            }
+        }
 
-        // Start by finding the input to rx and creating a map of the rx inputs inputs.
-        // When all the hash maps have found the first high.
-        let rx_input = self.find_rx_input()?;
+        Ok(())
+    }
+
+    // The conjunction that every pulse into sink comes from.
+    fn collector_for(&self, sink: &str) -> AOCResult<&Conjunction> {
+        let mut sources = self.modules.values().filter(|m| m.get_destinations().iter().any(|d| d == sink));
+
+        match (sources.next(), sources.next()) {
+            (Some(Module::ConjunctionType(c)), None) => Ok(c),
+            (None, _) => Err(AOCError::ProcessingError(format!("Nothing sends pulses to {}.", sink))),
+            _ => Err(AOCError::Unsupported(format!("The only input to {} has to be a conjunction.", sink))),
+        }
+    }
 
-        let mut input_trigger_counts: HashMap<String, Option<i32>> = HashMap::new();
+    // The module and every module that can send pulses its way, in name
+    // order. The broadcaster is left out, it doesn't keep any state.
+    fn upstream_of(&self, name: &str) -> Vec<String> {
+        let mut sources: HashMap<&str, Vec<&str>> = HashMap::new();
+        for m in self.modules.values() {
+            for d in m.get_destinations() {
+                sources.entry(d.as_str()).or_default().push(m.get_name().as_str());
+            }
+        }
 
-        for conjunction_input_name in rx_input.inputs.keys() {
-            input_trigger_counts.insert(conjunction_input_name.clone(), None);
+        let mut found: HashSet<&str> = HashSet::from([name]);
+        let mut to_visit: Vec<&str> = vec![name];
+        while let Some(next) = to_visit.pop() {
+            for source in sources.get(next).into_iter().flatten() {
+                if *source != "broadcaster" && found.insert(source) {
+                    to_visit.push(source);
+                }
+            }
         }
 
-        // Keep trigging the button until we see all the highs for the rx inputs inputs.
+        let mut upstream: Vec<String> = found.into_iter().map(String::from).collect();
+        upstream.sort();
+        upstream
+    }
+
+    // Presses the button until each sub-circuit feeding sink's collector is
+    // back in a state it's been in before, noting the presses where it sends
+    // the collector a high pulse. Works on a copy, so the network is left
+    // as it was.
+    pub fn analyze_cycles(&self, sink: &str) -> AOCResult<CycleReport> {
+        let collector = self.collector_for(sink)?;
+        let mut outputs: Vec<&String> = collector.inputs.keys().collect();
+        outputs.sort();
+
+        let mut sub_circuits: Vec<SubCircuitCycle> = outputs
+            .into_iter()
+            .map(|output| SubCircuitCycle {
+                output: output.clone(),
+                modules: self.upstream_of(output),
+                offset: 0,
+                length: 0,
+                high_presses: Vec::new(),
+            })
+            .collect();
+
+        let mut modules = self.clone();
+        let mut seen: Vec<HashMap<ModulesSnapshot, usize>> = sub_circuits
+            .iter()
+            .map(|sub_circuit| HashMap::from([(modules.snapshot_of(&sub_circuit.modules), 0)]))
+            .collect();
+        let mut highs: Vec<Vec<usize>> = vec![Vec::new(); sub_circuits.len()];
         let broadcaster = String::from("broadcaster");
-        let mut button_push_count = 0;
-
-        while input_trigger_counts.values().any(|c| c.is_none()) {
-            button_push_count += 1;
-
-            // Send the button push through and see if Highs are hit for the conjunction.
-            self.send_pulse(broadcaster.clone(), Pulse::Low, &mut |_source, _dst, dst_module, _pulse| {
-                match dst_module {
-                    Some(Module::ConjunctionType(dst_module)) => {
-                        if dst_module.name == rx_input.name {
-                            for (input_name, last_pulse) in &dst_module.inputs {
-                                if last_pulse == &Pulse::High {
-                                    input_trigger_counts.insert(input_name.clone(), Some(button_push_count));
-                                }
-                            }
-                        }
+
+        for press in 1 ..= MAX_CYCLE_PRESSES {
+            modules.send_pulse(broadcaster.clone(), Pulse::Low, &mut |source, destination, _module, pulse| {
+                if pulse == Pulse::High && *destination == collector.name {
+                    if let Some(idx) = sub_circuits.iter().position(|sub_circuit| sub_circuit.output == *source) {
+                        highs[idx].push(press);
+                    }
+                }
+            })?;
+
+            for (idx, sub_circuit) in sub_circuits.iter_mut().enumerate() {
+                if sub_circuit.length > 0 {
+                    continue;
+                }
+                let snapshot = modules.snapshot_of(&sub_circuit.modules);
+                match seen[idx].get(&snapshot) {
+                    Some(&offset) => {
+                        sub_circuit.offset = offset;
+                        sub_circuit.length = press - offset;
+                        sub_circuit.high_presses = std::mem::take(&mut highs[idx]);
+                    },
+                    None => {
+                        seen[idx].insert(snapshot, press);
                     },
-                    _ => {}
+                }
+            }
+
+            if sub_circuits.iter().all(|sub_circuit| sub_circuit.length > 0) {
+                let lcm = sub_circuits.iter().map(|sub_circuit| sub_circuit.length as i64).fold(1, lcm);
+                return Ok(CycleReport { sink: sink.into(), collector: collector.name.clone(), sub_circuits, lcm });
+            }
+        }
+
+        Err(AOCError::ProcessingError(format!("Not every sub-circuit into {} repeated within {} presses.", sink, MAX_CYCLE_PRESSES)))
+    }
+
+    // The lcm of the first press each input to sink's collector sends it a
+    // high pulse. Like first_low_press, that's only the first low pulse into
+    // sink when each sub-circuit fires once a cycle on a multiple of its
+    // length. analyze_cycles can check that, but snapshotting the
+    // sub-circuits on every press costs more than the pressing does.
+    fn lcm_of_first_highs(&self, sink: &str) -> AOCResult<i64> {
+        let collector = self.collector_for(sink)?;
+        let mut first_highs: HashMap<&String, usize> = HashMap::new();
+        let mut modules = self.clone();
+        let broadcaster = String::from("broadcaster");
+
+        for press in 1 ..= MAX_CYCLE_PRESSES {
+            modules.send_pulse(broadcaster.clone(), Pulse::Low, &mut |source, destination, _module, pulse| {
+                if pulse == Pulse::High && *destination == collector.name {
+                    if let Some((input, _)) = collector.inputs.get_key_value(source) {
+                        first_highs.entry(input).or_insert(press);
+                    }
                 }
             })?;
+
+            if first_highs.len() == collector.inputs.len() {
+                return Ok(first_highs.values().map(|press| *press as i64).fold(1, lcm));
+            }
         }
 
-        let common_cycle = input_trigger_counts
-            .values()
-            .filter_map(|x| *x)
-            .map(|x| x as i64)
-            .reduce(|a, b| lcm(a, b))
-            .ok_or_else(|| AOCError::ProcessingError("Couldn't calculate cycle".into()))?;
+        Err(AOCError::ProcessingError(format!("Not every input to {} sent it a high pulse within {} presses.", collector.name, MAX_CYCLE_PRESSES)))
+    }
+
+    // The fewest presses until target is sent pulse. A low pulse into a sink
+    // fed by sub-circuits that cycle is worked out from when each first
+    // fires. Anything else is pressed for one press at a time on a copy of
+    // the network, until it's sent or the whole network is back in a state
+    // it's been in, after which it never will be.
+    pub fn find_button_pushes_until(&self, target: &str, pulse: Pulse) -> AOCResult<i64> {
+        if pulse == Pulse::Low {
+            if let Ok(presses) = self.lcm_of_first_highs(target) {
+                return Ok(presses);
+            }
+        }
+        self.simulate_until(target, pulse)
+    }
+
+    // Brent's cycle finding, keeping just the one snapshot to compare with
+    // rather than every state seen.
+    fn simulate_until(&self, target: &str, pulse: Pulse) -> AOCResult<i64> {
+        let mut modules = self.clone();
+        let broadcaster = String::from("broadcaster");
+        let mut saved = modules.snapshot();
+        let (mut power, mut since_saved) = (1, 0);
+
+        for press in 1 ..= MAX_SIMULATED_PRESSES {
+            let mut sent = false;
+            modules.send_pulse(broadcaster.clone(), Pulse::Low, &mut |_source, destination, _module, sent_pulse| {
+                sent |= destination == target && sent_pulse == pulse;
+            })?;
+            if sent {
+                return Ok(press);
+            }
+
+            let snapshot = modules.snapshot();
+            since_saved += 1;
+            if snapshot == saved {
+                return Err(AOCError::ProcessingError(format!(
+                    "{} is never sent a {:?} pulse, the network repeats every {} presses.", target, pulse, since_saved)));
+            }
+            if since_saved == power {
+                saved = snapshot;
+                power *= 2;
+                since_saved = 0;
+            }
+        }
+
+        Err(AOCError::ProcessingError(format!("{} wasn't sent a {:?} pulse in {} presses.", target, pulse, MAX_SIMULATED_PRESSES)))
+    }
+
+    // The module graph in GraphViz's DOT language. Flip-flops are boxes,
+    // conjunctions diamonds, and destinations that aren't modules, like rx,
+    // are drawn as plain text.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph modules {\n");
+        let mut sinks: Vec<&String> = Vec::new();
+
+        for name in self.names_in_order() {
+            let module = &self.modules[name];
+            let shape = match module {
+                Module::BroadcasterType(_) => "doublecircle",
+                Module::FlipFlopType(_) => "box",
+                Module::ConjunctionType(_) => "diamond",
+            };
+            dot.push_str(&format!("    \"{}\" [shape={}];\n", name, shape));
+
+            for d in module.get_destinations() {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", name, d));
+                if !self.modules.contains_key(d) && !sinks.contains(&d) {
+                    sinks.push(d);
+                }
+            }
+        }
 
-        return Ok(common_cycle);
+        sinks.sort();
+        for sink in sinks {
+            dot.push_str(&format!("    \"{}\" [shape=plaintext];\n", sink));
+        }
+        dot.push_str("}\n");
+        dot
     }
 
+    // Returns the (high, low) pulses for these presses. The breakdown by module
+    // and press is kept in pulse_stats, if it's being collected.
     pub fn push_button(&mut self, n: i32)-> AOCResult<(i32, i32)> {
         let broadcaster = String::from("broadcaster");
-        let mut high_pulse_count = 0;
-        let mut low_pulse_count = 0;
+        let mut totals = PulseCounts::default();
+        let mut pulse_stats = self.pulse_stats.take();
 
         for _push_count in 0 .. n {
-            self.send_pulse(broadcaster.clone(), Pulse::Low, &mut |_source, _destination, _destination_module, pulse| {
-                match pulse {
-                    Pulse::High => high_pulse_count += 1,
-                    Pulse::Low => low_pulse_count += 1,
+            let mut press_counts = PulseCounts::default();
+
+            self.send_pulse(broadcaster.clone(), Pulse::Low, &mut |source, _destination, _destination_module, pulse| {
+                press_counts.add(pulse);
+                if let Some(pulse_stats) = pulse_stats.as_mut() {
+                    pulse_stats.per_module.entry(source.clone()).or_default().add(pulse);
                 }
             })?;
+
+            totals.high += press_counts.high;
+            totals.low += press_counts.low;
+            if let Some(pulse_stats) = pulse_stats.as_mut() {
+                pulse_stats.per_press.push(press_counts);
+            }
         }
 
-        Ok((high_pulse_count, low_pulse_count))
+        self.pulse_stats = pulse_stats;
+        Ok((totals.high, totals.low))
     }
 
     pub fn send_pulse<F>(&mut self, name: String, pulse: Pulse, on_pulse: &mut F) -> AOCResult<()>
@@ -391,17 +802,136 @@ impl Modules {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+impl Modules {
+
+    fn describe_module(&self, name: &str) -> AOCResult<String> {
+        let module = self.modules
+            .get(name)
+            .ok_or_else(|| AOCError::ProcessingError(format!("No module named: {}", name)))?;
+        let destinations = module.get_destinations().join(", ");
+
+        Ok(match module {
+            Module::BroadcasterType(_) => format!("broadcaster -> {}", destinations),
+            Module::FlipFlopType(f) => format!("%{} -> {}, {}", f.name, destinations, if f.on { "on" } else { "off" }),
+            Module::ConjunctionType(c) => {
+                let mut inputs: Vec<String> = c.inputs
+                    .iter()
+                    .map(|(input, pulse)| format!("{}={:?}", input, pulse))
+                    .collect();
+                inputs.sort();
+                format!("&{} -> {}, remembers {}", c.name, destinations, inputs.join(" "))
+            },
+        })
+    }
+
+    // Every flip-flop off and every conjunction remembering low pulses.
+    fn reset(&mut self) -> AOCResult<()> {
+        let mut cleared = ModulesSnapshot::new();
+        for _ in 0 .. self.snapshot().bit_count {
+            cleared.push(false);
+        }
+        self.restore(&cleared)?;
+        self.pulse_stats = None;
+        Ok(())
+    }
+}
+
+impl Explorable for Modules {
+
+    fn summary(&self) -> String {
+        let flip_flops = self.modules.values().filter(|m| matches!(m, Module::FlipFlopType(_))).count();
+        let conjunctions = self.modules.values().filter(|m| matches!(m, Module::ConjunctionType(_))).count();
+        format!("{} modules, {} flip-flops and {} conjunctions.", self.modules.len(), flip_flops, conjunctions)
+    }
+
+    fn commands(&self) -> Vec<ExploreCommand> {
+        vec![
+            ExploreCommand::new("module", "<name>", "A module's destinations and what it remembers"),
+            ExploreCommand::new("press", "[times]", "Push the button, once if times isn't given"),
+            ExploreCommand::new("state", "", "The flip-flops that are on"),
+            ExploreCommand::new("reset", "", "Put every module back how it started"),
+            ExploreCommand::new("cycles", "[sink]", "How the sub-circuits into sink cycle, rx if sink isn't given"),
+            ExploreCommand::new("dot", "", "The module graph for GraphViz"),
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String> {
+        match name {
+            "module" => self.describe_module(&explore::arg::<String>(args, 0, "name")?),
+            "press" => {
+                self.collect_stats();
+                let (high, low) = self.push_button(explore::arg_or(args, 0, "times", 1)?)?;
+                let presses = self.pulse_stats.as_ref().map_or(0, |pulse_stats| pulse_stats.per_press.len());
+                Ok(format!("{} high and {} low pulses, {} presses so far", high, low, presses))
+            },
+            "state" => {
+                let mut on: Vec<&String> = self.modules
+                    .values()
+                    .filter_map(|m| match m {
+                        Module::FlipFlopType(f) if f.on => Some(&f.name),
+                        _ => None,
+                    })
+                    .collect();
+                on.sort();
+                let names: Vec<&str> = on.iter().map(|name| name.as_str()).collect();
+                Ok(format!("{} on: {}", on.len(), names.join(" ")))
+            },
+            "reset" => {
+                self.reset()?;
+                Ok(self.summary())
+            },
+            "cycles" => Ok(self.analyze_cycles(&explore::arg_or(args, 0, "sink", String::from("rx"))?)?.to_string()),
+            "dot" => Ok(self.to_dot()),
+            _ => Err(explore::unknown_command(name)),
+        }
+    }
+}
+
+// Set problem20.export=dot to write the module graph to the artifacts
+// directory, for GraphViz to draw.
+fn export_dot(modules: &Modules) -> AOCResult<()> {
+    match settings::params().get("problem20.export") {
+        None => Ok(()),
+        Some("dot") => {
+            let path = artifact_path("problem20_modules.dot")?;
+            fs::write(&path, modules.to_dot())?;
+            tracing::debug!("Wrote: {}", path.display());
+            Ok(())
+        },
+        Some(format) => Err(AOCError::ProcessingError(format!("Unknown export format for problem20: {}", format))),
+    }
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let mut modules = Modules::parse(input)?;
+    if log::enabled(log::Level::Debug) {
+        modules.collect_stats();
+    }
     let (high_pulse_count, low_pulse_count) = modules.push_button(1000)?;
 
+    if let Some(pulse_stats) = &modules.pulse_stats {
+        if let Some((name, counts)) = pulse_stats.busiest_module() {
+            tracing::debug!("Busiest module: {} sent {} high and {} low pulses", name, counts.high, counts.low);
+        }
+    }
+
     let result = high_pulse_count * low_pulse_count;
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut modules = Modules::parse(input)?;
-    let result = modules.find_button_pushes_into_rx_single_low()?;
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let modules = Modules::parse(input)?;
+    export_dot(&modules)?;
+
+    if log::enabled(log::Level::Debug) {
+        match modules.analyze_cycles("rx") {
+            Ok(report) => tracing::debug!("{}", report.to_string().trim_end()),
+            Err(e) => tracing::debug!("No cycles into rx: {}", e),
+        }
+    }
+
+    let result = modules.find_button_pushes_until("rx", Pulse::Low)?;
     Ok(result.to_string())
 }
 
+crate::register_day!(day: 20, title: "Pulse Propagation", parts: [1 => part1 (example = "32000000"), 2 => part2]);
@@ -1,9 +1,21 @@
-use std::collections::HashMap;
-use std::collections::VecDeque;
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
+use crate::mathx::lcm;
+use crate::settings;
+use crate::strategy::Strategies;
+
+// The steps the elf has to take in each part, unless problem21.steps says
+// otherwise.
+const PART1_STEPS: i32 = 64;
+const PART2_STEPS: i32 = 26501365;
+
+// InfiniteGardenExtrapolator trusts a quadratic once its second difference
+// has been the same this many times in a row, and gives up after this many
+// periods.
+const STABLE_DIFFERENCES: usize = 3;
+const MAX_SAMPLE_PERIODS: usize = 20;
 
 #[derive(Debug, Copy, Clone)]
 pub enum Space {
@@ -38,6 +50,61 @@ impl GardenVisitNode {
     }
 }
 
+// How many steps it takes to get to each plot from where exploring started,
+// stored flat by y * width + x. Plots that can't be reached are -1.
+// A flat Vec is both the distances and the visited check, in place of the
+// HashMap<GardenVisitNode, i32> and BitGrid this used to keep.
+#[derive(Debug, Clone)]
+pub struct GardenDistances {
+    width: i32,
+    steps: Vec<i32>,
+}
+
+impl GardenDistances {
+
+    pub fn new(width: i32, height: i32) -> Self {
+        Self { width, steps: vec![-1; (width * height) as usize] }
+    }
+
+    fn index(&self, y: i32, x: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    // Returns false if the plot already had a distance.
+    pub fn insert(&mut self, y: i32, x: i32, steps: i32) -> bool {
+        let idx = self.index(y, x);
+        if self.steps[idx] == -1 {
+            self.steps[idx] = steps;
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GardenVisitNode, i32)> + '_ {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, steps)| **steps >= 0)
+            .map(|(idx, steps)| {
+                let idx = idx as i32;
+                (GardenVisitNode::new(idx / self.width, idx % self.width, *steps), *steps)
+            })
+    }
+
+    // Plots you could be standing on after exactly this many steps. You can
+    // always step back and forth, so that's any plot with the same even/odd
+    // distance that's close enough.
+    pub fn count_reachable(&self, steps: i32) -> i32 {
+        let steps_even_odd = steps % 2;
+        self.steps
+            .iter()
+            .filter(|node_steps| **node_steps >= 0 && **node_steps % 2 == steps_even_odd && **node_steps <= steps)
+            .count() as i32
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Garden {
     pub map: Vec<Vec<Space>>,
@@ -73,15 +140,15 @@ impl Garden {
 
         explored
             .iter()
-            .filter(|(gv_node, tgt_steps)| gv_node.even_odd == tgt_even_odd && **tgt_steps <= steps)
+            .filter(|(gv_node, tgt_steps)| gv_node.even_odd == tgt_even_odd && *tgt_steps <= steps)
             .map(|(gv_node, _)| (gv_node.y, gv_node.x))
             .collect()
     }
 
-    pub fn explore(&self, start_y: i32, start_x: i32) -> HashMap<GardenVisitNode, i32>  {
+    pub fn explore(&self, start_y: i32, start_x: i32) -> GardenDistances {
 
         let mut x_queue: VecDeque<(i32, i32, i32)> = VecDeque::new();
-        let mut visited: HashMap<GardenVisitNode, i32> = HashMap::new();
+        let mut visited = GardenDistances::new(self.width(), self.height());
 
         match self.get(start_y, start_x) {
             None|Some(Space::Rock) => return visited,
@@ -93,21 +160,13 @@ impl Garden {
         while let Some((cur_y, cur_x, cur_steps)) = x_queue.pop_front() {
             for (yd, xd) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
                 let adj_steps = cur_steps + 1;
+                let adj_y = cur_y + yd;
+                let adj_x = cur_x + xd;
 
-                let adj_gv_node = GardenVisitNode::new(
-                    cur_y + yd,
-                    cur_x + xd,
-                    adj_steps % 2
-                );
-
-                match self.get(adj_gv_node.y, adj_gv_node.x) {
-                    Some(Space::Start|Space::Plot) => {
-                        if !visited.contains_key(&adj_gv_node) {
-                            x_queue.push_back((adj_gv_node.y, adj_gv_node.x, adj_steps));
-                            visited.insert(adj_gv_node, adj_steps);
-                        }
-                    },
-                    _ => {}
+                if let Some(Space::Start|Space::Plot) = self.get(adj_y, adj_x) {
+                    if visited.insert(adj_y, adj_x, adj_steps) {
+                        x_queue.push_back((adj_y, adj_x, adj_steps));
+                    }
                 }
             }
         }
@@ -115,6 +174,43 @@ impl Garden {
         visited
     }
 
+    // The garden repeated copies times each way. Only the middle copy keeps
+    // its start.
+    pub fn tiled(&self, copies: usize) -> Garden {
+        let middle = copies / 2;
+        let mut map: Vec<Vec<Space>> = Vec::new();
+
+        for copy_y in 0 .. copies {
+            for row in &self.map {
+                let mut tiled_row: Vec<Space> = Vec::new();
+                for copy_x in 0 .. copies {
+                    tiled_row.extend(row.iter().map(|space| match space {
+                        Space::Start if copy_y != middle || copy_x != middle => Space::Plot,
+                        space => *space,
+                    }));
+                }
+                map.push(tiled_row);
+            }
+        }
+
+        Garden::new(map)
+    }
+
+    // What InfiniteGardenPathSolver needs, a square garden an odd number of
+    // plots across with the start in the middle, and no rocks in the start's
+    // row and column or around the edge.
+    pub fn has_clear_lanes(&self) -> bool {
+        let size = self.width();
+        if size != self.height() || size % 2 == 0 || self.find_start().ok() != Some((size / 2, size / 2)) {
+            return false;
+        }
+
+        let lanes = [0, size / 2, size - 1];
+        (0 .. size).all(|i| lanes.iter().all(|lane| {
+            !matches!(self.get(*lane, i), Some(Space::Rock)) && !matches!(self.get(i, *lane), Some(Space::Rock))
+        }))
+    }
+
     pub fn find_start(&self) -> AOCResult<(i32, i32)> {
         for (y, row) in self.map.iter().enumerate() {
             for (x, space) in row.iter().enumerate() {
@@ -126,7 +222,7 @@ impl Garden {
         Err(AOCError::ProcessingError("No start position found.".into()))
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Garden> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Garden> {
         let mut map: Vec<Vec<Space>> = Vec::new();
         let lines = read_lines_as_bytes(input)?;
 
@@ -137,24 +233,94 @@ impl Garden {
                 .collect::<AOCResult<Vec<Space>>>()?);
         }
 
+        let width = map.first().map_or(0, |row| row.len());
+        if width == 0 {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+        if let Some(y) = map.iter().position(|row| row.len() != width) {
+            return Err(AOCError::ParseError(format!("Row {} is {} wide, expected {}.", y + 1, map[y].len(), width)));
+        }
+
         Ok(Garden::new(map))
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+// Set problem21.steps to try other step counts, like the 6 to 5000 the
+// puzzle gives for the example.
+fn steps(default: i32) -> AOCResult<i32> {
+    let steps = settings::params().get_or("problem21.steps", default)?;
+    if steps < 0 {
+        return Err(AOCError::ProcessingError("problem21.steps can't be negative.".into()));
+    }
+    Ok(steps)
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let garden = Garden::parse(input)?;
 
     let (start_y, start_x) = garden.find_start()?;
-    let visited = garden.find_possible_end_positions(start_y, start_x, 64);
+    let visited = garden.find_possible_end_positions(start_y, start_x, steps(PART1_STEPS)?);
     let result = visited.len();
 
     Ok(result.to_string())
 }
 
+// The pieces the infinite garden's count is added up from. Each is the total
+// over every copy of the garden of that kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TilingComponents {
+    // The garden with the start.
+    pub middle: i128,
+    // Full gardens straight out from the middle.
+    pub straight_lines: i128,
+    // The partly reached gardens at the far end of each straight line.
+    pub ends: i128,
+    // Full gardens between the straight lines.
+    pub quadrants: i128,
+    // Partly reached gardens along the edge between the ends.
+    pub diagonals: i128,
+}
+
+impl TilingComponents {
+
+    pub fn total(&self) -> i128 {
+        self.middle + self.straight_lines + self.ends + self.quadrants + self.diagonals
+    }
+
+    pub fn named(&self) -> [(&'static str, i128); 5] {
+        [
+            ("middle", self.middle),
+            ("straight lines", self.straight_lines),
+            ("ends", self.ends),
+            ("quadrants", self.quadrants),
+            ("diagonals", self.diagonals),
+        ]
+    }
+
+    // Which component the garden this many copies over and down is part of,
+    // when the steps reach box_dist copies out in a straight line.
+    fn add(&mut self, copy_y: i32, copy_x: i32, box_dist: i32, count: i128) -> AOCResult<()> {
+        let dist = copy_y.abs() + copy_x.abs();
+        let on_line = copy_y == 0 || copy_x == 0;
+
+        let component = match (on_line, dist) {
+            (_, 0) => &mut self.middle,
+            (true, d) if d < box_dist => &mut self.straight_lines,
+            (true, d) if d == box_dist => &mut self.ends,
+            (false, d) if d < box_dist => &mut self.quadrants,
+            (false, d) if d <= box_dist + 1 => &mut self.diagonals,
+            _ => return Err(AOCError::ProcessingError(format!(
+                "Plots reached in a garden copy that should be out of reach: {}, {}", copy_y, copy_x))),
+        };
+
+        *component += count;
+        Ok(())
+    }
+}
+
 struct InfiniteGardenPathSolver<'a> {
     pub garden: &'a Garden,
     pub max_steps: i32,
-    pub debug: bool,
 }
 
 /*
@@ -170,24 +336,19 @@ struct InfiniteGardenPathSolver<'a> {
  */
 impl<'a> InfiniteGardenPathSolver<'a> {
 
-    pub fn new(garden: &'a Garden, max_steps: i32, debug: bool) -> Self {
-        Self { garden, max_steps, debug }
+    pub fn new(garden: &'a Garden, max_steps: i32) -> Self {
+        Self { garden, max_steps }
     }
 
-    fn count_visits(visits: &HashMap<GardenVisitNode, i32>, steps: i32) -> i32 {
-        let steps_even_odd = steps % 2;
-        let mut count = 0;
-
-        for (_, node_steps) in visits {
-            if node_steps % 2 == steps_even_odd && *node_steps <= steps {
-                count += 1;
-            }
-        }
-
-        count
+    fn count_visits(visits: &GardenDistances, steps: i32) -> i32 {
+        visits.count_reachable(steps)
     }
 
     pub fn solve(&self) -> AOCResult<i128> {
+        Ok(self.components()?.total())
+    }
+
+    pub fn components(&self) -> AOCResult<TilingComponents> {
         let (start_y, start_x) = self.garden.find_start()?;
 
         // This code could be broken down into seaparate methods, but
@@ -199,7 +360,8 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         // How many boxes after the current can we go in any straight direction?
         let box_dist = (self.max_steps - start_x) as f64 / self.garden.height() as f64;
         if box_dist.floor() != box_dist {
-            return Err(AOCError::ProcessingError("Can't deal with this!".into()));
+            return Err(AOCError::Unsupported(format!(
+                "Steps don't land on a box edge: steps={} height={}", self.max_steps, self.garden.height())));
         }
         let box_dist = box_dist as i32;
 
@@ -207,13 +369,11 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         // If you are in the middle you should end up in the next box.
         let move_half_amt = self.garden.width() / 2 + 1;
 
-        if self.debug {
-            println!("Map: {} by {}", self.garden.height(), self.garden.width());
-            println!("Start: {} by {}", start_y, start_x);
-            println!("Is initial even/odd: {}", self.max_steps % 2);
-            println!("Box dist: {box_dist}");
-            println!("move_half_amt: {}", move_half_amt);
-        }
+        tracing::debug!("Map: {} by {}", self.garden.height(), self.garden.width());
+        tracing::debug!("Start: {} by {}", start_y, start_x);
+        tracing::debug!("Is initial even/odd: {}", self.max_steps % 2);
+        tracing::debug!("Box dist: {box_dist}");
+        tracing::debug!("move_half_amt: {}", move_half_amt);
 
         // Record visits from start
         let base_visits   = self.garden.explore(start_y, start_x);
@@ -236,10 +396,8 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         // What the full box count would be if you were on an alternate step
         let base_alt_count = Self::count_visits(&base_visits, self.max_steps - 1) as i128;
 
-        if self.debug {
-            println!("base_count:     {}", base_count);
-            println!("base_alt_count: {}", base_alt_count);
-        }
+        tracing::debug!("base_count:     {}", base_count);
+        tracing::debug!("base_alt_count: {}", base_alt_count);
 
         // how many steps are left when you get past the edge of the last box in any direction.
         let end_steps_left = (self.max_steps - move_half_amt) % self.garden.width();
@@ -250,11 +408,9 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         // Move over to the diagnoal leaving more space and see how many steps needed.
         let long_diagonal_end_steps_left = short_diagonal_end_steps_left + self.garden.width();
 
-        if self.debug {
-            println!("end_steps_left:                {}", end_steps_left);
-            println!("short_diagonal_end_steps_left: {}", short_diagonal_end_steps_left);
-            println!("long_diagonal_end_steps_left:  {}", long_diagonal_end_steps_left);
-        }
+        tracing::debug!("end_steps_left:                {}", end_steps_left);
+        tracing::debug!("short_diagonal_end_steps_left: {}", short_diagonal_end_steps_left);
+        tracing::debug!("long_diagonal_end_steps_left:  {}", long_diagonal_end_steps_left);
 
         // Get the counts for the ends of the structure.
 
@@ -263,12 +419,10 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         let top_count    = Self::count_visits(&top_visits, end_steps_left) as i128;
         let bottom_count = Self::count_visits(&bottom_visits, end_steps_left) as i128;
 
-        if self.debug {
-            println!("left_count:   {}", left_count);
-            println!("right_count:  {}", right_count);
-            println!("top_count:    {}", top_count);
-            println!("bottom_count: {}", bottom_count);
-        }
+        tracing::debug!("left_count:   {}", left_count);
+        tracing::debug!("right_count:  {}", right_count);
+        tracing::debug!("top_count:    {}", top_count);
+        tracing::debug!("bottom_count: {}", bottom_count);
 
         // Get the counts for the diagoanls
         let tl_short_count = Self::count_visits(&tl_visits, short_diagonal_end_steps_left) as i128;
@@ -283,16 +437,14 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         let br_short_count = Self::count_visits(&br_visits, short_diagonal_end_steps_left) as i128;
         let br_long_count = Self::count_visits(&br_visits, long_diagonal_end_steps_left) as i128;
 
-        if self.debug {
-            println!("tl_short_count: {}", tl_short_count);
-            println!("tl_long_count:  {}", tl_long_count);
-            println!("tr_short_count: {}", tr_short_count);
-            println!("tr_long_count:  {}", tr_long_count);
-            println!("bl_short_count: {}", bl_short_count);
-            println!("bl_long_count:  {}", bl_long_count);
-            println!("br_short_count: {}", br_short_count);
-            println!("br_long_count:  {}", br_long_count);
-        }
+        tracing::debug!("tl_short_count: {}", tl_short_count);
+        tracing::debug!("tl_long_count:  {}", tl_long_count);
+        tracing::debug!("tr_short_count: {}", tr_short_count);
+        tracing::debug!("tr_long_count:  {}", tr_long_count);
+        tracing::debug!("bl_short_count: {}", bl_short_count);
+        tracing::debug!("bl_long_count:  {}", bl_long_count);
+        tracing::debug!("br_short_count: {}", br_short_count);
+        tracing::debug!("br_long_count:  {}", br_long_count);
 
         // Now that we have counts for box types, need to figure out how many of each box type
 
@@ -312,19 +464,15 @@ impl<'a> InfiniteGardenPathSolver<'a> {
         
         let sl_all_total = 4 * (box_sl_base_count * base_count as i128 + box_sl_alt_count * base_alt_count as i128);
         
-        if self.debug {
-            println!("box_sl_base_count: {}", box_sl_base_count); 
-            println!("box_sl_alt_count:  {}", box_sl_alt_count);
-            println!("sl_all_total:      {}", sl_all_total);
-        }
+        tracing::debug!("box_sl_base_count: {}", box_sl_base_count);
+        tracing::debug!("box_sl_alt_count:  {}", box_sl_alt_count);
+        tracing::debug!("sl_all_total:      {}", sl_all_total);
 
         // 3. End count
         //    The work was mostly done and just need to add up.
         let ends_total = left_count + right_count + top_count + bottom_count;
 
-        if self.debug {
-            println!("ends_total: {}", ends_total);
-        }
+        tracing::debug!("ends_total: {}", ends_total);
 
         // 4. Full boxes in a quadrant
 
@@ -345,10 +493,8 @@ impl<'a> InfiniteGardenPathSolver<'a> {
 
         let all_full_box_quandrants = full_box_quadrant_total * 4;
 
-        if self.debug {
-            println!("full_box_quadrant_total: {}", full_box_quadrant_total);
-            println!("all_full_box_quandrants: {}", all_full_box_quandrants);
-        }
+        tracing::debug!("full_box_quadrant_total: {}", full_box_quadrant_total);
+        tracing::debug!("all_full_box_quandrants: {}", all_full_box_quandrants);
 
         // 5. Diagonals
 
@@ -362,13 +508,11 @@ impl<'a> InfiniteGardenPathSolver<'a> {
 
         let diag_totals = tl_diag_total + tr_diag_total + bl_diag_total + br_diag_total;
 
-        if self.debug {
-            println!("tl_diag_total: {}", tl_diag_total);
-            println!("tr_diag_total: {}", tr_diag_total);
-            println!("bl_diag_total: {}", bl_diag_total);
-            println!("br_diag_total: {}", br_diag_total);
-            println!("diag_totals:   {}", diag_totals);
-        }
+        tracing::debug!("tl_diag_total: {}", tl_diag_total);
+        tracing::debug!("tr_diag_total: {}", tr_diag_total);
+        tracing::debug!("bl_diag_total: {}", bl_diag_total);
+        tracing::debug!("br_diag_total: {}", br_diag_total);
+        tracing::debug!("diag_totals:   {}", diag_totals);
 
         // 6 Final Total:
 
@@ -379,24 +523,195 @@ impl<'a> InfiniteGardenPathSolver<'a> {
             all_full_box_quandrants +
             diag_totals;
 
-        if self.debug {
-            println!(" Final Total: {}", final_total);
-            println!("   * Middle Count:                {}", base_count);
-            println!("   * Straight Line Count:         {}", sl_all_total);
-            println!("   * Ends Count:                  {}", ends_total);
-            println!("   * Full Boxes Quandrants Count: {}", all_full_box_quandrants);
-            println!("   * Diagonals Count:             {}", diag_totals);
+        tracing::debug!(" Final Total: {}", final_total);
+        tracing::debug!("   * Middle Count:                {}", base_count);
+        tracing::debug!("   * Straight Line Count:         {}", sl_all_total);
+        tracing::debug!("   * Ends Count:                  {}", ends_total);
+        tracing::debug!("   * Full Boxes Quandrants Count: {}", all_full_box_quandrants);
+        tracing::debug!("   * Diagonals Count:             {}", diag_totals);
+
+        Ok(TilingComponents {
+            middle: base_count,
+            straight_lines: sl_all_total,
+            ends: ends_total,
+            quadrants: all_full_box_quandrants,
+            diagonals: diag_totals,
+        })
+    }
+
+    // Counts the same components by walking a garden made of enough copies
+    // to hold everything the steps can reach.
+    pub fn simulate_components(&self) -> AOCResult<TilingComponents> {
+        let (start_y, start_x) = self.garden.find_start()?;
+        let height = self.garden.height();
+        let width = self.garden.width();
+
+        let box_dist = (self.max_steps - start_x) / height;
+        let copies = 2 * box_dist + 1;
+
+        let tiled = self.garden.tiled(copies as usize);
+        let visits = tiled.explore(start_y + box_dist * height, start_x + box_dist * width);
+
+        let steps_even_odd = self.max_steps % 2;
+        let mut counts: HashMap<(i32, i32), i128> = HashMap::new();
+        for (node, steps) in visits.iter() {
+            if node.even_odd == steps_even_odd && steps <= self.max_steps {
+                *counts.entry((node.y / height - box_dist, node.x / width - box_dist)).or_default() += 1;
+            }
         }
 
-        Ok(final_total)
+        let mut components = TilingComponents::default();
+        for ((copy_y, copy_x), count) in counts {
+            components.add(copy_y, copy_x, box_dist, count)?;
+        }
+
+        Ok(components)
     }
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+// Counts plots reachable in the garden repeated forever, for any garden and
+// any number of steps. Far enough out, the count after remainder + k * period
+// steps grows as a quadratic in k. Counts are walked out a period at a time
+// until the second difference has come out the same STABLE_DIFFERENCES times
+// in a row, and the quadratic carries on from there.
+pub struct InfiniteGardenExtrapolator<'a> {
+    garden: &'a Garden,
+    visited: HashSet<(i32, i32)>,
+    frontier: Vec<(i32, i32)>,
+    // How many plots are first reached at each number of steps.
+    layers: Vec<i128>,
+}
+
+impl<'a> InfiniteGardenExtrapolator<'a> {
+
+    pub fn new(garden: &'a Garden) -> AOCResult<Self> {
+        let start = garden.find_start()?;
+        Ok(Self { garden, visited: HashSet::from([start]), frontier: vec![start], layers: vec![1] })
+    }
+
+    fn is_plot(&self, y: i32, x: i32) -> bool {
+        let y = y.rem_euclid(self.garden.height());
+        let x = x.rem_euclid(self.garden.width());
+        !matches!(self.garden.get(y, x), Some(Space::Rock))
+    }
+
+    fn walk_to(&mut self, steps: usize) {
+        while self.layers.len() <= steps {
+            let mut next: Vec<(i32, i32)> = Vec::new();
+            for (y, x) in std::mem::take(&mut self.frontier) {
+                for (adj_y, adj_x) in [(y - 1, x), (y + 1, x), (y, x - 1), (y, x + 1)] {
+                    if self.is_plot(adj_y, adj_x) && self.visited.insert((adj_y, adj_x)) {
+                        next.push((adj_y, adj_x));
+                    }
+                }
+            }
+            self.layers.push(next.len() as i128);
+            self.frontier = next;
+        }
+    }
+
+    // Plots you could be on after exactly this many steps, by walking them.
+    pub fn count_reachable(&mut self, steps: usize) -> i128 {
+        self.walk_to(steps);
+        self.layers[.. steps + 1].iter().skip(steps % 2).step_by(2).sum()
+    }
+
+    // Plots reachable after this many steps, extrapolated. The garden's
+    // repeat is tried as the period first, then twice that, for gardens
+    // where odd and even copies don't grow the same way.
+    pub fn solve(&mut self, steps: usize) -> AOCResult<i128> {
+        let repeat = lcm(self.garden.width() as i64, self.garden.height() as i64) as usize;
+
+        for period in [repeat, 2 * repeat] {
+            if let Some(count) = self.extrapolate(steps, period) {
+                return Ok(count);
+            }
+        }
+
+        Err(AOCError::Unsupported(format!(
+            "Counts didn't settle into a quadratic within {} periods of {} steps.", MAX_SAMPLE_PERIODS, 2 * repeat)))
+    }
+
+    fn extrapolate(&mut self, steps: usize, period: usize) -> Option<i128> {
+        let remainder = steps % period;
+        let periods = (steps / period) as i128;
+        let mut samples: Vec<i128> = Vec::new();
+        let mut stable = 0;
+
+        for k in 0 ..= MAX_SAMPLE_PERIODS {
+            let sample_steps = remainder + k * period;
+            // Close enough to walk all the way.
+            if sample_steps >= steps {
+                return Some(self.count_reachable(steps));
+            }
+            samples.push(self.count_reachable(sample_steps));
+
+            let second_differences: Vec<i128> = samples.windows(3).rev().take(2).map(|w| w[2] - 2 * w[1] + w[0]).collect();
+            stable = match second_differences[..] {
+                [] => 0,
+                [latest, before] if latest == before => stable + 1,
+                _ => 1,
+            };
+
+            if stable >= STABLE_DIFFERENCES {
+                let latest = samples[k];
+                let first = latest - samples[k - 1];
+                let n = periods - k as i128;
+                return Some(latest + n * first + second_differences[0] * n * (n + 1) / 2);
+            }
+        }
+
+        None
+    }
+}
+
+// Runs the solver with few enough steps that a direct simulation is quick,
+// and compares each component so a mistake shows up where it happens
+// instead of in a huge answer that can't be checked.
+// The steps reach problem21.self_test_boxes copies out, 2 by default. The
+// components are logged at info, so -v shows them.
+fn self_test(garden: &Garden) -> AOCResult<()> {
+    let box_dist = settings::params().get_or("problem21.self_test_boxes", 2)?;
+    if box_dist < 1 {
+        return Err(AOCError::ProcessingError("problem21.self_test_boxes must be at least 1.".into()));
+    }
+
+    let (_, start_x) = garden.find_start()?;
+    let steps = start_x + box_dist * garden.height();
+    let solver = InfiniteGardenPathSolver::new(garden, steps);
+
+    let analytic = solver.components()?;
+    let simulated = solver.simulate_components()?;
+
+    tracing::info!("Self test: {} steps over {} by {} copies of the garden", steps, 2 * box_dist + 1, 2 * box_dist + 1);
+    tracing::info!("  {:<16} {:>12} {:>12}", "Component", "Analytic", "Simulated");
+    for ((name, a), (_, s)) in analytic.named().iter().zip(simulated.named().iter()) {
+        tracing::info!("  {:<16} {:>12} {:>12}{}", name, a, s, if a == s { "" } else { "  <-- differs" });
+    }
+
+    match analytic.named().iter().zip(simulated.named().iter()).find(|((_, a), (_, s))| a != s) {
+        None => Ok(()),
+        Some(((name, a), (_, s))) => Err(AOCError::ProcessingError(format!(
+            "Self test failed, {} is {} but the simulation gives {}.", name, a, s))),
+    }
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let garden = Garden::parse(input)?;
-    let infinite_garden_solver = InfiniteGardenPathSolver::new(&garden, 26501365, false);
 
-    let result = infinite_garden_solver.solve()?;
+    if settings::self_test() {
+        self_test(&garden)?;
+    }
+
+    // The components only add up when the steps end on the edge of a copy.
+    let result = Strategies::new("problem21::part2")
+        .add("components", |(garden, steps)| InfiniteGardenPathSolver::new(garden, *steps).solve())
+        .add("extrapolate", |(garden, steps)| InfiniteGardenExtrapolator::new(garden)?.solve(*steps as usize))
+        .run(&(garden, steps(PART2_STEPS)?), |(garden, steps)| {
+            if garden.has_clear_lanes() && (steps - garden.width() / 2) % garden.width() == 0 { "components" } else { "extrapolate" }
+        })?;
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 21, title: "Step Counter", parts: [1 => part1, 2 => part2]);
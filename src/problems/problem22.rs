@@ -1,16 +1,21 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufReader;
 use std::io::prelude::*;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::ProblemInput;
+use crate::explore::{self, Explorable, ExploreCommand};
+use crate::graph::Graph;
+use crate::progress::Progress;
+use crate::settings;
+use crate::strategy::Strategies;
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
+use crate::visualize::{self, Animation, Color, Picture, Visualizable};
 
 lazy_static! {
     static ref PIECE_REGEX: Regex = Regex::new(
@@ -20,9 +25,9 @@ lazy_static! {
 
 #[derive(Debug, Clone)]
 pub struct Position {
-    pub x: i32,
-    pub y: i32,
-    pub z: i32,
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
 }
 
 // This iterator moves towards an end
@@ -93,147 +98,274 @@ impl Piece {
         let cap = PIECE_REGEX.captures_must(text.as_ref())?;
 
         let start = Position {
-            x: cap.get_group(1)?.parse::<i32>()?,
-            y: cap.get_group(2)?.parse::<i32>()?,
-            z: cap.get_group(3)?.parse::<i32>()?,
+            x: cap.get_group(1)?.parse::<i64>()?,
+            y: cap.get_group(2)?.parse::<i64>()?,
+            z: cap.get_group(3)?.parse::<i64>()?,
         };
 
         let end = Position {
-            x: cap.get_group(4)?.parse::<i32>()?,
-            y: cap.get_group(5)?.parse::<i32>()?,
-            z: cap.get_group(6)?.parse::<i32>()?,
+            x: cap.get_group(4)?.parse::<i64>()?,
+            y: cap.get_group(5)?.parse::<i64>()?,
+            z: cap.get_group(6)?.parse::<i64>()?,
         };
 
         Ok(Self { id: -1, start, end })
     }
 
-    pub fn get_yx_lows(&self) -> HashMap<(i32, i32), i32> {
-        let mut lows: HashMap<(i32, i32), i32> = HashMap::new();
+    // Pieces are straight lines of cubes sitting above the ground at z 0.
+    pub fn validate(&self) -> AOCResult<()> {
+        let differing_axes = [
+            self.start.x != self.end.x,
+            self.start.y != self.end.y,
+            self.start.z != self.end.z,
+        ].iter().filter(|d| **d).count();
 
-        for pos in self.position_iter() {
-            if let Some(yx_low) = lows.get_mut(&(pos.y, pos.x)) {
-                if *yx_low > pos.z {
-                    *yx_low = pos.z;
-                }
-            }
-            else {
-                lows.insert((pos.y, pos.x), pos.z);
-            }
+        if differing_axes > 1 {
+            return Err(AOCError::ParseError(format!("Piece isn't a straight line: {:?} ~ {:?}", self.start, self.end)));
         }
-
-        lows
+        if self.get_low_z() < 1 {
+            return Err(AOCError::ParseError(format!("Piece is at or below the ground: {:?} ~ {:?}", self.start, self.end)));
+        }
+        Ok(())
     }
 
-    pub fn get_low_z(&self) -> i32 {
+    pub fn get_low_z(&self) -> i64 {
         self.start.z.min(self.end.z)
     }
+
+    pub fn get_high_z(&self) -> i64 {
+        self.start.z.max(self.end.z)
+    }
+
+    // The x and y ranges covered, looking down from above.
+    pub fn get_footprint(&self) -> ((i64, i64), (i64, i64)) {
+        (
+            (self.start.x.min(self.end.x), self.start.x.max(self.end.x)),
+            (self.start.y.min(self.end.y), self.start.y.max(self.end.y)),
+        )
+    }
 }
 
-pub const EMPTY_PIECE_ID: i32 = -1;
-pub const GROUND_ID: i32 = -2;
+// Pieces whose footprint covers more cells than this are kept in a list of
+// their own instead, so a very long piece doesn't fill the index.
+const MAX_PIECE_CELLS: i64 = 64;
 
 #[derive(Debug, Clone)]
-pub struct Pieces {
-    pub pieces: HashMap<i32, Piece>,
+struct IndexedPiece {
+    id: i32,
+    footprint: ((i64, i64), (i64, i64)),
+    high_z: i64,
+}
+
+impl IndexedPiece {
 
-    // Represents the 3-d space with the value being the piece id.
-    space_matrix: Vec<Vec<Vec<i32>>>,
+    fn overlaps(&self, footprint: ((i64, i64), (i64, i64))) -> bool {
+        let ((x1, x2), (y1, y2)) = self.footprint;
+        let ((ox1, ox2), (oy1, oy2)) = footprint;
+        x1 <= ox2 && ox1 <= x2 && y1 <= oy2 && oy1 <= y2
+    }
 }
 
-impl Pieces {
+// Pieces kept under each x,y cell their footprint covers, so pieces that
+// could be above or below a piece are found by looking in its cells. The real
+// input is only 10x10 cells across, and grouping cells into larger squares
+// was slower. The top of each piece is kept here too so a query doesn't need
+// to go back to the pieces.
+#[derive(Debug, Clone, Default)]
+pub struct FootprintIndex {
+    cells: HashMap<(i64, i64), Vec<IndexedPiece>>,
+    // Pieces too wide to index by cell, checked against every query.
+    wide: Vec<IndexedPiece>,
+}
 
-    pub fn new(pieces: Vec<Piece>) -> AOCResult<Self> {
+impl FootprintIndex {
+
+    fn is_wide(footprint: ((i64, i64), (i64, i64))) -> bool {
+        let ((x1, x2), (y1, y2)) = footprint;
+        (x2 - x1 + 1).saturating_mul(y2 - y1 + 1) > MAX_PIECE_CELLS
+    }
+
+    fn cells_for(footprint: ((i64, i64), (i64, i64))) -> impl Iterator<Item = (i64, i64)> {
+        let ((x1, x2), (y1, y2)) = footprint;
+        (y1 ..= y2).flat_map(move |y| (x1 ..= x2).map(move |x| (y, x)))
+    }
 
-        let mut _pieces: HashMap<i32, Piece> = HashMap::new();
-        for p in pieces {
-            _pieces.insert(p.id, p);
+    pub fn insert(&mut self, piece: &Piece) {
+        let footprint = piece.get_footprint();
+        let indexed = IndexedPiece { id: piece.id, footprint, high_z: piece.get_high_z() };
+        if Self::is_wide(footprint) {
+            self.wide.push(indexed);
+            return;
+        }
+        for cell in Self::cells_for(footprint) {
+            self.cells.entry(cell).or_default().push(indexed.clone());
         }
+    }
 
-        let mut _self = Self { pieces: _pieces, space_matrix: Vec::new() };
-        _self.intialize_space_matrix()?;
-        Ok(_self)
+    pub fn remove(&mut self, piece: &Piece) {
+        if Self::is_wide(piece.get_footprint()) {
+            self.wide.retain(|ip| ip.id != piece.id);
+            return;
+        }
+        for cell in Self::cells_for(piece.get_footprint()) {
+            if let Some(indexed) = self.cells.get_mut(&cell) {
+                indexed.retain(|ip| ip.id != piece.id);
+            }
+        }
     }
 
-    fn intialize_space_matrix(&mut self) -> AOCResult<()> {
+    // Call after a piece moves up or down.
+    pub fn update_high_z(&mut self, piece: &Piece) {
+        if Self::is_wide(piece.get_footprint()) {
+            for ip in self.wide.iter_mut().filter(|ip| ip.id == piece.id) {
+                ip.high_z = piece.get_high_z();
+            }
+            return;
+        }
+        for cell in Self::cells_for(piece.get_footprint()) {
+            if let Some(indexed) = self.cells.get_mut(&cell) {
+                for ip in indexed.iter_mut().filter(|ip| ip.id == piece.id) {
+                    ip.high_z = piece.get_high_z();
+                }
+            }
+        }
+    }
 
-        let max_z = self.pieces
-            .values()
-            .flat_map(|p| vec![p.start.z, p.end.z])
-            .max()
-            .ok_or_else(|| AOCError::ProcessingError(format!("Invalid pieces.")))?;
+    // The (id, high z) of other pieces whose footprint overlaps this one. A
+    // piece covering more than one cell can show up more than once. A wide
+    // piece looks through every cell rather than walking its own.
+    pub fn overlapping<'a>(&'a self, piece: &Piece) -> Box<dyn Iterator<Item = (i32, i64)> + 'a> {
+        let id = piece.id;
+        let footprint = piece.get_footprint();
+        let candidates: Box<dyn Iterator<Item = &IndexedPiece> + 'a> = if Self::is_wide(footprint) {
+            Box::new(self.cells.values().flatten())
+        }
+        else {
+            Box::new(Self::cells_for(footprint).filter_map(|cell| self.cells.get(&cell)).flatten())
+        };
+        Box::new(candidates
+            .chain(self.wide.iter())
+            .filter(move |ip| ip.id != id && ip.overlaps(footprint))
+            .map(|ip| (ip.id, ip.high_z)))
+    }
+}
 
-        let max_y = self.pieces
-            .values()
-            .flat_map(|p| vec![p.start.y, p.end.y])
-            .max()
-            .ok_or_else(|| AOCError::ProcessingError(format!("Invalid pieces.")))?;
+pub const GROUND_ID: i32 = -2;
 
-        let max_x = self.pieces
-            .values()
-            .flat_map(|p| vec![p.start.x, p.end.x])
-            .max()
-            .ok_or_else(|| AOCError::ProcessingError(format!("Invalid pieces.")))?;
+// Pieces are kept in id order, so going through them, and anything that
+// depends on the order they're gone through in, is the same every run. A
+// disintegrated piece leaves its slot empty so the rest don't move.
+#[derive(Debug, Clone)]
+pub struct Pieces {
+    pieces: Vec<Option<Piece>>,
 
-        // matrix will be y, x, z
-        // Initialize the empty space
+    // Piece id to its slot in pieces.
+    index_of: HashMap<i32, usize>,
 
-        for _y in 0 ..= max_y {
-            let mut plane: Vec<Vec<i32>> = Vec::new();
-            for _x in 0 ..= max_x {
-                plane.push(vec![EMPTY_PIECE_ID; max_z as usize + 1]);
-            }
-            self.space_matrix.push(plane);
+    count: usize,
+
+    footprint_index: FootprintIndex,
+}
+
+impl Pieces {
+
+    pub fn new(pieces: Vec<Piece>) -> AOCResult<Self> {
+        let _self = Self::indexed(pieces);
+        if let Some((id1, id2)) = _self.find_overlap() {
+            return Err(AOCError::ProcessingError(format!("Pieces {} and {} are in the same space.", id1, id2)));
         }
+        Ok(_self)
+    }
 
-        // Set space ids
+    fn indexed(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_by_key(|p| p.id);
 
-        let space_matrix = &mut self.space_matrix;
+        let mut footprint_index = FootprintIndex::default();
+        for p in &pieces {
+            footprint_index.insert(p);
+        }
 
-        for piece in self.pieces.values() {
-            for pos in piece.position_iter() {
-                if space_matrix[pos.y as usize][pos.x as usize][pos.z as usize] != EMPTY_PIECE_ID {
-                    return Err(AOCError::ProcessingError(format!("Too many things in a space.")));
-                }
-                else {
-                    space_matrix[pos.y as usize][pos.x as usize][pos.z as usize] = piece.id;
-                }
-            }
+        let index_of: HashMap<i32, usize> = pieces
+            .iter()
+            .enumerate()
+            .map(|(index, p)| (p.id, index))
+            .collect();
+
+        Self {
+            count: pieces.len(),
+            pieces: pieces.into_iter().map(Some).collect(),
+            index_of,
+            footprint_index,
         }
+    }
 
-        Ok(())
+    // In id order.
+    pub fn iter(&self) -> impl Iterator<Item = &Piece> {
+        self.pieces.iter().flatten()
+    }
+
+    pub fn get(&self, piece_id: i32) -> Option<&Piece> {
+        self.index_of.get(&piece_id).and_then(|index| self.pieces[*index].as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // The first two pieces, by id, that start out in the same place. Pieces
+    // whose footprints overlap are found through the footprint index and
+    // then only their heights need comparing, so no cells are walked and the
+    // coordinates can be as large as they like.
+    fn find_overlap(&self) -> Option<(i32, i32)> {
+        self.iter()
+            .filter_map(|piece| {
+                self.footprint_index
+                    .overlapping(piece)
+                    .filter(|(id, _)| *id > piece.id)
+                    .filter_map(|(id, _)| self.get(id))
+                    .filter(|other| other.get_low_z() <= piece.get_high_z() && piece.get_low_z() <= other.get_high_z())
+                    .map(|other| other.id)
+                    .min()
+                    .map(|other_id| (piece.id, other_id))
+            })
+            .next()
     }
 
     pub fn disintegrate(&mut self, piece_id: i32) {
-        if let Some(p) = self.pieces.remove(&piece_id) {
-            for pos in p.position_iter() {
-                self.space_matrix[pos.y as usize][pos.x as usize][pos.z as usize] = EMPTY_PIECE_ID;
-            }
+        let removed = self.index_of.get(&piece_id).and_then(|index| self.pieces[*index].take());
+        if let Some(p) = removed {
+            self.footprint_index.remove(&p);
+            self.count -= 1;
         }
     }
 
-    // Utility for inspecting the space matrix
+    // Utility for inspecting the occupied space
     #[allow(dead_code)]
-    pub fn get_space_matrix_debug_info(&self) -> String {
+    pub fn get_space_debug_info(&self) -> String {
         let mut output = String::new();
 
-        let mut fill_count = 0;
-
-        for z in (0 .. self.space_matrix[0][0].len()).rev() {
-            output.push_str(format!("Layer: {}", z).as_str());
-            output.push('\n');
-            for y in 0 .. self.space_matrix.len() {
-                for x in 0 .. self.space_matrix[0].len() {
-                    let p_id = self.space_matrix[y][x][z];
-                    if p_id != EMPTY_PIECE_ID {
-                        output.push_str(format!("  * [{},{}] -> {}", y, x, p_id).as_str());
-                        output.push('\n');
-                        fill_count += 1;
-                    }
-                }
+        let mut cells: Vec<(Position, i32)> = self
+            .iter()
+            .flat_map(|p| p.position_iter().map(|pos| (pos, p.id)))
+            .collect();
+        cells.sort_by_key(|(pos, _)| (-pos.z, pos.y, pos.x));
+
+        let mut layer = None;
+        for (pos, p_id) in &cells {
+            if layer != Some(pos.z) {
+                output.push_str(format!("Layer: {}", pos.z).as_str());
+                output.push('\n');
+                layer = Some(pos.z);
             }
+            output.push_str(format!("  * [{},{}] -> {}", pos.y, pos.x, p_id).as_str());
+            output.push('\n');
         }
 
-        output.push_str(format!("Fill Count: {}", fill_count).as_str());
+        output.push_str(format!("Fill Count: {}", cells.len()).as_str());
         output.push('\n');
 
         output
@@ -245,7 +377,7 @@ impl Pieces {
         let mut disintegratable: Vec<i32> = Vec::new();
 
         // See if any are not solely supporting
-        for p in self.pieces.values() {
+        for p in self.iter() {
             let mut support_count = 0;
 
             for (_, supporting_ids) in &held_by {
@@ -264,27 +396,37 @@ impl Pieces {
         disintegratable
     }
 
-    fn get_held_by(&self) -> HashMap<i32, HashSet<i32>> {
+    // Which pieces rest on which, for the pieces as they are now, so they
+    // need to have been lowered first.
+    pub fn support_graph(&self) -> SupportGraph {
+        SupportGraph::new(self)
+    }
+
+    // How many other pieces would fall if this one were disintegrated. For
+    // more than one piece, build the support graph once and ask it instead.
+    pub fn count_chain_reaction(&self, piece_id: i32) -> AOCResult<usize> {
+        self.support_graph().count_chain_reaction(piece_id)
+    }
+
+    fn get_held_by(&self) -> BTreeMap<i32, HashSet<i32>> {
         // Start by building up maps to know for each piece what is holding it up.
         // This could be the ground.
 
-        let mut held_by: HashMap<i32, HashSet<i32>> = HashMap::new();
+        let mut held_by: BTreeMap<i32, HashSet<i32>> = BTreeMap::new();
 
-        for p in self.pieces.values() {
-            let mut p_held_by: HashSet<i32> = HashSet::new();
+        for p in self.iter() {
+            let low_z = p.get_low_z();
 
-            for pos in p.position_iter() {
-                if pos.z == 1 {
-                    p_held_by.clear();
-                    p_held_by.insert(GROUND_ID);
-                }
-                else {
-                    let other_id = self.space_matrix[pos.y as usize][pos.x as usize][(pos.z - 1) as usize];
-                    if other_id != p.id && other_id != EMPTY_PIECE_ID {
-                        p_held_by.insert(other_id);
-                    }
-                }
+            let p_held_by: HashSet<i32> = if low_z == 1 {
+                HashSet::from([GROUND_ID])
             }
+            else {
+                self.footprint_index
+                    .overlapping(p)
+                    .filter(|(_, high_z)| *high_z == low_z - 1)
+                    .map(|(id, _)| id)
+                    .collect()
+            };
 
             held_by.insert(p.id, p_held_by);
         }
@@ -293,12 +435,19 @@ impl Pieces {
     }
 
     pub fn lower(&mut self) -> i32 {
+        // Nothing is drawn, so there's nothing to fail.
+        self.lower_animated(&mut Animation::off()).unwrap_or(0)
+    }
+
+    // With a frame after each piece that might fall. The frames stay as tall
+    // as the stack was to begin with.
+    pub fn lower_animated(&mut self, animation: &mut Animation) -> AOCResult<i32> {
+        let top = self.top();
         let mut potential_lowerable_pieces = self
-            .pieces
-            .values()
+            .iter()
             .filter(|p| p.get_low_z() > 1)
             .map(|p| (p.get_low_z(), p.id))
-            .collect::<Vec<(i32, i32)>>();
+            .collect::<Vec<(i64, i32)>>();
 
         potential_lowerable_pieces
             .sort_by_key(|(low_z, _p_id)| *low_z);
@@ -309,97 +458,281 @@ impl Pieces {
             if self.lower_piece(*id) {
                 lower_count += 1;
             }
+            animation.frame(|| self.side_view(top))?;
         }
 
-        return lower_count;
+        animation.finish(|| self.side_view(top))?;
+        Ok(lower_count)
     }
 
-    fn lower_piece(&mut self, piece_id: i32) -> bool {
-        let p = self.pieces.get(&piece_id).unwrap();
-
-        let yx_lows = p.get_yx_lows();
-
-        let mut z_deltas: Vec<i32> = Vec::new();
+    fn top(&self) -> i64 {
+        self.iter().map(|p| p.get_high_z()).max().unwrap_or(0)
+    }
 
-        // Figure out what can lower.
-        for ((y, x), low_z) in yx_lows {
+    // Looking along y at x and z on the left, and along x at y and z on the
+    // right, with the ground at the bottom. Each cell shows the nearest piece.
+    pub fn side_view(&self, top: i64) -> AOCResult<Picture> {
+        let max_x = self.iter().map(|p| p.start.x.max(p.end.x)).max().unwrap_or(0);
+        let max_y = self.iter().map(|p| p.start.y.max(p.end.y)).max().unwrap_or(0);
+        let (x_width, y_width) = (max_x as usize + 1, max_y as usize + 1);
+        let height = top.max(0) as usize;
 
-            let mut z_delta = 0;
-            let z_col = &self.space_matrix[y as usize][x as usize];
+        let mut picture = Picture::new(x_width + 1 + y_width, height);
+        // The depth of what's drawn in each cell so far.
+        let mut nearest: Vec<Option<i64>> = vec![None; picture.width() * height];
 
-            while low_z - z_delta > 1 {
-                let next_space_p_id = z_col[(low_z - z_delta - 1) as usize];
-                if next_space_p_id != EMPTY_PIECE_ID {
-                    break;
+        for piece in self.iter() {
+            let color = piece_color(piece.id);
+            for pos in piece.position_iter() {
+                if pos.z < 1 || pos.z > top {
+                    continue;
                 }
-                else {
-                    z_delta += 1;
+                let row = (top - pos.z) as usize;
+                for (column, depth) in [(pos.x as usize, pos.y), (x_width + 1 + pos.y as usize, pos.x)] {
+                    let at = row * picture.width() + column;
+                    if nearest[at].is_none_or(|d| depth < d) {
+                        nearest[at] = Some(depth);
+                        picture.set_cell(row, column, color);
+                    }
                 }
             }
-
-            z_deltas.push(z_delta);
         }
 
-        // Get the min of z deltas
-        match z_deltas.iter().min() {
-            Some(z_delta) if *z_delta > 0 => {
-                self.move_piece_down(piece_id, *z_delta);
-                true
-            },
-            _ => {
-                false
-            }
+        Ok(picture)
+    }
+
+    // Pieces are lowered from the bottom up, so everything under this piece
+    // has already settled and it comes to rest on the highest of them.
+    fn lower_piece(&mut self, piece_id: i32) -> bool {
+        let p = self.get(piece_id).unwrap();
+        let low_z = p.get_low_z();
+
+        let rest_z = self.footprint_index
+            .overlapping(p)
+            .map(|(_, high_z)| high_z)
+            .filter(|high_z| *high_z < low_z)
+            .max()
+            .map_or(1, |high_z| high_z + 1);
+
+        let z_delta = low_z - rest_z;
+        if z_delta > 0 {
+            self.move_piece_down(piece_id, z_delta);
+            true
+        }
+        else {
+            false
         }
     }
 
-    fn move_piece_down(&mut self, piece_id: i32, z_delta: i32) {
+    fn move_piece_down(&mut self, piece_id: i32, z_delta: i64) {
         if z_delta <= 0 {
             return;
         }
 
-        let p = &mut self.pieces.get_mut(&piece_id).unwrap();
-
-        // Move the space ids in the space matrix first
-        for pos in p.position_iter() {
-            let new_z = pos.z - z_delta;
+        let index = self.index_of[&piece_id];
+        let p = self.pieces[index].as_mut().unwrap();
 
-            let prev_id = self.space_matrix[pos.y as usize][pos.x as usize][new_z as usize];
-
-            self.space_matrix[pos.y as usize][pos.x as usize][new_z as usize] = p.id;
-
-            // This swap is to account for not caring about order of moving an object down.
-            // This method assumes the move has been validated.
-            self.space_matrix[pos.y as usize][pos.x as usize][pos.z as usize] = prev_id;
-        }
-
-        // Mutate the piece itself too
         p.start.z -= z_delta;
         p.end.z -= z_delta;
+
+        self.footprint_index.update_high_z(p);
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> {
-        let reader = BufReader::new(File::open(input)?);
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
+        let reader = input.open()?;
         let mut pieces: Vec<Piece> = Vec::new();
+        // The line each piece came from, by id.
+        let mut line_numbers: Vec<usize> = Vec::new();
 
-        for line in reader.lines() {
+        for (line_index, line) in reader.lines().enumerate() {
             let line = line?;
             let line = line.trim();
-            if line.len() > 0 {
-                let mut piece = Piece::parse(line)?;
-                piece.id = pieces.len() as i32 + 1;
-                pieces.push(piece);
+
+            // Blank lines and # comments are allowed so generated inputs can be annotated.
+            if line.is_empty() || line.starts_with('#') {
+                continue;
             }
 
+            let mut piece = Piece::parse(line)
+                .and_then(|piece| piece.validate().map(|_| piece))
+                .map_err(|e| match e {
+                    AOCError::ParseError(reason) => AOCError::ParseError(format!("line {}: {}", line_index + 1, reason)),
+                    e => e,
+                })?;
+            piece.id = pieces.len() as i32 + 1;
+            pieces.push(piece);
+            line_numbers.push(line_index + 1);
         }
 
-        Self::new(pieces)
+        let _self = Self::indexed(pieces);
+        if let Some((id1, id2)) = _self.find_overlap() {
+            return Err(AOCError::ParseError(format!("lines {} and {}: pieces are in the same space.",
+                line_numbers[id1 as usize - 1], line_numbers[id2 as usize - 1])));
+        }
+        Ok(_self)
     }
 
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+impl Explorable for Pieces {
+
+    fn summary(&self) -> String {
+        format!("{} pieces, the highest reaching z {}.", self.len(), self.top())
+    }
+
+    fn commands(&self) -> Vec<ExploreCommand> {
+        vec![
+            ExploreCommand::new("piece", "<id>", "Where a piece is, what holds it up and what it holds up"),
+            ExploreCommand::new("settle", "", "Let every piece fall as far as it can"),
+            ExploreCommand::new("remove", "<id>", "How many pieces would fall if this one went, leaving it in place"),
+            ExploreCommand::new("layer", "<z>", "Draw the pieces at a height, looking down with x across"),
+        ]
+    }
+
+    fn run_command(&mut self, name: &str, args: &[&str]) -> AOCResult<String> {
+        match name {
+            "piece" => {
+                let id: i32 = explore::arg(args, 0, "id")?;
+                let piece = self.get(id).ok_or_else(|| AOCError::ProcessingError(format!("No piece with id: {}", id)))?;
+                let held_by = self.get_held_by();
+
+                let mut under: Vec<i32> = held_by.get(&id).map(|ids| ids.iter().copied().collect()).unwrap_or_default();
+                under.sort();
+                let under: Vec<String> = under
+                    .iter()
+                    .map(|id| if *id == GROUND_ID { "ground".to_string() } else { id.to_string() })
+                    .collect();
+                let over: Vec<i32> = held_by
+                    .iter()
+                    .filter(|(_, ids)| ids.contains(&id))
+                    .map(|(over, _)| *over)
+                    .collect();
+
+                Ok(format!("{},{},{}~{},{},{}, held up by {}, holding up {:?}",
+                    piece.start.x, piece.start.y, piece.start.z, piece.end.x, piece.end.y, piece.end.z,
+                    under.join(" "), over))
+            },
+            "settle" => {
+                let lowered = self.lower();
+                Ok(format!("Pieces that fell: {}. {}", lowered, self.summary()))
+            },
+            "remove" => {
+                let id: i32 = explore::arg(args, 0, "id")?;
+                if self.get(id).is_none() {
+                    return Err(AOCError::ProcessingError(format!("No piece with id: {}", id)));
+                }
+                let mut without = self.clone();
+                without.disintegrate(id);
+                Ok(format!("Pieces that would fall: {}", without.lower()))
+            },
+            "layer" => {
+                let z: i64 = explore::arg(args, 0, "z")?;
+                let max_x = self.iter().map(|p| p.start.x.max(p.end.x)).max().unwrap_or(0);
+                let max_y = self.iter().map(|p| p.start.y.max(p.end.y)).max().unwrap_or(0);
+                let mut cells: HashMap<(i64, i64), i32> = HashMap::new();
+                for piece in self.iter() {
+                    for pos in piece.position_iter().filter(|pos| pos.z == z) {
+                        cells.insert((pos.x, pos.y), piece.id);
+                    }
+                }
+
+                let mut output = String::new();
+                for y in 0 ..= max_y {
+                    for x in 0 ..= max_x {
+                        output.push(if cells.contains_key(&(x, y)) { '#' } else { '.' });
+                    }
+                    output.push('\n');
+                }
+                let mut ids: Vec<i32> = cells.into_values().collect();
+                ids.sort();
+                ids.dedup();
+                output.push_str(&format!("Pieces: {:?}", ids));
+                Ok(output)
+            },
+            _ => Err(explore::unknown_command(name)),
+        }
+    }
+}
+
+// Which pieces rest on which. There's a node for each piece in id order and
+// an edge from each piece to every piece resting on it. Pieces on the ground
+// rest on nothing else, since there can't be a piece under them.
+//
+// Disintegrating a piece makes everything resting only on falling pieces fall
+// too. Counting how many of the pieces under each one have fallen finds them
+// without having to go through the pieces bottom up.
+pub struct SupportGraph {
+    graph: Graph<i32>,
+    node_of: HashMap<i32, usize>,
+    // How many pieces each one rests on.
+    resting_on: Vec<usize>,
+}
+
+impl SupportGraph {
+
+    pub fn new(pieces: &Pieces) -> Self {
+        let mut graph: Graph<i32> = Graph::new();
+        let mut node_of: HashMap<i32, usize> = HashMap::new();
+        for piece in pieces.iter() {
+            node_of.insert(piece.id, graph.add_node(piece.id));
+        }
+
+        for (id, held_by) in pieces.get_held_by() {
+            for under in held_by.iter().filter(|under| **under != GROUND_ID) {
+                graph.add_edge(node_of[under], node_of[&id]);
+            }
+        }
+
+        let resting_on = graph.in_degrees();
+        Self { graph, node_of, resting_on }
+    }
+
+    // How many other pieces fall if this one is disintegrated.
+    pub fn count_chain_reaction(&self, piece_id: i32) -> AOCResult<usize> {
+        let start = *self.node_of
+            .get(&piece_id)
+            .ok_or_else(|| AOCError::ProcessingError(format!("No piece with id: {}", piece_id)))?;
+
+        // Pieces with some of what they rest on gone, and how much.
+        let mut fallen_under: HashMap<usize, usize> = HashMap::new();
+        let mut falling: Vec<usize> = vec![start];
+        let mut count = 0;
+
+        while let Some(node) = falling.pop() {
+            for &over in self.graph.successors(node) {
+                let fallen = fallen_under.entry(over).or_default();
+                *fallen += 1;
+                if *fallen == self.resting_on[over] {
+                    count += 1;
+                    falling.push(over);
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    pub fn piece_count(&self) -> usize {
+        self.graph.node_count()
+    }
+}
+
+// Neighbouring ids get quite different colours so pieces stand apart.
+fn piece_color(id: i32) -> Color {
+    let fraction = (id.unsigned_abs() * 37 % 101) as f64 / 100.0;
+    Color::blend(visualize::ACCENT, visualize::WARM, fraction)
+}
+
+impl Visualizable for Pieces {
+    fn picture(&self) -> AOCResult<Picture> {
+        self.side_view(self.top())
+    }
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let mut pieces = Pieces::parse(input)?;
-    pieces.lower();
+    let mut animation = Animation::new("problem22", Some(pieces.len() as u64));
+    pieces.lower_animated(&mut animation)?;
 
     let disentegratable = pieces.get_disintegratable();
     let result = disentegratable.len();
@@ -407,18 +740,59 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+// Disintegrates each piece from a copy of the settled pieces and lowers the
+// rest again.
+fn chain_reaction_simulated(pieces: &Pieces, piece_id: i32) -> usize {
+    let mut pieces_new = pieces.clone();
+    pieces_new.disintegrate(piece_id);
+    pieces_new.lower() as usize
+}
+
+fn total_chain_reactions_simulated(pieces: &Pieces) -> AOCResult<usize> {
+    let mut total_affect_count: usize = 0;
+    let progress = Progress::new("disintegrating", Some(pieces.len() as u64));
+
+    for piece in pieces.iter() {
+        total_affect_count += chain_reaction_simulated(pieces, piece.id);
+        progress.add_with(1, || format!("total {}", total_affect_count));
+    }
+
+    Ok(total_affect_count)
+}
+
+// Works each piece out from the support graph. With --verify every piece is
+// simulated too and they have to agree.
+fn total_chain_reactions_graph(pieces: &Pieces) -> AOCResult<usize> {
+    let support_graph = pieces.support_graph();
+    let mut total_affect_count: usize = 0;
+
+    for piece in pieces.iter() {
+        let count = support_graph.count_chain_reaction(piece.id)?;
+
+        if settings::verify() {
+            let simulated = chain_reaction_simulated(pieces, piece.id);
+            if simulated != count {
+                return Err(AOCError::ProcessingError(format!(
+                    "Support graph has {} pieces falling without {}, simulating has {}.", count, piece.id, simulated)));
+            }
+        }
+
+        total_affect_count += count;
+    }
+
+    Ok(total_affect_count)
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let mut pieces = Pieces::parse(input)?;
     pieces.lower();
 
-    let mut total_affect_count: i32 = 0;
+    let result = Strategies::new("problem22")
+        .add("graph", total_chain_reactions_graph)
+        .add("simulate", total_chain_reactions_simulated)
+        .run(&pieces, |_| "graph")?;
 
-    for piece in pieces.pieces.values() {
-        let mut pieces_new = pieces.clone();
-        pieces_new.disintegrate(piece.id);
-        let lower_count = pieces_new.lower();
-        total_affect_count += lower_count;
-    }
+    Ok(result.to_string())
+}
 
-    Ok(total_affect_count.to_string())
-}
\ No newline at end of file
+crate::register_day!(day: 22, title: "Sand Slabs", parts: [1 => part1 (example = "5"), 2 => part2 (example = "7")]);
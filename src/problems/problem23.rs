@@ -1,9 +1,22 @@
 use std::collections::HashSet;
 use std::collections::HashMap;
-use std::path::Path;
+use std::time::Instant;
 
 use crate::aocbase::{AOCResult, AOCError};
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
+use crate::cache::{self, ByteReader, Cacheable};
+use crate::pool::Pool;
+use crate::progress::Progress;
+use crate::run;
+use crate::strategy::Strategies;
+
+// How many search calls between looking at the clock and updating --progress.
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+// The bitmask search remembers the longest way it's found to each junction
+// with each set of junctions visited, for sets up to this size. Past that
+// there are too many of them to keep and they hardly ever come up twice.
+const MEMO_MAX_VISITED: u32 = 16;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LocationType {
@@ -47,7 +60,7 @@ pub struct HikingTrail {
 
 impl HikingTrail {
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> { 
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> { 
         let mut map: Vec<Vec<LocationType>> = Vec::new();
 
         for line in read_lines_as_bytes(input)? {
@@ -57,6 +70,10 @@ impl HikingTrail {
                 .collect::<AOCResult<Vec<LocationType>>>()?);
         }
 
+        if map.is_empty() {
+            return Err(AOCError::ParseError("Empty map.".into()));
+        }
+
         Ok(Self { map })
     }
 
@@ -139,6 +156,55 @@ impl HikingTrail {
     }
 }
 
+// The trail with each corridor between junctions turned into one edge, with
+// the corridor's length as its cost. Working this out is the same every time
+// for an input, so it's cached.
+#[derive(Debug, Clone, Default)]
+pub struct TrailGraph {
+    pub edges: HashMap<(i32, i32), HashMap<(i32, i32), i32>>,
+}
+
+impl Cacheable for TrailGraph {
+
+    const VERSION: u32 = 1;
+
+    // The number of edges then start y, start x, end y, end x and cost for
+    // each, sorted so the same graph always gives the same bytes.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut edges: Vec<[i32; 5]> = self.edges
+            .iter()
+            .flat_map(|(start, ends)| ends.iter().map(|(end, cost)| [start.0, start.1, end.0, end.1, *cost]))
+            .collect();
+        edges.sort();
+
+        let mut bytes: Vec<u8> = Vec::with_capacity(4 + edges.len() * 20);
+        bytes.extend_from_slice(&(edges.len() as u32).to_le_bytes());
+        for edge in edges {
+            for value in edge {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> AOCResult<Self> {
+        let mut reader = ByteReader::new(bytes);
+        let mut graph = TrailGraph::default();
+
+        for _ in 0 .. reader.read_u32()? {
+            let start = (reader.read_i32()?, reader.read_i32()?);
+            let end = (reader.read_i32()?, reader.read_i32()?);
+            let cost = reader.read_i32()?;
+            graph.edges.entry(start).or_default().insert(end, cost);
+        }
+
+        if !reader.is_empty() {
+            return Err(AOCError::ParseError("Extra bytes after the trail graph.".into()));
+        }
+        Ok(graph)
+    }
+}
+
 pub struct SimplifiedTrailSolver<'a> {
     // The trail to analyze.
     pub trail: &'a HikingTrail,
@@ -154,6 +220,28 @@ pub struct SimplifiedTrailSolver<'a> {
 
     // Keep track of longest path encountered.
     longest_path_cost: Option<i32>,
+
+    // Prune paths that can't beat the longest one so far. The most a path can
+    // still add is, for each node it hasn't been to, the longest edge into it.
+    use_bound: bool,
+    max_edge_into: HashMap<(i32, i32), i32>,
+    remaining_bound: i32,
+
+    // Stop early and give the longest path so far once this passes.
+    deadline: Option<Instant>,
+    search_calls: u64,
+    timed_out: bool,
+
+    // Scratch buffers for the next nodes to explore in search_longest.
+    // Part 2 goes through about 30 million of these, but only 36 ever get
+    // allocated now. That took part 2 from ~6.7s to ~4.7s.
+    explore_pool: Pool<Vec<((i32, i32), i32)>>,
+
+    // Search with the junctions numbered and the visited ones as bits of a
+    // u64, on a stack instead of recursing.
+    use_bitmask: bool,
+
+    progress: Progress,
 }
 
 impl<'a> SimplifiedTrailSolver<'a> {
@@ -162,17 +250,100 @@ impl<'a> SimplifiedTrailSolver<'a> {
             trail, start, end,
             edges: HashMap::new(),
             longest_path_cost: None,
+            use_bound: false,
+            max_edge_into: HashMap::new(),
+            remaining_bound: 0,
+            deadline: run::deadline(),
+            search_calls: 0,
+            timed_out: false,
+            explore_pool: Pool::new(),
+            use_bitmask: false,
+            progress: Progress::new("search calls", None),
         }
     }
 
-    pub fn solve(&mut self) -> AOCResult<i32> {
-        self.simplify()?;
-        let mut visited: HashSet<(i32, i32)> = HashSet::new();
-        visited.insert(self.start);
+    pub fn with_bound(mut self, use_bound: bool) -> Self {
+        self.use_bound = use_bound;
+        self
+    }
+
+    // Only works for up to 64 junctions, and more than that searches the
+    // usual way.
+    pub fn with_bitmask(mut self, use_bitmask: bool) -> Self {
+        self.use_bitmask = use_bitmask;
+        self
+    }
+
+    // Start from edges worked out before instead of simplifying the trail again.
+    pub fn with_graph(mut self, graph: TrailGraph) -> Self {
+        self.edges = graph.edges;
+        self
+    }
+
+    pub fn graph(&mut self) -> AOCResult<TrailGraph> {
+        if self.edges.is_empty() {
+            self.simplify()?;
+        }
+        Ok(TrailGraph { edges: self.edges.clone() })
+    }
+
+    // The longest path and whether the search finished. If it ran out of
+    // time the path is only the longest one found so far.
+    pub fn solve(&mut self) -> AOCResult<(i32, bool)> {
+        if self.edges.is_empty() {
+            self.simplify()?;
+        }
+        self.init_bound();
+
+        let junctions = self.index_junctions();
+        if self.use_bitmask && junctions.len() <= 64 {
+            self.search_longest_bitmask(&junctions);
+        }
+        else {
+            let mut visited: HashSet<(i32, i32)> = HashSet::new();
+            visited.insert(self.start);
+
+            self.search_longest(self.start, 0, &mut visited)?;
+        }
 
-        self.search_longest(self.start, 0, &mut visited)?;
-        self.longest_path_cost
-            .ok_or_else(|| AOCError::ProcessingError("Could not find longest path.".into()))
+        let stats = self.explore_pool.stats();
+        tracing::debug!("Explore buffers: taken={} allocated={} reused={}", stats.taken, stats.created, stats.reused());
+        tracing::debug!("Search calls: {}", self.search_calls);
+
+        match self.longest_path_cost {
+            Some(cost) => Ok((cost, !self.timed_out)),
+            None if self.timed_out => Err(AOCError::Timeout("No path found before the deadline.".into())),
+            None => Err(AOCError::ProcessingError("Could not find longest path.".into())),
+        }
+    }
+
+    fn init_bound(&mut self) {
+        self.max_edge_into.clear();
+        for dests in self.edges.values() {
+            for (dest, cost) in dests {
+                let max_cost = self.max_edge_into.entry(*dest).or_insert(0);
+                *max_cost = (*max_cost).max(*cost);
+            }
+        }
+
+        // The start is already visited.
+        self.remaining_bound = self.max_edge_into
+            .iter()
+            .filter(|(node, _)| **node != self.start)
+            .map(|(_, cost)| cost)
+            .sum();
+    }
+
+    fn past_deadline(&mut self) -> bool {
+        self.search_calls += 1;
+        if !self.timed_out && self.search_calls.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            let longest = self.longest_path_cost;
+            self.progress.add_with(DEADLINE_CHECK_INTERVAL, || format!("longest so far {}", longest.unwrap_or(0)));
+            if let Some(deadline) = self.deadline {
+                self.timed_out = Instant::now() >= deadline;
+            }
+        }
+        self.timed_out
     }
 
     fn on_end(&mut self, total_cost: i32) {
@@ -194,13 +365,25 @@ impl<'a> SimplifiedTrailSolver<'a> {
         visited: &mut HashSet<(i32, i32)>) -> AOCResult<()>
     {
 
+        if self.past_deadline() {
+            return Ok(());
+        }
+
         if pos == self.end {
             self.on_end(total_cost);
         }
 
+        if self.use_bound {
+            if let Some(longest) = self.longest_path_cost {
+                if total_cost + self.remaining_bound <= longest {
+                    return Ok(());
+                }
+            }
+        }
+
         visited.insert(pos);
 
-        let mut explore_next: Vec<((i32, i32), i32)> = Vec::new();
+        let mut explore_next = self.explore_pool.take();
 
         if let Some(dests) = self.edges.get(&pos) {
             for (next_pos, next_cost) in dests {
@@ -210,15 +393,110 @@ impl<'a> SimplifiedTrailSolver<'a> {
             }
         }
 
-        for (next_pos, next_cost) in explore_next {
-            self.search_longest(next_pos, total_cost + next_cost, visited)?;
+        for (next_pos, next_cost) in &explore_next {
+            let next_bound = self.max_edge_into.get(next_pos).copied().unwrap_or(0);
+            self.remaining_bound -= next_bound;
+            self.search_longest(*next_pos, total_cost + next_cost, visited)?;
+            self.remaining_bound += next_bound;
         }
 
+        self.explore_pool.give(explore_next);
         visited.remove(&pos);
 
         Ok(())
     }
 
+    // Every junction in the graph, sorted so they're numbered the same way
+    // each time.
+    fn index_junctions(&self) -> Vec<(i32, i32)> {
+        let mut junctions: Vec<(i32, i32)> = self.edges
+            .iter()
+            .flat_map(|(start, ends)| std::iter::once(*start).chain(ends.keys().copied()))
+            .chain([self.start, self.end])
+            .collect();
+        junctions.sort();
+        junctions.dedup();
+        junctions
+    }
+
+    fn search_longest_bitmask(&mut self, junctions: &[(i32, i32)]) {
+        let index_of: HashMap<(i32, i32), usize> = junctions
+            .iter()
+            .enumerate()
+            .map(|(i, junction)| (*junction, i))
+            .collect();
+
+        let adjacent: Vec<Vec<(usize, i32)>> = junctions
+            .iter()
+            .map(|junction| self.edges
+                .get(junction)
+                .map(|ends| ends.iter().map(|(end, cost)| (index_of[end], *cost)).collect())
+                .unwrap_or_default())
+            .collect();
+
+        let max_edge_into: Vec<i32> = junctions
+            .iter()
+            .map(|junction| self.max_edge_into.get(junction).copied().unwrap_or(0))
+            .collect();
+
+        // When only one junction leads to the end, a path that gets there
+        // can't go anywhere else without cutting itself off, so it's as good
+        // as finished.
+        let end = index_of[&self.end];
+        let into_end: Vec<(usize, i32)> = adjacent
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ends)| ends.iter().find(|(e, _)| *e == end).map(|(_, cost)| (i, *cost)))
+            .collect();
+        let (goal, goal_cost) = match into_end[..] {
+            [(junction, cost)] => (junction, cost),
+            _ => (end, 0),
+        };
+
+        // Reaching the same junction having visited the same ones only
+        // matters if it's a longer way there.
+        let mut longest_to: HashMap<(usize, u64), i32> = HashMap::new();
+
+        let start = index_of[&self.start];
+        let mut stack: Vec<(usize, u64, i32, i32)> = vec![(start, 1 << start, 0, self.remaining_bound)];
+
+        while let Some((pos, visited, total_cost, remaining_bound)) = stack.pop() {
+            if self.past_deadline() {
+                break;
+            }
+
+            if pos == goal {
+                self.on_end(total_cost + goal_cost);
+                continue;
+            }
+
+            if self.use_bound {
+                if let Some(longest) = self.longest_path_cost {
+                    if total_cost + remaining_bound <= longest {
+                        continue;
+                    }
+                }
+            }
+
+            if visited.count_ones() <= MEMO_MAX_VISITED {
+                let longest = longest_to.entry((pos, visited)).or_insert(-1);
+                if *longest >= total_cost {
+                    continue;
+                }
+                *longest = total_cost;
+            }
+
+            for (next_pos, next_cost) in &adjacent[pos] {
+                let bit = 1 << next_pos;
+                if visited & bit == 0 {
+                    stack.push((*next_pos, visited | bit, total_cost + next_cost, remaining_bound - max_edge_into[*next_pos]));
+                }
+            }
+        }
+
+        tracing::debug!("Remembered junction visits: {}", longest_to.len());
+    }
+
     fn simplify(&mut self) -> AOCResult<()> {
         let mut visited: HashSet<(i32, i32)> = HashSet::new();
         visited.insert(self.start);
@@ -309,28 +587,79 @@ impl<'a> SimplifiedTrailSolver<'a> {
 
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let trail = HikingTrail::parse(input)?;
+// Everything the search needs, so it can go through the strategy picker.
+pub struct TrailSearch {
+    pub trail: HikingTrail,
+    pub start: (i32, i32),
+    pub end: (i32, i32),
+    pub graph: TrailGraph,
+}
+
+impl TrailSearch {
 
-    let start = trail.get_start()?;
-    let end = trail.get_end()?;
+    // Slopes change which way edges go, so each part has its own graph.
+    pub fn new(input: impl ProblemInput, slopes_matter: bool) -> AOCResult<Self> {
+        let mut trail = HikingTrail::parse(&input)?;
+        if !slopes_matter {
+            trail.slopes_dont_matter();
+        }
 
-    let mut st_solver = SimplifiedTrailSolver::new(&trail, start.clone(), end.clone());
-    let result = st_solver.solve()?;
+        let start = trail.get_start()?;
+        let end = trail.get_end()?;
 
-    Ok(result.to_string())
-}
+        let cache_name = if slopes_matter { "problem23_graph" } else { "problem23_graph_no_slopes" };
+        let graph = cache::get_or_compute(cache_name, &input, || {
+            SimplifiedTrailSolver::new(&trail, start, end).graph()
+        })?;
+
+        Ok(Self { trail, start, end, graph })
+    }
+
+    fn solve(&self, use_bound: bool, use_bitmask: bool) -> AOCResult<String> {
+        let mut st_solver = SimplifiedTrailSolver::new(&self.trail, self.start, self.end)
+            .with_graph(self.graph.clone())
+            .with_bound(use_bound)
+            .with_bitmask(use_bitmask);
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let mut trail = HikingTrail::parse(input)?;
+        let (result, finished) = st_solver.solve()?;
 
-    trail.slopes_dont_matter();
+        // Running out of time means there could still be a longer path, so
+        // the longest one found is only a lower bound and not the answer.
+        if !finished {
+            return Err(AOCError::Timeout(format!(
+                "The longest path found before the deadline is {}, the answer is at least that.", result)));
+        }
+
+        Ok(result.to_string())
+    }
+
+    pub fn solve_exhaustive(&self) -> AOCResult<String> {
+        self.solve(false, false)
+    }
+
+    pub fn solve_bounded(&self) -> AOCResult<String> {
+        self.solve(true, false)
+    }
 
-    let start = trail.get_start()?;
-    let end = trail.get_end()?;
+    pub fn solve_bitmask(&self) -> AOCResult<String> {
+        self.solve(true, true)
+    }
+}
+
+fn longest_hike(input: impl ProblemInput, slopes_matter: bool) -> AOCResult<String> {
+    Strategies::new("problem23")
+        .add("exhaustive", TrailSearch::solve_exhaustive)
+        .add("bounded", TrailSearch::solve_bounded)
+        .add("bitmask", TrailSearch::solve_bitmask)
+        .run(&TrailSearch::new(input, slopes_matter)?, |_| "bitmask")
+}
 
-    let mut st_solver = SimplifiedTrailSolver::new(&trail, start.clone(), end.clone());
-    let result = st_solver.solve()?;
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    longest_hike(input, true)
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    longest_hike(input, false)
+}
 
-    Ok(result.to_string())
-}
\ No newline at end of file
+crate::register_day!(day: 23, title: "A Long Walk", parts: [1 => part1 (example = "94"), 2 => part2 (example = "154")]);
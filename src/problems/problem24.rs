@@ -1,22 +1,21 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
-use std::io::prelude::*;
+use std::collections::HashSet;
+use std::fs;
 use std::num::ParseFloatError;
-use std::path::Path;
 
-use bigdecimal::FromPrimitive;
+use bigdecimal::num_bigint::BigInt;
 use bigdecimal::ToPrimitive;
-use bigdecimal::Zero;
 use lazy_static::lazy_static;
 use regex::Regex;
-use bigdecimal::BigDecimal;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::answer::Answer;
+use crate::aocio::{artifact_path, each_line_with_policy, ProblemInput};
+use crate::geometry::Vec2;
+use crate::linalg::{self, Rational};
+use crate::settings;
 
 lazy_static! {
     static ref HAIL_BALL_REGEX: Regex = Regex::new(r"[\s,@]+").unwrap();
-    static ref NEAR_ZERO: BigDecimal = BigDecimal::from_f64(0.000001).unwrap();
 }
 
 #[allow(dead_code)]
@@ -49,56 +48,130 @@ impl HailBall {
         })
     }
 
-    pub fn parse_all(input: impl AsRef<Path>) -> AOCResult<Vec<HailBall>> {
-        let reader = BufReader::new(File::open(input.as_ref())?);
-        
+    pub fn parse_all(input: impl ProblemInput) -> AOCResult<Vec<HailBall>> {
         let mut hail_balls: Vec<HailBall> = Vec::new();
 
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            hail_balls.push(Self::parse(line)?);
-        }
+        each_line_with_policy(input, settings::parse_policy(), |line| {
+            hail_balls.push(Self::parse(line.trim())?);
+            Ok(())
+        })?;
 
         Ok(hail_balls)
     }
 
-    pub fn xy_intersect(&self, other: &HailBall) -> Option<(f64, f64, f64, f64)> {
+    pub fn xy_position(&self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
 
-        // linear equation
-        //   y = mx + b
-        // 
-        // m = yv/xv
-        // b = y - mx
-        //
+    pub fn xy_velocity(&self) -> Vec2 {
+        Vec2::new(self.xv, self.yv)
+    }
 
-        let m1 = self.yv / self.xv;
-        let m2 = other.yv / other.xv;
+    // Same question as xy_crossing plus the window and future checks, but
+    // done exactly with integers so near parallel paths with huge coordinates
+    // can't get misjudged. The inputs are whole numbers well under 2^53 so
+    // they come through f64 unchanged.
+    //
+    // Solving p1 + t1 * v1 = p2 + t2 * v2 with d = p2 - p1 gives
+    //   t1 = cross(d, v2) / cross(v1, v2)
+    //   t2 = cross(d, v1) / cross(v1, v2)
+    // and everything is compared after multiplying through by the denominator.
+    pub fn xy_crosses_within(&self, other: &HailBall, start: i128, end: i128) -> bool {
+        let (x1, y1, xv1, yv1) = (self.x as i128, self.y as i128, self.xv as i128, self.yv as i128);
+        let (x2, y2, xv2, yv2) = (other.x as i128, other.y as i128, other.xv as i128, other.yv as i128);
+
+        let mut denom = xv1 * yv2 - yv1 * xv2;
+
+        // Parallel paths never cross.
+        if denom == 0 {
+            return false;
+        }
 
-        let b1 = self.y - m1 * self.x;
-        let b2 = other.y - m2 * other.x;
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let mut t1_num = dx * yv2 - dy * xv2;
+        let mut t2_num = dx * yv1 - dy * xv1;
 
-        // m1 * X + b1 = m2 * X + b2
-        // m1 * X = m2 * X + b2 - b1
-        // m1 * X - m2 * X = b2 - b1
-        // X (m1 - m2) = b2 - b1
-        // X = (b2 - b1) / (m1 - m2)
+        // Keep the denominator positive so the inequalities don't flip.
+        if denom < 0 {
+            denom = -denom;
+            t1_num = -t1_num;
+            t2_num = -t2_num;
+        }
 
-        if m1 == m2 {
-            return None;
+        if t1_num < 0 || t2_num < 0 {
+            return false;
         }
 
-        let x = (b2 - b1) / (m1 - m2);
-        let y = m1 * x + b1;
+        // Crossing point scaled up by the denominator.
+        let x_scaled = x1 * denom + t1_num * xv1;
+        let y_scaled = y1 * denom + t1_num * yv1;
 
-        // Figure out time
-        // Xn = Xi + t*xv
-        // (Xn - Xi) / xv = t
+        let start_scaled = start * denom;
+        let end_scaled = end * denom;
 
-        let t1 = (x - self.x) / self.xv;
-        let t2 = (x - other.x) / other.xv;
+        x_scaled >= start_scaled && x_scaled <= end_scaled &&
+            y_scaled >= start_scaled && y_scaled <= end_scaled
+    }
+}
 
-        Some((x, y, t1, t2))
+// Where the paths of p1 + t1 * v1 and p2 + t2 * v2 cross and at what times.
+// With d = p2 - p1
+//   t1 = cross(d, v2) / cross(v1, v2)
+//   t2 = cross(d, v1) / cross(v1, v2)
+pub fn xy_crossing(p1: Vec2, v1: Vec2, p2: Vec2, v2: Vec2) -> Option<(Vec2, f64, f64)> {
+    let denom = v1.cross(v2);
+    if denom == 0.0 {
+        return None;
+    }
+
+    let d = p2 - p1;
+    let t1 = d.cross(v2) / denom;
+    let t2 = d.cross(v1) / denom;
+
+    Some((p1 + v1 * t1, t1, t2))
+}
+
+// The float answer to xy_crosses_within when it's far enough from the edges
+// that rounding can't change it, otherwise None.
+//
+// The velocities are small so cross(v1, v2) is exact. The other crosses are
+// products of big coordinates and only good to a few ulps, so a time that is
+// nearly zero or a point nearly on the window's edge is left undecided.
+fn xy_crosses_within_quick(p1: Vec2, v1: Vec2, p2: Vec2, v2: Vec2, start: f64, end: f64) -> Option<bool> {
+    const TOLERANCE: f64 = 1e-9;
+
+    let denom = v1.cross(v2);
+    if denom == 0.0 {
+        return Some(false);
+    }
+
+    let d = p2 - p1;
+    let sign = denom.signum();
+    let t1_num = d.cross(v2) * sign;
+    let t2_num = d.cross(v1) * sign;
+
+    let d_size = d.x().abs() + d.y().abs();
+    let t1_tolerance = TOLERANCE * d_size * (v2.x().abs() + v2.y().abs());
+    let t2_tolerance = TOLERANCE * d_size * (v1.x().abs() + v1.y().abs());
+
+    if t1_num < -t1_tolerance || t2_num < -t2_tolerance {
+        return Some(false);
+    }
+    if t1_num <= t1_tolerance || t2_num <= t2_tolerance {
+        return None;
+    }
+
+    let point = p1 + v1 * (t1_num / denom.abs());
+    let margin = TOLERANCE * (point.x().abs().max(point.y().abs()) + end - start);
+
+    if !point.within(start - margin, end + margin) {
+        Some(false)
+    }
+    else if point.within(start + margin, end - margin) {
+        Some(true)
+    }
+    else {
+        None
     }
 }
 
@@ -109,22 +182,20 @@ pub fn get_future_xy_crossings<'a>(
 {
     let mut crossings: Vec<(&'a HailBall, &'a HailBall)> = Vec::new();
 
+    let paths: Vec<(Vec2, Vec2)> = hail_balls
+        .iter()
+        .map(|hb| (hb.xy_position(), hb.xy_velocity()))
+        .collect();
+
     for i1 in 0 .. hail_balls.len() - 1 {
+        let (p1, v1) = paths[i1];
         for i2 in i1+1 .. hail_balls.len() {
-            let hb1 = &hail_balls[i1];
-            let hb2 = &hail_balls[i2];
-
-            match hb1.xy_intersect(&hb2) {
-                Some((x, y, t1, t2)) => {
-                    if x >= test_start && x <= test_end &&
-                        y >= test_start && y <= test_end &&
-                        t1 >= 0.0 &&
-                        t2 >= 0.0
-                    {
-                        crossings.push((hb1, hb2));
-                    }
-                },
-                None => {}
+            let (p2, v2) = paths[i2];
+
+            if let Some((point, t1, t2)) = xy_crossing(p1, v1, p2, v2) {
+                if point.within(test_start, test_end) && t1 >= 0.0 && t2 >= 0.0 {
+                    crossings.push((&hail_balls[i1], &hail_balls[i2]));
+                }
             }
         }
     }
@@ -132,6 +203,161 @@ pub fn get_future_xy_crossings<'a>(
     crossings
 }
 
+// The future times a path is inside the window, if it ever is.
+fn window_times(p: Vec2, v: Vec2, start: f64, end: f64) -> Option<(f64, f64)> {
+    // Times the path is within the window on one axis.
+    let axis_times = |p: f64, v: f64| -> Option<(f64, f64)> {
+        if v == 0.0 {
+            (p >= start && p <= end).then_some((f64::NEG_INFINITY, f64::INFINITY))
+        }
+        else {
+            let (t_a, t_b) = ((start - p) / v, (end - p) / v);
+            Some((t_a.min(t_b), t_a.max(t_b)))
+        }
+    };
+
+    let (tx_lo, tx_hi) = axis_times(p.x(), v.x())?;
+    let (ty_lo, ty_hi) = axis_times(p.y(), v.y())?;
+
+    let t_lo = tx_lo.max(ty_lo).max(0.0);
+    let t_hi = tx_hi.min(ty_hi);
+    (t_lo <= t_hi).then_some((t_lo, t_hi))
+}
+
+// The part of a future path that's inside the test window, as the x and y
+// ranges it covers. Two paths can only cross in the window if these overlap.
+// The window is padded a little so rounding never drops a pair the exact
+// check would have kept.
+#[derive(Debug, Clone, Copy)]
+struct WindowSpan {
+    index: usize,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+}
+
+impl WindowSpan {
+
+    fn new(index: usize, p: Vec2, v: Vec2, start: f64, end: f64) -> Option<Self> {
+        let margin = 1e-9 * (end - start) + 1.0;
+        let (t_lo, t_hi) = window_times(p, v, start - margin, end + margin)?;
+
+        let (enter, exit) = (p + v * t_lo, p + v * t_hi);
+
+        Some(Self {
+            index,
+            x_range: (enter.x().min(exit.x()) - margin, enter.x().max(exit.x()) + margin),
+            y_range: (enter.y().min(exit.y()) - margin, enter.y().max(exit.y()) + margin),
+        })
+    }
+
+    fn y_overlaps(&self, other: &WindowSpan) -> bool {
+        self.y_range.0 <= other.y_range.1 && other.y_range.0 <= self.y_range.1
+    }
+}
+
+// Pairs of paths that might cross in the window. The spans are sorted by
+// where they enter on x and swept, so each one is only compared with the
+// spans that start before it ends. Paths that never enter the window in the
+// future drop out completely. Pairs come back as (lower index, higher index).
+fn get_window_candidates(paths: &[(Vec2, Vec2)], start: f64, end: f64) -> Vec<(usize, usize)> {
+    let mut spans: Vec<WindowSpan> = paths
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (p, v))| WindowSpan::new(index, *p, *v, start, end))
+        .collect();
+
+    spans.sort_by(|a, b| a.x_range.0.total_cmp(&b.x_range.0));
+
+    let mut candidates: Vec<(usize, usize)> = Vec::new();
+
+    for (i, span) in spans.iter().enumerate() {
+        for other in spans[i+1 ..].iter().take_while(|other| other.x_range.0 <= span.x_range.1) {
+            if span.y_overlaps(other) {
+                candidates.push((span.index.min(other.index), span.index.max(other.index)));
+            }
+        }
+    }
+
+    candidates
+}
+
+// Only pairs whose spans in the window overlap get looked at. Most of those
+// are settled by the float check and only the close calls are worked out
+// exactly.
+pub fn get_future_xy_crossings_exact<'a>(
+    hail_balls: &'a [HailBall],
+    test_start: i128,
+    test_end: i128) -> Vec<(&'a HailBall, &'a HailBall)>
+{
+    let mut crossings: Vec<(&'a HailBall, &'a HailBall)> = Vec::new();
+
+    let paths: Vec<(Vec2, Vec2)> = hail_balls
+        .iter()
+        .map(|hb| (hb.xy_position(), hb.xy_velocity()))
+        .collect();
+
+    let (start, end) = (test_start as f64, test_end as f64);
+
+    let mut candidates = get_window_candidates(&paths, start, end);
+    candidates.sort();
+
+    let all_pairs = hail_balls.len() * hail_balls.len().saturating_sub(1) / 2;
+    tracing::debug!("Checking {} of {} pairs after window pruning", candidates.len(), all_pairs);
+
+    for (i1, i2) in candidates {
+        let (p1, v1) = paths[i1];
+        let (p2, v2) = paths[i2];
+        let hb1 = &hail_balls[i1];
+        let hb2 = &hail_balls[i2];
+
+        let crosses = xy_crosses_within_quick(p1, v1, p2, v2, start, end)
+            .unwrap_or_else(|| hb1.xy_crosses_within(hb2, test_start, test_end));
+
+        if crosses {
+            crossings.push((hb1, hb2));
+        }
+    }
+
+    crossings
+}
+
+// Runs the float version too and complains about any pairs the two disagree
+// on, even when they find as many crossings as each other.
+fn verify_xy_crossings(
+    hail_balls: &Vec<HailBall>,
+    exact_crossings: &[(&HailBall, &HailBall)],
+    test_start: i128,
+    test_end: i128) -> AOCResult<()>
+{
+    let float_crossings = get_future_xy_crossings(hail_balls, test_start as f64, test_end as f64);
+
+    let pair_key = |c: &(&HailBall, &HailBall)| (c.0 as *const HailBall, c.1 as *const HailBall);
+    let float_pairs: HashSet<_> = float_crossings.iter().map(pair_key).collect();
+    let exact_pairs: HashSet<_> = exact_crossings.iter().map(pair_key).collect();
+
+    let mut differences = 0;
+    for crossing in &float_crossings {
+        if !exact_pairs.contains(&pair_key(crossing)) {
+            tracing::warn!("Only float found crossing: {:?}", crossing);
+            differences += 1;
+        }
+    }
+    for crossing in exact_crossings {
+        if !float_pairs.contains(&pair_key(crossing)) {
+            tracing::warn!("Only exact found crossing: {:?}", crossing);
+            differences += 1;
+        }
+    }
+
+    if differences > 0 {
+        return Err(AOCError::ProcessingError(format!(
+            "Verify failed: exact and float disagree on {} crossings (exact found {}, float found {})",
+            differences, exact_crossings.len(), float_crossings.len())));
+    }
+
+    Ok(())
+}
+
 pub struct HailBallIntersectSolverLR<'a> {
     hail_balls: &'a Vec<HailBall>,
     full_combinations: bool,
@@ -242,175 +468,249 @@ impl<'a> HailBallIntersectSolverLR<'a> {
     }
 
     pub fn solve(&self) -> AOCResult<HailBall> {
-        //
-        // I am bummed that I had to move this to BigDecimal.
-        // the precision was not working right for f64 and BigDecimal is
-        // so much slower. I know I could optimize out to 1/3 by not resolving
-        // for each variable on it's own, but I think finding a nother method
-        // that using multiplication ellimnation for a systme of equations would
-        // be better. I had orginally tried using linear regression and found
-        // it wasn't correct either. The answer was close, but I didn't know that
-        // at the time. I only determined what is really going on by plugging
-        // into Wolfram Alpha some intermediate equations to see the precise answer.
-        // I think I could move this to integer arithemetic if I really wanted too.
-        //
-        let matrix = self.build_equation_matrix();
-
-        let x  = self.solve_variable(&matrix, "x", "r")?.to_f64().unwrap().round();
-        let xv = self.solve_variable(&matrix, "xv", "r")?.to_f64().unwrap().round();
-        let y  = self.solve_variable(&matrix, "y", "r")?.to_f64().unwrap().round();
-        let yv = self.solve_variable(&matrix, "yv", "r")?.to_f64().unwrap().round();
-
-        // Get time of x hit
-        // t = (x - b1.x) / (b1.xv - xv)
-        
-        let b1 = &self.hail_balls[0];
-        let b2 = &self.hail_balls[1];
-        
-        let t1 = (x - b1.x) / (b1.xv - xv);
-        let t2 = (x - b2.x) / (b2.xv - xv);
-        
-        let z1 = b1.z + b1.zv * t1;
-        let z2 = b2.z + b2.zv * t2;
-        
-        let zv = (z2 - z1) / (t2 - t1);
-
-        // z + zv * t1 = z1
-        // z = z1 - zv * t1
-        let z = z1 - zv * t1;
-
-        Ok(HailBall{ x, y, z, xv, yv, zv })
-    }
-
-    fn solve_variable(&self, matrix: &HashMap<String, Vec<BigDecimal>>, solve_var: &str, result_var: &str)
-        -> AOCResult<BigDecimal>
-    {
-        let mut reduced_matrix = matrix.clone();
-        
-        for k in matrix.keys() {
-            if k != solve_var && k != result_var {
-                reduced_matrix = self.elliminate_variable(&reduced_matrix, k);
+        let [x, y, z, xv, yv, zv] = self.solve_exact()?.map(|n| n.to_f64().unwrap_or(f64::NAN));
+        Ok(HailBall { x, y, z, xv, yv, zv })
+    }
+
+    // The rock's x, y, z, xv, yv and zv. This used to be BigDecimal with the
+    // answers for each pair of equations averaged and rounded, which was
+    // close but not always right. Now the equations are solved as fractions
+    // and every one of them has to agree, so there's nothing to round.
+    pub fn solve_exact(&self) -> AOCResult<[BigInt; 6]> {
+        let xy = linalg::solve(self.build_equation_matrix()?, 4)?;
+        let (x, y, xv, yv) = (&xy[0], &xy[1], &xy[2], &xy[3]);
+
+        // With x and y known each ball's hit time is too, which leaves
+        // z + t * zv = bz + t * bzv for z and zv.
+        let mut rows: Vec<Vec<Rational>> = Vec::new();
+        for b in self.hail_balls {
+            let [bx, by, bxv, byv] = [b.x, b.y, b.xv, b.yv].map(|n| exact(n).map(Rational::from));
+            let (bx, by, bxv, byv) = (bx?, by?, bxv?, byv?);
+            let t = if &bxv != xv {
+                (x - &bx).checked_div(&(&bxv - xv))?
+            }
+            else if &byv != yv {
+                (y - &by).checked_div(&(&byv - yv))?
             }
+            else {
+                continue;
+            };
+
+            let (bz, bzv) = (Rational::from(exact(b.z)?), Rational::from(exact(b.zv)?));
+            let hit_z = &bz + &(&t * &bzv);
+            rows.push(vec![Rational::from(1), t, hit_z]);
         }
+        let z_zv = linalg::solve(rows, 2)?;
+
+        let whole = |n: &Rational| n.to_integer().ok_or_else(|| {
+            AOCError::ProcessingError(format!("The rock isn't thrown from whole numbers: {}", n))
+        });
+        Ok([whole(x)?, whole(y)?, whole(&z_zv[0])?, whole(xv)?, whole(yv)?, whole(&z_zv[1])?])
+    }
 
-        let values = reduced_matrix[solve_var]
-            .iter()
-            .zip(&reduced_matrix[result_var])
-            .filter(|(var_val, result_val)| &var_val.abs() >= &NEAR_ZERO && *result_val >= &NEAR_ZERO)
-            .map(|(var_val, result_val)| result_val / var_val)
-            .collect::<Vec<BigDecimal>>();
+    // A row per pair of balls for x, y, xv and yv, with the right hand side
+    // last, from the equations above.
+    fn build_equation_matrix(&self) -> AOCResult<Vec<Vec<Rational>>> {
+        let mut rows: Vec<Vec<Rational>> = Vec::new();
+
+        for i in 0 .. self.hail_balls.len().saturating_sub(1) {
+            let b1 = &self.hail_balls[i];
 
-        if values.len() == 0 {
-            return Err(AOCError::ProcessingError(format!("Unable to solve for: {}", solve_var)));
+            // We can generate more combinations if data is low.
+            let end_iter = if self.full_combinations { self.hail_balls.len() } else { i + 2};
+
+            for j in i+1 .. end_iter {
+                let b2 = &self.hail_balls[j];
+
+                rows.push(vec![
+                    Rational::from(exact(-b1.yv + b2.yv)?),
+                    Rational::from(exact(b1.xv - b2.xv)?),
+                    Rational::from(exact(b1.y - b2.y)?),
+                    Rational::from(exact(-b1.x + b2.x)?),
+                    Rational::from(
+                        -exact(b1.x)? * exact(b1.yv)? +
+                        exact(b1.y)? * exact(b1.xv)? +
+                        exact(b2.x)? * exact(b2.yv)? -
+                        exact(b2.y)? * exact(b2.xv)?),
+                ]);
+            }
         }
 
-        let values_len: BigDecimal = (values.len() as i64).try_into().unwrap();
-        let result: BigDecimal = values.iter().fold(BigDecimal::zero(), |a, b| a + b) / values_len;
+        Ok(rows)
+    }
+}
 
-        Ok(result)
+// Hail balls are parsed as f64, which holds whole numbers exactly up to 2^53.
+fn exact(n: f64) -> AOCResult<BigInt> {
+    if n.fract() != 0.0 || n.abs() >= 2f64.powi(53) {
+        return Err(AOCError::ProcessingError(format!("Not a whole number that can be solved exactly: {}", n)));
     }
+    Ok(BigInt::from(n as i64))
+}
 
-    fn elliminate_variable(&self, matrix: &HashMap<String, Vec<BigDecimal>>, var_name: &str)
-        -> HashMap<String, Vec<BigDecimal>>
-    {
-        // Use the multiplication method to get rid of a uknown for 2 equations equalling each other.
-        let keys = matrix.keys().filter(|k| *k != var_name).collect::<Vec<&String>>();
-        let len = matrix[var_name].len();
+const PLOT_SIZE: f64 = 1000.0;
 
-        let mut new_matrix: HashMap<String, Vec<BigDecimal>> = HashMap::new();
-        for k in &keys {
-            new_matrix.insert((*k).into(), Vec::new());
-        }
+// Draws into a PLOT_SIZE square showing the given bounds, with y going up
+// like the puzzle's coordinates instead of down like SVG's.
+struct SvgPlot {
+    min: Vec2,
+    scale: f64,
+    body: String,
+}
 
-        for i in 0 .. len - 1 {
-            // left_var * m = right_var;
-            // left_var = right_var / m
-            // m = right_var / left_var
+impl SvgPlot {
 
-            let left_var = &matrix[var_name][i];
+    fn new(min: Vec2, max: Vec2) -> Self {
+        let extent = (max.x() - min.x()).max(max.y() - min.y()).max(1.0);
+        Self { min, scale: PLOT_SIZE / extent, body: String::new() }
+    }
 
-            // We can generate more combinations if data is low.
-            let end_iter = if self.full_combinations { len } else { i + 2};
+    fn to_view(&self, p: Vec2) -> (f64, f64) {
+        ((p.x() - self.min.x()) * self.scale, PLOT_SIZE - (p.y() - self.min.y()) * self.scale)
+    }
 
-            for j in i+1 .. end_iter {
-                let right_var = &matrix[var_name][j];
-    
-                if &left_var.abs() <= &NEAR_ZERO || &right_var.abs() <= &NEAR_ZERO {
-                    continue;
-                }
-    
-                let multiplier = right_var / left_var;
-    
-                for k in &keys {
-                    let l_val = &matrix[*k][i];
-                    let r_val = &matrix[*k][j];
-                    let new_val = l_val - r_val / &multiplier;
-                    new_matrix.get_mut(*k).unwrap().push(new_val);
-                }
-            }
-        }
+    fn line(&mut self, a: Vec2, b: Vec2, class: &str) {
+        let ((x1, y1), (x2, y2)) = (self.to_view(a), self.to_view(b));
+        self.body.push_str(&format!(
+            "  <line class=\"{}\" x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>\n",
+            class, x1, y1, x2, y2));
+    }
 
-        new_matrix
+    fn point(&mut self, p: Vec2, class: &str) {
+        let (x, y) = self.to_view(p);
+        self.body.push_str(&format!("  <circle class=\"{}\" cx=\"{:.2}\" cy=\"{:.2}\" r=\"2\"/>\n", class, x, y));
     }
 
-    fn build_equation_matrix(&self) -> HashMap<String, Vec<BigDecimal>> {
-        let mut matrix: HashMap<String, Vec<BigDecimal>> = HashMap::new();
+    fn finish(self) -> String {
+        let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\">\n", PLOT_SIZE, PLOT_SIZE);
+        svg.push_str(concat!(
+            "  <style>\n",
+            "    line { stroke: #bbb; stroke-width: 0.5; }\n",
+            "    line.crossing-path { stroke: #47a; }\n",
+            "    line.rock { stroke: #c33; stroke-width: 2; }\n",
+            "    circle { fill: #c33; }\n",
+            "  </style>\n"));
+        svg.push_str(&self.body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
 
-        let mut x_vec: Vec<BigDecimal> = Vec::new();
-        let mut y_vec: Vec<BigDecimal> = Vec::new();
-        let mut xv_vec: Vec<BigDecimal> = Vec::new();
-        let mut yv_vec: Vec<BigDecimal> = Vec::new();
-        let mut r_vec: Vec<BigDecimal> = Vec::new();
+// Set problem24.export=svg to draw the paths seen from above into the
+// artifacts directory. Part 1 draws the test window with the paths that
+// cross in it and where, part 2 the rock's path through every hailstone.
+fn export_requested() -> AOCResult<bool> {
+    match settings::params().get("problem24.export") {
+        None => Ok(false),
+        Some("svg") => Ok(true),
+        Some(format) => Err(AOCError::ProcessingError(format!("Unknown export format for problem24: {}", format))),
+    }
+}
 
-        for i in 0 .. self.hail_balls.len() - 1 {
-            let b1 = &self.hail_balls[i];
+fn write_plot(file_name: &str, svg: String) -> AOCResult<()> {
+    let path = artifact_path(file_name)?;
+    fs::write(&path, svg)?;
+    tracing::debug!("Wrote: {}", path.display());
+    Ok(())
+}
 
-            // We can generate more combinations if data is low.
-            let end_iter = if self.full_combinations { self.hail_balls.len() } else { i + 2};
+fn plot_window(hail_balls: &[HailBall], crossings: &[(&HailBall, &HailBall)], start: f64, end: f64) -> String {
+    let mut plot = SvgPlot::new(Vec2::new(start, start), Vec2::new(end, end));
 
-            for j in i+1 .. end_iter {
-                let b2 = &self.hail_balls[j];
+    let crossing_paths: HashSet<*const HailBall> = crossings
+        .iter()
+        .flat_map(|(hb1, hb2)| [*hb1 as *const HailBall, *hb2 as *const HailBall])
+        .collect();
 
-                x_vec.push((-b1.yv + b2.yv).try_into().unwrap());
-                y_vec.push((b1.xv - b2.xv).try_into().unwrap());
-                xv_vec.push((b1.y - b2.y).try_into().unwrap());
-                yv_vec.push((-b1.x + b2.x).try_into().unwrap());
-
-                r_vec.push((
-                    -b1.x * b1.yv +
-                    b1.y * b1.xv +
-                    b2.x * b2.yv -
-                    b2.y * b2.xv
-                ).try_into().unwrap());
-            }
+    for hb in hail_balls {
+        let (p, v) = (hb.xy_position(), hb.xy_velocity());
+        if let Some((t_lo, t_hi)) = window_times(p, v, start, end) {
+            let class = if crossing_paths.contains(&(hb as *const HailBall)) { "crossing-path" } else { "path" };
+            plot.line(p + v * t_lo, p + v * t_hi, class);
+        }
+    }
+
+    for (hb1, hb2) in crossings {
+        if let Some((point, _, _)) = xy_crossing(hb1.xy_position(), hb1.xy_velocity(), hb2.xy_position(), hb2.xy_velocity()) {
+            plot.point(point, "crossing");
         }
+    }
+
+    plot.finish()
+}
 
-        matrix.insert("x".into(), x_vec);
-        matrix.insert("y".into(), y_vec);
-        matrix.insert("xv".into(), xv_vec);
-        matrix.insert("yv".into(), yv_vec);
-        matrix.insert("r".into(), r_vec);
+// Each hailstone is drawn from where it starts to where the rock hits it.
+fn plot_rock(hail_balls: &[HailBall], rock: &HailBall) -> String {
+    let hits: Vec<(Vec2, Vec2, f64)> = hail_balls
+        .iter()
+        .filter_map(|hb| {
+            // Whichever axis the rock moves differently on gives the time.
+            let t = if hb.xv != rock.xv {
+                (hb.x - rock.x) / (rock.xv - hb.xv)
+            }
+            else if hb.yv != rock.yv {
+                (hb.y - rock.y) / (rock.yv - hb.yv)
+            }
+            else {
+                return None;
+            };
+            Some((hb.xy_position(), hb.xy_position() + hb.xy_velocity() * t, t))
+        })
+        .collect();
+
+    let rock_start = rock.xy_position();
+    let last_hit = hits.iter().map(|(_, _, t)| *t).fold(0.0, f64::max);
+    let rock_end = rock_start + rock.xy_velocity() * last_hit;
+
+    let points = hits.iter().flat_map(|(start, hit, _)| [*start, *hit]).chain([rock_start, rock_end]);
+    let (min, max) = points.fold(
+        (Vec2::new(f64::INFINITY, f64::INFINITY), Vec2::new(f64::NEG_INFINITY, f64::NEG_INFINITY)),
+        |(min, max), p| (Vec2::new(min.x().min(p.x()), min.y().min(p.y())), Vec2::new(max.x().max(p.x()), max.y().max(p.y()))));
 
-        matrix
+    let mut plot = SvgPlot::new(min, max);
+    for (start, hit, _) in &hits {
+        plot.line(*start, *hit, "path");
     }
+    plot.line(rock_start, rock_end, "rock");
+    for (_, hit, _) in &hits {
+        plot.point(*hit, "hit");
+    }
+
+    plot.finish()
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let hail_balls = HailBall::parse_all(input)?;
 
-    //let crossings = get_future_xy_crossings(&hail_balls, 7.0, 27.0);
-    let crossings = get_future_xy_crossings(&hail_balls, 200000000000000.0, 400000000000000.0);
+    //let (test_start, test_end) = (7, 27);
+    let (test_start, test_end) = (200000000000000, 400000000000000);
+
+    let crossings = get_future_xy_crossings_exact(&hail_balls, test_start, test_end);
+
+    if settings::verify() {
+        verify_xy_crossings(&hail_balls, &crossings, test_start, test_end)?;
+    }
+
+    if export_requested()? {
+        write_plot("problem24_part1.svg", plot_window(&hail_balls, &crossings, test_start as f64, test_end as f64))?;
+    }
 
     let result = crossings.len();
 
     Ok(result.to_string())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let hail_balls = HailBall::parse_all(input)?;
     let solver = HailBallIntersectSolverLR::new(&hail_balls, false);
-    let b = solver.solve()?;
-    let result = b.x + b.y + b.z;
+    let [x, y, z, ..] = solver.solve_exact()?;
+    let result = x + y + z;
 
-    Ok(result.to_string())
-}
\ No newline at end of file
+    if export_requested()? {
+        write_plot("problem24_part2.svg", plot_rock(&hail_balls, &solver.solve()?))?;
+    }
+
+    result.to_answer()
+}
+
+crate::register_day!(day: 24, title: "Never Tell Me The Odds", parts: [1 => part1, 2 => part2], skips_invalid: true);
@@ -1,29 +1,33 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-use std::fs::File;
-use std::io::BufReader;
 use std::io::prelude::*;
-use std::path::Path;
 
 use rand;
 use rand::seq::SliceRandom;
 
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocio::ProblemInput;
+use crate::graph::Graph;
+use crate::strategy::Strategies;
 
-#[derive(Debug, Clone)]
+// Components by name, with an edge each way for every wire between two.
+#[derive(Debug, Clone, Default)]
 pub struct ComponentGraph {
-    pub edges: HashMap<String, HashSet<String>>,
+    pub graph: Graph<String>,
 }
 
 impl ComponentGraph {
     
     pub fn new() -> Self {
-        Self { edges: HashMap::new() }
+        Self::default()
     }
 
-    pub fn load(input_file: impl AsRef<Path>) -> AOCResult<Self> {
-        let reader = BufReader::new(File::open(input_file.as_ref())?);
+    pub fn load(input: impl ProblemInput) -> AOCResult<Self> {
+        let reader = input.open()?;
         let mut graph = ComponentGraph::new();
 
         for line in reader.lines() {
@@ -50,22 +54,143 @@ impl ComponentGraph {
         Ok(graph)
     }
 
+    // Wires that are listed twice are only added once.
     pub fn add(&mut self, node1: impl AsRef<str>, node2: impl AsRef<str>) {
-        self._add_direction(node1.as_ref(), node2.as_ref());
-        self._add_direction(node2.as_ref(), node1.as_ref());
+        let id1 = self.graph.intern(node1.as_ref().to_string());
+        let id2 = self.graph.intern(node2.as_ref().to_string());
+        if !self.graph.has_edge(id1, id2) {
+            self.graph.add_edge(id1, id2);
+            self.graph.add_edge(id2, id1);
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn name(&self, id: usize) -> &String {
+        self.graph.node(id)
+    }
+
+    pub fn stats(&self) -> GraphStats {
+        let adjacent: Vec<Vec<usize>> = (0 .. self.component_count())
+            .map(|id| self.graph.successors(id).to_vec())
+            .collect();
+
+        let mut degree_histogram: BTreeMap<usize, usize> = BTreeMap::new();
+        for connected in &adjacent {
+            *degree_histogram.entry(connected.len()).or_insert(0) += 1;
+        }
+
+        let edge_count = adjacent.iter().map(|connected| connected.len()).sum::<usize>() / 2;
+
+        let mut articulation_points: Vec<String> = Self::find_articulation_points(&adjacent)
+            .into_iter()
+            .map(|id| self.name(id).clone())
+            .collect();
+        articulation_points.sort();
+
+        GraphStats {
+            node_count: self.component_count(),
+            edge_count,
+            degree_histogram,
+            articulation_points,
+        }
     }
 
-    pub fn _add_direction(&mut self, node1: &str, node2: &str) {
-        match self.edges.get_mut(node1) {
-            None => {
-                let mut node_set = HashSet::<String>::new();
-                node_set.insert(node2.to_string());
-                self.edges.insert(node1.to_string(), node_set);
-            },
-            Some(node_set) => {
-                node_set.insert(node2.to_string());
+    // Tarjan's lowlink algorithm, done with an explicit stack so big graphs
+    // don't blow the call stack.
+    fn find_articulation_points(adjacent: &[Vec<usize>]) -> Vec<usize> {
+        const NONE: usize = usize::MAX;
+
+        let mut discovered = vec![NONE; adjacent.len()];
+        let mut low = vec![NONE; adjacent.len()];
+        let mut is_articulation = vec![false; adjacent.len()];
+        let mut time = 0;
+
+        for root in 0 .. adjacent.len() {
+            if discovered[root] != NONE {
+                continue;
             }
+
+            discovered[root] = time;
+            low[root] = time;
+            time += 1;
+
+            let mut root_children = 0;
+
+            // (node, parent, next neighbor to look at)
+            let mut stack: Vec<(usize, usize, usize)> = vec![(root, NONE, 0)];
+
+            while let Some(&(node, parent, next)) = stack.last() {
+                if next < adjacent[node].len() {
+                    stack.last_mut().unwrap().2 += 1;
+                    let child = adjacent[node][next];
+
+                    if discovered[child] == NONE {
+                        discovered[child] = time;
+                        low[child] = time;
+                        time += 1;
+                        if node == root {
+                            root_children += 1;
+                        }
+                        stack.push((child, node, 0));
+                    }
+                    else if child != parent {
+                        low[node] = low[node].min(discovered[child]);
+                    }
+                }
+                else {
+                    stack.pop();
+                    if parent != NONE {
+                        low[parent] = low[parent].min(low[node]);
+                        if parent != root && low[node] >= discovered[parent] {
+                            is_articulation[parent] = true;
+                        }
+                    }
+                }
+            }
+
+            // The root only splits the graph if the search had to leave it more than once.
+            if root_children > 1 {
+                is_articulation[root] = true;
+            }
+        }
+
+        (0 .. adjacent.len()).filter(|id| is_articulation[*id]).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    // degree -> number of nodes with that degree
+    pub degree_histogram: BTreeMap<usize, usize>,
+    pub articulation_points: Vec<String>,
+}
+
+impl GraphStats {
+
+    pub fn pretty_print(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(format!("Nodes: {}\n", self.node_count).as_str());
+        out.push_str(format!("Edges: {}\n", self.edge_count).as_str());
+
+        out.push_str("Degrees:\n");
+        let max_count = self.degree_histogram.values().max().copied().unwrap_or(1);
+        for (degree, count) in &self.degree_histogram {
+            let bar = "#".repeat((count * 50).div_ceil(max_count));
+            out.push_str(format!("  {:>3}: {:>5} {}\n", degree, count, bar).as_str());
         }
+
+        out.push_str(format!("Articulation Points: {}\n", self.articulation_points.len()).as_str());
+        for node in &self.articulation_points {
+            out.push_str(format!("  * {}\n", node).as_str());
+        }
+
+        out
     }
 }
 
@@ -96,6 +221,9 @@ pub struct KargersCutSolver<'a> {
     // super nodes
     pub sgraph_edges: HashMap<i32, KCSNode<'a>>,
 
+    // Every original edge once
+    edges: Vec<(&'a String, &'a String)>,
+
     // The order of edges to remove (random)
     edge_selection_order: Vec<(&'a String, &'a String)>,
 }
@@ -107,6 +235,7 @@ impl<'a> KargersCutSolver<'a> {
             graph,
             node_map: HashMap::new(),
             sgraph_edges: HashMap::new(),
+            edges: Vec::new(),
             edge_selection_order: Vec::new(),
         }
     }
@@ -146,34 +275,68 @@ impl<'a> KargersCutSolver<'a> {
     }
 
     pub fn solve(&mut self, target_min_cut: i32, max_iterations: i32) -> AOCResult<i32> {
-        for iteration in 0 .. max_iterations {
-            self.initialize();
-            self.condense()?;
-    
-            let node = self.sgraph_edges.values().nth(0).unwrap();
-            let min_cut = node.connections.values().nth(0).unwrap().len() as i32;
+        let mut rng = rand::thread_rng();
+        let mut abandoned = 0;
+
+        self.initialize_edges();
 
-            if min_cut <= target_min_cut {
-                return Ok(iteration + 1);
+        for iteration in 0 .. max_iterations {
+            self.initialize(&mut rng);
+
+            match self.condense(target_min_cut)? {
+                Some(min_cut) if min_cut <= target_min_cut => {
+                    tracing::debug!("Abandoned {} of {} iterations early.", abandoned, iteration + 1);
+                    return Ok(iteration + 1);
+                },
+                Some(min_cut) => tracing::debug!("Iteration {}: cut of {}", iteration + 1, min_cut),
+                None => {
+                    tracing::trace!("Iteration {}: abandoned", iteration + 1);
+                    abandoned += 1;
+                },
             }
         }
 
         Err(AOCError::ProcessingError("Could not determine min cut.".into()))
     }
 
-    fn condense(&mut self) -> AOCResult<()> {
-        while self.sgraph_edges.len() > 2 {
-            self.condense_one()?;
+    // Contracts down to 2 super nodes and gives the cut between them, or
+    // None once a cut of target_min_cut or less can't come out of it.
+    fn condense(&mut self, target_min_cut: i32) -> AOCResult<Option<i32>> {
+        let mut forced: Option<(i32, i32)> = None;
+
+        while self.sgraph_edges.len() > 2 || forced.is_some() {
+            let (node_id1, node_id2) = match forced.take() {
+                Some(pair) => pair,
+                None => self
+                    .pick_random_edge()
+                    .ok_or_else(|| AOCError::ProcessingError("Not enough edges.".to_string()))?,
+            };
+
+            self.condense_one(node_id1, node_id2);
+
+            // More parallel edges than the target can't all be cut, so the
+            // two ends have to end up on the same side. Joining them right
+            // away means a hopeless iteration runs down to a single node
+            // quickly instead of being contracted all the way out.
+            forced = self.sgraph_edges[&node_id1].connections
+                .iter()
+                .find(|(_, original_edges)| original_edges.len() as i32 > target_min_cut)
+                .map(|(connected_id, _)| (node_id1, *connected_id));
+
+            if self.sgraph_edges.len() < 2 {
+                return Ok(None);
+            }
         }
 
-        Ok(())
+        Ok(self.sgraph_edges
+            .values()
+            .next()
+            .and_then(|node| node.connections.values().next())
+            .map(|original_edges| original_edges.len() as i32))
     }
 
-    fn condense_one(&mut self) -> AOCResult<()> {
-        let (node_id1, node_id2) = self
-            .pick_random_edge()
-            .ok_or_else(|| AOCError::ProcessingError(format!("Not enough edges.")))?;
-
+    // Merges node 2 into node 1.
+    fn condense_one(&mut self, node_id1: i32, node_id2: i32) {
         let mut node1 = self.sgraph_edges.remove(&node_id1).unwrap();
         let node2 = self.sgraph_edges.remove(&node_id2).unwrap();
 
@@ -225,8 +388,6 @@ impl<'a> KargersCutSolver<'a> {
         
         // Add node 1 back
         self.sgraph_edges.insert(node1.id, node1);
-
-        Ok(())
     }
 
 
@@ -241,8 +402,8 @@ impl<'a> KargersCutSolver<'a> {
         None
     }
 
-    fn initialize(&mut self) {
-        self.initialize_edge_selection();
+    fn initialize(&mut self, rng: &mut impl rand::Rng) {
+        self.initialize_edge_selection(rng);
         self.initialize_condensed_graph();
     }
 
@@ -251,50 +412,186 @@ impl<'a> KargersCutSolver<'a> {
         // Clear previous state.
         self.sgraph_edges.clear();
 
-        // Create initial super nodes
-        for (k, _) in &self.graph.edges {
-            let node_id = self.sgraph_edges.len() as i32;
+        // Create initial super nodes, numbered the same as the components.
+        let graph = &self.graph.graph;
+        for (id, name) in graph.nodes().iter().enumerate() {
+            let node_id = id as i32;
             let mut node = KCSNode::new(node_id);
-            node.add_node(k);
+            node.add_node(name);
             self.sgraph_edges.insert(node_id, node);
-            self.node_map.insert(k, node_id);
+            self.node_map.insert(name, node_id);
         }
 
         // Connect the super nodes
-        for (_, node) in self.sgraph_edges.iter_mut() {
-            let original_node_name = *node.nodes.iter().nth(0).unwrap();
-            for original_connected_name in &self.graph.edges[original_node_name] {
-                let connected_id = self.node_map[original_connected_name];
-                node.connections.insert(connected_id, vec![(original_node_name, original_connected_name)]);
+        for (node_id, node) in self.sgraph_edges.iter_mut() {
+            let id = *node_id as usize;
+            for &connected in graph.successors(id) {
+                node.connections.insert(connected as i32, vec![(graph.node(id), graph.node(connected))]);
             }
         }
     }
 
-    fn initialize_edge_selection(&mut self) {
-        let mut rng = rand::thread_rng();
-        self.edge_selection_order = Vec::new();
+    // The edge list is the same every iteration, only the order changes.
+    fn initialize_edges(&mut self) {
+        self.edges.clear();
 
-        for (node1, n1_connections) in &self.graph.edges {
-            for node2 in n1_connections {
-                if node1 < node2 {
-                    self.edge_selection_order.push((node1, node2));
+        let graph = &self.graph.graph;
+        for (id1, node1) in graph.nodes().iter().enumerate() {
+            for &id2 in graph.successors(id1) {
+                if id1 < id2 {
+                    self.edges.push((node1, graph.node(id2)));
                 }
             }
         }
+    }
 
-        self.edge_selection_order.shuffle(&mut rng);
+    fn initialize_edge_selection(&mut self, rng: &mut impl rand::Rng) {
+        self.edge_selection_order.clone_from(&self.edges);
+        self.edge_selection_order.shuffle(rng);
+    }
+}
+
+// The fewest wires the puzzle says can be cut to split the machine in two.
+const WIRES_TO_CUT: i64 = 3;
 
+// The smallest cut of a graph and the components on one side of it.
+#[derive(Debug, Clone)]
+pub struct MinCut {
+    pub weight: i64,
+    pub side: Vec<String>,
+}
+
+// Finds the global min cut without any randomness, so it never has to try
+// again. Each phase adds components one at a time, always the one most
+// connected to those added so far, and the cut around the last one added
+// is the smallest between it and the one before it. Those two are then
+// merged, and the smallest of the phases' cuts is the answer.
+//
+// Merged components keep their connections to the others as weights, so
+// the phases get quicker as there are fewer of them.
+pub struct StoerWagnerSolver {
+    names: Vec<String>,
+    adjacent: Vec<HashMap<usize, i64>>,
+}
+
+impl StoerWagnerSolver {
+
+    pub fn new(graph: &ComponentGraph) -> Self {
+        let names = graph.graph.nodes().to_vec();
+        let adjacent = (0 .. names.len())
+            .map(|id| graph.graph.successors(id).iter().map(|connected| (*connected, 1)).collect())
+            .collect();
+
+        Self { names, adjacent }
+    }
+
+    pub fn solve(&self) -> AOCResult<MinCut> {
+        let n = self.names.len();
+        if n < 2 {
+            return Err(AOCError::ProcessingError("There have to be at least 2 components to cut between.".into()));
+        }
+
+        let mut adjacent = self.adjacent.clone();
+        let mut members: Vec<Vec<usize>> = (0 .. n).map(|i| vec![i]).collect();
+        let mut active: Vec<usize> = (0 .. n).collect();
+        let mut best: Option<(i64, usize)> = None;
+        let mut best_side: Vec<usize> = Vec::new();
+
+        while active.len() > 1 {
+            let mut connection: Vec<i64> = vec![0; n];
+            let mut added: Vec<bool> = vec![false; n];
+            let mut heap: BinaryHeap<(i64, Reverse<usize>)> = BinaryHeap::new();
+            heap.push((0, Reverse(active[0])));
+
+            let mut order: Vec<usize> = Vec::with_capacity(active.len());
+            while let Some((weight, Reverse(node))) = heap.pop() {
+                if added[node] || weight != connection[node] {
+                    continue;
+                }
+                added[node] = true;
+                order.push(node);
+
+                for (next, edge_weight) in &adjacent[node] {
+                    if !added[*next] {
+                        connection[*next] += edge_weight;
+                        heap.push((connection[*next], Reverse(*next)));
+                    }
+                }
+            }
+
+            // Nothing joins what was reached to the rest, so that's a cut of 0.
+            if order.len() < active.len() {
+                let side = order.iter().flat_map(|node| members[*node].iter().copied()).collect();
+                return Ok(self.min_cut(0, side));
+            }
+
+            let (s, t) = (order[order.len() - 2], order[order.len() - 1]);
+            if best.is_none_or(|(weight, _)| connection[t] < weight) {
+                best = Some((connection[t], t));
+                best_side = members[t].clone();
+            }
+
+            // Merge t into s.
+            let t_edges = std::mem::take(&mut adjacent[t]);
+            for (next, edge_weight) in t_edges {
+                adjacent[next].remove(&t);
+                if next != s {
+                    *adjacent[s].entry(next).or_default() += edge_weight;
+                    *adjacent[next].entry(s).or_default() += edge_weight;
+                }
+            }
+            let t_members = std::mem::take(&mut members[t]);
+            members[s].extend(t_members);
+            active.retain(|node| *node != t);
+        }
+
+        let (weight, _) = best.unwrap();
+        Ok(self.min_cut(weight, best_side))
+    }
+
+    fn min_cut(&self, weight: i64, side: Vec<usize>) -> MinCut {
+        let mut side: Vec<String> = side.into_iter().map(|i| self.names[i].clone()).collect();
+        side.sort();
+        MinCut { weight, side }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.names.len()
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+fn group_product_stoer_wagner(graph: &ComponentGraph) -> AOCResult<usize> {
+    let solver = StoerWagnerSolver::new(graph);
+    let min_cut = solver.solve()?;
+    tracing::debug!("Min cut of {} wires with {} components on one side.", min_cut.weight, min_cut.side.len());
+
+    if min_cut.weight != WIRES_TO_CUT {
+        return Err(AOCError::ProcessingError(format!("The smallest cut is {} wires, not {}.", min_cut.weight, WIRES_TO_CUT)));
+    }
+    Ok(min_cut.side.len() * (solver.component_count() - min_cut.side.len()))
+}
+
+fn group_product_karger(graph: &ComponentGraph) -> AOCResult<usize> {
+    let mut solver = KargersCutSolver::new(graph);
+
+    let iteration_count = solver.solve(WIRES_TO_CUT as i32, 2000)?;
+    tracing::debug!("Took {} iterations to find result.", iteration_count);
+    tracing::trace!("Graph: {}", solver.pretty_print());
+
+    Ok(solver.get_edge_product() as usize)
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let graph = ComponentGraph::load(input)?;
-    let mut solver = KargersCutSolver::new(&graph);
 
-    let iteration_count = solver.solve(3, 2000)?;
-    println!("Took {} iterations to find result.", iteration_count);
-    //println!("Graph: {}", solver.pretty_print());
+    tracing::debug!("{}", graph.stats().pretty_print().trim_end());
+
+    let result = Strategies::new("problem25")
+        .add("stoer-wagner", group_product_stoer_wagner)
+        .add("karger", group_product_karger)
+        .run(&graph, |_| "stoer-wagner")?;
 
-    let result = solver.get_edge_product();
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 25, title: "Snowverload", parts: [1 => part1 (example = "54")]);
@@ -1,10 +1,9 @@
-use std::path::Path;
 use std::collections::HashMap;
 
 use regex::bytes::Regex as BRegex;
 
 use crate::aocbase::AOCResult;
-use crate::aocio::read_lines_as_bytes;
+use crate::aocio::{read_lines_as_bytes, ProblemInput};
 
 fn parse_i32(input: &[u8]) -> AOCResult<i32> {
     let mut n: i32 = 0;
@@ -60,7 +59,7 @@ fn find_adjacent_gears(data: &Vec<Vec<u8>>, row: usize, start: usize, end: usize
     find_adjacent(data, row, start, end, is_gear)
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let data = read_lines_as_bytes(input)?;
     let num_regex = BRegex::new(r"(\d+)")?;
     let mut result = 0;
@@ -77,7 +76,7 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
     Ok(result.to_string())
 }
 
-fn read_gear_map(input: impl AsRef<Path>) -> AOCResult<HashMap<(usize, usize), Vec<i32>>> {
+fn read_gear_map(input: impl ProblemInput) -> AOCResult<HashMap<(usize, usize), Vec<i32>>> {
     let data = read_lines_as_bytes(input)?;
     let num_regex = BRegex::new(r"(\d+)")?;
 
@@ -116,9 +115,11 @@ fn calculate_gear_ratio_sums(gear_map: &HashMap<(usize, usize), Vec<i32>>) -> i3
     result
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let gear_map = read_gear_map(input)?;
     let result = calculate_gear_ratio_sums(&gear_map);
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 3, title: "Gear Ratios", parts: [1 => part1 (example = "4361"), 2 => part2 (example = "467835")]);
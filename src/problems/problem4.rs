@@ -1,13 +1,13 @@
 use std::collections::HashSet;
 use std::cmp::min;
 use std::num::ParseIntError;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::AOCResult;
-use crate::aocio::each_line;
+use crate::aocio::{each_line_with_policy, ProblemInput};
+use crate::settings;
 use crate::regex_ext::{RegexExt, CapturesExt};
 
 lazy_static! {
@@ -58,10 +58,10 @@ impl GameCard {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let mut result = 0;
 
-    each_line(input, |line| {
+    each_line_with_policy(input, settings::parse_policy(), |line| {
         let card = GameCard::parse(line)?;
         result += card.score();
         Ok(())
@@ -71,9 +71,9 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
 }
 
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let mut games: Vec<GameCard> = Vec::new();
-    each_line(input, |line| {
+    each_line_with_policy(input, settings::parse_policy(), |line| {
         games.push(GameCard::parse(line)?);
         Ok(())
     })?;
@@ -97,4 +97,6 @@ pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
     let result: usize = card_counts.iter().sum();
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 4, title: "Scratchcards", parts: [1 => part1 (example = "13"), 2 => part2 (example = "30")], skips_invalid: true);
@@ -1,14 +1,17 @@
 use std::collections::HashMap;
+use std::fs;
 use std::num::ParseIntError;
-use std::path::Path;
 use std::cmp;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCError, AOCResult};
-use crate::aocio::each_line;
+use crate::aocio::{each_line, ProblemInput};
+use crate::log;
 use crate::regex_ext::CapturesExt;
+use crate::settings;
+use crate::strategy::Strategies;
 
 lazy_static! {
     static ref SEEDS_REGEX: Regex = Regex::new(r"^seeds: (.*)").unwrap();
@@ -78,8 +81,8 @@ impl<'a> SeedRangeMinTranslator<'a> {
                 // check for gap jump
                 if overlap_start > cur_passthrough_pos {
                     update_min(&mut cur_min, cur_passthrough_pos);
-                    cur_passthrough_pos = overlap_end;
                 }
+                cur_passthrough_pos = overlap_end;
             }
         }
 
@@ -121,6 +124,57 @@ impl HorticultureMap {
         return n;
     }
 
+    // Draws each range as a source bar over its destination bar, all on
+    // one scale so the shifts line up. Values outside any range pass
+    // through unchanged and aren't drawn, nor are empty ranges.
+    pub fn render_intervals(&self, width: usize) -> String {
+        let mut range_maps: Vec<&HorticultureRangeMap> = self.range_maps
+            .iter()
+            .filter(|range_map| range_map.length > 0)
+            .collect();
+        range_maps.sort_by_key(|range_map| range_map.source_start);
+
+        let empty_count = self.range_maps.len() - range_maps.len();
+        let mut out = format!("{} -> {} ({} ranges", self.source_type, self.destination_type, range_maps.len());
+        if empty_count > 0 {
+            out.push_str(&format!(", {} empty", empty_count));
+        }
+        out.push_str(")\n");
+
+        let lo = range_maps.iter()
+            .map(|r| cmp::min(r.source_start, r.destination_start))
+            .min();
+        let hi = range_maps.iter()
+            .map(|r| cmp::max(r.source_start, r.destination_start) + r.length)
+            .max();
+
+        let (lo, hi) = match (lo, hi) {
+            (Some(lo), Some(hi)) if hi > lo => (lo, hi),
+            _ => return out,
+        };
+
+        let bar = |start: i64, end: i64| -> String {
+            let col = |n: i64| ((n - lo) as i128 * width as i128 / (hi - lo) as i128) as usize;
+            let start_col = cmp::min(col(start), width - 1);
+            let end_col = cmp::max(cmp::min(col(end), width), start_col + 1);
+            (0..width)
+                .map(|c| if c >= start_col && c < end_col { '#' } else { '.' })
+                .collect()
+        };
+
+        for r in range_maps {
+            let delta = r.destination_start - r.source_start;
+            out.push_str(&format!("  [{}, {}) -> [{}, {}) shift {:+}\n",
+                r.source_start, r.source_start + r.length,
+                r.destination_start, r.destination_start + r.length,
+                delta));
+            out.push_str(&format!("    src |{}|\n", bar(r.source_start, r.source_start + r.length)));
+            out.push_str(&format!("    dst |{}|\n", bar(r.destination_start, r.destination_start + r.length)));
+        }
+
+        out
+    }
+
     fn flatten_range_layer(
         cur_map_range: &HorticultureRangeMap,
         next_range_maps: &Vec<&HorticultureRangeMap>,
@@ -212,7 +266,9 @@ impl HorticultureMap {
 
         let mut range_events: Vec<RangeEvent> = Vec::new();
 
-        for range_map in cur_range_maps {
+        // An empty range would start and end at the same place, and with the
+        // end sorted first it would never be left.
+        for range_map in cur_range_maps.iter().filter(|range_map| range_map.length > 0) {
             range_events.push(RangeEvent {
                 id: range_map.source_start,
                 layer: Layer::One,
@@ -220,14 +276,14 @@ impl HorticultureMap {
                 delta: -1,
             });
             range_events.push(RangeEvent {
-                id: range_map.source_start,
+                id: range_map.source_start + range_map.length,
                 layer: Layer::One,
                 event_type: EventType::End,
                 delta: -1,
             });
         }
         
-        for range_map in next_range_maps {
+        for range_map in next_range_maps.iter().filter(|range_map| range_map.length > 0) {
             range_events.push(RangeEvent {
                 id: range_map.source_start,
                 layer: Layer::Two,
@@ -235,14 +291,16 @@ impl HorticultureMap {
                 delta: range_map.destination_start - range_map.source_start,
             });
             range_events.push(RangeEvent {
-                id: range_map.source_start,
+                id: range_map.source_start + range_map.length,
                 layer: Layer::Two,
                 event_type: EventType::End,
                 delta: range_map.destination_start - range_map.source_start,
             });
         }
 
-        range_events.sort_by_key(|e| e.id);
+        // A range ends one past its last value, and ends go first so ranges
+        // that touch don't look like they overlap.
+        range_events.sort_by_key(|e| (e.id, matches!(e.event_type, EventType::Start)));
 
         let mut in_layer_1 = false;
         let mut in_layer_2 = false;
@@ -251,7 +309,7 @@ impl HorticultureMap {
 
         for e in &range_events {
 
-            if pos >= 0 {
+            if pos >= 0 && e.id > pos {
                 // Something to process
                 if !in_layer_1 && in_layer_2 {
                     let new_map = HorticultureRangeMap {
@@ -354,6 +412,38 @@ impl HorticulturePlan {
         }
     }
 
+    // The maps in the order a seed goes through them.
+    pub fn get_layers(&self) -> Vec<&HorticultureMap> {
+        let mut layers: Vec<&HorticultureMap> = Vec::new();
+        let mut cur_map = self.maps.get("seed");
+        while let Some(map) = cur_map {
+            if layers.len() >= self.maps.len() {
+                break;
+            }
+            layers.push(map);
+            cur_map = self.maps.get(map.destination_type.as_str());
+        }
+        layers
+    }
+
+    // Every layer followed by the flattened seed to location map, to see
+    // whether the combining did what it should.
+    pub fn render_diagram(&self, width: usize) -> String {
+        let mut out = String::new();
+        for layer in self.get_layers() {
+            out.push_str(&layer.render_intervals(width));
+            out.push('\n');
+        }
+        match self.get_reduced("seed", "location") {
+            None => out.push_str("No seed -> location mapping.\n"),
+            Some(combined_map) => {
+                out.push_str("Combined ");
+                out.push_str(&combined_map.render_intervals(width));
+            }
+        }
+        out
+    }
+
     pub fn get_all_values<'a>(&'a self, seed: i64) -> HashMap<&'a str, i64> {
         let mut values_map: HashMap<&'a str, i64> = HashMap::new();
         let mut cur_mapping = self.maps.get("seed");
@@ -375,7 +465,7 @@ impl HorticulturePlan {
         values_map
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
         let mut plan = HorticulturePlan::new();
         let mut maps: Vec<HorticultureMap> = Vec::new();
 
@@ -437,9 +527,8 @@ impl HorticulturePlan {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    let plan = HorticulturePlan::parse(input)?;
-
+// Walk each seed through every map.
+fn lowest_location_direct(plan: &HorticulturePlan) -> AOCResult<Option<i64>> {
     let mut location_min: Option<i64> = None;
 
     for seed in plan.seeds.iter() {
@@ -449,13 +538,66 @@ pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
         }
     }
 
+    Ok(location_min)
+}
+
+// Combine the maps into a single seed to location map first.
+fn lowest_location_flattened(plan: &HorticulturePlan) -> AOCResult<Option<i64>> {
+    Ok(plan
+        .get_reduced("seed", "location")
+        .and_then(|combined_map| plan.seeds.iter().map(|seed| combined_map.translate(*seed)).min()))
+}
+
+// Flattening costs about the same as walking a handful of seeds through, so
+// it only pays off once there are more seeds than range mappings.
+fn choose_lowest_location_strategy(plan: &HorticulturePlan) -> &'static str {
+    let range_map_count: usize = plan.maps.values().map(|map| map.range_maps.len()).sum();
+    if plan.seeds.len() > range_map_count {
+        "flattened"
+    }
+    else {
+        "direct"
+    }
+}
+
+// Shows the layers when asked: --param problem5.diagram=FILE writes them out,
+// debug logging shows them.
+fn show_diagram(plan: &HorticulturePlan) -> AOCResult<()> {
+    let params = settings::params();
+    let diagram_file = params.get("problem5.diagram");
+    if diagram_file.is_none() && !log::enabled(log::Level::Debug) {
+        return Ok(());
+    }
+
+    let width = params.get_or("problem5.diagram_width", 60usize)?;
+    if width == 0 {
+        return Err(AOCError::ProcessingError("Diagram width must be more than 0.".into()));
+    }
+
+    let diagram = plan.render_diagram(width);
+    match diagram_file {
+        Some(file) => fs::write(file, diagram)?,
+        None => tracing::debug!("{}", diagram.trim_end()),
+    }
+    Ok(())
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let plan = HorticulturePlan::parse(input)?;
+    show_diagram(&plan)?;
+
+    let location_min = Strategies::new("problem5::part1")
+        .add("direct", lowest_location_direct)
+        .add("flattened", lowest_location_flattened)
+        .run(&plan, choose_lowest_location_strategy)?;
+
     Ok(match location_min {
         None => "".into(),
         Some(min) => min.to_string()
     })
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let plan = HorticulturePlan::parse(input)?;
 
     let mut location_min: Option<i64> = None;
@@ -474,3 +616,5 @@ pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
         Some(min) => min.to_string()
     })
 }
+
+crate::register_day!(day: 5, title: "If You Give A Seed A Fertilizer", parts: [1 => part1 (example = "35"), 2 => part2 (example = "46")]);
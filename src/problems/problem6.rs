@@ -1,8 +1,11 @@
 use std::num::ParseIntError;
-use std::path::Path;
 
 use crate::aocbase::{AOCError, AOCResult};
-use crate::aocio::each_line;
+use crate::aocio::{each_line, ProblemInput};
+use crate::settings;
+
+// Races longer than this take too long to check by simulating every hold time.
+const MAX_SIMULATED_TIME: i64 = 1_000_000;
 
 #[derive(Debug)]
 pub struct RaceWinner {
@@ -12,13 +15,29 @@ pub struct RaceWinner {
 
 impl RaceWinner {
 
+    // How far the boat goes when the button is held for hold_time.
+    pub fn simulate(&self, hold_time: i64) -> i64 {
+        hold_time * (self.time - hold_time).max(0)
+    }
+
+    // Every (hold time, distance) in the race, for plotting the distance curve.
+    pub fn simulation(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        (0..=self.time).map(|hold_time| (hold_time, self.simulate(hold_time)))
+    }
+
+    pub fn simulate_n_ways_to_beat(&self) -> i64 {
+        self.simulation().filter(|(_, distance)| *distance > self.distance).count() as i64
+    }
+
     pub fn get_n_ways_to_beat(&self) -> i64 {
         let (winner_h_left, winner_h_right) = self.calculate_hold_times();
 
-        let win_start = (winner_h_left as i64) + 1;
-        let win_end = winner_h_right as i64;
+        // Only hold times strictly between the roots beat the record, so a
+        // root that's a whole number is left out.
+        let win_start = winner_h_left.floor() as i64 + 1;
+        let win_end = winner_h_right.ceil() as i64 - 1;
     
-        if win_end > win_start {
+        if win_end >= win_start {
             win_end - win_start + 1
         }
         else {
@@ -55,15 +74,39 @@ pub struct RaceRecords {
 }
 
 impl RaceRecords {
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> {
+
+    pub fn winners(&self) -> &[RaceWinner] {
+        &self.winners
+    }
+
+    // The product of the ways to beat each race.
+    pub fn total_ways(&self) -> i64 {
+        self.winners.iter().map(|w| w.get_n_ways_to_beat()).product()
+    }
+
+    // Checks the closed form against simulating the races that are short enough.
+    pub fn verify(&self) -> AOCResult<()> {
+        for winner in self.winners.iter().filter(|w| w.time <= MAX_SIMULATED_TIME) {
+            let expected = winner.simulate_n_ways_to_beat();
+            let actual = winner.get_n_ways_to_beat();
+            if expected != actual {
+                return Err(AOCError::ProcessingError(format!(
+                    "Verify failed for {:?}: closed form found {} but simulation found {}",
+                    winner, actual, expected)));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
         RaceRecords::_parse(input, |line| line.into())
     }
 
-    pub fn parse_bad_kearning(input: impl AsRef<Path>) -> AOCResult<Self> {
+    pub fn parse_bad_kearning(input: impl ProblemInput) -> AOCResult<Self> {
         RaceRecords::_parse(input, |line| line.trim().replace(' ', "").into())
     }
 
-    fn _parse<F>(input: impl AsRef<Path>, line_xform: F) -> AOCResult<Self>
+    fn _parse<F>(input: impl ProblemInput, line_xform: F) -> AOCResult<Self>
         where F: Fn(&String) -> String
     {
         let mut time_numbers: Option<Vec<i64>> = None;
@@ -112,22 +155,21 @@ fn parse_info_numbers(line: impl AsRef<str>) -> AOCResult<Vec<i64>> {
 }
 
 fn run_part(race_records: &RaceRecords) -> AOCResult<String> {
-    let mut result = 1;
-
-    for race_record in &race_records.winners {
-        let ways_to_win = race_record.get_n_ways_to_beat();
-        result *= ways_to_win;
+    if settings::verify() {
+        race_records.verify()?;
     }
 
-    Ok(result.to_string())
+    Ok(race_records.total_ways().to_string())
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     let race_records = RaceRecords::parse(input)?;
     run_part(&race_records)
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     let race_records = RaceRecords::parse_bad_kearning(input)?;
     run_part(&race_records)
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 6, title: "Wait For It", parts: [1 => part1, 2 => part2 (example = "71503")]);
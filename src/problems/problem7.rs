@@ -1,15 +1,16 @@
-use std::path::Path;
 use std::collections::HashMap;
 
 use crate::aocbase::{AOCError, AOCResult};
-use crate::aocio::each_line;
+use crate::aocio::{each_line_with_policy, ProblemInput};
+use crate::settings;
 use crate::regex_ext::{RegexExt, CapturesExt};
+use crate::strategy::Strategies;
 
 use regex::Regex;
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref HAND_REGEX: Regex = Regex::new(r"^([AKQJT2-9]{5}) (\d+)").unwrap();
+    static ref HAND_REGEX: Regex = Regex::new(r"^([AKQJT2-9]{5}) (\d+)\s*$").unwrap();
 }
 
 #[derive(Debug, Eq, Hash, PartialEq, Copy, Clone, PartialOrd, Ord)]
@@ -51,6 +52,13 @@ impl Card {
     }
 }
 
+// Rank scores for 5 card hands squeezed together, one slot per possible score.
+const CARD_DIGITS: usize = Card::Ace as usize + 1;
+const RANK_INDEX_COUNT: usize = (HandType::FiveOfAKind as usize + 1) * CARD_DIGITS.pow(5);
+
+// Hand files bigger than this are scored as they're read instead of loaded.
+const STREAMING_MIN_BYTES: u64 = 64 * 1024 * 1024;
+
 #[derive(Debug, Eq, Hash, PartialEq, Copy, Clone, PartialOrd, Ord)]
 pub enum HandType {
     HighCard = 0,
@@ -95,6 +103,14 @@ impl Hand {
         score
     }
 
+    // Orders the same as rank_score, but the hand types sit right next to
+    // each other so it can be used as an index.
+    pub fn rank_index(&self) -> usize {
+        self.cards
+            .iter()
+            .fold(self.hand_type as usize, |index, card| index * CARD_DIGITS + *card as usize)
+    }
+
     fn get_hand_type(cards: &Vec<Card>) -> HandType {
         let mut count_counts = Hand::get_count_counts(cards.iter().filter(|card| **card != Card::Joker));
         let joker_count = cards.iter().filter(|card| **card == Card::Joker).count() as i32;
@@ -193,9 +209,9 @@ pub struct Hands {
 }
 
 impl Hands {
-    pub fn load(input: impl AsRef<Path>, joker_type: Option<Card>) -> AOCResult<Hands> {
+    pub fn load(input: impl ProblemInput, joker_type: Option<Card>) -> AOCResult<Hands> {
         let mut hands: Vec<Hand> = Vec::new();
-        each_line(input, |line| {
+        each_line_with_policy(input, settings::parse_policy(), |line| {
             hands.push(Hand::parse(line, joker_type)?);
             Ok(())
         })?;
@@ -212,21 +228,86 @@ impl Hands {
             .map(|(rank, hand)| (rank as i64 + 1) * hand.bid as i64)
             .sum()
     }
+
+    // Works out the total score without keeping the hands. Each hand is
+    // counted into the slot for its rank score, so going through the slots
+    // in order is the same as sorting. Hands with the same score rank in the
+    // order they were read, like the stable sort.
+    pub fn total_score_streaming(input: impl ProblemInput, joker_type: Option<Card>) -> AOCResult<i64> {
+        let mut counts = vec![0u32; RANK_INDEX_COUNT];
+        let mut bid_sums = vec![0i64; RANK_INDEX_COUNT];
+
+        // A repeat of a hand ranks one higher than the one before it.
+        let mut tie_extras: HashMap<usize, i64> = HashMap::new();
+
+        each_line_with_policy(input, settings::parse_policy(), |line| {
+            let hand = Hand::parse(line, joker_type)?;
+            let index = hand.rank_index();
+
+            if counts[index] > 0 {
+                *tie_extras.entry(index).or_insert(0) += counts[index] as i64 * hand.bid as i64;
+            }
+            counts[index] += 1;
+            bid_sums[index] += hand.bid as i64;
+            Ok(())
+        })?;
+
+        let mut total: i64 = 0;
+        let mut rank: i64 = 1;
+
+        for (index, count) in counts.iter().enumerate().filter(|(_, count)| **count > 0) {
+            total += rank * bid_sums[index] + tie_extras.get(&index).copied().unwrap_or(0);
+            rank += *count as i64;
+        }
+
+        Ok(total)
+    }
+}
+
+pub struct ScoreQuery<I: ProblemInput> {
+    pub input: I,
+    pub joker_type: Option<Card>,
+}
+
+impl<I: ProblemInput> ScoreQuery<I> {
+
+    fn sorted(&self) -> AOCResult<i64> {
+        let mut hands = Hands::load(&self.input, self.joker_type)?;
+        hands.sort_hands();
+        Ok(hands.total_score())
+    }
+
+    fn streaming(&self) -> AOCResult<i64> {
+        Hands::total_score_streaming(&self.input, self.joker_type)
+    }
 }
 
-fn run_part(input: impl AsRef<Path>, joker_type: Option<Card>) -> AOCResult<String> {
-    let mut hands = Hands::load(input, joker_type)?;
-    hands.sort_hands();
+// The slots cost the same no matter how few hands there are, so small files
+// are quicker to just sort.
+fn choose_score_strategy<I: ProblemInput>(query: &ScoreQuery<I>) -> &'static str {
+    match query.input.byte_len() {
+        Some(len) if len >= STREAMING_MIN_BYTES => "streaming",
+        _ => "sorted",
+    }
+}
 
-    let result = hands.total_score();
+fn run_part(input: impl ProblemInput, joker_type: Option<Card>) -> AOCResult<String> {
+    let query = ScoreQuery { input, joker_type };
+
+    let result = Strategies::new("problem7")
+        .add("sorted", ScoreQuery::sorted)
+        .add("streaming", ScoreQuery::streaming)
+        .run(&query, choose_score_strategy)?;
 
     Ok(result.to_string())
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, None)
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
     run_part(input, Some(Card::Jack))
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 7, title: "Camel Cards", parts: [1 => part1 (example = "6440"), 2 => part2 (example = "5905")], skips_invalid: true);
@@ -1,13 +1,15 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::aocbase::{AOCError, AOCResult};
-use crate::aocio::each_line;
+use crate::aocio::{each_line, ProblemInput};
 use crate::regex_ext::CapturesExt;
+use crate::mathx;
+use crate::settings;
+use crate::strategy::Strategies;
 
 lazy_static! {
     static ref COMMAND_REGEX: Regex = Regex::new(r"^\s*([RL]+)\s*$").unwrap();
@@ -54,7 +56,7 @@ impl Network {
         Ok(self
             .nodes
             .get(id.as_ref())
-            .ok_or_else(|| AOCError::ProcessingError(format!("Invalid start location: {}", id.as_ref())))?)
+            .ok_or_else(|| AOCError::ProcessingError(format!("Unknown node: {}", id.as_ref())))?)
     }
 
     /// Gives a list of ids in order of encounter using commands and the visit step at which a cycle would start.
@@ -86,40 +88,128 @@ impl Network {
         return Err(AOCError::ProcessingError("never!".into()))
     }
 
-    pub fn search(&self, start: &str, end: &str, commands: &Vec<Command>) -> AOCResult<i32> {
-        let mut steps = 0;
+    fn next_id<'a>(node: &'a Node, command: &Command) -> &'a str {
+        match command {
+            Command::Left => &node.left,
+            Command::Right => &node.right,
+        }
+    }
 
-        let mut cur_node = self
-            .nodes
-            .get(start)
-            .ok_or_else(|| AOCError::ProcessingError(format!("Invalid start location: {start}")))?;
+    // Walks from start following the commands until end is reached.
+    pub fn search<'a>(&'a self, start: &str, end: &str, commands: &[Command]) -> AOCResult<Route<'a>> {
+        let mut path: Vec<&str> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut cur_node = self.get_node(start)?;
+
+        for (i, c) in commands.iter().enumerate().cycle() {
+            path.push(&cur_node.id);
 
-        for c in commands.iter().cycle() {
             if cur_node.id == end {
-                return Ok(steps);
+                return Ok(Route::new(path));
             }
 
-            let next_id = match c {
-                Command::Left => &cur_node.left,
-                Command::Right => &cur_node.right,
-            };
+            // Back somewhere we've been before at the start of the commands.
+            if i == 0 && !visited.insert(cur_node.id.as_str()) {
+                break;
+            }
 
-            cur_node = self
-                .nodes
-                .get(next_id)
-                .ok_or_else(|| AOCError::ProcessingError(format!("Invalid location: {next_id}")))?;
+            cur_node = self.get_node(Self::next_id(cur_node, c))?;
+        }
 
+        Err(AOCError::ProcessingError(format!("Could not find end: {end}")))
+    }
+
+    // Meet in the middle. Walking forward from the start is a single path, but
+    // going backwards from the end branches out over every node and command
+    // position that could lead there. The two sides take turns, one step
+    // forward and one level backward, until the forward walk lands on a state
+    // the backward side has already seen.
+    //
+    // A state is a node and where we are in the commands. Since walking
+    // forward is deterministic, the backward distance to a state is exactly
+    // how many steps it takes to first reach the end from it.
+    //
+    // The backward side can have nodes times commands states to go through,
+    // so this is usually a lot slower than walking, and is only used when
+    // asked for with --strategy bidirectional.
+    pub fn search_bidirectional<'a>(&'a self, start: &str, end: &str, commands: &[Command]) -> AOCResult<Route<'a>> {
+        if commands.is_empty() {
+            return Err(AOCError::ProcessingError("There are no commands.".into()));
+        }
+        self.get_node(end)?;
+
+        let command_count = commands.len();
+
+        // Which nodes lead to a node and with which command.
+        let mut leads_to: HashMap<&str, Vec<(&str, bool)>> = HashMap::new();
+        for node in self.nodes.values() {
+            leads_to.entry(node.left.as_str()).or_default().push((node.id.as_str(), true));
+            leads_to.entry(node.right.as_str()).or_default().push((node.id.as_str(), false));
+        }
+
+        let mut to_end: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut backward: Vec<(&str, usize)> = Vec::new();
+        for i in 0 .. command_count {
+            to_end.insert((end, i), 0);
+            backward.push((end, i));
+        }
+        let mut backward_depth = 0;
+
+        let mut forward_visited: HashSet<(&str, usize)> = HashSet::new();
+        let mut node = self.get_node(start)?;
+        let mut steps = 0;
+
+        loop {
+            let state = (node.id.as_str(), steps % command_count);
+
+            if let Some(remaining) = to_end.get(&state) {
+                return Ok(Route::new(self.search_path(start, steps + remaining, commands)?));
+            }
+
+            if !forward_visited.insert(state) {
+                return Err(AOCError::ProcessingError(format!("Could not find end: {end}")));
+            }
+
+            node = self.get_node(Self::next_id(node, &commands[state.1]))?;
             steps += 1;
+
+            // One more level backward.
+            backward_depth += 1;
+            let mut next_backward: Vec<(&str, usize)> = Vec::new();
+            for (id, i) in backward {
+                let prev_i = (i + command_count - 1) % command_count;
+                let is_left = matches!(commands[prev_i], Command::Left);
+
+                for (prev_id, via_left) in leads_to.get(id).into_iter().flatten() {
+                    let prev_state = (*prev_id, prev_i);
+                    if *via_left == is_left && !to_end.contains_key(&prev_state) {
+                        to_end.insert(prev_state, backward_depth);
+                        next_backward.push(prev_state);
+                    }
+                }
+            }
+            backward = next_backward;
         }
+    }
 
-        Err(AOCError::ProcessingError(format!("Could not find end: {end}")))
+    // The ids visited in the first steps from start, including start.
+    fn search_path<'a>(&'a self, start: &str, steps: usize, commands: &[Command]) -> AOCResult<Vec<&'a str>> {
+        let mut node = self.get_node(start)?;
+        let mut path: Vec<&str> = vec![&node.id];
+
+        for c in commands.iter().cycle().take(steps) {
+            node = self.get_node(Self::next_id(node, c))?;
+            path.push(&node.id);
+        }
+
+        Ok(path)
     }
 
     pub fn add_node(&mut self, node: Node) {
         self.nodes.insert(node.id.clone(), node);
     }
 
-    pub fn parse(input: impl AsRef<Path>) -> AOCResult<Self> {
+    pub fn parse(input: impl ProblemInput) -> AOCResult<Self> {
         let mut network = Network::new();
 
         each_line(input, |line| {
@@ -157,16 +247,63 @@ impl Network {
     }
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
+// A way through the network and the ids visited along it, start and end included.
+#[derive(Debug, Clone)]
+pub struct Route<'a> {
+    pub path: Vec<&'a str>,
+}
+
+impl<'a> Route<'a> {
+
+    pub fn new(path: Vec<&'a str>) -> Self {
+        Self { path }
+    }
+
+    pub fn steps(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+pub struct RouteQuery<'a> {
+    pub network: &'a Network,
+    pub start: String,
+    pub end: String,
+}
+
+impl<'a> RouteQuery<'a> {
+
+    fn walk(&self) -> AOCResult<Route<'a>> {
+        self.network.search(&self.start, &self.end, &self.network.commands)
+    }
+
+    fn bidirectional(&self) -> AOCResult<Route<'a>> {
+        self.network.search_bidirectional(&self.start, &self.end, &self.network.commands)
+    }
+}
+
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    let params = settings::params();
     let network = Network::parse(input)?;
-    let result = network.search("AAA", "ZZZ", &network.commands)?;
 
-    Ok(result.to_string())
+    let query = RouteQuery {
+        network: &network,
+        start: params.get_or("problem8.start", "AAA".to_string())?,
+        end: params.get_or("problem8.end", "ZZZ".to_string())?,
+    };
+
+    let route = Strategies::new("problem8::part1")
+        .add("walk", RouteQuery::walk)
+        .add("bidirectional", RouteQuery::bidirectional)
+        .run(&query, |_| "walk")?;
+
+    tracing::debug!("Route: {}", route.path.join(" -> "));
+
+    Ok(route.steps().to_string())
 }
 
 /// Using information about a cycle in the network and choosing target nodes
 /// of interest you can figure out when the next target node will be visited.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NetworkCycleIterator {
     pub pre_cycle: Vec<usize>,
     pub in_cycle: Vec<usize>,
@@ -207,6 +344,20 @@ impl NetworkCycleIterator {
         })
     }
 
+    /// Steps to go round the cycle once.
+    pub fn cycle_length(&self) -> usize {
+        self.visit_length - self.cycle_start
+    }
+
+    /// True when the only target is in the cycle and first reached on a
+    /// multiple of the cycle length, so every multiple of the cycle length from
+    /// there on reaches it. The puzzle inputs are made this way.
+    pub fn is_aligned(&self) -> bool {
+        self.pre_cycle.is_empty() &&
+            self.in_cycle.len() == 1 &&
+            self.in_cycle[0].is_multiple_of(self.cycle_length())
+    }
+
     /// For the nth target node get the step
     pub fn get_step_for_nth_target(&self, nth: usize) -> usize {
         if nth < self.pre_cycle.len() {
@@ -266,18 +417,42 @@ fn find_common_step(nc_iter_states: &mut Vec<NCIterState>) -> usize {
     }
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    let network = Network::parse(input)?;
+// When every start is aligned the steps they all reach a target together are
+// the multiples of the lcm of their cycle lengths, so it's the first of those
+// that every start has got to its target by.
+fn common_step_by_lcm(iterators: &[NetworkCycleIterator]) -> AOCResult<usize> {
+    if !iterators.iter().all(NetworkCycleIterator::is_aligned) {
+        return Err(AOCError::ProcessingError("The targets aren't reached on multiples of the cycle lengths.".into()));
+    }
 
-    /*
-        Finds a cycle in going through the commands for each start
-        Using this cycle you can map out each ending node and instead of 
-        walking each node, you skip steps using the cycle.
-        This way you can iterate over each start looking at the next target item
-        in step order and see when steps match.
-        This is still slower than I would like, but I don't have another algorithm yet.
-        Running ~ 15 seconds.
-    */
+    let cycle_lcm = iterators
+        .iter()
+        .fold(1, |cycle_lcm, iterator| mathx::lcm(cycle_lcm, iterator.cycle_length() as i64)) as usize;
+    let first = iterators.iter().map(|iterator| iterator.in_cycle[0]).max().unwrap_or(0);
+
+    Ok(first.div_ceil(cycle_lcm) * cycle_lcm)
+}
+
+/*
+    Finds a cycle in going through the commands for each start
+    Using this cycle you can map out each ending node and instead of
+    walking each node, you skip steps using the cycle.
+    This way you can iterate over each start looking at the next target item
+    in step order and see when steps match.
+    This works for any input, but is slow on the real one.
+    Running ~ 20 seconds.
+*/
+fn common_step_by_merging(iterators: &[NetworkCycleIterator]) -> AOCResult<usize> {
+    let mut nc_iter_states: Vec<NCIterState> = iterators
+        .iter()
+        .map(|iterator| NCIterState::new(iterator.clone()))
+        .collect();
+
+    Ok(find_common_step(&mut nc_iter_states))
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    let network = Network::parse(input)?;
 
     let starts = network.nodes
         .keys()
@@ -285,14 +460,20 @@ pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
         .map(|node| node.as_str())
         .collect::<Vec<&str>>();
 
-    let mut nc_iter_states: Vec<NCIterState> = Vec::new();
+    let mut iterators: Vec<NetworkCycleIterator> = Vec::new();
 
     for start in starts {
-        let iterator = NetworkCycleIterator::new(&network, start, |id| id.ends_with("Z"))?;
-        nc_iter_states.push(NCIterState::new(iterator));
+        iterators.push(NetworkCycleIterator::new(&network, start, |id| id.ends_with("Z"))?);
     }
 
-    let result = find_common_step(&mut nc_iter_states);
+    let result = Strategies::new("problem8::part2")
+        .add("lcm", |iterators: &Vec<NetworkCycleIterator>| common_step_by_lcm(iterators))
+        .add("merge", |iterators: &Vec<NetworkCycleIterator>| common_step_by_merging(iterators))
+        .run(&iterators, |iterators| {
+            if iterators.iter().all(NetworkCycleIterator::is_aligned) { "lcm" } else { "merge" }
+        })?;
 
     Ok(result.to_string())
-}
\ No newline at end of file
+}
+
+crate::register_day!(day: 8, title: "Haunted Wasteland", parts: [1 => part1, 2 => part2 (example = "6")]);
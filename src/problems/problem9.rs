@@ -1,8 +1,9 @@
+use std::collections::VecDeque;
 use std::num::ParseIntError;
-use std::path::Path;
 
 use crate::aocbase::AOCResult;
-use crate::aocio::each_line;
+use crate::aocio::{each_line_with_policy, ProblemInput};
+use crate::settings;
 
 pub fn parse_line(line: impl AsRef<str>) -> AOCResult<Vec<i64>> {
     Ok(line.as_ref()
@@ -12,75 +13,161 @@ pub fn parse_line(line: impl AsRef<str>) -> AOCResult<Vec<i64>> {
         .collect::<Result<Vec<i64>, ParseIntError>>()?)
 }
 
-pub struct NumStack {
-    nums: Vec<Vec<i64>>,
+// One row of the difference pyramid, keeping count of what isn't zero so
+// finding the all zero row doesn't mean looking through each one.
+#[derive(Clone, Default)]
+struct Layer {
+    values: VecDeque<i64>,
+    non_zero: usize,
 }
 
-impl NumStack {
+impl Layer {
+
+    fn push(&mut self, value: i64) {
+        if value != 0 {
+            self.non_zero += 1;
+        }
+        self.values.push_back(value);
+    }
+
+    fn pop(&mut self) -> Option<i64> {
+        let value = self.values.pop_front()?;
+        if value != 0 {
+            self.non_zero -= 1;
+        }
+        Some(value)
+    }
+
+    fn is_end_layer(&self) -> bool {
+        self.values.len() <= 1 || self.non_zero == 0
+    }
+}
+
+// Predicts the values either side of a sequence from its differences. Values
+// can be pushed on the end and the oldest popped off the front as they come,
+// and only the ends of the pyramid change, so each one costs as much as the
+// pyramid is deep rather than rebuilding it.
+//
+// With a window, pushing past it drops the oldest value, to follow the last
+// few values of a longer stream.
+#[derive(Clone, Default)]
+pub struct Extrapolator {
+    layers: Vec<Layer>,
+    window: Option<usize>,
+}
+
+impl Extrapolator {
 
     pub fn new(initial: Vec<i64>) -> Self {
-        let mut nums: Vec<Vec<i64>> = vec![initial];
+        let mut extrapolator = Extrapolator::default();
+        for value in initial {
+            extrapolator.push(value);
+        }
+        extrapolator
+    }
+
+    pub fn with_window(window: usize) -> Self {
+        Extrapolator { layers: Vec::new(), window: Some(window) }
+    }
+
+    pub fn push(&mut self, value: i64) {
+        if let Some(window) = self.window {
+            if window == 0 {
+                return;
+            }
+            if self.len() == window {
+                self.pop_oldest();
+            }
+        }
 
-        loop {
-            let last_layer = &nums[nums.len() - 1];
-            if NumStack::is_end_layer(last_layer) {
-                return NumStack { nums };
+        // Each row gets one more difference, from the last two of the row above.
+        let mut value = value;
+        for depth in 0 .. {
+            if depth == self.layers.len() {
+                self.layers.push(Layer::default());
+            }
+            let layer = &mut self.layers[depth];
+            let previous = layer.values.back().copied();
+            layer.push(value);
+            match previous {
+                Some(previous) => value -= previous,
+                None => break,
             }
-            nums.push(NumStack::next_layer(last_layer));
         }
     }
 
+    // Each row loses its first difference, and the last row goes when it's empty.
+    pub fn pop_oldest(&mut self) -> Option<i64> {
+        let oldest = self.layers.first_mut()?.pop();
+        for layer in self.layers.iter_mut().skip(1) {
+            layer.pop();
+        }
+        while self.layers.last().is_some_and(|layer| layer.values.is_empty()) {
+            self.layers.pop();
+        }
+        oldest
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.first().map_or(0, |layer| layer.values.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = i64> + '_ {
+        self.layers.iter().take(1).flat_map(|layer| layer.values.iter().copied())
+    }
+
     pub fn extrapolate_next(&self) -> i64 {
-        self.extrapolate(|a, cur| a[a.len() - 1] + cur)
+        self.extrapolate(|layer, cur| layer.values[layer.values.len() - 1] + cur)
     }
 
     pub fn extrapolate_prev(&self) -> i64 {
-        self.extrapolate(|a, cur| a[0] - cur)
+        self.extrapolate(|layer, cur| layer.values[0] - cur)
     }
 
+    // Works back up from the row above the first all zero one.
     fn extrapolate<F>(&self, f: F) -> i64
-        where F: Fn(&Vec<i64>, i64) -> i64
+        where F: Fn(&Layer, i64) -> i64
     {
+        let depth = self.layers
+            .iter()
+            .position(|layer| layer.is_end_layer())
+            .unwrap_or(self.layers.len());
+
         let mut cur: i64 = 0;
 
-        for depth in (0 .. self.nums.len() - 1).rev() {
-            let a = &self.nums[depth];
-            cur = f(a, cur);
+        for layer in self.layers[.. depth].iter().rev() {
+            cur = f(layer, cur);
         }
 
         cur
     }
-
-    fn next_layer(layer: &Vec<i64>) -> Vec<i64> {
-        (0..layer.len()-1)
-            .map(|idx| layer[idx+1] - layer[idx])
-            .collect()
-    }
-
-    fn is_end_layer(layer: &Vec<i64>) -> bool {
-        layer.len() <= 1 || layer.iter().all(|n| *n == 0)
-    }
 }
 
-fn run_part<F>(input: impl AsRef<Path>, f: F) -> AOCResult<String>
-    where F: Fn(&NumStack) -> i64
+fn run_part<F>(input: impl ProblemInput, f: F) -> AOCResult<String>
+    where F: Fn(&Extrapolator) -> i64
 {
     let mut result: i64 = 0;
 
-    each_line(input, |line| {
+    each_line_with_policy(input, settings::parse_policy(), |line| {
         let nums = parse_line(line)?;
-        let num_stack = NumStack::new(nums);
-        result += f(&num_stack);
+        let extrapolator = Extrapolator::new(nums);
+        result += f(&extrapolator);
         Ok(())
     })?;
 
     Ok(result.to_string())
 }
 
-pub fn part1(input: impl AsRef<Path>) -> AOCResult<String> {
-    run_part(input, |num_stack| num_stack.extrapolate_next())
+pub fn part1(input: impl ProblemInput) -> AOCResult<String> {
+    run_part(input, |extrapolator| extrapolator.extrapolate_next())
+}
+
+pub fn part2(input: impl ProblemInput) -> AOCResult<String> {
+    run_part(input, |extrapolator| extrapolator.extrapolate_prev())
 }
 
-pub fn part2(input: impl AsRef<Path>) -> AOCResult<String> {
-    run_part(input, |num_stack| num_stack.extrapolate_prev())
-}
\ No newline at end of file
+crate::register_day!(day: 9, title: "Mirage Maintenance", parts: [1 => part1 (example = "114"), 2 => part2 (example = "2")], skips_invalid: true);
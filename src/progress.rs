@@ -0,0 +1,115 @@
+// How far along slow solvers are, for --progress. Solvers count what they've
+// done and a line on stderr is updated every so often, so a long run shows
+// it's getting somewhere without slowing it down. Nothing is shown without
+// --progress.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::log;
+use crate::settings;
+
+const REPORT_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Progress {
+    label: String,
+    total: Option<u64>,
+    done: AtomicU64,
+    started: Instant,
+    last_report: Mutex<Option<Instant>>,
+    enabled: bool,
+}
+
+impl Progress {
+
+    // total is how many steps there will be, if that's known up front.
+    pub fn new(label: impl Into<String>, total: Option<u64>) -> Self {
+        Self {
+            label: label.into(),
+            total,
+            done: AtomicU64::new(0),
+            started: Instant::now(),
+            last_report: Mutex::new(None),
+            enabled: settings::progress(),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, steps: u64) {
+        self.add_with(steps, String::new);
+    }
+
+    // detail is shown after the count, like the best answer so far. It's
+    // only worked out when a line is about to be written.
+    pub fn add_with<F>(&self, steps: u64, detail: F)
+        where F: FnOnce() -> String
+    {
+        if !self.enabled {
+            return;
+        }
+
+        let done = self.done.fetch_add(steps, Ordering::Relaxed) + steps;
+
+        // Another thread writing the line right now has it covered.
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        let now = Instant::now();
+        let due = match *last_report {
+            None => now.duration_since(self.started) >= REPORT_INTERVAL,
+            Some(last) => now.duration_since(last) >= REPORT_INTERVAL,
+        };
+        if due {
+            *last_report = Some(now);
+            self.write(done, &detail());
+        }
+    }
+
+    fn line(&self, done: u64, detail: &str) -> String {
+        let mut line = match log::current_problem() {
+            Some(problem) => format!("[{}] {}: ", problem, self.label),
+            None => format!("{}: ", self.label),
+        };
+
+        match self.total {
+            Some(total) if total > 0 => line.push_str(&format!("{}/{} ({}%)", done, total, done * 100 / total)),
+            _ => line.push_str(&done.to_string()),
+        }
+
+        line.push_str(&format!(" {:.1}s", self.started.elapsed().as_secs_f64()));
+        if !detail.is_empty() {
+            line.push(' ');
+            line.push_str(detail);
+        }
+        line
+    }
+
+    // A terminal gets one line that's written over, anything else a line
+    // per update.
+    fn write(&self, done: u64, detail: &str) {
+        let line = self.line(done, detail);
+        let mut stderr = io::stderr().lock();
+        if stderr.is_terminal() {
+            let _ = write!(stderr, "\r{}\x1b[K", line);
+        }
+        else {
+            let _ = writeln!(stderr, "{}", line);
+        }
+        let _ = stderr.flush();
+    }
+}
+
+// Whatever was last shown on a terminal is cleared so results print cleanly.
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let reported = self.last_report.get_mut().map(|last| last.is_some()).unwrap_or(false);
+        if self.enabled && reported && io::stderr().is_terminal() {
+            let _ = write!(io::stderr(), "\r\x1b[K");
+        }
+    }
+}
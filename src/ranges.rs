@@ -0,0 +1,176 @@
+// Ranges of whole numbers, for working on every value a puzzle allows at
+// once instead of listing them out. Intervals are half open, start is in and
+// end isn't, so splitting one at a value never loses or repeats anything.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+
+    // Anything with end <= start is empty.
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end: end.max(start) }
+    }
+
+    // min through max, both included, the way puzzles usually say it.
+    pub fn inclusive(min: i64, max: i64) -> Self {
+        Self::new(min, max + 1)
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    pub fn len(&self) -> i64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        value >= self.start && value < self.end
+    }
+
+    pub fn intersect(&self, other: &Interval) -> Interval {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    // The values below at and the rest.
+    pub fn split_at(&self, at: i64) -> (Interval, Interval) {
+        let at = at.clamp(self.start, self.end);
+        (Interval::new(self.start, at), Interval::new(at, self.end))
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// Any number of values as the intervals that cover them. They're kept sorted,
+// with none empty, overlapping or touching.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_intervals(intervals: impl IntoIterator<Item = Interval>) -> Self {
+        let mut intervals: Vec<Interval> = intervals.into_iter().filter(|interval| !interval.is_empty()).collect();
+        intervals.sort_by_key(|interval| interval.start);
+
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+
+        Self { intervals: merged }
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    // How many values are in the set.
+    pub fn len(&self) -> i64 {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let i = self.intervals.partition_point(|interval| interval.end <= value);
+        self.intervals.get(i).is_some_and(|interval| interval.contains(value))
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_intervals(self.intervals.iter().chain(&other.intervals).copied())
+    }
+
+    pub fn intersect(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals: Vec<Interval> = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (self.intervals[i], other.intervals[j]);
+            intervals.push(a.intersect(&b));
+            if a.end < b.end {
+                i += 1;
+            }
+            else {
+                j += 1;
+            }
+        }
+
+        IntervalSet::from_intervals(intervals)
+    }
+
+    // Everything in within that isn't in this set.
+    pub fn complement(&self, within: Interval) -> IntervalSet {
+        let mut intervals: Vec<Interval> = Vec::new();
+        let mut start = within.start;
+
+        for interval in &self.intervals {
+            intervals.push(Interval::new(start, interval.start.min(within.end)));
+            start = start.max(interval.end);
+        }
+        intervals.push(Interval::new(start, within.end));
+
+        IntervalSet::from_intervals(intervals)
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(first), Some(last)) => self.intersect(&other.complement(Interval::new(first.start, last.end))),
+            _ => IntervalSet::new(),
+        }
+    }
+
+    // The values below at and the rest.
+    pub fn split_at(&self, at: i64) -> (IntervalSet, IntervalSet) {
+        let (below, rest): (Vec<Interval>, Vec<Interval>) = self.intervals
+            .iter()
+            .map(|interval| interval.split_at(at))
+            .unzip();
+        (IntervalSet::from_intervals(below), IntervalSet::from_intervals(rest))
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        IntervalSet::from_intervals([interval])
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let intervals: Vec<String> = self.intervals.iter().map(|interval| interval.to_string()).collect();
+        write!(f, "{{{}}}", intervals.join(", "))
+    }
+}
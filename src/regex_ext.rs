@@ -17,6 +17,9 @@ impl RegexExt for Regex {
 
 pub trait CapturesExt<'h> {
     fn get_group(&self, i: usize) -> AOCResult<&'h str>;
+
+    // The first character of a group, for groups that are a single letter.
+    fn get_char(&self, i: usize) -> AOCResult<char>;
 }
 
 impl<'h> CapturesExt<'h> for Captures<'h> {
@@ -25,4 +28,12 @@ impl<'h> CapturesExt<'h> for Captures<'h> {
             .ok_or_else(|| AOCError::InvalidRegexOperation(format!("Invalid capture group ({}).", i)))?
             .as_str())
     }
+
+    fn get_char(&self, i: usize) -> AOCResult<char> {
+        let group = self.get_group(i)?;
+        group
+            .chars()
+            .next()
+            .ok_or_else(|| AOCError::ParseError(format!("Empty capture group ({}) in: {}", i, &self[0])))
+    }
 }
\ No newline at end of file
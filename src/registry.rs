@@ -0,0 +1,122 @@
+// Each problem module describes the parts it solves with register_day!, and
+// problems::registered() gathers them all up. Adding a day is the module
+// with its register_day! and one name in the days! list in problems/mod.rs.
+
+use crate::aocbase::AOCResult;
+use crate::aocio::{InputSource, ProblemInput};
+
+pub trait AocProblem: Send + Sync {
+
+    fn day(&self) -> u32;
+
+    fn part(&self) -> u32;
+
+    fn solve(&self, input: &dyn ProblemInput) -> AOCResult<String>;
+
+    // Input that's already in memory, like a test's.
+    fn solve_str(&self, input: &str) -> AOCResult<String> {
+        self.solve(&InputSource::text(input))
+    }
+
+    // The puzzle's title on adventofcode.com.
+    fn title(&self) -> &str;
+
+    // The answer for the puzzle's example, where it's been written down.
+    fn sample_answer(&self) -> Option<&str>;
+
+    // Where the example input is when it isn't in one of the usual places
+    // samples looks.
+    fn sample_input(&self) -> Option<&str>;
+
+    // Whether lines that fail to parse can be skipped with --skip-invalid.
+    fn skips_invalid(&self) -> bool;
+
+    fn name(&self) -> String {
+        format!("problem{}::part{}", self.day(), self.part())
+    }
+}
+
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    pub title: &'static str,
+    pub sample_answer: Option<&'static str>,
+    pub sample_input: Option<&'static str>,
+    pub skips_invalid: bool,
+    pub solve: fn(&dyn ProblemInput) -> AOCResult<String>,
+}
+
+impl AocProblem for Solution {
+
+    fn day(&self) -> u32 {
+        self.day
+    }
+
+    fn part(&self) -> u32 {
+        self.part
+    }
+
+    fn solve(&self, input: &dyn ProblemInput) -> AOCResult<String> {
+        (self.solve)(input)
+    }
+
+    fn title(&self) -> &str {
+        self.title
+    }
+
+    fn sample_answer(&self) -> Option<&str> {
+        self.sample_answer
+    }
+
+    fn sample_input(&self) -> Option<&str> {
+        self.sample_input
+    }
+
+    fn skips_invalid(&self) -> bool {
+        self.skips_invalid
+    }
+}
+
+// Declares the SOLUTIONS of a problem module, like:
+//   register_day!(day: 7, title: "Camel Cards", parts: [1 => part1 (example = "6440"), 2 => part2]);
+// where the example answer in brackets is optional, and can be followed by
+// input = "path" when the example input isn't in a file samples finds itself.
+// Days that read their lines with each_line_with_policy end with
+// skips_invalid: true, so --skip-invalid can be used with them.
+//
+// It also gives the module a solve_str, for solving a part from input that's
+// in memory, like problem7::solve_str(1, input).
+#[macro_export]
+macro_rules! register_day {
+    (day: $day:expr, title: $title:expr, parts: [$(
+        $part:literal => $solve:ident $(($(example = $sample:literal)? $(,)? $(input = $sample_input:literal)?))?
+    ),+ $(,)?] $(, skips_invalid: $skips_invalid:literal)?) => {
+        pub const SOLUTIONS: &[$crate::registry::Solution] = {
+            const SKIPS_INVALID: bool = $crate::register_day!(@flag $($skips_invalid)?);
+            &[
+                $($crate::registry::Solution {
+                    day: $day,
+                    part: $part,
+                    title: $title,
+                    sample_answer: $crate::register_day!(@optional $($($sample)?)?),
+                    sample_input: $crate::register_day!(@optional $($($sample_input)?)?),
+                    skips_invalid: SKIPS_INVALID,
+                    solve: |input| $solve(input),
+                },)+
+            ]
+        };
+
+        pub fn solve_str(part: u32, input: &str) -> $crate::aocbase::AOCResult<String> {
+            use $crate::registry::AocProblem;
+            SOLUTIONS
+                .iter()
+                .find(|solution| solution.part == part)
+                .ok_or_else(|| $crate::aocbase::AOCError::ProcessingError(format!("There's no part {} for day {}.", part, $day)))?
+                .solve_str(input)
+        }
+    };
+    (@optional) => { None };
+    (@optional $value:literal) => { Some($value) };
+    (@flag) => { false };
+    (@flag $value:literal) => { $value };
+}
@@ -0,0 +1,43 @@
+// Reads explore commands a line at a time and prints what they say, until
+// quit or the end of the input. A command that fails prints its error and
+// the next one is read.
+
+use std::io::{BufRead, Write};
+
+use crate::aocbase::AOCResult;
+use crate::explore::{self, Explorable, ExploreCommand};
+
+const PROMPT: &str = "> ";
+
+pub fn run(explorable: &mut dyn Explorable, input: impl BufRead, mut output: impl Write) -> AOCResult<()> {
+    writeln!(output, "{}", explorable.summary().trim_end())?;
+    writeln!(output, "Type help for commands, quit to stop.")?;
+    write!(output, "{}", PROMPT)?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.split_first() {
+            None => {},
+            Some((&"quit", _)) | Some((&"exit", _)) => return Ok(()),
+            Some((&"help", _)) => {
+                let mut commands = explorable.commands();
+                commands.push(ExploreCommand::new("help", "", "Show these commands"));
+                commands.push(ExploreCommand::new("quit", "", "Stop exploring"));
+                write!(output, "{}", explore::command_help(&commands))?;
+            },
+            Some((name, args)) => match explorable.run_command(name, args) {
+                Ok(text) => writeln!(output, "{}", text.trim_end())?,
+                Err(e) => writeln!(output, "Error: {}", e)?,
+            },
+        }
+
+        write!(output, "{}", PROMPT)?;
+        output.flush()?;
+    }
+
+    writeln!(output)?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+// A write up of a run, from the results it left in a results CSV. Markdown
+// has the answers, how long each problem took and anything that went wrong,
+// with a bar chart of the durations in text. HTML has the same with the
+// chart drawn as an SVG.
+
+use crate::run::{AnswerStatus, ResultRecord};
+
+const TEXT_BAR_WIDTH: usize = 40;
+
+const SVG_WIDTH: f64 = 640.0;
+const SVG_LABEL_WIDTH: f64 = 160.0;
+const SVG_VALUE_WIDTH: f64 = 90.0;
+const SVG_ROW_HEIGHT: f64 = 18.0;
+
+struct Summary {
+    total: usize,
+    answered: usize,
+    errors: usize,
+    wrong: usize,
+    total_ms: f64,
+}
+
+impl Summary {
+
+    fn new(records: &[ResultRecord]) -> Self {
+        Self {
+            total: records.len(),
+            answered: records.iter().filter(|record| record.error.is_empty()).count(),
+            errors: records.iter().filter(|record| !record.error.is_empty()).count(),
+            wrong: records.iter().filter(|record| record.status == AnswerStatus::Wrong).count(),
+            total_ms: records.iter().map(|record| record.duration_ms).sum(),
+        }
+    }
+
+    fn line(&self) -> String {
+        format!("Problems: {} Answered: {} Errors: {} Wrong: {} Total time: {:.1} ms",
+            self.total, self.answered, self.errors, self.wrong, self.total_ms)
+    }
+}
+
+fn max_duration(records: &[ResultRecord]) -> f64 {
+    records.iter().map(|record| record.duration_ms).fold(0.0, f64::max)
+}
+
+// How much of width a duration gets, with anything that took any time at
+// all getting some of it so it doesn't look like it didn't run.
+fn bar_length(duration_ms: f64, max_ms: f64, width: f64) -> f64 {
+    if max_ms <= 0.0 || duration_ms <= 0.0 {
+        0.0
+    }
+    else {
+        (duration_ms / max_ms * width).max(width / TEXT_BAR_WIDTH as f64)
+    }
+}
+
+// Pipes would end the table cell and new lines the row.
+fn escape_markdown(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub fn markdown(title: &str, records: &[ResultRecord]) -> String {
+    let mut out = format!("# {}\n\n{}\n\n", title, Summary::new(records).line());
+
+    out.push_str("| Problem | Answer | Duration (ms) | Status | Error |\n");
+    out.push_str("|---|---|--:|---|---|\n");
+    for record in records {
+        out.push_str(&format!("| {} | {} | {:.3} | {} | {} |\n",
+            record.name,
+            escape_markdown(&record.answer),
+            record.duration_ms,
+            record.status.as_str(),
+            escape_markdown(&record.error)));
+    }
+
+    out.push_str("\n## Durations\n\n```\n");
+    out.push_str(&text_chart(records));
+    out.push_str("```\n");
+    out
+}
+
+pub fn text_chart(records: &[ResultRecord]) -> String {
+    let max_ms = max_duration(records);
+    let name_width = records.iter().map(|record| record.name.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for record in records {
+        let length = bar_length(record.duration_ms, max_ms, TEXT_BAR_WIDTH as f64).round() as usize;
+        out.push_str(&format!("{:<name_width$} {:<bar_width$} {:.1} ms\n",
+            record.name,
+            "#".repeat(length),
+            record.duration_ms,
+            name_width = name_width,
+            bar_width = TEXT_BAR_WIDTH));
+    }
+    out
+}
+
+pub fn svg_chart(records: &[ResultRecord]) -> String {
+    let max_ms = max_duration(records);
+    let bar_space = SVG_WIDTH - SVG_LABEL_WIDTH - SVG_VALUE_WIDTH;
+    let height = SVG_ROW_HEIGHT * records.len().max(1) as f64;
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\">\n", SVG_WIDTH, height);
+    svg.push_str(concat!(
+        "  <style>\n",
+        "    text { font: 11px monospace; dominant-baseline: middle; }\n",
+        "    rect { fill: #47a; }\n",
+        "    rect.error { fill: #c33; }\n",
+        "  </style>\n"));
+
+    for (i, record) in records.iter().enumerate() {
+        let y = i as f64 * SVG_ROW_HEIGHT;
+        let middle = y + SVG_ROW_HEIGHT / 2.0;
+        let length = bar_length(record.duration_ms, max_ms, bar_space);
+        let class = if record.error.is_empty() { "" } else { " class=\"error\"" };
+
+        svg.push_str(&format!("  <text x=\"0\" y=\"{:.1}\">{}</text>\n", middle, escape_html(&record.name)));
+        svg.push_str(&format!("  <rect{} x=\"{}\" y=\"{:.1}\" width=\"{:.2}\" height=\"{:.1}\"/>\n",
+            class, SVG_LABEL_WIDTH, y + 2.0, length, SVG_ROW_HEIGHT - 4.0));
+        svg.push_str(&format!("  <text x=\"{:.2}\" y=\"{:.1}\">{:.1} ms</text>\n",
+            SVG_LABEL_WIDTH + length + 4.0, middle, record.duration_ms));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn html(title: &str, records: &[ResultRecord]) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    out.push_str(concat!(
+        "<style>\n",
+        "  body { font-family: sans-serif; }\n",
+        "  table { border-collapse: collapse; }\n",
+        "  th, td { border: 1px solid #ccc; padding: 2px 8px; text-align: left; }\n",
+        "  td.duration { text-align: right; }\n",
+        "  tr.error td { color: #c33; }\n",
+        "</style>\n",
+        "</head>\n<body>\n"));
+
+    out.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    out.push_str(&format!("<p>{}</p>\n", escape_html(&Summary::new(records).line())));
+
+    out.push_str("<table>\n<tr><th>Problem</th><th>Answer</th><th>Duration (ms)</th><th>Status</th><th>Error</th></tr>\n");
+    for record in records {
+        let class = if record.error.is_empty() { "" } else { " class=\"error\"" };
+        out.push_str(&format!("<tr{}><td>{}</td><td>{}</td><td class=\"duration\">{:.3}</td><td>{}</td><td>{}</td></tr>\n",
+            class,
+            escape_html(&record.name),
+            escape_html(&record.answer),
+            record.duration_ms,
+            record.status.as_str(),
+            escape_html(&record.error)));
+    }
+    out.push_str("</table>\n");
+
+    out.push_str("<h2>Durations</h2>\n");
+    out.push_str(&svg_chart(records));
+    out.push_str("</body>\n</html>\n");
+    out
+}
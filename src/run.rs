@@ -1,17 +1,199 @@
+// Finding, running and timing the problems.
+//
+// Each problem with a timeout or memory limit runs on a thread of its own so
+// it can be given up on. Threads can't be killed, so a problem given up on
+// keeps running in the background until the process exits, and the problems
+// run after it share the CPU with it, which makes their timings slower than
+// they'd otherwise be. Solvers that can stop early check deadline() and
+// return on their own, problem23's search does. For timings that matter, run
+// slow problems on their own or without a timeout.
+
 use crate::aocbase::{AOCResult, AOCError};
+use crate::aocfetch;
+use crate::aocio::{self, LineError};
+use crate::log;
+use crate::memory;
+use crate::problems;
+use crate::run_history;
+use crate::registry::AocProblem;
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::path::Path;
 use std::fs::create_dir_all;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
 use std::time::{Instant, Duration};
 use regex::Regex;
 
 use crate::regex_ext::CapturesExt;
 use crate::regex_ext::RegexExt;
 
+// How often a problem with a memory limit is checked on.
+const MEMORY_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+thread_local! {
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+// When the running problem should wrap up by, if there's a timeout. Problems
+// that get closer to the answer as they go can check this and stop early,
+// with a timeout error saying how far they got, instead of getting cut off
+// with nothing. It's a little before the real timeout so there is time left
+// to hand that back.
+pub fn deadline() -> Option<Instant> {
+    DEADLINE.with(|deadline| deadline.get())
+}
+
+fn set_deadline(timeout: Option<Duration>) {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout.mul_f64(0.9));
+    DEADLINE.with(|d| d.set(deadline));
+}
+
+// A time limit for a whole run of problems. Once it's used up no more
+// problems are started, and the one running is cut off when it runs out.
+pub struct RunBudget {
+    deadline: Option<Instant>,
+}
+
+impl RunBudget {
+
+    pub fn new(limit: Option<Duration>) -> Self {
+        Self { deadline: limit.map(|limit| Instant::now() + limit) }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    pub fn is_spent(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    // The problem's own timeout, shortened to what's left of the budget.
+    pub fn limit_timeout(&self, timeout: Option<Duration>) -> Option<Duration> {
+        match (self.deadline, timeout) {
+            (None, timeout) => timeout,
+            (Some(deadline), None) => Some(deadline.saturating_duration_since(Instant::now())),
+            (Some(deadline), Some(timeout)) => Some(timeout.min(deadline.saturating_duration_since(Instant::now()))),
+        }
+    }
+}
+
+// Runs problems on a number of threads at once, handing each result back as
+// it comes in. Each problem is still timed on its own, but with more jobs
+// than cores they'll all take longer than they would one at a time.
+pub fn run_in_parallel(
+    problems: &[(&Problem, String)],
+    jobs: usize,
+    budget: &RunBudget,
+    timeout: Option<Duration>,
+    mut on_result: impl FnMut(ProblemResult))
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0 .. jobs.max(1) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || {
+                while let Some((p, input)) = problems.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let result = if budget.is_spent() {
+                        ProblemResult::not_run(&p.name, "Out of time for the run.")
+                    }
+                    else {
+                        p.execute(input, budget.limit_timeout(timeout), None)
+                    };
+                    if sender.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        // Only the workers have senders now, so this ends when they're done.
+        drop(sender);
+        for result in receiver {
+            on_result(result);
+        }
+    });
+}
+
+// Puts the problems that took longest last time at the end, so a limited run
+// gets through as many as it can. Ones without a time from last run go last.
+pub fn order_slowest_last(problems: &mut [&Problem], last_durations: &HashMap<String, f64>) {
+    problems.sort_by(|p1, p2| {
+        let d1 = last_durations.get(&p1.name).copied().unwrap_or(f64::MAX);
+        let d2 = last_durations.get(&p2.name).copied().unwrap_or(f64::MAX);
+        d1.total_cmp(&d2)
+    });
+}
+
+// Solves a problem from its input, on whichever worker thread picks it up.
+pub type Runner = dyn Fn(&String) -> AOCResult<String> + Send + Sync;
+
 pub struct Problem {
     pub name: String,
-    pub runner: Box<dyn Fn(&String) -> AOCResult<String>>,
+    pub runner: Arc<Runner>,
+
+    // From the registration, for --samples.
+    pub sample_answer: Option<String>,
+    pub sample_input: Option<String>,
+
+    // Whether --skip-invalid does anything for it.
+    pub skips_invalid: bool,
+}
+
+impl From<&'static dyn AocProblem> for Problem {
+    fn from(problem: &'static dyn AocProblem) -> Self {
+        Problem {
+            name: problem.name(),
+            runner: Arc::new(move |input: &String| problem.solve(input)),
+            sample_answer: problem.sample_answer().map(String::from),
+            sample_input: problem.sample_input().map(String::from),
+            skips_invalid: problem.skips_invalid(),
+        }
+    }
+}
+
+pub fn registered_problems() -> Vec<Problem> {
+    problems::registered().into_iter().map(Problem::from).collect()
+}
+
+// How much we know about whether an answer is right.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum AnswerStatus {
+    Unverified,
+    MatchesExpected,
+    AcceptedByAoc,
+    Wrong,
+}
+
+impl AnswerStatus {
+
+    pub fn as_str(&self) -> &'static str {
+        use AnswerStatus::*;
+        match self {
+            Unverified => "unverified",
+            MatchesExpected => "matches-expected",
+            AcceptedByAoc => "accepted-by-aoc",
+            Wrong => "wrong",
+        }
+    }
+
+    pub fn parse(s: impl AsRef<str>) -> AOCResult<AnswerStatus> {
+        use AnswerStatus::*;
+        Ok(match s.as_ref().trim() {
+            "" | "unverified" => Unverified,
+            "matches-expected" => MatchesExpected,
+            "accepted-by-aoc" => AcceptedByAoc,
+            "wrong" => Wrong,
+            other => return Err(AOCError::ParseError(format!("Invalid answer status: {}", other))),
+        })
+    }
 }
 
 pub struct ProblemResult {
@@ -19,33 +201,133 @@ pub struct ProblemResult {
     pub start: Instant,
     pub duration: Duration,
     pub result: AOCResult<String>,
+    pub status: AnswerStatus,
+    // Input lines that were skipped because of --skip-invalid.
+    pub skipped_lines: Vec<LineError>,
+    // The most bytes allocated at once while it ran, with the peak-memory feature.
+    pub peak_memory: Option<usize>,
 }
 
 impl ProblemResult {
 
+    pub fn not_run(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        ProblemResult {
+            name: name.into(),
+            start: Instant::now(),
+            duration: Duration::ZERO,
+            result: Err(AOCError::NotRun(reason.into())),
+            status: AnswerStatus::Unverified,
+            skipped_lines: Vec::new(),
+            peak_memory: None,
+        }
+    }
+
     pub fn get_duration_ms(&self) -> f64 {
         self.duration.as_micros() as f64 / 1000.0
     }
 
+    // Empty without a measurement, for the CSVs.
+    pub fn get_peak_memory_field(&self) -> String {
+        self.peak_memory.map(|bytes| bytes.to_string()).unwrap_or_default()
+    }
+
     pub fn to_stdout(&self) {
         println!("Finished: {}", &self.name);
         println!("Duration: {} milliseconds", self.get_duration_ms());
+        if let Some(bytes) = self.peak_memory {
+            println!("Peak memory: {}", run_history::format_size(bytes as u64));
+        }
         match &self.result {
             Ok(answer) => {
                 println!("Answer: {}", answer);
             },
+            Err(AOCError::Timeout(msg)) => {
+                println!("Timed out: {}", msg);
+            },
+            Err(AOCError::Unsupported(msg)) => {
+                println!("Unsupported: {}", msg);
+            },
+            Err(AOCError::NotRun(msg)) => {
+                println!("Not run: {}", msg);
+            },
             Err(e) => {
                 println!("Failed: {:?}", e);
             }
         }
+        if !self.skipped_lines.is_empty() {
+            print!("{}", aocio::line_errors_report(&self.skipped_lines));
+        }
+    }
+
+    // Just name<TAB>answer for scripts. A problem without an answer still
+    // gets its line, with the reason going to stderr.
+    pub fn to_stdout_answer_only(&self) {
+        match &self.result {
+            Ok(answer) => {
+                println!("{}\t{}", &self.name, answer);
+            },
+            Err(e) => {
+                println!("{}\t", &self.name);
+                eprintln!("{}: {}", &self.name, e);
+            }
+        }
     }
 }
 
 pub struct ProblemResults {
 }
 
+// A row of a results CSV, as it was written.
+#[derive(Debug, Clone)]
+pub struct ResultRecord {
+    pub name: String,
+    pub duration_ms: f64,
+    pub answer: String,
+    pub error: String,
+    pub status: AnswerStatus,
+    // Results written without the peak-memory feature, or before there was a
+    // PeakMemory column, don't have one.
+    pub peak_memory: Option<usize>,
+}
+
 impl ProblemResults {
 
+    // Every row, in the order they were written.
+    pub fn load(csv_path: impl AsRef<Path>) -> AOCResult<Vec<ResultRecord>> {
+        let csv_path = csv_path.as_ref();
+
+        if !csv_path.is_file() {
+            return Err(AOCError::ProcessingError(format!("No results found at {}, run some problems first.", csv_path.display())));
+        }
+
+        let mut records: Vec<ResultRecord> = Vec::new();
+
+        let mut csv_in = csv::Reader::from_path(csv_path)?;
+        for record in csv_in.deserialize() {
+            let record: HashMap<String, String> = record?;
+            let field = |name: &str| {
+                record
+                    .get(name)
+                    .cloned()
+                    .ok_or(AOCError::ParseError(format!("{} field not present.", name)))
+            };
+
+            records.push(ResultRecord {
+                name: field("Problem")?,
+                duration_ms: field("Duration")?.parse::<f64>()?,
+                answer: field("Answer")?,
+                error: record.get("Error").cloned().unwrap_or_default(),
+                status: AnswerStatus::parse(record.get("Status").map_or("", |status| status.as_str()))?,
+                peak_memory: match record.get("PeakMemory").filter(|bytes| !bytes.is_empty()) {
+                    None => None,
+                    Some(bytes) => Some(bytes.parse::<usize>()?),
+                },
+            });
+        }
+
+        Ok(records)
+    }
+
     pub fn load_answers(csv_path: impl AsRef<Path>) -> AOCResult<HashMap<String, String>> {
         let csv_path = csv_path.as_ref();
 
@@ -73,6 +355,65 @@ impl ProblemResults {
         Ok(answers)
     }
 
+    // How long each problem took, in milliseconds. Problems that didn't
+    // finish, like ones left out for time, have no duration worth keeping.
+    pub fn load_durations(csv_path: impl AsRef<Path>) -> AOCResult<HashMap<String, f64>> {
+        let csv_path = csv_path.as_ref();
+
+        if !csv_path.is_file() {
+            return Ok(HashMap::new());
+        }
+
+        let mut durations: HashMap<String, f64> = HashMap::new();
+
+        let mut csv_in = csv::Reader::from_path(csv_path)?;
+        for record in csv_in.deserialize() {
+            let record: HashMap<String, String> = record?;
+
+            let problem = record
+                .get("Problem")
+                .ok_or(AOCError::ParseError("Problem field not present.".into()))?;
+
+            if record.get("Error").is_some_and(|error| !error.is_empty()) {
+                continue;
+            }
+
+            let duration = record
+                .get("Duration")
+                .ok_or(AOCError::ParseError("Duration field not present.".into()))?;
+
+            durations.insert(problem.into(), duration.parse::<f64>()?);
+        }
+
+        Ok(durations)
+    }
+
+    // Result files written before the Status column existed just don't have any.
+    pub fn load_statuses(csv_path: impl AsRef<Path>) -> AOCResult<HashMap<String, AnswerStatus>> {
+        let csv_path = csv_path.as_ref();
+
+        if !csv_path.is_file() {
+            return Ok(HashMap::new());
+        }
+
+        let mut statuses: HashMap<String, AnswerStatus> = HashMap::new();
+
+        let mut csv_in = csv::Reader::from_path(csv_path)?;
+        for record in csv_in.deserialize() {
+            let record: HashMap<String, String> = record?;
+
+            let problem = record
+                .get("Problem")
+                .ok_or(AOCError::ParseError("Problem field not present.".into()))?;
+
+            if let Some(status) = record.get("Status") {
+                statuses.insert(problem.into(), AnswerStatus::parse(status)?);
+            }
+        }
+
+        Ok(statuses)
+    }
+
     pub fn write_csv(path: impl AsRef<Path>, results: &Vec<ProblemResult>) -> AOCResult<()> {
         let path = path.as_ref();
 
@@ -83,7 +424,7 @@ impl ProblemResults {
 
         let mut csv_out = csv::Writer::from_path(path)?;
 
-        csv_out.write_record(vec!["Problem", "Duration", "Answer", "Error"])?;
+        csv_out.write_record(vec!["Problem", "Duration", "Answer", "Error", "Status", "PeakMemory"])?;
 
         for result in results {
             match &result.result {
@@ -92,7 +433,9 @@ impl ProblemResults {
                         result.name.clone(),
                         result.get_duration_ms().to_string(),
                         answer.into(),
-                        "".into()
+                        "".into(),
+                        result.status.as_str().into(),
+                        result.get_peak_memory_field(),
                     ])?;
                 },
                 Err(e) => {
@@ -101,6 +444,8 @@ impl ProblemResults {
                         result.get_duration_ms().to_string(),
                         "".into(),
                         e.to_string(),
+                        result.status.as_str().into(),
+                        result.get_peak_memory_field(),
                     ])?;
                 }
             }
@@ -113,48 +458,187 @@ impl ProblemResults {
 
 impl Problem {
 
-    pub fn run(&self, input: &String) -> ProblemResult {
+    pub fn run(&self, input: &String, timeout: Option<Duration>, memory_limit: Option<usize>) -> ProblemResult {
         println!("--------------------------------------");
         println!("Starting: {}", self.name);
+        self.execute(input, timeout, memory_limit)
+    }
+
+    // Runs without printing anything about it.
+    pub fn execute(&self, input: &String, timeout: Option<Duration>, memory_limit: Option<usize>) -> ProblemResult {
+        let peak_memory = memory::PeakMemory::start();
         let start = Instant::now();
-        let result = (self.runner)(input);
+        let (result, skipped_lines) = match (timeout, memory_limit) {
+            (None, None) => {
+                set_deadline(None);
+                run_collecting_skipped(&self.name, &*self.runner, input)
+            },
+            _ => self.run_with_limits(input, timeout, memory_limit),
+        };
         let duration = start.elapsed();
         ProblemResult {
             name: self.name.clone(),
             start,
             duration,
-            result
+            result,
+            status: AnswerStatus::Unverified,
+            skipped_lines,
+            peak_memory: peak_memory.bytes(),
         }
     }
 
+    // The problem runs on its own thread so we can give up waiting on it.
+    // There isn't a way to kill the thread, so a problem that times out or
+    // goes over the memory limit keeps running in the background until the
+    // process exits. Each problem gets a memory run of its own, so nothing
+    // left running is counted against the problems after it.
+    fn run_with_limits(&self, input: &str, timeout: Option<Duration>, memory_limit: Option<usize>) -> (AOCResult<String>, Vec<LineError>) {
+        let (sender, receiver) = mpsc::channel();
+        let runner = self.runner.clone();
+        let name = self.name.clone();
+        let input = input.to_string();
+        let started = Instant::now();
+
+        let memory_run = memory_limit.map_or(0, memory::set_limit);
+
+        thread::spawn(move || {
+            memory::join_run(memory_run);
+            set_deadline(timeout);
+            // Nobody is listening anymore if we gave up on it.
+            let _ = sender.send(run_collecting_skipped(&name, &*runner, &input));
+        });
+
+        // With a memory limit we have to keep looking in on it.
+        let result = loop {
+            let mut wait = timeout.map_or(Duration::MAX, |timeout| timeout.saturating_sub(started.elapsed()));
+            if memory_limit.is_some() {
+                wait = wait.min(MEMORY_CHECK_INTERVAL);
+            }
+
+            match receiver.recv_timeout(wait) {
+                Ok(result) => break result,
+                Err(RecvTimeoutError::Timeout) if memory::limit_exceeded() => {
+                    let limit_mb = memory_limit.unwrap_or(0) / (1024 * 1024);
+                    break (Err(AOCError::ProcessingError(format!("Memory limit exceeded: over {} MB.", limit_mb))), Vec::new());
+                },
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(timeout) = timeout.filter(|timeout| started.elapsed() >= *timeout) {
+                        break (Err(AOCError::Timeout(format!("Did not finish within {} seconds.", timeout.as_secs_f64()))), Vec::new());
+                    }
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    break (Err(AOCError::ProcessingError("Problem stopped without a result.".into())), Vec::new());
+                }
+            }
+        };
+
+        memory::clear_limit();
+        result
+    }
+
+    // Missing inputs are downloaded when there's a session token to do it with.
     pub fn get_default_input(&self) -> AOCResult<String> {
         let p_num = parse_number(&self.name)?;
-        Ok(format!("input/input_{:0>2}.txt", p_num).into())
+        let input = format!("input/input_{:0>2}.txt", p_num);
+        aocfetch::ensure_input(p_num, &input)?;
+        Ok(input)
     }
 }
 
-pub fn parse_number(name: impl AsRef<str>) -> AOCResult<i32> {
-    Ok(Regex::new(r"(\d+)")?
-        .captures_must(name.as_ref())?
-        .get_group(1)?
-        .parse::<i32>()?)
+// Skipped lines and the problem messages are logged for are tracked per
+// thread, so they're set up on whatever thread the problem runs on.
+fn run_collecting_skipped(name: &str, runner: &Runner, input: &String) -> (AOCResult<String>, Vec<LineError>) {
+    let _span = log::enter_problem(name);
+    aocio::take_skipped_lines();
+    let result = runner(input);
+    (result, aocio::take_skipped_lines())
 }
 
-macro_rules! problems {
-    [$($problem:ident::$part:ident,)*] => {
-        vec![$(problem!($problem::$part),)*]
+// Picks problems by a comma separated list where each entry is one of:
+//   problem17::part2   an exact name
+//   problem17 or 17    both parts of a day
+//   5..10              both parts of days 5 through 10
+//   problem1*::part2   a pattern where * is anything and ? is one character
+// They come back in the order they were registered, each only once.
+pub fn select_problems<'a>(problems: &'a [Problem], selection: &str) -> AOCResult<Vec<&'a Problem>> {
+    let mut selected = vec![false; problems.len()];
+
+    for entry in selection.split(',').map(|entry| entry.trim()).filter(|entry| !entry.is_empty()) {
+        let matches_entry = problem_matcher(entry)?;
+        let mut found = false;
+
+        for (idx, p) in problems.iter().enumerate() {
+            if matches_entry(&p.name) {
+                selected[idx] = true;
+                found = true;
+            }
+        }
+
+        if !found {
+            return Err(AOCError::ParseError(format!("No problems match: {}", entry)));
+        }
     }
+
+    Ok(problems.iter().zip(selected).filter(|(_, s)| *s).map(|(p, _)| p).collect())
 }
 
-macro_rules! problem {
-    ($problem:ident::$part:ident) => {{
-        use problems::$problem;
-        use crate::run::Problem;
+type NameMatcher = Box<dyn Fn(&str) -> bool>;
 
-        let name = format!("{}::{}", stringify!($problem), stringify!($part)).to_string();
-        Problem {
-            name: name,
-            runner: Box::new(|input: &String| $problem::$part(input))
+fn problem_matcher(entry: &str) -> AOCResult<NameMatcher> {
+    let day = |name: &str| parse_number(name).ok();
+
+    if let Some((start, end)) = entry.split_once("..") {
+        let start = start.trim().parse::<i32>()?;
+        let end = end.trim().parse::<i32>()?;
+        if start > end {
+            return Err(AOCError::ParseError(format!("Invalid day range: {}", entry)));
         }
-    }}
-}
\ No newline at end of file
+        return Ok(Box::new(move |name| day(name).is_some_and(|d| d >= start && d <= end)));
+    }
+
+    if let Ok(only_day) = entry.parse::<i32>() {
+        return Ok(Box::new(move |name| day(name) == Some(only_day)));
+    }
+
+    // A day without a part means all of its parts.
+    let pattern = if entry.contains("::") { entry.to_string() } else { format!("{}::*", entry) };
+    Ok(Box::new(move |name| glob_match(&pattern, name)))
+}
+
+// Only * and ? are special.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Where to go back to if what came after the last * stops matching.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        }
+        else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        }
+        else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        }
+        else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+pub fn parse_number(name: impl AsRef<str>) -> AOCResult<i32> {
+    Ok(Regex::new(r"(\d+)")?
+        .captures_must(name.as_ref())?
+        .get_group(1)?
+        .parse::<i32>()?)
+}
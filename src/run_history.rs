@@ -0,0 +1,386 @@
+// Every run's results, kept in a directory with a file per run, so timings can
+// be compared over time. latest.csv and last.csv only go back one run, which
+// isn't enough to see whether a refactor slowed something down a few runs ago.
+//
+// Files are named <timestamp>-<run id>.csv. The run id is a short hash like a
+// git commit's, so a run can be talked about without quoting a timestamp.
+// Each run also notes the commit it was run at, when there's git to ask.
+//
+// Runs pile up, one per run forever, so there's pruning by age, by how many
+// to keep and down to the fastest run of each commit.
+
+use std::collections::BTreeMap;
+use std::fs::{self, create_dir_all};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::history;
+use crate::run::ProblemResult;
+use crate::sha256::sha256_hex;
+
+pub const DEFAULT_RUN_HISTORY_DIR: &str = "results/history";
+pub const DEFAULT_TREND_RUNS: usize = 5;
+
+const RUN_ID_LEN: usize = 7;
+
+// Changes smaller than this are put down to noise.
+const TREND_THRESHOLD_PERCENT: f64 = 5.0;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRun {
+    pub run_id: String,
+    pub timestamp: u64,
+    // Runs recorded before commits were kept, or without git, don't have one.
+    pub commit: Option<String>,
+    // Problem name to duration in milliseconds.
+    pub durations: BTreeMap<String, f64>,
+    pub path: PathBuf,
+}
+
+impl RecordedRun {
+
+    pub fn total_ms(&self) -> f64 {
+        self.durations.values().sum()
+    }
+
+    pub fn problems(&self) -> Vec<&str> {
+        self.durations.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+// How much the run history takes up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryUsage {
+    pub runs: usize,
+    pub bytes: u64,
+}
+
+impl HistoryUsage {
+
+    pub fn line(&self, dir: &Path) -> String {
+        format!("Run history: {} runs, {} in {}", self.runs, format_size(self.bytes), dir.display())
+    }
+}
+
+// Which runs to get rid of. A run goes if it's older than older_than or isn't
+// one of the last keep_last runs. With keep_best_per_commit the fastest run
+// of each commit always stays and the other runs of that commit go. Runs are
+// only up against runs of the same problems, so a quick run of one problem
+// doesn't beat a full run of the same commit.
+#[derive(Debug, Clone, Default)]
+pub struct PrunePolicy {
+    pub keep_last: Option<usize>,
+    pub keep_best_per_commit: bool,
+    pub older_than: Option<u64>,
+}
+
+impl PrunePolicy {
+
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none() && !self.keep_best_per_commit && self.older_than.is_none()
+    }
+
+    // The runs to remove, out of runs given oldest first.
+    pub fn select<'a>(&self, runs: &'a [RecordedRun]) -> Vec<&'a RecordedRun> {
+        let keep_from = self.keep_last.map_or(0, |keep_last| runs.len().saturating_sub(keep_last));
+
+        // The fastest run of each commit and set of problems, the later one
+        // on a tie.
+        let best_key = |run: &'a RecordedRun| run.commit.as_deref().map(|commit| (commit, run.problems()));
+        let mut best: BTreeMap<(&str, Vec<&str>), usize> = BTreeMap::new();
+        for (i, run) in runs.iter().enumerate() {
+            if let Some(key) = best_key(run) {
+                let best_i = best.entry(key).or_insert(i);
+                if run.total_ms() <= runs[*best_i].total_ms() {
+                    *best_i = i;
+                }
+            }
+        }
+
+        runs
+            .iter()
+            .enumerate()
+            .filter(|(i, run)| {
+                let is_best = best_key(run).is_some_and(|key| best[&key] == *i);
+                if self.keep_best_per_commit && is_best {
+                    return false;
+                }
+
+                *i < keep_from ||
+                    self.older_than.is_some_and(|older_than| run.timestamp < older_than) ||
+                    (self.keep_best_per_commit && run.commit.is_some())
+            })
+            .map(|(_, run)| run)
+            .collect()
+    }
+}
+
+pub struct RunHistory {
+    dir: PathBuf,
+}
+
+impl RunHistory {
+
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self { dir: dir.as_ref().to_path_buf() }
+    }
+
+    // Adds the run and gives back its id.
+    pub fn append(&self, results: &[ProblemResult]) -> AOCResult<String> {
+        create_dir_all(&self.dir)?;
+
+        let timestamp = history::now_timestamp();
+        let run_id = new_run_id(results);
+        let path = self.dir.join(format!("{:010}-{}.csv", timestamp, run_id));
+        let commit = current_commit().unwrap_or_default();
+
+        let mut csv_out = csv::Writer::from_path(path)?;
+        csv_out.write_record(["Problem", "Duration", "Answer", "Error", "Status", "Commit", "PeakMemory"])?;
+
+        for result in results {
+            let (answer, error) = match &result.result {
+                Ok(answer) => (answer.to_string(), String::new()),
+                Err(e) => (String::new(), e.to_string()),
+            };
+            csv_out.write_record([
+                result.name.clone(),
+                result.get_duration_ms().to_string(),
+                answer,
+                error,
+                result.status.as_str().into(),
+                commit.clone(),
+                result.get_peak_memory_field(),
+            ])?;
+        }
+        csv_out.flush()?;
+
+        Ok(run_id)
+    }
+
+    // Oldest first.
+    fn run_paths(&self) -> AOCResult<Vec<PathBuf>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "csv"))
+            .collect();
+
+        // The timestamps are padded so names sort in the order runs were made.
+        paths.sort();
+        Ok(paths)
+    }
+
+    // The most recent runs, oldest first.
+    pub fn last_runs(&self, count: usize) -> AOCResult<Vec<RecordedRun>> {
+        let paths = self.run_paths()?;
+        let skip = paths.len().saturating_sub(count);
+
+        paths[skip ..].iter().map(|path| load_run(path)).collect()
+    }
+
+    pub fn runs(&self) -> AOCResult<Vec<RecordedRun>> {
+        self.last_runs(usize::MAX)
+    }
+
+    pub fn usage(&self) -> AOCResult<HistoryUsage> {
+        let mut usage = HistoryUsage::default();
+        for path in self.run_paths()? {
+            usage.runs += 1;
+            usage.bytes += fs::metadata(&path)?.len();
+        }
+        Ok(usage)
+    }
+
+    // Removes the runs the policy picks, unless it's a dry run, and gives
+    // them back with how many bytes they took up.
+    pub fn prune(&self, policy: &PrunePolicy, dry_run: bool) -> AOCResult<(Vec<RecordedRun>, u64)> {
+        let runs = self.runs()?;
+        let mut removed: Vec<RecordedRun> = Vec::new();
+        let mut bytes = 0;
+
+        for run in policy.select(&runs) {
+            bytes += fs::metadata(&run.path)?.len();
+            if !dry_run {
+                fs::remove_file(&run.path)?;
+            }
+            removed.push(run.clone());
+        }
+
+        Ok((removed, bytes))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+// The short hash of the commit checked out, if this is a git checkout and git
+// is there to ask.
+fn current_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=7", "HEAD"])
+        .output()
+        .ok()?;
+
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !commit.is_empty() {
+        Some(commit)
+    }
+    else {
+        None
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0 ..= 1023 => format!("{} B", bytes),
+        1024 ..= 1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn new_run_id(results: &[ProblemResult]) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let mut seed = format!("{}:{}", nanos, process::id());
+    for result in results {
+        seed.push_str(&format!(":{}={}", result.name, result.get_duration_ms()));
+    }
+
+    sha256_hex(seed.as_bytes())[.. RUN_ID_LEN].to_string()
+}
+
+fn load_run(path: &Path) -> AOCResult<RecordedRun> {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| AOCError::ParseError(format!("Invalid run history file name: {}", path.display())))?;
+
+    let (timestamp, run_id) = stem
+        .split_once('-')
+        .ok_or_else(|| AOCError::ParseError(format!("Invalid run history file name: {}", path.display())))?;
+
+    let mut durations: BTreeMap<String, f64> = BTreeMap::new();
+    let mut commit: Option<String> = None;
+
+    let mut csv_in = csv::Reader::from_path(path)?;
+    for record in csv_in.records() {
+        let record = record?;
+
+        if let Some(run_commit) = record.get(5).filter(|run_commit| !run_commit.is_empty()) {
+            commit = Some(run_commit.to_string());
+        }
+
+        let problem = record
+            .get(0)
+            .ok_or(AOCError::ParseError("Problem field not present.".into()))?;
+
+        let duration = record
+            .get(1)
+            .ok_or(AOCError::ParseError("Duration field not present.".into()))?
+            .parse::<f64>()?;
+
+        durations.insert(problem.into(), duration);
+    }
+
+    Ok(RecordedRun {
+        run_id: run_id.to_string(),
+        timestamp: timestamp.parse::<u64>()?,
+        commit,
+        durations,
+        path: path.to_path_buf(),
+    })
+}
+
+fn percent_change(from: f64, to: f64) -> Option<f64> {
+    if from > 0.0 {
+        Some((to - from) / from * 100.0)
+    }
+    else {
+        None
+    }
+}
+
+fn format_change(from: Option<f64>, to: Option<f64>) -> String {
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return "-".into(),
+    };
+
+    let delta = to - from;
+    match percent_change(from, to) {
+        Some(percent) if percent.abs() >= TREND_THRESHOLD_PERCENT => format!("{:+.1} ms ({:+.0}%)", delta, percent),
+        Some(_) => format!("{:+.1} ms (~)", delta),
+        None => format!("{:+.1} ms", delta),
+    }
+}
+
+// A row per problem with its duration in each run, then how the latest run
+// compares with the one before it and with the oldest shown. Only problems
+// in names are included, in that order.
+pub fn trend_report(runs: &[RecordedRun], names: &[&str]) -> String {
+    if runs.is_empty() {
+        return "No runs recorded yet.\n".into();
+    }
+
+    let mut out = String::new();
+
+    for run in runs {
+        out.push_str(&format!("{}  {}\n", run.run_id, history::format_timestamp(run.timestamp)));
+    }
+    out.push('\n');
+
+    let name_width = names.iter().map(|name| name.len()).max().unwrap_or(0).max("Problem".len());
+
+    out.push_str(&format!("{:<width$}", "Problem", width = name_width));
+    for run in runs {
+        out.push_str(&format!(" {:>10}", run.run_id));
+    }
+    out.push_str(&format!(" {:>20} {:>20}\n", "vs previous", "vs oldest"));
+
+    for name in names {
+        let durations: Vec<Option<f64>> = runs.iter().map(|run| run.durations.get(*name).copied()).collect();
+        if durations.iter().all(|duration| duration.is_none()) {
+            continue;
+        }
+
+        out.push_str(&format!("{:<width$}", name, width = name_width));
+        for duration in &durations {
+            match duration {
+                Some(duration) => out.push_str(&format!(" {:>10.1}", duration)),
+                None => out.push_str(&format!(" {:>10}", "-")),
+            }
+        }
+
+        let latest = durations[durations.len() - 1];
+        let previous = if durations.len() > 1 { durations[durations.len() - 2] } else { None };
+        out.push_str(&format!(" {:>20} {:>20}\n", format_change(previous, latest), format_change(durations[0], latest)));
+    }
+
+    out
+}
+
+// A line per run that was, or would be, pruned.
+pub fn prune_report(removed: &[RecordedRun], bytes: u64, dry_run: bool) -> String {
+    let mut out = String::new();
+    for run in removed {
+        out.push_str(&format!("{} {}  {}  {:.1} ms\n",
+            if dry_run { "Would remove" } else { "Removed" },
+            run.run_id,
+            history::format_timestamp(run.timestamp),
+            run.total_ms()));
+    }
+    out.push_str(&format!("{} runs {}, freeing {}.\n",
+        removed.len(),
+        if dry_run { "would be removed" } else { "removed" },
+        format_size(bytes)));
+    out
+}
@@ -0,0 +1,92 @@
+// Running problems against the examples in the puzzle text, where the answer
+// is known up front. Each day's example goes in input/sample_NN.txt, or
+// input/sample_NN_partP.txt when a part has its own. The older
+// input/input_NN_test.txt files are used when there's no sample file, and a
+// problem can register an input = "path" for one somewhere else.
+//
+// Expected answers come from the example answers problems register, and can
+// be added to or overridden in input/samples.toml, which looks like:
+//
+//   [problem1]
+//   part2 = "281"
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::run::{parse_number, Problem};
+use crate::verification::ExpectedAnswers;
+
+pub const SAMPLE_ANSWERS_FILE: &str = "input/samples.toml";
+
+// The registered input wins over the usual places.
+pub fn sample_input_for(problem: &Problem) -> AOCResult<Option<PathBuf>> {
+    if let Some(sample_input) = &problem.sample_input {
+        let path = PathBuf::from(sample_input);
+        return Ok(Some(path).filter(|path| path.is_file()));
+    }
+
+    let (day, part) = problem.name
+        .split_once("::")
+        .ok_or_else(|| AOCError::ParseError(format!("Not a problem name: {}", problem.name)))?;
+    Ok(sample_input(parse_number(day)?, parse_number(part)?))
+}
+
+pub fn sample_input(day: i32, part: i32) -> Option<PathBuf> {
+    [
+        format!("input/sample_{:0>2}_part{}.txt", day, part),
+        format!("input/sample_{:0>2}.txt", day),
+        format!("input/input_{:0>2}_test.txt", day),
+    ]
+    .into_iter()
+    .map(PathBuf::from)
+    .find(|path| path.is_file())
+}
+
+// Only the bit of TOML needed here: [problemN] tables of partN = "answer"
+// lines, and # comments.
+pub fn parse_sample_answers(text: &str) -> AOCResult<HashMap<String, String>> {
+    let mut answers: HashMap<String, String> = HashMap::new();
+    let mut table: Option<String> = None;
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: &str| AOCError::ParseError(format!("line {}: {}: {}", line_index + 1, reason, line));
+
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            table = Some(name.trim().to_string());
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| invalid("Expected part = \"answer\""))?;
+        let table = table.as_ref().ok_or_else(|| invalid("Answer before any [problem] table"))?;
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .ok_or_else(|| invalid("Answers must be quoted"))?;
+
+        answers.insert(format!("{}::{}", table, key.trim()), value.to_string());
+    }
+
+    Ok(answers)
+}
+
+pub fn load_expected(problems: &[&Problem], answers_file: impl AsRef<Path>) -> AOCResult<ExpectedAnswers> {
+    let mut answers: HashMap<String, String> = problems
+        .iter()
+        .filter_map(|problem| problem.sample_answer.clone().map(|answer| (problem.name.clone(), answer)))
+        .collect();
+
+    let answers_file = answers_file.as_ref();
+    if answers_file.is_file() {
+        answers.extend(parse_sample_answers(&read_to_string(answers_file)?)?);
+    }
+
+    Ok(ExpectedAnswers::from_answers(answers))
+}
@@ -0,0 +1,143 @@
+
+// Global settings from the command line that problems can look at.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::ParsePolicy;
+use crate::log::{self, LogFilter};
+
+// Values given with --param name=value. Problems look up the ones they know
+// about by name, like problem8.start, and use them in place of the puzzle's
+// usual values.
+#[derive(Debug, Clone, Default)]
+pub struct ProblemParams {
+    values: HashMap<String, String>,
+}
+
+impl ProblemParams {
+
+    pub fn parse(entries: &[String]) -> AOCResult<Self> {
+        let mut values: HashMap<String, String> = HashMap::new();
+
+        for entry in entries {
+            let (name, value) = entry
+                .split_once('=')
+                .ok_or_else(|| AOCError::ParseError(format!("Invalid param, expected name=value: {}", entry)))?;
+            values.insert(name.trim().to_string(), value.trim().to_string());
+        }
+
+        Ok(Self { values })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> AOCResult<T> {
+        match self.get(name) {
+            None => Ok(default),
+            Some(value) => value
+                .parse::<T>()
+                .map_err(|_| AOCError::ParseError(format!("Invalid value for param {}: {}", name, value))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    // How much to print about how problems are being solved, overall and per problem.
+    pub log_filter: LogFilter,
+
+    // Force problems to use the strategy with this name if they have one.
+    pub strategy: Option<String>,
+
+    // Cross check answers with slower, simpler solutions where problems can.
+    pub verify: bool,
+
+    // Work everything out again instead of using cached preprocessing.
+    pub no_cache: bool,
+
+    // Check solvers that take shortcuts against a direct simulation of a small case first.
+    pub self_test: bool,
+
+    // Show how answers are worked out, for problems that can.
+    pub explain: bool,
+
+    // What line based parsers that support it do with lines they can't parse.
+    pub parse_policy: ParsePolicy,
+
+    // Named values for problems that can be asked different questions.
+    pub params: ProblemParams,
+
+    // Session cookie for downloading inputs from adventofcode.com.
+    pub session: Option<String>,
+
+    // Show how far along slow solvers are.
+    pub progress: bool,
+
+    // Where simulations draw their frames, a GIF file name or - for the terminal.
+    pub animate: Option<String>,
+}
+
+lazy_static! {
+    static ref SETTINGS: RwLock<Settings> = RwLock::new(Settings::default());
+}
+
+// All of them, to change some and set them back after.
+pub fn current() -> Settings {
+    SETTINGS.read().unwrap().clone()
+}
+
+pub fn set(settings: Settings) {
+    log::set_max_level(settings.log_filter.max_level());
+    *SETTINGS.write().unwrap() = settings;
+}
+
+pub fn log_filter() -> LogFilter {
+    SETTINGS.read().unwrap().log_filter.clone()
+}
+
+pub fn strategy() -> Option<String> {
+    SETTINGS.read().unwrap().strategy.clone()
+}
+
+pub fn verify() -> bool {
+    SETTINGS.read().unwrap().verify
+}
+
+pub fn no_cache() -> bool {
+    SETTINGS.read().unwrap().no_cache
+}
+
+pub fn self_test() -> bool {
+    SETTINGS.read().unwrap().self_test
+}
+
+pub fn explain() -> bool {
+    SETTINGS.read().unwrap().explain
+}
+
+pub fn parse_policy() -> ParsePolicy {
+    SETTINGS.read().unwrap().parse_policy
+}
+
+pub fn params() -> ProblemParams {
+    SETTINGS.read().unwrap().params.clone()
+}
+
+pub fn session() -> Option<String> {
+    SETTINGS.read().unwrap().session.clone()
+}
+
+pub fn progress() -> bool {
+    SETTINGS.read().unwrap().progress
+}
+
+pub fn animate() -> Option<String> {
+    SETTINGS.read().unwrap().animate.clone()
+}
@@ -0,0 +1,145 @@
+// Pictures problems draw of their example inputs, checked against copies kept
+// in tests/snapshots so a change to how something is drawn, or to the grid and
+// geometry code under it, doesn't go unnoticed. After a change that's meant,
+// the snapshots subcommand with --update writes them all out again.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::aocbase::AOCResult;
+use crate::problems::{problem10, problem11, problem13, problem14, problem18, problem20};
+
+pub const SNAPSHOT_DIR: &str = "tests/snapshots";
+
+pub struct Snapshot {
+    // Like problem10_pipe_map, which is also the file name.
+    pub name: &'static str,
+    pub day: u32,
+    pub render: fn() -> AOCResult<String>,
+}
+
+impl Snapshot {
+
+    pub fn path(&self) -> PathBuf {
+        Path::new(SNAPSHOT_DIR).join(format!("{}.txt", self.name))
+    }
+}
+
+pub fn snapshots() -> Vec<Snapshot> {
+    vec![
+        Snapshot {
+            name: "problem10_pipe_map",
+            day: 10,
+            render: || Ok(problem10::PipeMap::parse("input/input_10_test3.txt")?.render()),
+        },
+        Snapshot {
+            name: "problem11_space_map",
+            day: 11,
+            render: || Ok(problem11::SpaceMap::parse("input/input_11_test.txt")?.render()),
+        },
+        Snapshot {
+            name: "problem13_island_maps",
+            day: 13,
+            render: || {
+                let island_maps = problem13::IslandMap::parse_all("input/input_13_test.txt")?;
+                Ok(island_maps.iter().map(|island_map| island_map.render()).collect::<Vec<String>>().join("\n"))
+            },
+        },
+        Snapshot {
+            name: "problem14_mirror_platform",
+            day: 14,
+            render: || Ok(problem14::MirrorPlatform::parse("input/input_14_test.txt")?.render()),
+        },
+        Snapshot {
+            name: "problem18_dig_site",
+            day: 18,
+            render: || Ok(problem18::DigSite::parse("input/input_18_test.txt", false)?.render()),
+        },
+        Snapshot {
+            name: "problem18_dig_site_part2",
+            day: 18,
+            render: || Ok(problem18::DigSite::parse("input/input_18_test.txt", true)?.render_scaled(60, 30)),
+        },
+        Snapshot {
+            name: "problem20_module_graph",
+            day: 20,
+            render: || Ok(problem20::Modules::parse("input/input_20_test.txt")?.to_dot()),
+        },
+    ]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotStatus {
+    Matches,
+    Written,
+    Missing,
+    // The first line that's different, counting from 1.
+    Differs(usize),
+}
+
+#[derive(Debug)]
+pub struct SnapshotResult {
+    pub name: &'static str,
+    pub status: AOCResult<SnapshotStatus>,
+}
+
+pub fn check(snapshot: &Snapshot, update: bool) -> AOCResult<SnapshotStatus> {
+    let rendered = (snapshot.render)()?;
+    let path = snapshot.path();
+
+    if update {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &rendered)?;
+        return Ok(SnapshotStatus::Written);
+    }
+
+    if !path.is_file() {
+        return Ok(SnapshotStatus::Missing);
+    }
+
+    let expected = fs::read_to_string(&path)?;
+    if expected == rendered {
+        return Ok(SnapshotStatus::Matches);
+    }
+
+    let line_count = expected.lines().count().max(rendered.lines().count());
+    let differs_at = (0 .. line_count)
+        .find(|i| expected.lines().nth(*i) != rendered.lines().nth(*i))
+        .unwrap_or(line_count);
+
+    Ok(SnapshotStatus::Differs(differs_at + 1))
+}
+
+pub fn check_all(snapshots: &[Snapshot], update: bool) -> Vec<SnapshotResult> {
+    snapshots
+        .iter()
+        .map(|snapshot| SnapshotResult { name: snapshot.name, status: check(snapshot, update) })
+        .collect()
+}
+
+pub fn failure_count(results: &[SnapshotResult]) -> usize {
+    results
+        .iter()
+        .filter(|result| !matches!(result.status, Ok(SnapshotStatus::Matches) | Ok(SnapshotStatus::Written)))
+        .count()
+}
+
+pub fn report(results: &[SnapshotResult]) -> String {
+    let mut out = String::new();
+
+    for result in results {
+        let line = match &result.status {
+            Ok(SnapshotStatus::Matches) => format!("PASS {}\n", result.name),
+            Ok(SnapshotStatus::Written) => format!("WROTE {}\n", result.name),
+            Ok(SnapshotStatus::Missing) => format!("FAIL {}: no snapshot, run with --update to write it\n", result.name),
+            Ok(SnapshotStatus::Differs(line)) => format!("FAIL {}: differs from line {}\n", result.name, line),
+            Err(e) => format!("FAIL {}: {}\n", result.name, e),
+        };
+        out.push_str(&line);
+    }
+
+    out.push_str(&format!("Checked: {} Failed: {}\n", results.len(), failure_count(results)));
+    out
+}
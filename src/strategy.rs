@@ -0,0 +1,54 @@
+
+// Some problems have more than one way to solve them. A problem lists its
+// strategies by name and gives a heuristic that looks at the input to pick
+// one. The --strategy flag forces a strategy by name for any problem that
+// has one called that, otherwise the heuristic still decides.
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::settings;
+
+pub type StrategyFn<I, R> = fn(&I) -> AOCResult<R>;
+
+pub struct Strategies<I, R> {
+    problem: String,
+    strategies: Vec<(&'static str, StrategyFn<I, R>)>,
+}
+
+impl<I, R> Strategies<I, R> {
+
+    pub fn new(problem: impl Into<String>) -> Self {
+        Self { problem: problem.into(), strategies: Vec::new() }
+    }
+
+    pub fn add(mut self, name: &'static str, strategy: StrategyFn<I, R>) -> Self {
+        self.strategies.push((name, strategy));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.strategies.iter().map(|(name, _)| *name).collect()
+    }
+
+    fn find(&self, name: &str) -> Option<&(&'static str, StrategyFn<I, R>)> {
+        self.strategies.iter().find(|(n, _)| *n == name)
+    }
+
+    // Runs the forced strategy if there is one for this problem, otherwise the one picked by choose.
+    pub fn run(&self, input: &I, choose: impl FnOnce(&I) -> &'static str) -> AOCResult<R> {
+        let forced = settings::strategy().and_then(|name| self.find(&name));
+
+        let (name, strategy) = match forced {
+            Some(found) => found,
+            None => {
+                let chosen = choose(input);
+                self.find(chosen).ok_or_else(|| {
+                    AOCError::ProcessingError(format!("{} has no strategy named: {}", self.problem, chosen))
+                })?
+            }
+        };
+
+        tracing::debug!("{} strategy: {} (available: {})", self.problem, name, self.names().join(", "));
+
+        strategy(input)
+    }
+}
@@ -0,0 +1,143 @@
+// Checking a day's strategies against each other on random inputs from gen,
+// for the stress subcommand. Each input is solved with every strategy of
+// each part that has more than one, and they all have to give the same
+// answer, or all fail. The first input they don't agree on is saved to
+// artifacts/stress so it can be run again with --input.
+//
+// Inputs are made from seed, seed + 1 and so on, so a run can be repeated
+// by giving the same seed.
+
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::{artifact_path, InputSource};
+use crate::gen;
+use crate::problems;
+use crate::settings;
+
+pub struct StressTest {
+    pub day: u32,
+    // Each part with strategies and their names.
+    pub parts: &'static [(u32, &'static [&'static str])],
+    pub generate: fn(&mut StdRng) -> String,
+}
+
+pub fn stress_tests() -> Vec<StressTest> {
+    vec![
+        StressTest { day: 5, parts: &[(1, &["direct", "flattened"])], generate: gen::problem5 },
+        StressTest { day: 7, parts: &[(1, &["sorted", "streaming"]), (2, &["sorted", "streaming"])], generate: gen::problem7 },
+        StressTest { day: 8, parts: &[(1, &["walk", "bidirectional"])], generate: gen::problem8 },
+        StressTest { day: 14, parts: &[(2, &["grid", "segments"])], generate: gen::problem14 },
+        StressTest { day: 16, parts: &[(2, &["graph", "simulate", "parallel"])], generate: gen::problem16 },
+        StressTest { day: 17, parts: &[(1, &["astar", "buckets"]), (2, &["astar", "buckets"])], generate: gen::problem17 },
+        StressTest { day: 22, parts: &[(2, &["graph", "simulate"])], generate: gen::problem22 },
+        StressTest {
+            day: 23,
+            parts: &[(1, &["exhaustive", "bounded", "bitmask"]), (2, &["exhaustive", "bounded", "bitmask"])],
+            generate: gen::problem23,
+        },
+    ]
+}
+
+pub fn stress_test_for(day: u32) -> AOCResult<StressTest> {
+    stress_tests()
+        .into_iter()
+        .find(|stress_test| stress_test.day == day)
+        .ok_or_else(|| AOCError::ProcessingError(format!("problem{} has nothing to stress test.", day)))
+}
+
+// An input the strategies for a part didn't agree on.
+#[derive(Debug)]
+pub struct Disagreement {
+    pub seed: u64,
+    pub part: u32,
+    pub input: String,
+    // Each strategy's answer, or its error.
+    pub answers: Vec<(&'static str, AOCResult<String>)>,
+}
+
+#[derive(Debug)]
+pub struct StressReport {
+    pub inputs: usize,
+    pub disagreement: Option<Disagreement>,
+}
+
+impl StressTest {
+
+    pub fn generate(&self, seed: u64) -> String {
+        (self.generate)(&mut StdRng::seed_from_u64(seed))
+    }
+
+    // Each strategy's answer for a part. A solver that panics is an error
+    // like any other.
+    pub fn answers(&self, part: u32, strategies: &[&'static str], input: &str) -> AOCResult<Vec<(&'static str, AOCResult<String>)>> {
+        let solution = problems::registered()
+            .into_iter()
+            .find(|problem| problem.day() == self.day && problem.part() == part)
+            .ok_or_else(|| AOCError::ProcessingError(format!("There's no part {} for day {}.", part, self.day)))?;
+
+        let before = settings::current();
+        let answers = strategies
+            .iter()
+            .map(|strategy| {
+                // Random inputs shouldn't fill up the cache.
+                settings::set(settings::Settings { strategy: Some(strategy.to_string()), no_cache: true, ..before.clone() });
+                let answer = panic::catch_unwind(AssertUnwindSafe(|| solution.solve(&InputSource::text(input))))
+                    .unwrap_or_else(|_| Err(AOCError::ProcessingError("Panicked.".into())));
+                (*strategy, answer)
+            })
+            .collect();
+        settings::set(before);
+
+        Ok(answers)
+    }
+
+    // The strategies for each part on the input from one seed.
+    pub fn check(&self, seed: u64) -> AOCResult<Option<Disagreement>> {
+        let input = self.generate(seed);
+
+        for (part, strategies) in self.parts {
+            let answers = self.answers(*part, strategies, &input)?;
+            if !agree(&answers) {
+                return Ok(Some(Disagreement { seed, part: *part, input, answers }));
+            }
+        }
+        Ok(None)
+    }
+
+    // Checks inputs until the time's up, at least one, or until one doesn't agree.
+    pub fn run(&self, seed: u64, duration: Duration) -> AOCResult<StressReport> {
+        let started = Instant::now();
+        let mut inputs = 0;
+
+        loop {
+            let disagreement = self.check(seed.wrapping_add(inputs as u64))?;
+            inputs += 1;
+            if disagreement.is_some() || started.elapsed() >= duration {
+                return Ok(StressReport { inputs, disagreement });
+            }
+        }
+    }
+}
+
+// The same answer from each, or errors from them all.
+fn agree(answers: &[(&'static str, AOCResult<String>)]) -> bool {
+    let oks: Vec<&String> = answers.iter().filter_map(|(_, answer)| answer.as_ref().ok()).collect();
+    oks.is_empty() || (oks.len() == answers.len() && oks.iter().all(|answer| *answer == oks[0]))
+}
+
+impl Disagreement {
+
+    pub fn save(&self, day: u32) -> AOCResult<PathBuf> {
+        let path = artifact_path("stress")?.join(format!("problem{}_seed{}.txt", day, self.seed));
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, &self.input)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,154 @@
+
+// A live table of the problems as they run, drawn with plain ANSI escape
+// codes. It draws on the terminal's alternate screen so anything problems
+// print themselves gets wiped by the next frame, then prints the final
+// table normally once everything is done.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::aocbase::AOCError;
+use crate::run::ProblemResult;
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const FRAME_TIME: Duration = Duration::from_millis(100);
+const ANSWER_WIDTH: usize = 40;
+
+enum RowState {
+    Waiting,
+    Running(Instant),
+    Finished { duration: Duration, text: String, ok: bool },
+}
+
+struct Row {
+    name: String,
+    state: RowState,
+}
+
+pub struct Dashboard {
+    rows: Arc<Mutex<Vec<Row>>>,
+    done: Arc<AtomicBool>,
+    start: Instant,
+    render_thread: Option<JoinHandle<()>>,
+}
+
+impl Dashboard {
+
+    pub fn start(names: &[&str]) -> Self {
+        let rows: Vec<Row> = names
+            .iter()
+            .map(|name| Row { name: name.to_string(), state: RowState::Waiting })
+            .collect();
+
+        let rows = Arc::new(Mutex::new(rows));
+        let done = Arc::new(AtomicBool::new(false));
+        let start = Instant::now();
+
+        let render_thread = {
+            let rows = rows.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                // Switch to the alternate screen and hide the cursor.
+                print!("\x1b[?1049h\x1b[?25l");
+                let mut frame = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let table = render(&rows.lock().unwrap(), start.elapsed(), Some(frame));
+                    print!("\x1b[2J\x1b[H{}", table);
+                    let _ = io::stdout().flush();
+                    frame += 1;
+                    thread::sleep(FRAME_TIME);
+                }
+                // Back to the normal screen with the cursor showing.
+                print!("\x1b[?25h\x1b[?1049l");
+                let _ = io::stdout().flush();
+            })
+        };
+
+        Self { rows, done, start, render_thread: Some(render_thread) }
+    }
+
+    pub fn started(&self, idx: usize) {
+        self.rows.lock().unwrap()[idx].state = RowState::Running(Instant::now());
+    }
+
+    pub fn finished(&self, idx: usize, result: &ProblemResult) {
+        let (text, ok) = match &result.result {
+            Ok(answer) => (answer.clone(), true),
+            Err(AOCError::Timeout(_)) => ("timed out".to_string(), false),
+            Err(AOCError::Unsupported(msg)) => (format!("unsupported: {}", msg), false),
+            Err(AOCError::NotRun(_)) => ("not run".to_string(), false),
+            Err(e) => (format!("failed: {}", e), false),
+        };
+        self.rows.lock().unwrap()[idx].state = RowState::Finished { duration: result.duration, text, ok };
+    }
+
+    pub fn stop(mut self) {
+        self.stop_rendering();
+        print!("{}", render(&self.rows.lock().unwrap(), self.start.elapsed(), None));
+    }
+
+    // Puts the terminal back the way it was.
+    fn stop_rendering(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(render_thread) = self.render_thread.take() {
+            let _ = render_thread.join();
+        }
+    }
+}
+
+// A run that ends early with an error still leaves the alternate screen.
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.stop_rendering();
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    }
+    else {
+        let mut s: String = text.chars().take(width - 3).collect();
+        s.push_str("...");
+        s
+    }
+}
+
+// frame is None for the final table, which has nothing left to spin.
+fn render(rows: &[Row], elapsed: Duration, frame: Option<usize>) -> String {
+    let mut out = String::new();
+
+    let finished = rows.iter().filter(|row| matches!(row.state, RowState::Finished { .. })).count();
+    let failed = rows.iter().filter(|row| matches!(row.state, RowState::Finished { ok: false, .. })).count();
+    let running = rows.iter().filter(|row| matches!(row.state, RowState::Running(_))).count();
+
+    out.push_str(&format!(
+        "Finished {}/{}  running: {}  failed: {}  elapsed: {:.1}s\n\n",
+        finished, rows.len(), running, failed, elapsed.as_secs_f64()));
+
+    out.push_str(&format!("  {:<18} {:>12}  {}\n", "Problem", "Time (ms)", "Answer"));
+
+    for row in rows {
+        let line = match &row.state {
+            RowState::Waiting => {
+                format!("  \x1b[2m{:<18} {:>12}\x1b[0m\n", row.name, "-")
+            },
+            RowState::Running(started) => {
+                let spinner = frame.map(|f| SPINNER[f % SPINNER.len()]).unwrap_or(' ');
+                format!("{} \x1b[33m{:<18} {:>12.0}\x1b[0m\n",
+                    spinner, row.name, started.elapsed().as_secs_f64() * 1000.0)
+            },
+            RowState::Finished { duration, text, ok } => {
+                let (mark, color) = if *ok { ('+', "32") } else { ('x', "31") };
+                format!("{} \x1b[{}m{:<18} {:>12.3}  {}\x1b[0m\n",
+                    mark, color, row.name, duration.as_micros() as f64 / 1000.0, truncate(text, ANSWER_WIDTH))
+            },
+        };
+        out.push_str(&line);
+    }
+
+    out
+}
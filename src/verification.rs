@@ -0,0 +1,115 @@
+// Checking answers against a file of answers known to be right, so a run
+// can be used as a regression test. The file is a CSV with Problem and
+// Answer columns, so a results file from a good run works as one.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::run::{AnswerStatus, ProblemResult};
+
+pub struct ExpectedAnswers {
+    answers: HashMap<String, String>,
+}
+
+impl ExpectedAnswers {
+
+    pub fn load(csv_path: impl AsRef<Path>) -> AOCResult<Self> {
+        let csv_path = csv_path.as_ref();
+        let mut answers: HashMap<String, String> = HashMap::new();
+
+        let mut csv_in = csv::Reader::from_path(csv_path)?;
+        for record in csv_in.deserialize() {
+            let record: HashMap<String, String> = record?;
+
+            let problem = record
+                .get("Problem")
+                .ok_or(AOCError::ParseError("Problem field not present.".into()))?;
+
+            let answer = record
+                .get("Answer")
+                .ok_or(AOCError::ParseError("Answer field not present.".into()))?;
+
+            // A blank answer isn't known yet.
+            if !answer.is_empty() {
+                answers.insert(problem.into(), answer.into());
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    pub fn from_answers(answers: HashMap<String, String>) -> Self {
+        Self { answers }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.answers.get(name).map(|answer| answer.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String, actual: String },
+    // Nothing to check it against, which isn't a failure.
+    NoExpected,
+}
+
+pub struct Verification {
+    pub name: String,
+    pub verdict: Verdict,
+}
+
+// A problem that didn't give an answer fails if there was one expected.
+pub fn verify(results: &[ProblemResult], expected: &ExpectedAnswers) -> Vec<Verification> {
+    results
+        .iter()
+        .map(|result| {
+            let verdict = match (expected.get(&result.name), &result.result) {
+                (None, _) => Verdict::NoExpected,
+                (Some(expected), Ok(answer)) if expected == answer => Verdict::Pass,
+                (Some(expected), Ok(answer)) => Verdict::Fail { expected: expected.into(), actual: answer.clone() },
+                (Some(expected), Err(e)) => Verdict::Fail { expected: expected.into(), actual: e.to_string() },
+            };
+            Verification { name: result.name.clone(), verdict }
+        })
+        .collect()
+}
+
+// Answers that pass are known to be right, ones that fail are known wrong.
+// Being accepted by AOC is still the better thing to know.
+pub fn apply_statuses(results: &mut [ProblemResult], verifications: &[Verification]) {
+    for (result, verification) in results.iter_mut().zip(verifications) {
+        match verification.verdict {
+            Verdict::Pass if result.status != AnswerStatus::AcceptedByAoc => result.status = AnswerStatus::MatchesExpected,
+            Verdict::Fail { .. } if result.result.is_ok() => result.status = AnswerStatus::Wrong,
+            _ => {},
+        }
+    }
+}
+
+pub fn failure_count(verifications: &[Verification]) -> usize {
+    verifications.iter().filter(|v| matches!(v.verdict, Verdict::Fail { .. })).count()
+}
+
+pub fn report(verifications: &[Verification]) -> String {
+    let mut out = String::new();
+
+    for verification in verifications {
+        let line = match &verification.verdict {
+            Verdict::Pass => format!("PASS {}\n", verification.name),
+            Verdict::Fail { expected, actual } => format!("FAIL {}: expected {} but got {}\n", verification.name, expected, actual),
+            Verdict::NoExpected => format!("---- {}: no expected answer\n", verification.name),
+        };
+        out.push_str(&line);
+    }
+
+    let passed = verifications.iter().filter(|v| v.verdict == Verdict::Pass).count();
+    out.push_str(&format!("Passed: {} Failed: {} Unchecked: {}\n",
+        passed,
+        failure_count(verifications),
+        verifications.len() - passed - failure_count(verifications)));
+
+    out
+}
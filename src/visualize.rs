@@ -0,0 +1,742 @@
+// Pictures of what problems work on, a grid of coloured cells with paths
+// drawn over the top, written out as SVG or PNG. Problems that can be drawn
+// implement Visualizable, and visualizations() has each day that can be with
+// how to get its picture from an input.
+//
+// Cell (y, x) covers x to x + 1 across and y to y + 1 down, so a path through
+// the middle of cells goes through x + 0.5, y + 0.5.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::aocbase::{AOCError, AOCResult};
+use crate::aocio::ProblemInput;
+use crate::geometry::Vec2;
+use crate::problems::{problem10, problem13, problem14, problem16, problem17, problem18, problem22};
+use crate::settings;
+
+// Animations keep at most this many frames, spread over the steps when the
+// solver knows how many there'll be, and the last one is always kept.
+const MAX_FRAMES: u64 = 300;
+
+// Cells in a GIF frame are drawn this many pixels across, less for big grids
+// so frames stay about MAX_GIF_SIZE pixels.
+const MIN_GIF_SCALE: usize = 1;
+const MAX_GIF_SCALE: usize = 6;
+const MAX_GIF_SIZE: usize = 480;
+
+// Hundredths of a second each GIF frame is shown for, and the last for longer.
+const GIF_FRAME_DELAY: u16 = 5;
+const GIF_LAST_FRAME_DELAY: u16 = 300;
+
+const TERMINAL_FRAME_DELAY: Duration = Duration::from_millis(40);
+
+// Cells are drawn at least this many pixels across in a PNG, more for small
+// grids, up to about MAX_PNG_SIZE pixels for the longer side.
+const MIN_PNG_SCALE: usize = 2;
+const MAX_PNG_SCALE: usize = 16;
+const MAX_PNG_SIZE: usize = 1024;
+
+// How wide paths are, in cells.
+const PATH_WIDTH: f64 = 0.35;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    // Between from and to, with fraction 0 being from and 1 being to.
+    pub fn blend(from: Color, to: Color, fraction: f64) -> Color {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+        Color::rgb(mix(from.r, to.r), mix(from.g, to.g), mix(from.b, to.b))
+    }
+
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+pub const BACKGROUND: Color = Color::rgb(0x1e, 0x1e, 0x24);
+pub const STONE: Color = Color::rgb(0x80, 0x80, 0x88);
+pub const LIGHT: Color = Color::rgb(0xe8, 0xe4, 0xd8);
+pub const HIGHLIGHT: Color = Color::rgb(0xff, 0xc8, 0x30);
+pub const ACCENT: Color = Color::rgb(0x40, 0xa0, 0xe0);
+pub const WARM: Color = Color::rgb(0xe0, 0x60, 0x40);
+
+#[derive(Debug, Clone)]
+pub struct PicturePath {
+    pub points: Vec<Vec2>,
+    pub color: Color,
+    // Goes back from the last point to the first.
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Picture {
+    width: usize,
+    height: usize,
+    background: Color,
+    cells: Vec<Option<Color>>,
+    paths: Vec<PicturePath>,
+}
+
+pub trait Visualizable {
+    fn picture(&self) -> AOCResult<Picture>;
+}
+
+impl Picture {
+
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            background: BACKGROUND,
+            cells: vec![None; width * height],
+            paths: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn set_cell(&mut self, y: usize, x: usize, color: Color) {
+        self.cells[y * self.width + x] = Some(color);
+    }
+
+    pub fn cell(&self, y: usize, x: usize) -> Color {
+        self.cells[y * self.width + x].unwrap_or(self.background)
+    }
+
+    pub fn add_path(&mut self, points: Vec<Vec2>, color: Color, closed: bool) {
+        self.paths.push(PicturePath { points, color, closed });
+    }
+
+    // A path through the middle of each cell, given as (y, x).
+    pub fn add_cell_path(&mut self, cells: &[(usize, usize)], color: Color, closed: bool) {
+        let points = cells
+            .iter()
+            .map(|(y, x)| Vec2::new(*x as f64 + 0.5, *y as f64 + 0.5))
+            .collect();
+        self.add_path(points, color, closed);
+    }
+
+    pub fn paths(&self) -> &[PicturePath] {
+        &self.paths
+    }
+
+    // One picture under another with gap empty rows between them, as wide as
+    // the widest.
+    pub fn stacked(pictures: &[Picture], gap: usize) -> Picture {
+        let width = pictures.iter().map(|picture| picture.width).max().unwrap_or(0);
+        let height = pictures.iter().map(|picture| picture.height).sum::<usize>()
+            + gap * pictures.len().saturating_sub(1);
+
+        let mut stacked = Picture::new(width, height);
+        let mut top = 0;
+        for picture in pictures {
+            for y in 0 .. picture.height {
+                for x in 0 .. picture.width {
+                    stacked.cells[(top + y) * width + x] = picture.cells[y * picture.width + x];
+                }
+            }
+            for path in &picture.paths {
+                let points = path.points.iter().map(|p| Vec2::new(p.x(), p.y() + top as f64)).collect();
+                stacked.add_path(points, path.color, path.closed);
+            }
+            top += picture.height + gap;
+        }
+        stacked
+    }
+
+    pub fn to_svg(&self) -> String {
+        let scale = self.png_scale();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\">\n",
+            self.width, self.height, self.width * scale, self.height * scale);
+        svg.push_str(&format!("  <rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            self.width, self.height, self.background.hex()));
+
+        // Runs of the same colour along a row are one rect, which keeps big
+        // plain areas from taking a rect per cell.
+        svg.push_str("  <g shape-rendering=\"crispEdges\">\n");
+        for y in 0 .. self.height {
+            let mut x = 0;
+            while x < self.width {
+                let color = self.cells[y * self.width + x];
+                let run = (x .. self.width).take_while(|x2| self.cells[y * self.width + x2] == color).count();
+                if let Some(color) = color {
+                    svg.push_str(&format!("    <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" fill=\"{}\"/>\n",
+                        x, y, run, color.hex()));
+                }
+                x += run;
+            }
+        }
+        svg.push_str("  </g>\n");
+
+        for path in &self.paths {
+            let points: Vec<String> = path.points.iter().map(|p| format!("{},{}", p.x(), p.y())).collect();
+            svg.push_str(&format!(
+                "  <{} points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>\n",
+                if path.closed { "polygon" } else { "polyline" },
+                points.join(" "),
+                path.color.hex(),
+                PATH_WIDTH));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // Pixels across each cell.
+    fn png_scale(&self) -> usize {
+        (MAX_PNG_SIZE / self.width.max(self.height).max(1)).clamp(MIN_PNG_SCALE, MAX_PNG_SCALE)
+    }
+
+    // RGB, a row at a time.
+    pub fn to_pixels(&self) -> (usize, usize, Vec<u8>) {
+        self.pixels_at(self.png_scale())
+    }
+
+    // RGB with each cell scale pixels across.
+    pub fn pixels_at(&self, scale: usize) -> (usize, usize, Vec<u8>) {
+        let (width, height) = (self.width * scale, self.height * scale);
+        let mut pixels: Vec<u8> = Vec::with_capacity(width * height * 3);
+
+        for py in 0 .. height {
+            for px in 0 .. width {
+                let color = self.cell(py / scale, px / scale);
+                pixels.extend([color.r, color.g, color.b]);
+            }
+        }
+
+        // Each segment is stamped with a square brush every half pixel.
+        let radius = (PATH_WIDTH * scale as f64 / 2.0).max(0.5);
+        for path in &self.paths {
+            let mut points: Vec<Vec2> = path.points.clone();
+            if path.closed && !points.is_empty() {
+                points.push(points[0]);
+            }
+            for segment in points.windows(2) {
+                let (from, to) = (segment[0] * scale as f64, segment[1] * scale as f64);
+                let length = (to - from).dot(to - from).sqrt();
+                let steps = (length * 2.0).ceil().max(1.0) as usize;
+                for step in 0 ..= steps {
+                    let at = from + (to - from) * (step as f64 / steps as f64);
+                    let (y_start, y_end) = ((at.y() - radius).floor().max(0.0) as usize, (at.y() + radius).ceil() as usize);
+                    let (x_start, x_end) = ((at.x() - radius).floor().max(0.0) as usize, (at.x() + radius).ceil() as usize);
+                    for py in y_start .. y_end.min(height) {
+                        for px in x_start .. x_end.min(width) {
+                            let i = (py * width + px) * 3;
+                            pixels[i .. i + 3].copy_from_slice(&[path.color.r, path.color.g, path.color.b]);
+                        }
+                    }
+                }
+            }
+        }
+
+        (width, height, pixels)
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let (width, height, pixels) = self.to_pixels();
+        png(width, height, &pixels)
+    }
+
+    // PNG for a .png file, otherwise SVG.
+    pub fn write(&self, path: impl AsRef<Path>) -> AOCResult<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => fs::write(path, self.to_png())?,
+            _ => fs::write(path, self.to_svg())?,
+        }
+        Ok(())
+    }
+}
+
+// An 8 bit RGB PNG. The image data is zlib with stored blocks, so it isn't
+// compressed at all, which keeps this small and is fine for pictures this size.
+pub fn png(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    let mut raw: Vec<u8> = Vec::with_capacity((width * 3 + 1) * height);
+    for row in pixels.chunks(width * 3) {
+        // No filter.
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr: Vec<u8> = Vec::new();
+    ihdr.extend((width as u32).to_be_bytes());
+    ihdr.extend((height as u32).to_be_bytes());
+    ihdr.extend([8, 2, 0, 0, 0]);
+
+    let mut png: Vec<u8> = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+    png_chunk(&mut png, b"IHDR", &ihdr);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start ..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // Deflate with no preset dictionary and the fastest level, as nothing is compressed.
+    let mut out: Vec<u8> = vec![0x78, 0x01];
+
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![data] } else { data.chunks(0xffff).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        out.push(if i == blocks.len() - 1 { 1 } else { 0 });
+        out.extend((block.len() as u16).to_le_bytes());
+        out.extend((!(block.len() as u16)).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+// The CRC-32 PNG chunks end with, a bit at a time since chunks are few.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0 .. 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+pub struct Visualization {
+    pub day: u32,
+    pub picture: fn(&dyn ProblemInput) -> AOCResult<Picture>,
+}
+
+pub fn visualizations() -> Vec<Visualization> {
+    vec![
+        Visualization {
+            day: 10,
+            picture: |input| problem10::PipeMap::parse(input)?.picture(),
+        },
+        Visualization {
+            day: 13,
+            picture: |input| {
+                let pictures = problem13::IslandMap::parse_all(input)?
+                    .iter()
+                    .map(|island_map| island_map.picture())
+                    .collect::<AOCResult<Vec<Picture>>>()?;
+                Ok(Picture::stacked(&pictures, 1))
+            },
+        },
+        Visualization {
+            day: 14,
+            picture: |input| problem14::MirrorPlatform::parse(input)?.picture(),
+        },
+        Visualization {
+            day: 16,
+            picture: |input| problem16::ReflectionGrid::parse(input)?.picture(),
+        },
+        Visualization {
+            day: 17,
+            picture: |input| problem17::HeatLossMap::parse(input)?.picture(),
+        },
+        Visualization {
+            day: 18,
+            picture: |input| problem18::DigSite::parse(input, false)?.picture(),
+        },
+        Visualization {
+            day: 22,
+            picture: |input| {
+                let mut pieces = problem22::Pieces::parse(input)?;
+                pieces.lower();
+                pieces.picture()
+            },
+        },
+    ]
+}
+
+pub fn visualization_for(day: u32) -> AOCResult<Visualization> {
+    visualizations()
+        .into_iter()
+        .find(|visualization| visualization.day == day)
+        .ok_or_else(|| AOCError::ProcessingError(format!("problem{} can't be drawn.", day)))
+}
+
+// Where an animation's frames go.
+pub trait FrameSink {
+    fn add(&mut self, picture: &Picture, last: bool) -> AOCResult<()>;
+    fn finish(&mut self) -> AOCResult<()>;
+}
+
+// Frames drawn over each other on stderr, two pixel rows to a line with half
+// blocks, at a pixel per cell.
+pub struct TerminalSink {
+    frames: usize,
+}
+
+impl TerminalSink {
+
+    pub fn new() -> Self {
+        Self { frames: 0 }
+    }
+}
+
+impl Default for TerminalSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameSink for TerminalSink {
+
+    fn add(&mut self, picture: &Picture, _last: bool) -> AOCResult<()> {
+        let (width, height, pixels) = picture.pixels_at(1);
+        let pixel = |y: usize, x: usize| {
+            let i = (y * width + x) * 3;
+            (pixels[i], pixels[i + 1], pixels[i + 2])
+        };
+
+        // Clear the screen the first time and go back to the top after that.
+        let mut out = String::from(if self.frames == 0 { "\x1b[2J\x1b[H" } else { "\x1b[H" });
+        for y in (0 .. height).step_by(2) {
+            for x in 0 .. width {
+                let (r, g, b) = pixel(y, x);
+                let (br, bg, bb) = if y + 1 < height { pixel(y + 1, x) } else { (0, 0, 0) };
+                out.push_str(&format!("\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}", r, g, b, br, bg, bb));
+            }
+            out.push_str("\x1b[0m\n");
+        }
+
+        let mut stderr = io::stderr().lock();
+        stderr.write_all(out.as_bytes())?;
+        stderr.flush()?;
+        self.frames += 1;
+
+        thread::sleep(TERMINAL_FRAME_DELAY);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> AOCResult<()> {
+        Ok(())
+    }
+}
+
+// A looping GIF. Each frame is compressed as it comes so only the compressed
+// frames are kept. Colours go into one palette in the order they're first
+// seen, and once it's full any new colour becomes the closest one in it.
+pub struct GifSink {
+    path: PathBuf,
+    size: Option<(usize, usize)>,
+    palette: Vec<Color>,
+    // The compressed image data of each frame, with how long to show it.
+    frames: Vec<(Vec<u8>, u16)>,
+}
+
+impl GifSink {
+
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self { path: path.as_ref().to_path_buf(), size: None, palette: Vec::new(), frames: Vec::new() }
+    }
+
+    fn palette_index(&mut self, color: Color) -> u8 {
+        if let Some(index) = self.palette.iter().position(|c| *c == color) {
+            return index as u8;
+        }
+        if self.palette.len() < 256 {
+            self.palette.push(color);
+            return (self.palette.len() - 1) as u8;
+        }
+
+        let distance = |c: &Color| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(c.r, color.r) + d(c.g, color.g) + d(c.b, color.b)
+        };
+        (0 .. self.palette.len()).min_by_key(|i| distance(&self.palette[*i])).unwrap() as u8
+    }
+}
+
+impl FrameSink for GifSink {
+
+    fn add(&mut self, picture: &Picture, last: bool) -> AOCResult<()> {
+        let scale = (MAX_GIF_SIZE / picture.width.max(picture.height).max(1)).clamp(MIN_GIF_SCALE, MAX_GIF_SCALE);
+        let (width, height, pixels) = picture.pixels_at(scale);
+
+        match self.size {
+            None => self.size = Some((width, height)),
+            Some(size) if size != (width, height) => {
+                return Err(AOCError::ProcessingError(format!(
+                    "Animation frames have to be the same size, {}x{} != {}x{}", width, height, size.0, size.1)));
+            },
+            Some(_) => {},
+        }
+
+        // Most frames have few colours, so the last lookup is tried first.
+        let mut last_color: Option<(Color, u8)> = None;
+        let mut indexes: Vec<u8> = Vec::with_capacity(width * height);
+        for rgb in pixels.chunks(3) {
+            let color = Color::rgb(rgb[0], rgb[1], rgb[2]);
+            let index = match last_color {
+                Some((c, index)) if c == color => index,
+                _ => self.palette_index(color),
+            };
+            last_color = Some((color, index));
+            indexes.push(index);
+        }
+
+        self.frames.push((lzw(&indexes), if last { GIF_LAST_FRAME_DELAY } else { GIF_FRAME_DELAY }));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> AOCResult<()> {
+        let Some((width, height)) = self.size else {
+            return Ok(());
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, gif(width, height, &self.palette, &self.frames))?;
+        tracing::info!("Wrote animation: {}", self.path.display());
+        Ok(())
+    }
+}
+
+// The frames have to have been compressed with lzw, with a code size of 8.
+fn gif(width: usize, height: usize, palette: &[Color], frames: &[(Vec<u8>, u16)]) -> Vec<u8> {
+    let mut gif: Vec<u8> = b"GIF89a".to_vec();
+    gif.extend((width as u16).to_le_bytes());
+    gif.extend((height as u16).to_le_bytes());
+    // A global colour table of 256 entries, with 8 bits a channel.
+    gif.extend([0xf7, 0, 0]);
+    for i in 0 .. 256 {
+        let color = palette.get(i).copied().unwrap_or(BACKGROUND);
+        gif.extend([color.r, color.g, color.b]);
+    }
+
+    // Loop forever.
+    gif.extend([0x21, 0xff, 0x0b]);
+    gif.extend(b"NETSCAPE2.0");
+    gif.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for (data, delay) in frames {
+        gif.extend([0x21, 0xf9, 0x04, 0x00]);
+        gif.extend(delay.to_le_bytes());
+        gif.extend([0x00, 0x00]);
+
+        gif.push(0x2c);
+        gif.extend([0, 0, 0, 0]);
+        gif.extend((width as u16).to_le_bytes());
+        gif.extend((height as u16).to_le_bytes());
+        gif.push(0);
+
+        gif.push(8);
+        for block in data.chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+    }
+
+    gif.push(0x3b);
+    gif
+}
+
+// GIF's variable width LZW on 8 bit indexes, codes packed from the low bit up.
+// The table starts again with a clear code when it's full at 12 bits.
+pub fn lzw(indexes: &[u8]) -> Vec<u8> {
+    const MIN_CODE_SIZE: u32 = 8;
+    const MAX_CODES: u16 = 4096;
+    let clear: u16 = 1 << MIN_CODE_SIZE;
+    let end: u16 = clear + 1;
+
+    let mut out: Vec<u8> = Vec::new();
+    let (mut bits, mut bit_count) = (0u32, 0u32);
+    let mut emit = |code: u16, size: u32, out: &mut Vec<u8>| {
+        bits |= (code as u32) << bit_count;
+        bit_count += size;
+        while bit_count >= 8 {
+            out.push(bits as u8);
+            bits >>= 8;
+            bit_count -= 8;
+        }
+    };
+
+    // A code followed by an index to the code for both.
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = MIN_CODE_SIZE + 1;
+
+    emit(clear, size, &mut out);
+
+    let Some((first, rest)) = indexes.split_first() else {
+        emit(end, size, &mut out);
+        if bit_count > 0 {
+            out.push(bits as u8);
+        }
+        return out;
+    };
+
+    let mut prefix = *first as u16;
+    for index in rest {
+        if let Some(code) = table.get(&(prefix, *index)) {
+            prefix = *code;
+            continue;
+        }
+
+        emit(prefix, size, &mut out);
+        // The decoder's table is a code behind, so it widens when the next
+        // code reaches the current width.
+        if next >= 1 << size && size < 12 {
+            size += 1;
+        }
+
+        if next < MAX_CODES {
+            table.insert((prefix, *index), next);
+            next += 1;
+        }
+        else {
+            emit(clear, size, &mut out);
+            table.clear();
+            next = end + 1;
+            size = MIN_CODE_SIZE + 1;
+        }
+        prefix = *index as u16;
+    }
+
+    emit(prefix, size, &mut out);
+    if next >= 1 << size && size < 12 {
+        size += 1;
+    }
+    emit(end, size, &mut out);
+    if bit_count > 0 {
+        out.push(bits as u8);
+    }
+    out
+}
+
+// Frames of a simulation as it goes, for --animate. Solvers call frame at
+// each step and it's only drawn when it's going to be kept, so it costs
+// nothing when there's no --animate. With --animate - frames go to the
+// terminal, otherwise to a GIF named after the file given and the label.
+pub struct Animation {
+    sink: Option<Box<dyn FrameSink>>,
+    // Every stride'th step is drawn.
+    stride: u64,
+    steps: u64,
+    frames: u64,
+}
+
+impl Animation {
+
+    // expected_steps spreads the frames over the steps if it's known.
+    pub fn new(label: &str, expected_steps: Option<u64>) -> Self {
+        let sink: Option<Box<dyn FrameSink>> = match settings::animate() {
+            None => None,
+            Some(target) if target == "-" => Some(Box::new(TerminalSink::new())),
+            Some(target) => Some(Box::new(GifSink::new(animation_path(&target, label)))),
+        };
+
+        Self::with(sink, expected_steps)
+    }
+
+    // Drawing into the given sink, whatever --animate is.
+    pub fn with_sink(sink: Box<dyn FrameSink>, expected_steps: Option<u64>) -> Self {
+        Self::with(Some(sink), expected_steps)
+    }
+
+    fn with(sink: Option<Box<dyn FrameSink>>, expected_steps: Option<u64>) -> Self {
+        Self {
+            sink,
+            stride: expected_steps.map_or(1, |steps| steps.div_ceil(MAX_FRAMES).max(1)),
+            steps: 0,
+            frames: 0,
+        }
+    }
+
+    pub fn off() -> Self {
+        Self::with(None, None)
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub fn frame<F>(&mut self, picture: F) -> AOCResult<()>
+        where F: FnOnce() -> AOCResult<Picture>
+    {
+        let Some(sink) = &mut self.sink else {
+            return Ok(());
+        };
+
+        let step = self.steps;
+        self.steps += 1;
+        if !step.is_multiple_of(self.stride) || self.frames >= MAX_FRAMES - 1 {
+            return Ok(());
+        }
+
+        sink.add(&picture()?, false)?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    // The end of the simulation, which is always drawn.
+    pub fn finish<F>(&mut self, picture: F) -> AOCResult<()>
+        where F: FnOnce() -> AOCResult<Picture>
+    {
+        let Some(sink) = &mut self.sink else {
+            return Ok(());
+        };
+
+        sink.add(&picture()?, true)?;
+        sink.finish()?;
+        self.sink = None;
+        Ok(())
+    }
+}
+
+// out.gif for problem14 is out_problem14.gif.
+fn animation_path(target: &str, label: &str) -> PathBuf {
+    let target = Path::new(target);
+    let stem = target.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    target.with_file_name(format!("{}_{}.gif", stem, label.replace("::", "_")))
+}
@@ -0,0 +1,45 @@
+// Writing computed values out as answer text.
+
+use bigdecimal::num_bigint::BigInt;
+
+use rook_aoc_2023::answer::{Answer, FloatAnswerPolicy};
+
+#[test]
+fn whole_floats_are_integers() {
+    assert_eq!(42.0.to_answer().unwrap(), "42");
+    assert_eq!(41.9999999999.to_answer().unwrap(), "42");
+    assert_eq!((-7.0).to_answer().unwrap(), "-7");
+
+    // Far past where floats have any fractions, and never in scientific notation.
+    assert_eq!(1e20.to_answer().unwrap(), "100000000000000000000");
+}
+
+#[test]
+fn other_floats_are_fixed_point() {
+    assert_eq!(2.5.to_answer().unwrap(), "2.500000");
+    assert_eq!((-0.125).to_answer().unwrap(), "-0.125000");
+
+    let policy = FloatAnswerPolicy { epsilon: 0.01, decimals: 2 };
+    assert_eq!(policy.format(1.23456).unwrap(), "1.23");
+    assert_eq!(policy.format(2.999).unwrap(), "3");
+}
+
+#[test]
+fn negative_zero_is_zero() {
+    assert_eq!((-0.0).to_answer().unwrap(), "0");
+    assert_eq!((-0.0000000001).to_answer().unwrap(), "0");
+}
+
+#[test]
+fn non_finite_floats_are_errors() {
+    assert!(f64::NAN.to_answer().is_err());
+    assert!(f64::INFINITY.to_answer().is_err());
+    assert!(f64::NEG_INFINITY.to_answer().is_err());
+}
+
+#[test]
+fn exact_integers_are_written_in_full() {
+    let big: BigInt = "557743507346379".parse().unwrap();
+    assert_eq!(big.to_answer().unwrap(), "557743507346379");
+    assert_eq!(BigInt::from(-3).to_answer().unwrap(), "-3");
+}
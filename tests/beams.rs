@@ -0,0 +1,32 @@
+// Beams sent through the problem16 example, each keeping what it lit up in
+// its own visitor so the one grid can be shared.
+
+use rook_aoc_2023::problems::problem16::{Photon, ReflectionGrid};
+use rook_aoc_2023::geometry::Direction;
+
+#[test]
+fn visitors_share_the_grid() {
+    let grid = ReflectionGrid::parse("input/input_16_test.txt").unwrap();
+
+    let first = grid.send_photon(&Photon::new(0, 0, Direction::East));
+    let best = grid.send_photon(&Photon::new(0, 3, Direction::South));
+    assert_eq!(first.energized_count(), 46);
+    assert_eq!(best.energized_count(), 51);
+
+    // Sending the same photon again gives the same again.
+    assert_eq!(grid.send_photon(&Photon::new(0, 0, Direction::East)).energized_count(), 46);
+}
+
+#[test]
+fn passes_and_visited_bits_agree() {
+    let grid = ReflectionGrid::parse("input/input_16_test.txt").unwrap();
+    let visitor = grid.send_photon(&Photon::new(0, 0, Direction::East));
+
+    let stats = visitor.stats();
+    stats.check_energized(visitor.energized_count()).unwrap();
+    assert!(visitor.passes(0, 0) >= 1);
+    assert_eq!(visitor.passes(9, 0), 0);
+
+    let passes: u32 = (0 .. 10).flat_map(|y| (0 .. 10).map(move |x| (y, x))).map(|(y, x)| visitor.passes(y, x)).sum();
+    assert_eq!(passes as usize, stats.photons_processed);
+}
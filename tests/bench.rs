@@ -0,0 +1,55 @@
+// Benchmark runs are held to the same timeout as a normal run, and don't use
+// the cache.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rook_aoc_2023::bench;
+use rook_aoc_2023::run::Problem;
+use rook_aoc_2023::settings;
+
+#[test]
+fn slow_runs_stop_at_the_timeout() {
+    let problem = Problem {
+        name: "slow::part1".into(),
+        runner: Arc::new(|_: &String| {
+            thread::sleep(Duration::from_secs(2));
+            Ok("0".into())
+        }),
+        sample_answer: None,
+        sample_input: None,
+        skips_invalid: false,
+    };
+
+    let start = Instant::now();
+    let result = bench::bench(&problem, &String::new(), 2, 5, Some(Duration::from_millis(50)), None);
+
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(result.durations.is_empty());
+    assert!(result.error.is_some());
+}
+
+#[test]
+fn every_run_skips_the_cache() {
+    let cached_runs = Arc::new(AtomicUsize::new(0));
+    let counter = cached_runs.clone();
+    let problem = Problem {
+        name: "cached::part1".into(),
+        runner: Arc::new(move |_: &String| {
+            if !settings::no_cache() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok("0".into())
+        }),
+        sample_answer: None,
+        sample_input: None,
+        skips_invalid: false,
+    };
+
+    let result = bench::bench(&problem, &String::new(), 1, 3, None, None);
+
+    assert_eq!(result.durations.len(), 3);
+    assert_eq!(cached_runs.load(Ordering::SeqCst), 0);
+}
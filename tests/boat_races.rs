@@ -0,0 +1,21 @@
+// The problem6 example races, one at a time through the parsed records.
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::problem6::RaceRecords;
+
+#[test]
+fn example_races_have_the_puzzles_ways_to_win() {
+    let records = RaceRecords::parse(InputSource::text("Time:      7  15   30\nDistance:  9  40  200\n")).unwrap();
+
+    let races: Vec<(i64, i64, i64)> = records
+        .winners()
+        .iter()
+        .map(|w| (w.time, w.distance, w.get_n_ways_to_beat()))
+        .collect();
+    assert_eq!(races, vec![(7, 9, 4), (15, 40, 8), (30, 200, 9)]);
+
+    for winner in records.winners() {
+        assert_eq!(winner.get_n_ways_to_beat(), winner.simulate_n_ways_to_beat(), "{:?}", winner);
+    }
+    assert_eq!(records.total_ways(), 288);
+}
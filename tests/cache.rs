@@ -0,0 +1,66 @@
+// Cached preprocessing is only reused for the same input and version.
+
+use std::fs;
+use std::path::PathBuf;
+
+use rook_aoc_2023::aocbase::AOCResult;
+use rook_aoc_2023::aocio::{artifact_path, InputSource};
+use rook_aoc_2023::cache::{self, ByteReader, Cacheable};
+
+struct Old(u32);
+struct New(u32);
+
+impl Cacheable for Old {
+    const VERSION: u32 = 1;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> AOCResult<Self> {
+        Ok(Old(ByteReader::new(bytes).read_u32()?))
+    }
+}
+
+impl Cacheable for New {
+    const VERSION: u32 = 2;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> AOCResult<Self> {
+        Ok(New(ByteReader::new(bytes).read_u32()?))
+    }
+}
+
+// The files get_or_compute keeps the test values in, so they can be cleared
+// before and after rather than left in artifacts/cache.
+fn cache_files(input: &InputSource) -> Vec<PathBuf> {
+    let hash = cache::input_hash(input).unwrap();
+    [Old::VERSION, New::VERSION]
+        .iter()
+        .map(|version| artifact_path(format!("cache/cache_test_v{}_{}.bin", version, hash)).unwrap())
+        .collect()
+}
+
+fn remove_cache_files(input: &InputSource) {
+    for file in cache_files(input) {
+        let _ = fs::remove_file(file);
+    }
+}
+
+#[test]
+fn a_new_version_is_worked_out_again() {
+    let input = InputSource::text("cache test\n");
+    remove_cache_files(&input);
+
+    assert_eq!(cache::get_or_compute("cache_test", &input, || Ok(Old(1))).unwrap().0, 1);
+    assert_eq!(cache::get_or_compute("cache_test", &input, || -> AOCResult<Old> { panic!("should be cached") }).unwrap().0, 1);
+
+    assert_eq!(cache::get_or_compute("cache_test", &input, || Ok(New(2))).unwrap().0, 2);
+    assert_eq!(cache::get_or_compute("cache_test", &input, || -> AOCResult<New> { panic!("should be cached") }).unwrap().0, 2);
+
+    assert!(cache_files(&input).iter().all(|file| file.exists()));
+    remove_cache_files(&input);
+}
@@ -0,0 +1,336 @@
+// Runs the built binary the way it's used from the command line, so the whole
+// path from picking problems through running them, writing results and
+// comparing with the last run is covered, not just the solvers.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+// Days whose examples are quick to solve, even in a debug build.
+const EXAMPLE_PROBLEMS: &str = "1,problem6,problem7,problem9,problem13,problem19";
+
+// Somewhere of its own for each test's results, so tests can run at once.
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rook_aoc_2023_cli_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Results and history go to dir rather than results/ in the repo.
+fn cli(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rook_aoc_2023"));
+    command
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("--result-file").arg(dir.join("latest.csv"))
+        .arg("--last-result-file").arg(dir.join("last.csv"))
+        .arg("--history-file").arg(dir.join("history.csv"))
+        .args(args);
+    command
+}
+
+fn run_cli(dir: &Path, args: &[&str]) -> Output {
+    cli(dir, args).output().unwrap()
+}
+
+fn run_cli_with_stdin(dir: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = cli(dir, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// Problem name to its answer and status.
+fn read_results(path: &Path) -> HashMap<String, (String, String)> {
+    let mut csv_in = csv::Reader::from_path(path).unwrap();
+    csv_in
+        .deserialize()
+        .map(|record| {
+            let record: HashMap<String, String> = record.unwrap();
+            (record["Problem"].clone(), (record["Answer"].clone(), record["Status"].clone()))
+        })
+        .collect()
+}
+
+#[test]
+fn examples_match_their_answers() {
+    let dir = scratch_dir("examples");
+    let output = run_cli(&dir, &["--problem", EXAMPLE_PROBLEMS, "--example"]);
+    let out = stdout(&output);
+
+    assert!(output.status.success(), "{}", out);
+    for name in ["problem1::part1", "problem7::part1", "problem7::part2", "problem19::part2"] {
+        assert!(out.contains(&format!("PASS {}\n", name)), "{} didn't pass:\n{}", name, out);
+    }
+    assert!(out.contains("Failed: 0"), "{}", out);
+}
+
+#[test]
+fn answers_are_printed_and_written_to_csv() {
+    let dir = scratch_dir("csv");
+    let output = run_cli(&dir, &["--problem", "problem7", "--input", "input/input_07_test.txt"]);
+    let out = stdout(&output);
+
+    assert!(output.status.success(), "{}", out);
+    assert!(out.contains("Answer: 6440\n"), "{}", out);
+    assert!(out.contains("Answer: 5905\n"), "{}", out);
+
+    let results = read_results(&dir.join("latest.csv"));
+    assert_eq!(results.len(), 2);
+    assert_eq!(results["problem7::part1"], ("6440".to_string(), "unverified".to_string()));
+    assert_eq!(results["problem7::part2"], ("5905".to_string(), "unverified".to_string()));
+
+    // The run is kept in the history next to the result file too.
+    assert_eq!(fs::read_dir(dir.join("history")).unwrap().count(), 1);
+}
+
+#[test]
+fn peak_memory_is_recorded_with_the_feature() {
+    let dir = scratch_dir("peak_memory");
+    let output = run_cli(&dir, &["--problem", "problem7::part1", "--input", "input/input_07_test.txt"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    let mut csv_in = csv::Reader::from_path(dir.join("latest.csv")).unwrap();
+    let record: HashMap<String, String> = csv_in.deserialize().next().unwrap().unwrap();
+    if cfg!(feature = "peak-memory") {
+        assert!(record["PeakMemory"].parse::<usize>().unwrap() > 0, "{:?}", record);
+        assert!(stdout(&output).contains("Peak memory: "));
+    }
+    else {
+        assert_eq!(record["PeakMemory"], "");
+    }
+}
+
+#[test]
+fn answers_are_compared_with_the_last_run() {
+    let dir = scratch_dir("compare");
+    let args = ["--problem", "problem9", "--input", "input/input_09_test.txt"];
+
+    let first = stdout(&run_cli(&dir, &args));
+    assert!(first.contains("New Answer: [problem9::part1] 114"), "{}", first);
+
+    fs::copy(dir.join("latest.csv"), dir.join("last.csv")).unwrap();
+    let second = stdout(&run_cli(&dir, &args));
+    assert!(!second.contains("New Answer"), "{}", second);
+    assert!(!second.contains("Mismatch"), "{}", second);
+
+    // A different answer last time is called out.
+    let last = fs::read_to_string(dir.join("last.csv")).unwrap().replace(",114,", ",115,");
+    fs::write(dir.join("last.csv"), last).unwrap();
+    let third = stdout(&run_cli(&dir, &args));
+    assert!(third.contains("Mismatch: [problem9::part1] 115 != 114"), "{}", third);
+}
+
+#[test]
+fn accepted_answers_stay_accepted() {
+    let dir = scratch_dir("accepted");
+    let args = ["--problem", "problem9::part1", "--input", "input/input_09_test.txt"];
+    fs::write(dir.join("last.csv"), "Problem,Duration,Answer,Error,Status\nproblem9::part1,1,114,,accepted-by-aoc\n").unwrap();
+
+    run_cli(&dir, &args);
+    assert_eq!(read_results(&dir.join("latest.csv"))["problem9::part1"], ("114".to_string(), "accepted-by-aoc".to_string()));
+
+    // Not getting to run doesn't take it away.
+    run_cli(&dir, &[&args[..], &["--time-limit", "0"]].concat());
+    assert_eq!(read_results(&dir.join("latest.csv"))["problem9::part1"], ("".to_string(), "accepted-by-aoc".to_string()));
+
+    // Nor does keeping a run as the last one.
+    run_cli(&dir, &args);
+    fs::copy(dir.join("latest.csv"), dir.join("last.csv")).unwrap();
+    run_cli(&dir, &args);
+    assert_eq!(read_results(&dir.join("latest.csv"))["problem9::part1"], ("114".to_string(), "accepted-by-aoc".to_string()));
+
+    // A different answer is wrong.
+    let last = fs::read_to_string(dir.join("last.csv")).unwrap().replace(",114,", ",115,");
+    fs::write(dir.join("last.csv"), last).unwrap();
+    run_cli(&dir, &args);
+    assert_eq!(read_results(&dir.join("latest.csv"))["problem9::part1"].1, "wrong");
+}
+
+#[test]
+fn wrong_expected_answers_fail_the_run() {
+    let dir = scratch_dir("expected");
+    let expected = dir.join("expected.csv");
+    fs::write(&expected, "Problem,Answer\nproblem7::part1,6440\nproblem7::part2,1\n").unwrap();
+
+    let output = run_cli(&dir, &[
+        "--problem", "problem7",
+        "--input", "input/input_07_test.txt",
+        "--expected", expected.to_str().unwrap(),
+    ]);
+
+    assert!(!output.status.success(), "{}", stdout(&output));
+
+    // Results are still written so the failure can be looked at.
+    let results = read_results(&dir.join("latest.csv"));
+    assert_eq!(results["problem7::part1"].0, "6440");
+    assert_eq!(results["problem7::part2"].0, "5905");
+}
+
+#[test]
+fn answer_only_output_is_name_and_answer() {
+    let dir = scratch_dir("answer_only");
+    let output = run_cli(&dir, &["--problem", "problem9", "--input", "input/input_09_test.txt", "--print-answer-only"]);
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "problem9::part1\t114\nproblem9::part2\t2\n");
+}
+
+#[test]
+fn logs_stay_out_of_answer_only_output() {
+    let dir = scratch_dir("answer_only_logs");
+    let output = run_cli(&dir, &["--problem", "problem21::part2", "--input", "input/input_21_test.txt",
+        "--self-test", "-v", "--print-answer-only"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output).lines().count(), 1);
+    assert!(stdout(&output).starts_with("problem21::part2\t"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Self test:"));
+}
+
+#[test]
+fn input_can_be_piped_in() {
+    let dir = scratch_dir("stdin");
+    let input = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("input/input_07_test.txt")).unwrap();
+    let output = run_cli_with_stdin(&dir, &["--problem", "problem7", "--input", "-", "--print-answer-only"], &input);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "problem7::part1\t6440\nproblem7::part2\t5905\n");
+}
+
+#[test]
+fn visualize_writes_a_picture_per_day() {
+    let dir = scratch_dir("visualize");
+    let output = dir.join("pictures/map.svg");
+    let result = run_cli(&dir, &["--problem", "problem9,problem14", "--input", "input/input_14_test.txt",
+        "visualize", "--output", output.to_str().unwrap()]);
+
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(fs::read_to_string(&output).unwrap().starts_with("<svg"));
+}
+
+#[test]
+fn animate_writes_a_gif_per_problem() {
+    let dir = scratch_dir("animate");
+    let result = run_cli(&dir, &["--problem", "problem16::part1", "--input", "input/input_16_test.txt",
+        "--animate", dir.join("frames.gif").to_str().unwrap()]);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    let gif = fs::read(dir.join("frames_problem16.gif")).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert_eq!(gif.last(), Some(&0x3b));
+}
+
+#[test]
+fn stress_checks_strategies_for_as_long_as_asked() {
+    let dir = scratch_dir("stress");
+    let result = run_cli(&dir, &["stress", "22", "--seconds", "0", "--seed", "5"]);
+
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert_eq!(stdout(&result), "problem22: 1 inputs from seed 5, strategies all agreed.\n");
+}
+
+#[test]
+fn params_reach_the_solvers() {
+    let dir = scratch_dir("params");
+    let output = run_cli(&dir, &["--problem", "problem21", "--input", "input/input_21_test.txt",
+        "--param", "problem21.steps=6", "--print-answer-only"]);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "problem21::part1\t16\nproblem21::part2\t16\n");
+
+    let output = run_cli(&dir, &["--problem", "problem21::part2", "--input", "input/input_21_test.txt",
+        "--param", "problem21.steps=100", "--print-answer-only"]);
+    assert_eq!(stdout(&output), "problem21::part2\t6536\n");
+
+    let output = run_cli(&dir, &["--problem", "problem12::part2", "--input", "input/input_12_test.txt",
+        "--param", "problem12.copies=1", "--print-answer-only"]);
+    assert_eq!(stdout(&output), "problem12::part2\t21\n");
+}
+
+#[test]
+fn turn_penalties_past_the_bound_are_parse_errors() {
+    let dir = scratch_dir("turn_penalty");
+    for strategy in ["astar", "buckets"] {
+        let output = run_cli(&dir, &["--problem", "problem17::part1", "--input", "input/input_17_test.txt",
+            "--param", "problem17.turn_penalty=2000000000", "--strategy", strategy, "--print-answer-only"]);
+        let all = stdout(&output) + &String::from_utf8_lossy(&output.stderr);
+
+        assert!(all.contains("Parse error: Turn penalty must be from 0 to 1000000"), "{}", all);
+        assert!(!all.contains("panicked"), "{}", all);
+
+        let output = run_cli(&dir, &["--problem", "problem17::part1", "--input", "input/input_17_test.txt",
+            "--param", "problem17.turn_penalty=1000000", "--strategy", strategy, "--print-answer-only"]);
+        assert_eq!(stdout(&output), "problem17::part1\t7000131\n");
+    }
+}
+
+#[test]
+fn paths_cut_short_are_not_answers() {
+    let dir = scratch_dir("cut_short");
+    let output = run_cli(&dir, &["--problem", "problem23::part2", "--input", "input/input_23.txt",
+        "--strategy", "exhaustive", "--no-cache", "--timeout", "0.3"]);
+    let stdout = stdout(&output);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("the answer is at least that"), "{}", stdout);
+    assert!(!stdout.contains("Answer:"), "{}", stdout);
+    assert_eq!(read_results(&dir.join("latest.csv"))["problem23::part2"].0, "");
+}
+
+#[test]
+fn bad_timeouts_are_errors() {
+    let dir = scratch_dir("bad_timeouts");
+    for option in ["--timeout", "--time-limit"] {
+        for seconds in ["-1", "NaN", "inf"] {
+            let output = run_cli(&dir, &["--problem", "problem6::part1", "--input", "input/input_06_test.txt",
+                &format!("{}={}", option, seconds)]);
+
+            let stderr = String::from_utf8_lossy(&output.stderr);
+
+            assert_eq!(output.status.code(), Some(1), "{}={}", option, seconds);
+            assert!(stderr.contains(&format!("Invalid {} seconds", option)), "{}={}", option, seconds);
+            assert!(!stderr.contains("panicked"), "{}={}: {}", option, seconds, stderr);
+        }
+    }
+}
+
+#[test]
+fn bad_problem_names_are_errors() {
+    let dir = scratch_dir("bad_problem");
+    let output = run_cli(&dir, &["--problem", "problm6"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("No problems match: problm6"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+#[test]
+fn skip_invalid_drops_bad_lines_where_supported() {
+    let dir = scratch_dir("skip_invalid");
+    let input = fs::read_to_string("input/input_09_test.txt").unwrap() + "\nnot a number\n";
+    let args = ["--problem", "problem9::part1", "--input", "-", "--print-answer-only"];
+
+    let output = run_cli_with_stdin(&dir, &args, &input);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Parse error"), "{}", stdout(&output));
+
+    let output = run_cli_with_stdin(&dir, &[&args[..], &["--skip-invalid"]].concat(), &input);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(stdout(&output), "problem9::part1\t114\n");
+
+    // Days that can't skip a line say so instead of ignoring the flag.
+    let output = run_cli(&dir, &["--problem", "problem6::part1", "--input", "input/input_06_test.txt", "--skip-invalid"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--skip-invalid isn't supported by: problem6::part1"));
+}
@@ -0,0 +1,121 @@
+// The problem17 searches, the A* one and Dial's buckets with dominance
+// pruning, agreeing on the example maps with and without a turn penalty.
+
+use rook_aoc_2023::algorithms;
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::problem17::{
+    HLPathFinder, HeatLossMap, Part1PathFinderRules, Part2PathFinderRules, PathFindState, StandardCostModel, TurnPenaltyCostModel,
+};
+
+// The second example in part 2, where the ultra crucible can't stop early.
+const UNFORTUNATE: &str = "111111111111\n999999999991\n999999999991\n999999999991\n999999999991\n";
+
+fn both_ways(hl_map: &HeatLossMap, penalty: i32) -> [(i32, i32); 2] {
+    let path_finder = HLPathFinder::new(hl_map, (hl_map.height() - 1, hl_map.width() - 1));
+    let cost_model = TurnPenaltyCostModel { penalty };
+    [
+        (
+            path_finder.find_with_cost((0, 0), &Part1PathFinderRules::new(), &cost_model).unwrap().cost,
+            path_finder.find_with_buckets((0, 0), &Part1PathFinderRules::new(), &cost_model).unwrap().cost,
+        ),
+        (
+            path_finder.find_with_cost((0, 0), &Part2PathFinderRules::new(), &cost_model).unwrap().cost,
+            path_finder.find_with_buckets((0, 0), &Part2PathFinderRules::new(), &cost_model).unwrap().cost,
+        ),
+    ]
+}
+
+#[test]
+fn buckets_agree_with_astar() {
+    let hl_map = HeatLossMap::parse("input/input_17_test.txt").unwrap();
+    let [part1, part2] = both_ways(&hl_map, 0);
+    assert_eq!(part1, (102, 102));
+    assert_eq!(part2.0, part2.1);
+
+    let hl_map = HeatLossMap::parse(InputSource::text(UNFORTUNATE)).unwrap();
+    let [_, part2] = both_ways(&hl_map, 0);
+    assert_eq!(part2.0, part2.1);
+}
+
+#[test]
+fn buckets_agree_with_a_turn_penalty() {
+    let hl_map = HeatLossMap::parse("input/input_17_test.txt").unwrap();
+    for penalty in [1, 5, 20] {
+        for (astar, buckets) in both_ways(&hl_map, penalty) {
+            assert_eq!(astar, buckets, "penalty {}", penalty);
+        }
+    }
+}
+
+#[test]
+fn bucket_paths_add_up_to_their_cost() {
+    let hl_map = HeatLossMap::parse("input/input_17_test.txt").unwrap();
+    let path_finder = HLPathFinder::new(&hl_map, (hl_map.height() - 1, hl_map.width() - 1));
+    let found = path_finder.find_with_buckets((0, 0), &Part2PathFinderRules::new(), &StandardCostModel {}).unwrap();
+
+    let heat_lost: i32 = found.path.iter().skip(1).map(|pf_st| hl_map.get_value(pf_st.y, pf_st.x)).sum();
+    assert_eq!(heat_lost, found.cost);
+    assert_eq!((found.goal().y, found.goal().x), (12, 12));
+}
+
+#[test]
+fn dial_skips_what_is_dominated() {
+    // Steps of 1 or 3 along a line, where reaching a number is all that
+    // matters, so any state seen before is dominated.
+    let mut seen: Vec<u32> = Vec::new();
+    let found = algorithms::dial(
+        0u32,
+        3usize,
+        |n: &u32| vec![(n + 1, 2usize), (n + 3, 3usize)],
+        |n: &u32| {
+            let dominated = seen.contains(n);
+            seen.push(*n);
+            dominated
+        },
+        |n: &u32| *n == 7,
+    ).unwrap();
+
+    assert_eq!(found.cost, 8);
+    assert_eq!((found.path[0], *found.goal()), (0, 7));
+    assert_eq!(found.path.len(), 4);
+}
+
+#[test]
+fn dial_goes_round_its_buckets() {
+    // Only four buckets for costs that go well past that.
+    let found = algorithms::dial(0u32, 3usize, |n: &u32| vec![(n + 1, 3usize), (n + 2, 2usize)], |_| false, |n: &u32| *n == 101).unwrap();
+    assert_eq!(found.cost, 103);
+}
+
+#[test]
+fn dial_takes_steps_too_big_for_buckets() {
+    let step = 1usize << 40;
+    let found = algorithms::dial(0u32, 2 * step, |n: &u32| vec![(n + 1, 2 * step), (n + 2, step)], |_| false, |n: &u32| *n == 9).unwrap();
+    assert_eq!(found.cost, 6 * step);
+}
+
+#[test]
+fn buckets_agree_with_a_large_turn_penalty() {
+    let hl_map = HeatLossMap::parse("input/input_17_test.txt").unwrap();
+    for penalty in [100_000, 5_000_000] {
+        for (astar, buckets) in both_ways(&hl_map, penalty) {
+            assert_eq!(astar, buckets, "penalty {}", penalty);
+        }
+    }
+}
+
+#[test]
+fn pruned_astar_agrees_with_a_plain_search() {
+    for hl_map in [HeatLossMap::parse("input/input_17_test.txt").unwrap(), HeatLossMap::parse(InputSource::text(UNFORTUNATE)).unwrap()] {
+        let end = (hl_map.height() - 1, hl_map.width() - 1);
+        let path_finder = HLPathFinder::new(&hl_map, end);
+        let mut at_end = [|pf_st: &PathFindState| (pf_st.y, pf_st.x) == end];
+
+        for penalty in [0, 5] {
+            let cost_model = TurnPenaltyCostModel { penalty };
+            let pruned = path_finder.find_with_cost((0, 0), &Part2PathFinderRules::new(), &cost_model).unwrap();
+            let plain = path_finder.find_each_with_cost((0, 0), &Part2PathFinderRules::new(), &cost_model, &mut at_end);
+            assert_eq!(pruned.cost, plain[0].as_ref().unwrap().cost, "penalty {}", penalty);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// Commands run against the example inputs of the days that can be explored,
+// the way the repl subcommand runs them.
+
+use std::ops::Bound;
+
+use rook_aoc_2023::aocbase::AOCResult;
+use rook_aoc_2023::explore::{self, Explorable};
+
+fn explore(day: u32, input: &str) -> Box<dyn Explorable> {
+    let explorer = explore::explorer_for(day).unwrap();
+    (explorer.explore)(&input.to_string()).unwrap()
+}
+
+fn run(explorable: &mut Box<dyn Explorable>, line: &str) -> AOCResult<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    explorable.run_command(words[0], &words[1 ..])
+}
+
+#[test]
+fn ranges_parse_like_rust_ranges() {
+    let range = |text: &str| explore::range_arg(&[text], 0, "rows");
+    assert_eq!(range("3..7").unwrap(), (Bound::Included(3), Bound::Excluded(7)));
+    assert_eq!(range("3..=6").unwrap(), (Bound::Included(3), Bound::Included(6)));
+    assert_eq!(range("..").unwrap(), (Bound::Unbounded, Bound::Unbounded));
+    assert_eq!(range("4").unwrap(), (Bound::Included(4), Bound::Included(4)));
+    assert!(range("a..b").is_err());
+    assert!(explore::range_arg(&[], 0, "rows").is_err());
+
+    assert_eq!(explore::range_within(range("3..").unwrap(), 10), 3 .. 10);
+    assert_eq!(explore::range_within(range("..=20").unwrap(), 10), 0 .. 10);
+    assert_eq!(explore::range_within(range("12..15").unwrap(), 10), 10 .. 10);
+}
+
+#[test]
+fn pipe_loop_is_explored() {
+    let mut pipe_map = explore(10, "input/input_10_test3.txt");
+    assert!(run(&mut pipe_map, "loop").unwrap().contains("80 steps from the start, 10 tiles enclosed"));
+    assert!(run(&mut pipe_map, "tile 0 4").unwrap().contains("step 0 of the loop"));
+    assert_eq!(run(&mut pipe_map, "region 4 10..14").unwrap(), "IIII\n");
+    assert!(run(&mut pipe_map, "tile 99 0").is_err());
+}
+
+#[test]
+fn galaxies_are_explored() {
+    let mut space_map = explore(11, "input/input_11_test.txt");
+    assert_eq!(run(&mut space_map, "distance 4 8").unwrap(), "7");
+    run(&mut space_map, "expand 1").unwrap();
+    assert_eq!(run(&mut space_map, "distance 4 8").unwrap(), "9");
+    assert_eq!(run(&mut space_map, "region 0 ..").unwrap(), "....#........\nGalaxies: [0]");
+}
+
+#[test]
+fn parts_go_through_workflows() {
+    let mut workflows = explore(19, "input/input_19_test.txt");
+    assert_eq!(run(&mut workflows, "workflow in").unwrap(), "in{s<1351:px,qqz}");
+    assert_eq!(run(&mut workflows, "rate {x=787,m=2655,a=1222,s=2876}").unwrap(), "in -> qqz -> qs -> lnx -> A, rating 7540");
+    assert_eq!(run(&mut workflows, "rate {x=1679,m=44,a=2067,s=496}").unwrap(), "in -> px -> rfg -> gd -> R");
+    assert!(run(&mut workflows, "accepted").unwrap().contains("167409079868000 total"));
+}
+
+#[test]
+fn modules_keep_their_state_between_commands() {
+    let mut modules = explore(20, "input/input_20_test.txt");
+    assert_eq!(run(&mut modules, "press 1000").unwrap(), "4000 high and 8000 low pulses, 1000 presses so far");
+    assert_eq!(run(&mut modules, "module inv").unwrap(), "&inv -> a, remembers c=Low");
+    assert!(run(&mut modules, "module nope").is_err());
+    assert!(run(&mut modules, "dot").unwrap().contains("    \"inv\" -> \"a\";\n"));
+    assert!(run(&mut modules, "cycles").is_err());
+    assert!(run(&mut modules, "fly").is_err());
+}
+
+#[test]
+fn pieces_settle_and_hold_each_other_up() {
+    let mut pieces = explore(22, "input/input_22_test.txt");
+    run(&mut pieces, "settle").unwrap();
+    assert_eq!(run(&mut pieces, "piece 1").unwrap(), "1,0,1~1,2,1, held up by ground, holding up [2, 3]");
+    assert_eq!(run(&mut pieces, "remove 1").unwrap(), "Pieces that would fall: 6");
+    assert_eq!(run(&mut pieces, "remove 6").unwrap(), "Pieces that would fall: 1");
+}
+
+#[cfg(feature = "repl")]
+#[test]
+fn repl_carries_on_after_errors() {
+    let mut space_map = explore(11, "input/input_11_test.txt");
+    let mut output: Vec<u8> = Vec::new();
+    rook_aoc_2023::repl::run(space_map.as_mut(), "galaxy 99\n\ngalaxy 8\nquit\ngalaxy 0\n".as_bytes(), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("> Error: Problem processing error: No galaxy with id: 99\n"), "{}", output);
+    assert!(output.contains("> row 9, column 4\n"), "{}", output);
+    assert!(!output.contains("row 0"), "{}", output);
+}
@@ -0,0 +1,69 @@
+// Extrapolating problem9's example sequences, and streams of values pushed
+// and popped a few at a time.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rook_aoc_2023::problems::problem9::Extrapolator;
+
+#[test]
+fn example_sequences_extrapolate_as_the_puzzle_says() {
+    let cases = [
+        (vec![0, 3, 6, 9, 12, 15], 18, -3),
+        (vec![1, 3, 6, 10, 15, 21], 28, 0),
+        (vec![10, 13, 16, 21, 30, 45], 68, 5),
+    ];
+    for (values, next, prev) in cases {
+        let extrapolator = Extrapolator::new(values);
+        assert_eq!(extrapolator.extrapolate_next(), next);
+        assert_eq!(extrapolator.extrapolate_prev(), prev);
+    }
+
+    assert_eq!(Extrapolator::new(Vec::new()).extrapolate_next(), 0);
+}
+
+#[test]
+fn pushing_one_at_a_time_follows_the_sequence() {
+    let mut extrapolator = Extrapolator::default();
+    for n in 0 .. 10i64 {
+        extrapolator.push(n * n * n - 2 * n);
+    }
+    assert_eq!(extrapolator.len(), 10);
+    assert_eq!(extrapolator.extrapolate_next(), 10 * 10 * 10 - 20);
+    assert_eq!(extrapolator.extrapolate_prev(), 1);
+}
+
+#[test]
+fn windows_predict_like_starting_over() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let mut extrapolator = Extrapolator::with_window(6);
+    let mut stream: Vec<i64> = Vec::new();
+
+    for _ in 0 .. 200 {
+        let value = rng.gen_range(-50 .. 50);
+        extrapolator.push(value);
+        stream.push(value);
+
+        let window = stream[stream.len().saturating_sub(6) ..].to_vec();
+        assert_eq!(extrapolator.values().collect::<Vec<i64>>(), window);
+
+        let rebuilt = Extrapolator::new(window);
+        assert_eq!(extrapolator.extrapolate_next(), rebuilt.extrapolate_next());
+        assert_eq!(extrapolator.extrapolate_prev(), rebuilt.extrapolate_prev());
+    }
+}
+
+#[test]
+fn popping_drains_the_oldest_first() {
+    let mut extrapolator = Extrapolator::new(vec![1, 4, 9, 16, 25]);
+    assert_eq!(extrapolator.pop_oldest(), Some(1));
+    assert_eq!(extrapolator.extrapolate_next(), 36);
+    assert_eq!(extrapolator.extrapolate_prev(), 1);
+
+    assert_eq!(extrapolator.pop_oldest(), Some(4));
+    assert_eq!(extrapolator.pop_oldest(), Some(9));
+    assert_eq!(extrapolator.pop_oldest(), Some(16));
+    assert_eq!(extrapolator.pop_oldest(), Some(25));
+    assert_eq!(extrapolator.pop_oldest(), None);
+    assert!(extrapolator.is_empty());
+}
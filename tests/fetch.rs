@@ -0,0 +1,25 @@
+// Session tokens only go to curl when they're hex, since anything else could
+// change the curl config they're written into.
+
+use rook_aoc_2023::aocbase::AOCError;
+use rook_aoc_2023::aocfetch;
+use rook_aoc_2023::settings;
+
+#[test]
+fn tokens_that_arent_hex_are_never_sent() {
+    assert!(aocfetch::is_hex_token("53616c7465645f5f0123456789abcdefABCDEF"));
+    assert!(!aocfetch::is_hex_token(""));
+    assert!(!aocfetch::is_hex_token("abc\"\nurl = \"https://example.com/"));
+
+    let mut with_session = settings::current();
+    with_session.session = Some("abc\"\noutput = \"/tmp/elsewhere".to_string());
+    settings::set(with_session);
+
+    let path = std::env::temp_dir().join(format!("rook_aoc_2023_fetch_{}.txt", std::process::id()));
+    match aocfetch::ensure_input(1, &path) {
+        Err(AOCError::HttpError(reason)) => assert!(reason.contains("hex"), "{}", reason),
+        other => panic!("Expected the token to be refused: {:?}", other),
+    }
+    assert!(!path.exists());
+    assert!(!path.with_extension("download").exists());
+}
@@ -0,0 +1,66 @@
+// The grid directions the problems share, and Vec2's lane math. Run with
+// and without --features simd, Vec2 has to come out the same as working
+// each lane out by hand.
+
+use rook_aoc_2023::geometry::{Direction, Vec2};
+
+const POINTS: [(f64, f64); 6] = [(0.0, 0.0), (1.5, -2.25), (-3.0, 4.0), (1e15, 3.0), (0.1, 0.2), (-7.75, -1e-9)];
+
+#[test]
+fn vec2_lanes_match_scalar_math() {
+    for (ax, ay) in POINTS {
+        for (bx, by) in POINTS {
+            let (a, b) = (Vec2::new(ax, ay), Vec2::new(bx, by));
+
+            let sum = a + b;
+            assert_eq!((sum.x(), sum.y()), (ax + bx, ay + by));
+            let difference = a - b;
+            assert_eq!((difference.x(), difference.y()), (ax - bx, ay - by));
+            let scaled = a * bx;
+            assert_eq!((scaled.x(), scaled.y()), (ax * bx, ay * bx));
+
+            assert_eq!(a.dot(b), ax * bx + ay * by);
+            assert_eq!(a.cross(b), ax * by - ay * bx);
+        }
+    }
+}
+
+#[test]
+fn vec2_within_checks_both_lanes() {
+    assert!(Vec2::new(1.0, 2.0).within(1.0, 2.0));
+    assert!(!Vec2::new(1.0, 2.5).within(1.0, 2.0));
+    assert!(!Vec2::new(0.5, 2.0).within(1.0, 2.0));
+    assert!(!Vec2::new(f64::NAN, 1.5).within(1.0, 2.0));
+}
+
+#[test]
+fn turning_goes_round_clockwise() {
+    assert_eq!(Direction::North.turn_right(), Direction::East);
+    assert_eq!(Direction::West.turn_right(), Direction::North);
+    assert_eq!(Direction::North.turn_left(), Direction::West);
+
+    for direction in Direction::ALL {
+        assert_eq!(direction.turn_left().turn_right(), direction);
+        assert_eq!(direction.turn_right().turn_right(), direction.opposite());
+        assert_eq!(direction.opposite().opposite(), direction);
+    }
+}
+
+#[test]
+fn opposite_directions_undo_each_other() {
+    for direction in Direction::ALL {
+        let (dy, dx) = direction.delta();
+        let (oy, ox) = direction.opposite().delta();
+        assert_eq!((dy + oy, dx + ox), (0, 0));
+        assert_eq!(direction.is_horizontal(), dy == 0);
+    }
+    assert_eq!(Direction::North.delta(), (-1, 0));
+}
+
+#[test]
+fn letters_and_arrows_parse() {
+    let parsed: Vec<Direction> = "URDL^>v<".chars().map(|c| Direction::try_from(c).unwrap()).collect();
+    assert_eq!(parsed[.. 4], Direction::ALL);
+    assert_eq!(parsed[4 ..], Direction::ALL);
+    assert!(Direction::try_from('N').is_err());
+}
@@ -0,0 +1,73 @@
+// Every problem8 ghost reaching its target together, by lcm when the cycles
+// line up and by merging the steps when they don't.
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::problem8::{self, Network, NetworkCycleIterator};
+
+// Each ghost reaches its target first on a multiple of its cycle length, one
+// only after going round its cycle twice.
+const ALIGNED: &str = "\
+L
+
+11A = (11P, 11P)
+11P = (11Q, 11Q)
+11Q = (11R, 11R)
+11R = (11Z, 11Z)
+11Z = (11R, 11R)
+22A = (22Z, 22Z)
+22Z = (22Z, 22Z)
+33A = (33B, 33B)
+33B = (33C, 33C)
+33C = (33Z, 33Z)
+33Z = (33B, 33B)
+";
+
+const EXAMPLE: &str = "\
+LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)
+";
+
+fn iterator(network: &Network, start: &str) -> NetworkCycleIterator {
+    NetworkCycleIterator::new(network, start, |id| id.ends_with('Z')).unwrap()
+}
+
+#[test]
+fn cycles_are_aligned_when_targets_land_on_their_length() {
+    let network = Network::parse(InputSource::text(ALIGNED)).unwrap();
+    assert_eq!(iterator(&network, "11A").cycle_length(), 2);
+    assert!(iterator(&network, "11A").is_aligned());
+    assert!(iterator(&network, "22A").is_aligned());
+    assert_eq!(iterator(&network, "33A").cycle_length(), 3);
+    assert!(iterator(&network, "33A").is_aligned());
+
+    // 22Z is reached twice going round the cycle once.
+    let network = Network::parse(InputSource::text(EXAMPLE)).unwrap();
+    assert!(iterator(&network, "11A").is_aligned());
+    assert!(!iterator(&network, "22A").is_aligned());
+}
+
+#[test]
+fn aligned_cycles_meet_at_the_first_common_multiple_reached() {
+    // Cycles of 2, 1 and 3 meet every 6 steps, but 11A isn't at its target
+    // until step 4, so the first time is 6.
+    assert_eq!(problem8::part2(InputSource::text(ALIGNED)).unwrap(), "6");
+
+    let network = Network::parse(InputSource::text(ALIGNED)).unwrap();
+    let mut steps: Vec<usize> = (0 ..).map(|nth| iterator(&network, "11A").get_step_for_nth_target(nth)).take(3).collect();
+    assert_eq!(steps, vec![4, 6, 8]);
+    steps = (0 ..).map(|nth| iterator(&network, "33A").get_step_for_nth_target(nth)).take(2).collect();
+    assert_eq!(steps, vec![3, 6]);
+}
+
+#[test]
+fn unaligned_cycles_fall_back_to_merging() {
+    assert_eq!(problem8::part2(InputSource::text(EXAMPLE)).unwrap(), "6");
+}
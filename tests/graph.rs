@@ -0,0 +1,230 @@
+// Orderings and components of small graphs whose answers are easy to see,
+// and of random ones checked against what the answers have to satisfy.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rook_aoc_2023::graph::{self, Graph};
+
+fn graph_of(node_count: usize, edges: &[(usize, usize)]) -> Graph<()> {
+    let mut graph: Graph<()> = Graph::new();
+    for _ in 0 .. node_count {
+        graph.add_node(());
+    }
+    for &(from, to) in edges {
+        graph.add_edge(from, to);
+    }
+    graph
+}
+
+fn random_graph(rng: &mut StdRng, node_count: usize, edge_count: usize) -> Graph<()> {
+    let edges: Vec<(usize, usize)> = (0 .. edge_count)
+        .map(|_| (rng.gen_range(0 .. node_count), rng.gen_range(0 .. node_count)))
+        .collect();
+    graph_of(node_count, &edges)
+}
+
+// Whether to can be got to from from, following edges.
+fn reaches(graph: &Graph<()>, from: usize, to: usize) -> bool {
+    let mut seen = vec![false; graph.node_count()];
+    let mut stack = vec![from];
+    while let Some(node) = stack.pop() {
+        if node == to {
+            return true;
+        }
+        for &next in graph.successors(node) {
+            if !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+fn sorted_components(components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    let mut components: Vec<Vec<usize>> = components
+        .into_iter()
+        .map(|mut component| {
+            component.sort();
+            component
+        })
+        .collect();
+    components.sort();
+    components
+}
+
+#[test]
+fn topological_sort_puts_nodes_before_their_successors() {
+    let graph = graph_of(6, &[(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1)]);
+    assert_eq!(graph::topological_sort(&graph).unwrap(), vec![4, 5, 0, 2, 3, 1]);
+}
+
+#[test]
+fn topological_sort_keeps_unrelated_nodes_in_order() {
+    let graph = graph_of(4, &[]);
+    assert_eq!(graph::topological_sort(&graph).unwrap(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn topological_sort_of_a_cycle_is_an_error() {
+    assert!(graph::topological_sort(&graph_of(3, &[(0, 1), (1, 2), (2, 0)])).is_err());
+    assert!(graph::topological_sort(&graph_of(1, &[(0, 0)])).is_err());
+}
+
+#[test]
+fn components_of_an_empty_graph() {
+    assert!(graph::strongly_connected_components(&graph_of(0, &[])).is_empty());
+}
+
+#[test]
+fn components_group_cycles() {
+    let graph = graph_of(8, &[
+        (0, 1), (1, 2), (2, 0),
+        (2, 3), (3, 4), (4, 3),
+        (4, 5), (5, 5),
+        (6, 7),
+    ]);
+
+    assert_eq!(
+        sorted_components(graph::strongly_connected_components(&graph)),
+        vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6], vec![7]]);
+}
+
+#[test]
+fn components_come_after_the_ones_they_lead_to() {
+    let graph = graph_of(5, &[(0, 1), (1, 0), (1, 2), (2, 3), (3, 2), (3, 4)]);
+    let components = graph::strongly_connected_components(&graph);
+
+    let position = |node: usize| components.iter().position(|component| component.contains(&node)).unwrap();
+    assert!(position(4) < position(2));
+    assert!(position(2) < position(0));
+}
+
+#[test]
+fn long_chains_dont_overflow_the_stack() {
+    let node_count = 200_000;
+    let edges: Vec<(usize, usize)> = (0 .. node_count - 1).map(|n| (n, n + 1)).chain([(node_count - 1, 0)]).collect();
+    let graph = graph_of(node_count, &edges);
+
+    let components = graph::strongly_connected_components(&graph);
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0].len(), node_count);
+}
+
+#[test]
+fn random_graphs_match_reachability() {
+    let mut rng = StdRng::seed_from_u64(2023);
+
+    for _ in 0 .. 200 {
+        let node_count = rng.gen_range(1 .. 12);
+        let edge_count = rng.gen_range(0 .. 24);
+        let graph = random_graph(&mut rng, node_count, edge_count);
+        let components = graph::strongly_connected_components(&graph);
+
+        // Every node is in exactly one component.
+        let mut members: Vec<usize> = components.iter().flatten().copied().collect();
+        members.sort();
+        assert_eq!(members, (0 .. node_count).collect::<Vec<usize>>());
+
+        // Two nodes share a component when each reaches the other.
+        let component_of = |node: usize| components.iter().position(|component| component.contains(&node)).unwrap();
+        for a in 0 .. node_count {
+            for b in 0 .. node_count {
+                let mutual = reaches(&graph, a, b) && reaches(&graph, b, a);
+                assert_eq!(component_of(a) == component_of(b), mutual || a == b, "{} {} in {:?}", a, b, graph);
+            }
+        }
+
+        // Edges between components only go to ones that came before.
+        for a in 0 .. node_count {
+            for &b in graph.successors(a) {
+                assert!(component_of(b) <= component_of(a));
+            }
+        }
+    }
+}
+
+#[test]
+fn condensation_is_ordered_and_acyclic() {
+    let mut rng = StdRng::seed_from_u64(17);
+
+    for _ in 0 .. 200 {
+        let node_count = rng.gen_range(1 .. 15);
+        let edge_count = rng.gen_range(0 .. 30);
+        let graph = random_graph(&mut rng, node_count, edge_count);
+        let (condensed, component_of) = graph::condensation(&graph);
+
+        let order = graph::topological_sort(&condensed).unwrap();
+        assert_eq!(order.len(), condensed.node_count());
+
+        for a in 0 .. node_count {
+            assert!(condensed.node(component_of[a]).contains(&a));
+            for &b in graph.successors(a) {
+                let (from, to) = (component_of[a], component_of[b]);
+                assert!(from == to || condensed.successors(from).contains(&to));
+            }
+        }
+    }
+}
+
+#[test]
+fn interned_nodes_are_added_once() {
+    let mut graph: Graph<String, i32> = Graph::new();
+    let a = graph.intern("a".to_string());
+    let b = graph.intern("b".to_string());
+    assert_eq!(graph.intern("a".to_string()), a);
+    assert_eq!(graph.node_count(), 2);
+    assert_eq!(graph.find(&"b".to_string()), Some(b));
+    assert_eq!(graph.find(&"c".to_string()), None);
+
+    graph.add_edge_with(a, b, 7);
+    graph.add_edge(b, a);
+    assert!(graph.has_edge(a, b));
+    assert_eq!(graph.edges_from(a).collect::<Vec<(usize, &i32)>>(), [(b, &7)]);
+    assert_eq!(graph.edges_from(b).collect::<Vec<(usize, &i32)>>(), [(a, &0)]);
+}
+
+#[test]
+fn searches_go_by_distance_or_depth() {
+    //   0 -> 1 -> 3
+    //   0 -> 2 -> 4, and 5 can't be got to.
+    let graph = graph_of(6, &[(0, 1), (0, 2), (1, 3), (2, 4), (5, 0)]);
+
+    assert_eq!(graph::bfs_order(&graph, 0), [0, 1, 2, 3, 4]);
+    assert_eq!(graph::dfs_order(&graph, 0), [0, 1, 3, 2, 4]);
+    assert_eq!(graph::bfs_distances(&graph, 0), [Some(0), Some(1), Some(1), Some(2), Some(2), None]);
+
+    // Edges are followed in the order they were added, not by node number.
+    let graph = graph_of(5, &[(0, 3), (0, 1), (3, 2), (1, 4)]);
+    assert_eq!(graph::bfs_order(&graph, 0), [0, 3, 1, 2, 4]);
+    assert_eq!(graph::dfs_order(&graph, 0), [0, 3, 2, 1, 4]);
+}
+
+#[test]
+fn connected_components_ignore_direction() {
+    let graph = graph_of(7, &[(1, 0), (2, 1), (3, 4), (6, 6)]);
+    assert_eq!(graph::connected_components(&graph), vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6]]);
+
+    // Matches reachability once every edge goes both ways.
+    let mut rng = StdRng::seed_from_u64(25);
+    for _ in 0 .. 100 {
+        let node_count = rng.gen_range(1 .. 12);
+        let edge_count = rng.gen_range(0 .. 12);
+        let graph = random_graph(&mut rng, node_count, edge_count);
+        let mut both_ways = graph.clone();
+        for a in 0 .. node_count {
+            for &b in graph.successors(a) {
+                both_ways.add_edge(b, a);
+            }
+        }
+
+        let components = graph::connected_components(&graph);
+        let component_of = |node: usize| components.iter().position(|component| component.contains(&node)).unwrap();
+        for a in 0 .. node_count {
+            for b in 0 .. node_count {
+                assert_eq!(component_of(a) == component_of(b), reaches(&both_ways, a, b));
+            }
+        }
+    }
+}
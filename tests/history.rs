@@ -0,0 +1,53 @@
+// Dates and timestamps for when answers were first right, and the stars
+// calendar made from them.
+
+use rook_aoc_2023::aocbase::AOCError;
+use rook_aoc_2023::history::{format_timestamp, parse_date, AnswerHistory};
+
+// 2023-12-01 00:00 UTC, when the first puzzle opened.
+const DECEMBER_FIRST: u64 = 1_701_388_800;
+
+#[test]
+fn dates_parse_to_the_start_of_the_day() {
+    assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+    assert_eq!(parse_date("2023-12-01").unwrap(), DECEMBER_FIRST);
+    assert_eq!(parse_date(" 2024-02-29 ").unwrap(), 1_709_164_800);
+    assert_eq!(parse_date("2024-03-01").unwrap() - parse_date("2024-02-28").unwrap(), 2 * 86400);
+}
+
+#[test]
+fn dates_that_dont_exist_are_errors() {
+    for date in ["2023-02-31", "2023-02-29", "2100-02-29", "2023-04-31", "2023-13-01", "2023-00-10", "2023-01-00", "1969-12-31", "2023-12", "Dec 1"] {
+        assert!(matches!(parse_date(date), Err(AOCError::ParseError(_))), "{}", date);
+    }
+    assert!(parse_date("2000-02-29").is_ok());
+}
+
+#[test]
+fn timestamps_format_as_utc() {
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00 UTC");
+    assert_eq!(format_timestamp(DECEMBER_FIRST + 5 * 3600 + 7 * 60 + 59), "2023-12-01 05:07 UTC");
+    assert_eq!(format_timestamp(parse_date("2024-02-29").unwrap()), "2024-02-29 00:00 UTC");
+
+    for date in ["2000-02-29", "2023-12-25", "2038-01-19"] {
+        assert_eq!(format_timestamp(parse_date(date).unwrap()), format!("{} 00:00 UTC", date));
+    }
+}
+
+#[test]
+fn calendar_shows_stars_and_when_they_were_earned() {
+    let path = std::env::temp_dir().join(format!("rook_aoc_2023_history_{}.csv", std::process::id()));
+    let mut history = AnswerHistory::load(&path).unwrap();
+    assert!(history.record_correct("problem1::part1", DECEMBER_FIRST + 3600));
+    assert!(history.record_correct("problem1::part2", DECEMBER_FIRST + 7200));
+    assert!(history.record_correct("problem25::part1", DECEMBER_FIRST + 24 * 86400));
+    assert!(!history.record_correct("problem1::part1", DECEMBER_FIRST + 86400));
+
+    let calendar = history.stars_calendar();
+    let lines: Vec<&str> = calendar.lines().collect();
+    assert_eq!(lines.len(), 26);
+    assert_eq!(lines[0], "2023 stars: 3/50");
+    assert_eq!(lines[1], "Day  1 **  part1: 2023-12-01 01:00 UTC  part2: 2023-12-01 02:00 UTC");
+    assert_eq!(lines[2], "Day  2   ");
+    assert_eq!(lines[25], "Day 25 *   part1: 2023-12-25 00:00 UTC");
+}
@@ -0,0 +1,58 @@
+// Solving from input that's in memory, the way another crate would use the
+// solvers, without any input files.
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::{problem7, problem9, problem19};
+
+const CAMEL_CARDS: &str = "\
+32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+";
+
+const OASIS_REPORT: &str = "\
+0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45
+";
+
+const WORKFLOWS: &str = "\
+px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}
+";
+
+#[test]
+fn parts_solve_from_strings() {
+    assert_eq!(problem7::solve_str(1, CAMEL_CARDS).unwrap(), "6440");
+    assert_eq!(problem7::solve_str(2, CAMEL_CARDS).unwrap(), "5905");
+    assert_eq!(problem9::solve_str(1, OASIS_REPORT).unwrap(), "114");
+    assert_eq!(problem19::solve_str(1, WORKFLOWS).unwrap(), "19114");
+    assert_eq!(problem19::solve_str(2, WORKFLOWS).unwrap(), "167409079868000");
+}
+
+#[test]
+fn part_functions_take_text_input() {
+    assert_eq!(problem9::part2(InputSource::text(OASIS_REPORT)).unwrap(), "2");
+}
+
+#[test]
+fn missing_parts_are_errors() {
+    assert!(problem9::solve_str(3, OASIS_REPORT).is_err());
+}
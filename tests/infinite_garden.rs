@@ -0,0 +1,46 @@
+// Plots reached in the problem21 example garden repeated forever, counted by
+// extrapolating and checked against the puzzle's numbers and against walking.
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::problem21::{Garden, InfiniteGardenExtrapolator};
+
+fn example() -> Garden {
+    Garden::parse("input/input_21_test.txt").unwrap()
+}
+
+#[test]
+fn extrapolated_counts_are_as_the_puzzle_says() {
+    let garden = example();
+    let mut extrapolator = InfiniteGardenExtrapolator::new(&garden).unwrap();
+
+    for (steps, expected) in [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167004), (1000, 668697), (5000, 16733044)] {
+        assert_eq!(extrapolator.solve(steps).unwrap(), expected, "{} steps", steps);
+    }
+}
+
+#[test]
+fn extrapolating_agrees_with_walking() {
+    let garden = example();
+    let mut extrapolator = InfiniteGardenExtrapolator::new(&garden).unwrap();
+    let mut walker = InfiniteGardenExtrapolator::new(&garden).unwrap();
+
+    for steps in [300, 301, 377, 512] {
+        assert_eq!(extrapolator.solve(steps).unwrap(), walker.count_reachable(steps), "{} steps", steps);
+    }
+}
+
+#[test]
+fn gardens_that_need_extrapolating() {
+    // Rocks in the start's row.
+    assert!(!example().has_clear_lanes());
+
+    // Not square, with the start off to one side.
+    let garden = Garden::parse(InputSource::text("S..#\n.#..\n....\n")).unwrap();
+    assert!(!garden.has_clear_lanes());
+    let mut extrapolator = InfiniteGardenExtrapolator::new(&garden).unwrap();
+    let mut walker = InfiniteGardenExtrapolator::new(&garden).unwrap();
+    assert_eq!(extrapolator.solve(200).unwrap(), walker.count_reachable(200));
+
+    let clear = Garden::parse(InputSource::text("...\n.S.\n...\n")).unwrap();
+    assert!(clear.has_clear_lanes());
+}
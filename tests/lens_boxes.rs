@@ -0,0 +1,107 @@
+// problem15's HASH and HASHMAP against a plain model of the boxes, as
+// proptest properties. Run with --features property-tests.
+
+use proptest::prelude::*;
+
+use rook_aoc_2023::problems::problem15::{string_hash, LightBoxes};
+
+// Few letters and short labels so boxes fill up with more than one lens.
+const LABEL: &str = "[a-e]{1,3}";
+
+// A label with the focal length to put in, or none to take it out.
+fn steps() -> impl Strategy<Value = Vec<(String, Option<i32>)>> {
+    prop::collection::vec((LABEL, prop::option::weighted(0.7, 1 ..= 9)), 0 .. 200)
+}
+
+// Each box as the lenses in it in order, kept the simplest way.
+struct Model {
+    boxes: Vec<Vec<(String, i32)>>,
+}
+
+impl Model {
+    fn add(&mut self, label: &str, focal_length: i32) {
+        let lenses = &mut self.boxes[string_hash(label) as usize];
+        match lenses.iter_mut().find(|(l, _)| l == label) {
+            Some(lens) => lens.1 = focal_length,
+            None => lenses.push((label.to_string(), focal_length)),
+        }
+    }
+
+    fn remove(&mut self, label: &str) {
+        self.boxes[string_hash(label) as usize].retain(|(l, _)| l != label);
+    }
+}
+
+fn contents(light_boxes: &LightBoxes, box_id: usize) -> Vec<(String, i32)> {
+    light_boxes.get_box(box_id).lenses().iter().map(|lens| (lens.label.clone(), lens.focal_length)).collect()
+}
+
+#[test]
+fn hashes_of_known_strings() {
+    assert_eq!(string_hash("HASH"), 52);
+    assert_eq!(string_hash(""), 0);
+}
+
+proptest! {
+    #[test]
+    fn hashes_name_a_box_and_build_up_a_character_at_a_time(text in "[ -~]{0,20}", c in b' ' ..= b'~') {
+        let hash = string_hash(&text);
+        prop_assert!((0 .. 256).contains(&hash));
+        prop_assert_eq!(string_hash(format!("{}{}", text, c as char)), (hash + c as i32) * 17 % 256);
+    }
+
+    #[test]
+    fn boxes_follow_the_model(steps in steps()) {
+        let mut light_boxes = LightBoxes::new();
+        let mut model = Model { boxes: vec![Vec::new(); 256] };
+
+        for (label, focal_length) in &steps {
+            let box_id = string_hash(label) as usize;
+            let before = contents(&light_boxes, box_id);
+
+            match focal_length {
+                None => {
+                    light_boxes.remove(label);
+                    model.remove(label);
+
+                    // Gone, with the others in the order they were.
+                    let kept: Vec<(String, i32)> = before.into_iter().filter(|(l, _)| l != label).collect();
+                    prop_assert_eq!(contents(&light_boxes, box_id), kept);
+                },
+                Some(focal_length) => {
+                    light_boxes.add(label, *focal_length);
+                    model.add(label, *focal_length);
+
+                    // A lens already there keeps its slot, a new one goes last.
+                    let after = contents(&light_boxes, box_id);
+                    match before.iter().position(|(l, _)| l == label) {
+                        Some(slot) => {
+                            prop_assert_eq!(after.len(), before.len());
+                            prop_assert_eq!(&after[slot], &(label.clone(), *focal_length));
+                        },
+                        None => prop_assert_eq!(after.last(), Some(&(label.clone(), *focal_length))),
+                    }
+                },
+            }
+
+            // Each label is in at most one slot, in the box its hash names.
+            let labels = light_boxes.get_box(box_id).labels();
+            let mut unique = labels.clone();
+            unique.sort();
+            unique.dedup();
+            prop_assert_eq!(unique.len(), labels.len());
+            prop_assert!(labels.iter().all(|l| string_hash(l) as usize == box_id));
+        }
+
+        for box_id in 0 .. 256 {
+            prop_assert_eq!(&contents(&light_boxes, box_id), &model.boxes[box_id], "box {}", box_id);
+        }
+
+        let power: i64 = model.boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(box_id, lenses)| lenses.iter().enumerate().map(move |(slot, (_, f))| (box_id as i64 + 1) * (slot as i64 + 1) * *f as i64))
+            .sum();
+        prop_assert_eq!(light_boxes.get_focussing_power(), power);
+    }
+}
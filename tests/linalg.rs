@@ -0,0 +1,64 @@
+// Exact fractions and solving with them, and problem24's rock which needs
+// them to come out right.
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::linalg::{self, Rational};
+use rook_aoc_2023::problems::problem24;
+
+fn r(numer: i64, denom: i64) -> Rational {
+    Rational::new(numer, denom).unwrap()
+}
+
+fn rows(rows: &[&[i64]]) -> Vec<Vec<Rational>> {
+    rows.iter().map(|row| row.iter().map(|n| Rational::from(*n)).collect()).collect()
+}
+
+#[test]
+fn fractions_stay_in_lowest_terms() {
+    assert_eq!(r(2, -4), r(-1, 2));
+    assert_eq!(r(-1, 2).to_string(), "-1/2");
+    assert_eq!(&r(1, 3) + &r(1, 6), r(1, 2));
+    assert_eq!(&r(1, 3) - &r(1, 3), Rational::zero());
+    assert_eq!(&r(2, 3) * &r(3, 4), r(1, 2));
+    assert_eq!((&r(1, 2) / &r(1, 4)).to_integer(), Some(2.into()));
+    assert!(r(1, 3) < r(1, 2));
+    assert!(Rational::new(1, 0).is_err());
+    assert!(r(1, 2).checked_div(&Rational::zero()).is_err());
+}
+
+#[test]
+fn solving_is_exact() {
+    // x + y = 1, x - y = 0 has halves no float would get wrong, but a third
+    // equation agreeing is fine.
+    let solution = linalg::solve(rows(&[&[1, 1, 1], &[1, -1, 0], &[2, 2, 2]]), 2).unwrap();
+    assert_eq!(solution, [r(1, 2), r(1, 2)]);
+
+    // Needs the rows swapped to find a pivot.
+    let solution = linalg::solve(rows(&[&[0, 3, 6], &[2, 0, 3]]), 2).unwrap();
+    assert_eq!(solution, [r(3, 2), r(2, 1)]);
+}
+
+#[test]
+fn unsolvable_systems_are_errors() {
+    assert!(linalg::solve(rows(&[&[1, 1, 1], &[2, 2, 2]]), 2).is_err());
+    assert!(linalg::solve(rows(&[&[1, 1, 1], &[1, -1, 0], &[1, 0, 5]]), 2).is_err());
+    assert!(linalg::solve(rows(&[&[1, 1, 1], &[1, 0]]), 2).is_err());
+
+    // Too few equations, none at all included.
+    assert!(linalg::solve(rows(&[&[1, 1, 1]]), 2).is_err());
+    assert!(linalg::solve(Vec::new(), 2).is_err());
+    assert_eq!(linalg::solve(Vec::new(), 0).unwrap(), []);
+}
+
+#[test]
+fn too_few_hailstones_are_errors() {
+    for input in ["", "19, 13, 30 @ -2,  1, -2\n", "19, 13, 30 @ -2,  1, -2\n18, 19, 22 @ -1, -1, -2\n"] {
+        assert!(problem24::part2(InputSource::text(input)).is_err(), "{:?}", input);
+    }
+}
+
+#[test]
+fn rock_is_thrown_from_the_example_position() {
+    // 24, 13, 10 @ -3, 1, 2
+    assert_eq!(problem24::part2("input/input_24_test.txt").unwrap(), "47");
+}
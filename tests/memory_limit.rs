@@ -0,0 +1,74 @@
+// Going over the memory limit is flagged for the runner rather than holding
+// up or refusing the thread that did it, so the run can always move on. Only
+// the threads of the run going on now are counted, and threads started by a
+// problem's thread are counted along with it.
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use rook_aoc_2023::memory::{self, CountingAllocator};
+use rook_aoc_2023::parallel;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const LIMIT: usize = 3 * 1024 * 1024;
+const OVER_LIMIT: usize = 4 * 1024 * 1024;
+
+// Whether the thread got to the end within a second.
+fn finishes(run: usize, work: impl FnOnce() + Send + 'static) -> bool {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        memory::join_run(run);
+        work();
+        let _ = sender.send(());
+    });
+    receiver.recv_timeout(Duration::from_secs(1)).is_ok()
+}
+
+// One test, as there's only one limited run at a time.
+#[test]
+fn going_over_the_limit_is_flagged_for_that_run_only() {
+    let run = memory::set_limit(LIMIT);
+    assert!(finishes(0, || drop(vec![1u8; OVER_LIMIT])));
+    assert!(!memory::limit_exceeded());
+
+    // Going far past the limit still doesn't refuse anything.
+    assert!(finishes(run, || drop(vec![1u8; 4 * OVER_LIMIT])));
+    assert!(memory::limit_exceeded());
+
+    // A run that was given up on keeps allocating without touching the next.
+    let (sender, receiver) = mpsc::channel::<()>();
+    let (started, started_receiver) = mpsc::channel();
+    thread::spawn(move || {
+        memory::join_run(run);
+        let kept = vec![1u8; OVER_LIMIT];
+        let _ = started.send(());
+        let _ = receiver.recv();
+        drop(vec![1u8; OVER_LIMIT]);
+        drop(kept);
+    });
+    started_receiver.recv().unwrap();
+
+    let next = memory::set_limit(LIMIT);
+    assert_ne!(next, run);
+    assert!(!memory::limit_exceeded());
+    sender.send(()).unwrap();
+    assert!(finishes(next, || drop(vec![1u8; LIMIT / 2])));
+    assert!(!memory::limit_exceeded());
+
+    memory::clear_limit();
+    assert!(!memory::limit_exceeded());
+}
+
+#[test]
+fn threads_started_by_a_limited_one_are_limited_too() {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        memory::join_run(usize::MAX);
+        let _ = sender.send(parallel::map_in_order(&[0, 1, 2, 3], |_| memory::current_run()));
+    });
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(1)).unwrap(), vec![usize::MAX; 4]);
+    assert_eq!(memory::current_run(), 0);
+}
@@ -0,0 +1,48 @@
+// Min cuts of problem25's example and of small graphs worked out by hand.
+
+use rook_aoc_2023::problems::problem25::{self, ComponentGraph, StoerWagnerSolver};
+
+fn graph(edges: &[(&str, &str)]) -> ComponentGraph {
+    let mut graph = ComponentGraph::new();
+    for (node1, node2) in edges {
+        graph.add(node1, node2);
+    }
+    graph
+}
+
+#[test]
+fn example_splits_where_the_puzzle_says() {
+    let graph = ComponentGraph::load("input/input_25_test.txt").unwrap();
+    let min_cut = StoerWagnerSolver::new(&graph).solve().unwrap();
+    assert_eq!(min_cut.weight, 3);
+
+    // hfx/pzl, bvb/cmg and nvd/jqt are cut, leaving groups of 9 and 6.
+    let mut sizes = [min_cut.side.len(), 15 - min_cut.side.len()];
+    sizes.sort();
+    assert_eq!(sizes, [6, 9]);
+    let cmg_side = min_cut.side.contains(&"cmg".to_string());
+    assert_eq!(min_cut.side.contains(&"bvb".to_string()), !cmg_side);
+}
+
+#[test]
+fn part1_answers_the_example() {
+    assert_eq!(problem25::part1("input/input_25_test.txt").unwrap(), "54");
+}
+
+#[test]
+fn triangles_joined_by_one_edge_cut_there() {
+    let graph = graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d"), ("d", "e"), ("e", "f"), ("f", "d")]);
+    let min_cut = StoerWagnerSolver::new(&graph).solve().unwrap();
+    assert_eq!(min_cut.weight, 1);
+    assert!(min_cut.side == ["a", "b", "c"] || min_cut.side == ["d", "e", "f"], "{:?}", min_cut.side);
+}
+
+#[test]
+fn disconnected_graphs_cut_for_nothing() {
+    let graph = graph(&[("a", "b"), ("c", "d"), ("d", "e")]);
+    let min_cut = StoerWagnerSolver::new(&graph).solve().unwrap();
+    assert_eq!(min_cut.weight, 0);
+    assert!(min_cut.side == ["a", "b"] || min_cut.side == ["c", "d", "e"], "{:?}", min_cut.side);
+
+    assert!(StoerWagnerSolver::new(&ComponentGraph::new()).solve().is_err());
+}
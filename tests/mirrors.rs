@@ -0,0 +1,66 @@
+// Smudges in problem13's example, and in maps wide enough for the candidate
+// lines to be split across threads.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rook_aoc_2023::grid::Grid;
+use rook_aoc_2023::problems::problem13::{IslandMap, MirrorFinder};
+
+fn island_map(rows: &[Vec<u8>]) -> IslandMap {
+    IslandMap::new(Grid::parse_lines(rows).unwrap())
+}
+
+#[test]
+fn example_smudges_are_where_the_puzzle_says() {
+    let island_maps = IslandMap::parse_all("input/input_13_test.txt").unwrap();
+
+    // The first map's smudge is in its top left corner, moving the mirror to
+    // below row 3. The second's makes the mirror below row 1.
+    let first = MirrorFinder::new(&island_maps[0]);
+    assert_eq!(first.find_row_mirror_smudges(), [(0, 0, 2)]);
+    assert!(first.find_column_mirror_smudges().is_empty());
+
+    let second = MirrorFinder::new(&island_maps[1]);
+    assert_eq!(second.find_row_mirror_smudges(), [(0, 4, 0)]);
+    assert!(second.find_column_mirror_smudges().is_empty());
+}
+
+// A few rows, hundreds of thousands of columns wide, of random cells mirrored
+// after column mirror, with one of them then changed.
+fn wide_map(width: usize, mirror: usize, smudge: (usize, usize)) -> Vec<Vec<u8>> {
+    let mut rng = StdRng::seed_from_u64(13);
+    let mut rows: Vec<Vec<u8>> = (0 .. 4)
+        .map(|_| (0 .. width).map(|_| if rng.gen_bool(0.5) { b'#' } else { b'.' }).collect())
+        .collect();
+
+    for row in rows.iter_mut() {
+        for delta in 0 .. (mirror + 1).min(width - mirror - 1) {
+            row[mirror + 1 + delta] = row[mirror - delta];
+        }
+    }
+
+    let (y, x) = smudge;
+    rows[y][x] = if rows[y][x] == b'#' { b'.' } else { b'#' };
+    rows
+}
+
+#[test]
+fn smudges_in_wide_maps_are_found_across_threads() {
+    let (width, mirror) = (300_000, 180_000);
+    let rows = wide_map(width, mirror, (2, 200_000));
+    let wide = island_map(&rows);
+    let smudges = MirrorFinder::new(&wide).find_column_mirror_smudges();
+
+    // Short lines near the edges can have smudges by chance too, but each one
+    // has to make a mirror when it's cleaned up.
+    // Smudges are given on the near side of the mirror.
+    assert!(smudges.contains(&(2, 2 * mirror + 1 - 200_000, mirror)), "{:?}", smudges);
+    assert!(smudges.windows(2).all(|pair| pair[0].2 < pair[1].2));
+
+    for (y, x, col) in smudges {
+        let mut cleaned = rows.clone();
+        cleaned[y][x] = if cleaned[y][x] == b'#' { b'.' } else { b'#' };
+        assert!(MirrorFinder::new(&island_map(&cleaned)).find_verticals().contains(&col), "column {}", col);
+    }
+}
@@ -0,0 +1,19 @@
+// Work split across threads comes out the same as doing it on one.
+
+use rook_aoc_2023::parallel;
+
+#[test]
+fn pieces_are_worked_on_whole_and_in_place() {
+    let mut items: Vec<usize> = (0 .. 1003 * 7).collect();
+    parallel::for_each_piece_mut(&mut items, 7, |piece| piece.reverse());
+
+    let expected: Vec<usize> = (0 .. 1003).flat_map(|p| (p * 7 .. p * 7 + 7).rev()).collect();
+    assert_eq!(items, expected);
+
+    let mut serial: Vec<usize> = (0 .. 1003 * 7).collect();
+    parallel::for_each_piece_mut_if(false, &mut serial, 7, |piece| piece.reverse());
+    assert_eq!(serial, expected);
+
+    parallel::for_each_piece_mut(&mut items, 0, |piece| piece.reverse());
+    assert_eq!(items, expected);
+}
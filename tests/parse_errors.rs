@@ -0,0 +1,80 @@
+// Empty inputs and truncated or empty tokens come back as parse errors
+// instead of panicking.
+
+use rook_aoc_2023::aocbase::AOCError;
+use rook_aoc_2023::aocio::{first_char, InputSource};
+use rook_aoc_2023::problems::{problem7, problem11, problem14, problem16, problem18, problem19, problem21, problem22};
+
+fn is_parse_error<T: std::fmt::Debug>(result: Result<T, AOCError>) -> bool {
+    matches!(result, Err(AOCError::ParseError(_)))
+}
+
+#[test]
+fn empty_maps_are_errors() {
+    for input in ["", "\n"] {
+        assert!(is_parse_error(problem11::part1(InputSource::text(input))), "{:?}", input);
+        assert!(is_parse_error(problem14::part1(InputSource::text(input))), "{:?}", input);
+        assert!(is_parse_error(problem16::part1(InputSource::text(input))), "{:?}", input);
+        assert!(is_parse_error(problem21::part1(InputSource::text(input))), "{:?}", input);
+    }
+    assert!(is_parse_error(problem18::part1(InputSource::text(""))));
+    assert!(is_parse_error(problem18::part2(InputSource::text(""))));
+}
+
+#[test]
+fn ragged_maps_are_errors() {
+    assert!(is_parse_error(problem11::part1(InputSource::text("#..\n.#\n"))));
+    assert!(is_parse_error(problem14::part1(InputSource::text("O.#\n.O\n"))));
+    assert!(is_parse_error(problem14::part1(InputSource::text("O.\n.O#\n"))));
+    assert!(is_parse_error(problem16::part1(InputSource::text(".|.\n.\\\n"))));
+    assert!(is_parse_error(problem21::part1(InputSource::text("...\n.S\n...\n"))));
+}
+
+#[test]
+fn truncated_hands_are_errors() {
+    for line in ["32T3K", "32T3K ", "32T3 765", "32T3K 7x", "32T3K 765 1", "32T3K -765"] {
+        assert!(problem7::part1(InputSource::text(format!("{}\n", line))).is_err(), "{:?}", line);
+    }
+    assert_eq!(problem7::part1(InputSource::text("32T3K 765 \n")).unwrap(), "765");
+}
+
+#[test]
+fn truncated_dig_operations_are_errors() {
+    for line in ["R 6", "R 6 (#)", "R (#70c710)", "6 (#70c710)", " 6 (#70c710)"] {
+        assert!(is_parse_error(problem18::part1(InputSource::text(format!("{}\n", line)))), "{:?}", line);
+    }
+
+    // The color has to hold an amount as well as a direction.
+    assert!(is_parse_error(problem18::part2(InputSource::text("R 6 (#0)\n"))));
+    assert!(is_parse_error(problem18::part2(InputSource::text("R 6 (#70c714)\n"))));
+}
+
+#[test]
+fn empty_part_ratings_are_errors() {
+    for part in ["{=1,m=2,a=3,s=4}", "{x=1,m=2,a=3,s}"] {
+        let input = format!("in{{A}}\n\n{}\n", part);
+        assert!(problem19::part1(InputSource::text(input)).is_err(), "{:?}", part);
+    }
+}
+
+#[test]
+fn overlapping_bricks_are_errors_with_their_lines() {
+    let input = "# bricks\n0,0,1~2,0,1\n\n1,1,1~1,1,1\n1,0,1~1,1,1\n";
+    match problem22::part1(InputSource::text(input)) {
+        Err(AOCError::ParseError(reason)) => assert!(reason.starts_with("lines 2 and 5:"), "{}", reason),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn long_bricks_are_not_walked_cell_by_cell() {
+    let input = "0,0,1~1000000000,0,1\n5,0,2~5,1000000000,2\n";
+    assert_eq!(problem22::part1(InputSource::text(input)).unwrap(), "1");
+    assert!(is_parse_error(problem22::part1(InputSource::text("0,0,1~1000000000,0,1\n999999999,0,1~999999999,5,1\n"))));
+}
+
+#[test]
+fn first_char_needs_a_character() {
+    assert_eq!(first_char("xmas").unwrap(), 'x');
+    assert!(is_parse_error(first_char("")));
+}
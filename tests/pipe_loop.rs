@@ -0,0 +1,66 @@
+// Working out the pipe under the problem10 start, walking the loop from it
+// and counting what it encloses.
+
+use rook_aoc_2023::problems::problem10::{enclosed_by_area, enclosed_by_flood, enclosed_by_rays, Pipe, PipeMap};
+
+fn resolved(input: &str) -> PipeMap {
+    let mut pipe_map = PipeMap::parse(input).unwrap();
+    pipe_map.resolve_start().unwrap();
+    pipe_map
+}
+
+#[test]
+fn start_takes_the_pipe_its_neighbors_connect_to() {
+    let pipe_map = resolved("input/input_10_test1.txt");
+    assert_eq!(pipe_map.get_start().unwrap(), (1, 1));
+    assert_eq!(pipe_map.map[1][1], Pipe::SouthEast);
+
+    let pipe_map = resolved("input/input_10_test3.txt");
+    assert_eq!(pipe_map.get_start().unwrap(), (0, 4));
+    assert_eq!(pipe_map.map[0][4], Pipe::SouthWest);
+}
+
+#[test]
+fn loop_walks_back_round_to_the_start() {
+    for (input, tiles) in [("input/input_10_test1.txt", 46), ("input/input_10_test2.txt", 140), ("input/input_10_test3.txt", 160)] {
+        let pipe_map = resolved(input);
+        let path = pipe_map.enclosure_path().unwrap();
+        assert_eq!(path.len() - 1, tiles, "{}", input);
+        assert_eq!(path.first(), path.last());
+        for pair in path.windows(2) {
+            assert_eq!(pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1), 1, "{}", input);
+        }
+    }
+}
+
+#[test]
+fn start_has_to_be_resolved_and_resolvable() {
+    let pipe_map = PipeMap::parse("input/input_10_test1.txt").unwrap();
+    assert!(pipe_map.enclosure_path().is_err());
+
+    let mut pipe_map = PipeMap::new(vec![
+        vec![Pipe::Ground, Pipe::NorthSouth, Pipe::Ground],
+        vec![Pipe::EastWest, Pipe::Start, Pipe::EastWest],
+        vec![Pipe::Ground, Pipe::Ground, Pipe::Ground],
+    ]).unwrap();
+    assert!(pipe_map.resolve_start().is_err());
+}
+
+#[test]
+fn every_count_of_enclosed_tiles_agrees() {
+    for (input, enclosed) in [("input/input_10_test1.txt", 4), ("input/input_10_test2.txt", 8), ("input/input_10_test3.txt", 10)] {
+        let pipe_map = resolved(input);
+        let path = pipe_map.enclosure_path().unwrap();
+        assert_eq!(enclosed_by_flood(&pipe_map, &path), enclosed, "{}", input);
+        assert_eq!(enclosed_by_rays(&pipe_map, &path), enclosed, "{}", input);
+        assert_eq!(enclosed_by_area(&path), enclosed, "{}", input);
+    }
+}
+
+#[test]
+fn area_is_the_same_either_way_round() {
+    let pipe_map = resolved("input/input_10_test2.txt");
+    let mut path = pipe_map.enclosure_path().unwrap();
+    path.reverse();
+    assert_eq!(enclosed_by_area(&path), 8);
+}
@@ -0,0 +1,133 @@
+// Cycles of the sub-circuits feeding a sink in problem20, and pressing until
+// a module is sent a pulse, mostly on a small network of two counters, one
+// going round every 2 presses and one every 4.
+
+use rook_aoc_2023::aocbase::AOCError;
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::problems::problem20::{Modules, ModulesSnapshot, Pulse};
+
+const COUNTERS: &str = "\
+broadcaster -> a, c
+%a -> ia
+&ia -> bb
+%c -> d
+%d -> id
+&id -> bb
+&bb -> rx
+";
+
+fn counters() -> Modules {
+    Modules::parse(InputSource::text(COUNTERS)).unwrap()
+}
+
+#[test]
+fn sub_circuits_are_found_with_their_cycles() {
+    let modules = counters();
+    let report = modules.analyze_cycles("rx").unwrap();
+
+    assert_eq!(report.collector, "bb");
+    let found: Vec<(&str, usize, usize, &[usize])> = report.sub_circuits
+        .iter()
+        .map(|s| (s.output.as_str(), s.offset, s.length, &s.high_presses[..]))
+        .collect();
+    assert_eq!(found, vec![("ia", 0, 2, &[2][..]), ("id", 0, 4, &[4][..])]);
+    assert_eq!(report.sub_circuits[0].modules, ["a", "ia"]);
+    assert_eq!(report.sub_circuits[1].modules, ["c", "d", "id"]);
+    assert_eq!(report.lcm, 4);
+
+    // The network itself hasn't been pressed.
+    assert_eq!(modules.snapshot(), counters().snapshot());
+}
+
+#[test]
+fn first_low_press_matches_pressing() {
+    let report = counters().analyze_cycles("rx").unwrap();
+    assert_eq!(report.first_low_press().unwrap(), 4);
+
+    let mut modules = counters();
+    let mut press = 0;
+    let mut first_low = None;
+    while first_low.is_none() {
+        press += 1;
+        modules.send_pulse("broadcaster".into(), Pulse::Low, &mut |_source, destination, _module, pulse| {
+            if destination == "rx" && pulse == Pulse::Low && first_low.is_none() {
+                first_low = Some(press);
+            }
+        }).unwrap();
+    }
+    assert_eq!(first_low, Some(4));
+}
+
+#[test]
+fn snapshots_are_saved_and_loaded() {
+    let mut modules = counters();
+    for _ in 0 .. 3 {
+        modules.send_pulse("broadcaster".into(), Pulse::Low, &mut |_, _, _, _| {}).unwrap();
+    }
+    let snapshot = modules.snapshot();
+    assert_ne!(snapshot, counters().snapshot());
+
+    let path = std::env::temp_dir().join(format!("rook_aoc_2023_snapshot_{}.bin", std::process::id()));
+    snapshot.save(&path).unwrap();
+    let loaded = ModulesSnapshot::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(loaded, snapshot);
+
+    let mut restored = counters();
+    restored.restore(&loaded).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+}
+
+#[test]
+fn snapshot_bytes_are_checked() {
+    let mut bytes = counters().snapshot().to_bytes();
+    let bit_count = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+    assert!(!bit_count.is_multiple_of(8));
+
+    // Bits past the bit count would make the same state compare unequal.
+    *bytes.last_mut().unwrap() |= 0x80;
+    assert!(matches!(ModulesSnapshot::from_bytes(&bytes), Err(AOCError::ParseError(_))));
+
+    assert!(ModulesSnapshot::from_bytes(b"P20X\0\0\0\0").is_err());
+    assert!(ModulesSnapshot::from_bytes(&bytes[.. bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn sinks_need_a_conjunction_in_front() {
+    let modules = counters();
+    assert!(matches!(modules.analyze_cycles("bb"), Err(AOCError::Unsupported(_))));
+    assert!(modules.analyze_cycles("nowhere").is_err());
+}
+
+#[test]
+fn dot_has_every_module_and_connection() {
+    let dot = counters().to_dot();
+    assert!(dot.starts_with("digraph modules {\n"));
+    assert!(dot.contains("    \"broadcaster\" [shape=doublecircle];\n"));
+    assert!(dot.contains("    \"a\" [shape=box];\n"));
+    assert!(dot.contains("    \"bb\" [shape=diamond];\n"));
+    assert!(dot.contains("    \"rx\" [shape=plaintext];\n"));
+    assert!(dot.contains("    \"broadcaster\" -> \"c\";\n"));
+    assert!(dot.contains("    \"bb\" -> \"rx\";\n"));
+    assert_eq!(dot.matches(" -> ").count(), 8);
+}
+
+#[test]
+fn pushes_until_a_pulse_with_any_wiring() {
+    let modules = counters();
+    assert_eq!(modules.find_button_pushes_until("rx", Pulse::Low).unwrap(), 4);
+    assert_eq!(modules.find_button_pushes_until("rx", Pulse::High).unwrap(), 1);
+    assert_eq!(modules.find_button_pushes_until("d", Pulse::Low).unwrap(), 2);
+
+    // Flip-flops straight into rx, with no conjunction in front.
+    let flip_flops = Modules::parse(InputSource::text("broadcaster -> a, c\n%a -> rx\n%c -> d\n%d -> rx\n")).unwrap();
+    assert_eq!(flip_flops.find_button_pushes_until("rx", Pulse::Low).unwrap(), 2);
+    assert_eq!(flip_flops.find_button_pushes_until("rx", Pulse::High).unwrap(), 1);
+}
+
+#[test]
+fn pulses_that_never_come_are_an_error() {
+    let modules = Modules::parse("input/input_20_test.txt").unwrap();
+    let error = modules.find_button_pushes_until("rx", Pulse::Low).unwrap_err();
+    assert!(error.to_string().contains("never sent a Low pulse"), "{}", error);
+}
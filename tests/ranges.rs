@@ -0,0 +1,144 @@
+// Intervals and sets of them, on their own rather than through the problem19
+// answers: empty ones, ones that touch, overlap or sit inside each other, and
+// ones outside the window asked about.
+
+use rook_aoc_2023::ranges::{Interval, IntervalSet};
+
+fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+    IntervalSet::from_intervals(intervals.iter().map(|&(start, end)| Interval::new(start, end)))
+}
+
+fn bounds(set: &IntervalSet) -> Vec<(i64, i64)> {
+    set.intervals().iter().map(|interval| (interval.start(), interval.end())).collect()
+}
+
+#[test]
+fn intervals_are_half_open() {
+    let interval = Interval::inclusive(1, 4000);
+    assert_eq!((interval.start(), interval.end(), interval.len()), (1, 4001, 4000));
+    assert!(interval.contains(1) && interval.contains(4000));
+    assert!(!interval.contains(0) && !interval.contains(4001));
+
+    let empty = Interval::new(5, 2);
+    assert!(empty.is_empty());
+    assert_eq!(empty.len(), 0);
+    assert!(!empty.contains(5) && !empty.contains(2));
+}
+
+#[test]
+fn interval_intersections() {
+    let a = Interval::new(0, 10);
+    assert_eq!(a.intersect(&Interval::new(5, 15)), Interval::new(5, 10));
+    assert_eq!(a.intersect(&Interval::new(2, 4)), Interval::new(2, 4));
+    assert!(a.intersect(&Interval::new(10, 20)).is_empty());
+    assert!(a.intersect(&Interval::new(30, 40)).is_empty());
+    assert!(a.intersect(&Interval::new(3, 3)).is_empty());
+
+    assert!(a.overlaps(&Interval::new(9, 20)));
+    assert!(!a.overlaps(&Interval::new(10, 20)));
+    assert!(!a.overlaps(&Interval::new(-5, 0)));
+}
+
+#[test]
+fn interval_splits_keep_every_value() {
+    let a = Interval::new(0, 10);
+    assert_eq!(a.split_at(4), (Interval::new(0, 4), Interval::new(4, 10)));
+    assert_eq!(a.split_at(0), (Interval::new(0, 0), Interval::new(0, 10)));
+    assert_eq!(a.split_at(-5), (Interval::new(0, 0), Interval::new(0, 10)));
+    assert_eq!(a.split_at(25), (Interval::new(0, 10), Interval::new(10, 10)));
+
+    let (below, rest) = a.split_at(7);
+    assert_eq!(below.len() + rest.len(), a.len());
+}
+
+#[test]
+fn sets_merge_touching_and_overlapping_intervals() {
+    assert_eq!(bounds(&set(&[(5, 8), (0, 3), (3, 5)])), [(0, 8)]);
+    assert_eq!(bounds(&set(&[(0, 10), (2, 4), (8, 12)])), [(0, 12)]);
+    assert_eq!(bounds(&set(&[(0, 2), (3, 5)])), [(0, 2), (3, 5)]);
+    assert_eq!(bounds(&set(&[(4, 4), (9, 1)])), []);
+    assert!(set(&[(4, 4)]).is_empty());
+
+    let s = set(&[(0, 2), (3, 5)]);
+    assert_eq!(s.len(), 4);
+    assert_eq!(s.to_string(), "{0..2, 3..5}");
+}
+
+#[test]
+fn set_membership() {
+    let s = set(&[(0, 2), (5, 7), (10, 11)]);
+    let members: Vec<i64> = (-2 .. 13).filter(|v| s.contains(*v)).collect();
+    assert_eq!(members, [0, 1, 5, 6, 10]);
+    assert!(!IntervalSet::new().contains(0));
+}
+
+#[test]
+fn set_unions() {
+    let a = set(&[(0, 2), (10, 12)]);
+    assert_eq!(bounds(&a.union(&set(&[(2, 4)]))), [(0, 4), (10, 12)]);
+    assert_eq!(bounds(&a.union(&set(&[(1, 11)]))), [(0, 12)]);
+    assert_eq!(bounds(&a.union(&set(&[(5, 6)]))), [(0, 2), (5, 6), (10, 12)]);
+    assert_eq!(a.union(&IntervalSet::new()), a);
+}
+
+#[test]
+fn set_intersections() {
+    let a = set(&[(0, 5), (10, 15)]);
+    assert_eq!(bounds(&a.intersect(&set(&[(3, 12)]))), [(3, 5), (10, 12)]);
+    assert_eq!(bounds(&a.intersect(&set(&[(1, 2), (11, 12)]))), [(1, 2), (11, 12)]);
+    assert_eq!(bounds(&a.intersect(&set(&[(-10, 100)]))), [(0, 5), (10, 15)]);
+    assert!(a.intersect(&set(&[(5, 10)])).is_empty());
+    assert!(a.intersect(&set(&[(20, 30)])).is_empty());
+    assert!(a.intersect(&IntervalSet::new()).is_empty());
+}
+
+#[test]
+fn set_complements_stay_in_their_window() {
+    let a = set(&[(2, 4), (6, 8)]);
+    assert_eq!(bounds(&a.complement(Interval::new(0, 10))), [(0, 2), (4, 6), (8, 10)]);
+    assert_eq!(bounds(&a.complement(Interval::new(3, 7))), [(4, 6)]);
+    assert_eq!(bounds(&a.complement(Interval::new(2, 8))), [(4, 6)]);
+    assert_eq!(bounds(&a.complement(Interval::new(20, 30))), [(20, 30)]);
+    assert_eq!(bounds(&a.complement(Interval::new(-10, 0))), [(-10, 0)]);
+    assert!(a.complement(Interval::new(6, 8)).is_empty());
+    assert!(a.complement(Interval::new(5, 5)).is_empty());
+    assert_eq!(bounds(&IntervalSet::new().complement(Interval::new(1, 3))), [(1, 3)]);
+}
+
+#[test]
+fn set_differences() {
+    let a = set(&[(0, 10), (20, 30)]);
+    assert_eq!(bounds(&a.difference(&set(&[(5, 25)]))), [(0, 5), (25, 30)]);
+    assert_eq!(bounds(&a.difference(&set(&[(2, 3), (22, 23)]))), [(0, 2), (3, 10), (20, 22), (23, 30)]);
+    assert_eq!(bounds(&a.difference(&set(&[(10, 20)]))), [(0, 10), (20, 30)]);
+    assert_eq!(bounds(&a.difference(&set(&[(-50, -40), (40, 50)]))), [(0, 10), (20, 30)]);
+    assert!(a.difference(&set(&[(-1, 31)])).is_empty());
+    assert!(IntervalSet::new().difference(&a).is_empty());
+    assert_eq!(a.difference(&IntervalSet::new()), a);
+}
+
+#[test]
+fn set_splits_keep_every_value() {
+    let a = set(&[(0, 5), (10, 15)]);
+    let (below, rest) = a.split_at(12);
+    assert_eq!(bounds(&below), [(0, 5), (10, 12)]);
+    assert_eq!(bounds(&rest), [(12, 15)]);
+
+    let (below, rest) = a.split_at(7);
+    assert_eq!(bounds(&below), [(0, 5)]);
+    assert_eq!(bounds(&rest), [(10, 15)]);
+
+    let (below, rest) = a.split_at(-3);
+    assert!(below.is_empty());
+    assert_eq!(rest, a);
+
+    let (below, rest) = a.split_at(100);
+    assert_eq!(below, a);
+    assert!(rest.is_empty());
+
+    for at in -2 .. 18 {
+        let (below, rest) = a.split_at(at);
+        assert_eq!(below.union(&rest), a, "split at {}", at);
+        assert!(below.intersect(&rest).is_empty(), "split at {}", at);
+    }
+}
@@ -0,0 +1,118 @@
+// Pruning the run history, on run files written out by hand.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rook_aoc_2023::history::parse_date;
+use rook_aoc_2023::run_history::{PrunePolicy, RunHistory};
+
+fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rook_aoc_2023_run_history_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// A run of one problem taking duration_ms, with no commit if it's empty.
+fn write_run(dir: &Path, timestamp: u64, run_id: &str, duration_ms: f64, commit: &str) {
+    fs::write(
+        dir.join(format!("{:010}-{}.csv", timestamp, run_id)),
+        format!("Problem,Duration,Answer,Error,Status,Commit\nproblem1::part1,{},1,,unverified,{}\n", duration_ms, commit)).unwrap();
+}
+
+fn run_ids(run_history: &RunHistory) -> Vec<String> {
+    run_history.runs().unwrap().into_iter().map(|run| run.run_id).collect()
+}
+
+// Two commits with two runs each, then a run from before commits were kept.
+fn sample_history(test: &str) -> RunHistory {
+    let dir = scratch_dir(test);
+    write_run(&dir, 1_000, "aaaaaaa", 30.0, "c1");
+    write_run(&dir, 2_000, "bbbbbbb", 20.0, "c1");
+    write_run(&dir, 3_000, "ccccccc", 10.0, "c2");
+    write_run(&dir, 4_000, "ddddddd", 15.0, "c2");
+    fs::write(dir.join("0000005000-eeeeeee.csv"), "Problem,Duration,Answer,Error,Status\nproblem1::part1,5,1,,unverified\n").unwrap();
+    RunHistory::new(dir)
+}
+
+#[test]
+fn keep_last_removes_the_oldest() {
+    let run_history = sample_history("keep_last");
+    let policy = PrunePolicy { keep_last: Some(2), ..PrunePolicy::default() };
+
+    let (removed, bytes) = run_history.prune(&policy, false).unwrap();
+    assert_eq!(removed.len(), 3);
+    assert!(bytes > 0);
+    assert_eq!(run_ids(&run_history), ["ddddddd", "eeeeeee"]);
+}
+
+#[test]
+fn best_per_commit_keeps_the_fastest() {
+    let run_history = sample_history("best");
+    let policy = PrunePolicy { keep_best_per_commit: true, ..PrunePolicy::default() };
+
+    run_history.prune(&policy, false).unwrap();
+    assert_eq!(run_ids(&run_history), ["bbbbbbb", "ccccccc", "eeeeeee"]);
+}
+
+#[test]
+fn best_per_commit_only_compares_runs_of_the_same_problems() {
+    let dir = scratch_dir("best_same_problems");
+    fs::write(
+        dir.join("0000001000-aaaaaaa.csv"),
+        "Problem,Duration,Answer,Error,Status,Commit\nproblem1::part1,5,1,,unverified,c1\nproblem2::part1,50,2,,unverified,c1\n").unwrap();
+    fs::write(
+        dir.join("0000002000-bbbbbbb.csv"),
+        "Problem,Duration,Answer,Error,Status,Commit\nproblem1::part1,6,1,,unverified,c1\nproblem2::part1,40,2,,unverified,c1\n").unwrap();
+    write_run(&dir, 3_000, "ccccccc", 4.0, "c1");
+    write_run(&dir, 4_000, "ddddddd", 3.0, "c1");
+    let run_history = RunHistory::new(dir);
+
+    let policy = PrunePolicy { keep_best_per_commit: true, ..PrunePolicy::default() };
+    run_history.prune(&policy, false).unwrap();
+    assert_eq!(run_ids(&run_history), ["bbbbbbb", "ddddddd"]);
+}
+
+#[test]
+fn best_per_commit_outlasts_the_other_rules() {
+    let run_history = sample_history("best_and_last");
+    let policy = PrunePolicy { keep_last: Some(1), keep_best_per_commit: true, older_than: Some(4_500) };
+
+    run_history.prune(&policy, false).unwrap();
+    assert_eq!(run_ids(&run_history), ["bbbbbbb", "ccccccc", "eeeeeee"]);
+}
+
+#[test]
+fn older_than_removes_runs_before_it() {
+    let run_history = sample_history("older_than");
+    let policy = PrunePolicy { older_than: Some(3_000), ..PrunePolicy::default() };
+
+    run_history.prune(&policy, false).unwrap();
+    assert_eq!(run_ids(&run_history), ["ccccccc", "ddddddd", "eeeeeee"]);
+}
+
+#[test]
+fn dry_runs_leave_everything() {
+    let run_history = sample_history("dry_run");
+    let policy = PrunePolicy { keep_last: Some(1), ..PrunePolicy::default() };
+
+    let (removed, _) = run_history.prune(&policy, true).unwrap();
+    assert_eq!(removed.len(), 4);
+    assert_eq!(run_history.usage().unwrap().runs, 5);
+}
+
+#[test]
+fn commits_are_read_back() {
+    let runs = sample_history("commits").runs().unwrap();
+    assert_eq!(runs[0].commit.as_deref(), Some("c1"));
+    assert_eq!(runs[4].commit, None);
+}
+
+#[test]
+fn dates_are_days_in_utc() {
+    assert_eq!(parse_date("1970-01-01").unwrap(), 0);
+    assert_eq!(parse_date("2023-12-01").unwrap(), 1_701_388_800);
+    assert_eq!(parse_date("2024-02-29").unwrap(), 1_709_164_800);
+    assert!(parse_date("2023-13-01").is_err());
+    assert!(parse_date("yesterday").is_err());
+}
@@ -0,0 +1,42 @@
+// Ordering a run by how long problems took last time.
+
+use std::fs;
+use std::sync::Arc;
+
+use rook_aoc_2023::run::{order_slowest_last, Problem, ProblemResults};
+
+fn problem(name: &str) -> Problem {
+    Problem {
+        name: name.into(),
+        runner: Arc::new(|_: &String| Ok("0".into())),
+        sample_answer: None,
+        sample_input: None,
+        skips_invalid: false,
+    }
+}
+
+#[test]
+fn problems_that_did_not_finish_stay_last() {
+    let path = std::env::temp_dir().join(format!("rook_aoc_2023_run_order_{}.csv", std::process::id()));
+    fs::write(&path, "\
+Problem,Duration,Answer,Error,Status
+problem1::part1,5,1,,unverified
+problem2::part1,0,,Not run: Out of time for the run.,unverified
+problem3::part1,2,3,,unverified
+problem4::part1,1000,,Timed out: Did not finish within 1 seconds.,unverified
+").unwrap();
+
+    let durations = ProblemResults::load_durations(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(durations.len(), 2);
+
+    let problems: Vec<Problem> = ["problem1::part1", "problem2::part1", "problem3::part1", "problem4::part1", "problem5::part1"]
+        .into_iter()
+        .map(problem)
+        .collect();
+    let mut order: Vec<&Problem> = problems.iter().collect();
+    order_slowest_last(&mut order, &durations);
+
+    let names: Vec<&str> = order.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(&names[.. 2], ["problem3::part1", "problem1::part1"]);
+}
@@ -0,0 +1,86 @@
+// Combining problem5's maps into one seed to location map, against walking
+// seeds through every map.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use rook_aoc_2023::aocio::InputSource;
+use rook_aoc_2023::gen;
+use rook_aoc_2023::problems::problem5::{self, HorticulturePlan};
+
+// The input from 'stress 5 --seed 1' that the flattened strategy got 35 on
+// instead of 86.
+const STRESS_SEED_10: &str = "\
+seeds: 18 62 35 77
+
+seed-to-soil map:
+69 48 30
+
+soil-to-fertilizer map:
+9 12 12
+62 63 7
+35 83 16
+
+fertilizer-to-water map:
+17 17 7
+
+water-to-light map:
+31 0 27
+38 33 1
+93 71 1
+15 77 21
+
+light-to-temperature map:
+23 12 20
+76 36 56
+
+temperature-to-humidity map:
+9 35 13
+31 72 13
+
+humidity-to-location map:
+98 8 23
+59 41 27
+";
+
+fn walked(plan: &HorticulturePlan, seed: i64) -> i64 {
+    plan.get_all_values(seed)["location"]
+}
+
+// Every value the generator's maps touch goes the same place either way.
+fn assert_combined_matches_walking(input: &str) {
+    let plan = HorticulturePlan::parse(InputSource::text(input)).unwrap();
+    let combined = plan.get_reduced("seed", "location").unwrap();
+    for seed in 0 .. 250 {
+        assert_eq!(combined.translate(seed), walked(&plan, seed), "seed {} in\n{}", seed, input);
+    }
+}
+
+// Part 2 by walking every seed in every range, blank when they're all empty
+// like part 2 gives.
+fn lowest_location_walked(input: &str) -> String {
+    let plan = HorticulturePlan::parse(InputSource::text(input)).unwrap();
+    plan.get_seed_range_pairs()
+        .into_iter()
+        .flat_map(|(start, length)| start .. start + length)
+        .map(|seed| walked(&plan, seed))
+        .min()
+        .map_or(String::new(), |location| location.to_string())
+}
+
+#[test]
+fn stress_input_combines_like_it_walks() {
+    assert_combined_matches_walking(STRESS_SEED_10);
+    assert_eq!(problem5::part1(InputSource::text(STRESS_SEED_10)).unwrap(), "86");
+    assert_eq!(problem5::part2(InputSource::text(STRESS_SEED_10)).unwrap(), "31");
+    assert_eq!(lowest_location_walked(STRESS_SEED_10), "31");
+}
+
+#[test]
+fn random_inputs_combine_like_they_walk() {
+    for seed in 0 .. 200 {
+        let input = gen::problem5(&mut StdRng::seed_from_u64(seed));
+        assert_combined_matches_walking(&input);
+        assert_eq!(problem5::part2(InputSource::text(&input)).unwrap(), lowest_location_walked(&input), "seed {}", seed);
+    }
+}
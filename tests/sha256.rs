@@ -0,0 +1,27 @@
+// SHA-256 against known digests, around the lengths where the padding needs
+// a second block.
+
+use rook_aoc_2023::sha256::{sha256, sha256_hex};
+
+#[test]
+fn known_digests() {
+    let cases: [(String, &str); 6] = [
+        ("".into(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        ("abc".into(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        ("a".repeat(55), "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318"),
+        ("a".repeat(56), "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"),
+        ("a".repeat(64), "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"),
+        ("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".into(), "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"),
+    ];
+
+    for (text, digest) in &cases {
+        assert_eq!(sha256_hex(text.as_bytes()), *digest, "{} bytes", text.len());
+    }
+}
+
+#[test]
+fn a_million_bytes() {
+    let data = vec![b'a'; 1_000_000];
+    assert_eq!(sha256_hex(&data), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+    assert_eq!(sha256(&data)[.. 4], [0xcd, 0xc7, 0x6e, 0x5c]);
+}
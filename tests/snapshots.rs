@@ -0,0 +1,15 @@
+// Every picture drawn of the example inputs matches its copy in
+// tests/snapshots. After a change to drawing that's meant, run the snapshots
+// subcommand with --update to write them out again.
+
+use rook_aoc_2023::snapshots::{self, SnapshotStatus};
+
+#[test]
+fn rendered_examples_match_the_snapshots() {
+    let results = snapshots::check_all(&snapshots::snapshots(), false);
+
+    for result in &results {
+        assert!(matches!(result.status, Ok(SnapshotStatus::Matches)), "{}", snapshots::report(&results));
+    }
+    assert_eq!(snapshots::failure_count(&results), 0);
+}
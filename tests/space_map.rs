@@ -0,0 +1,39 @@
+// Looking up single galaxies and pairs in the problem11 example, with the
+// distances the puzzle gives for it.
+
+use rook_aoc_2023::problems::problem11::SpaceMap;
+
+fn expanded_example() -> SpaceMap {
+    SpaceMap::parse("input/input_11_test.txt").unwrap().expand(1)
+}
+
+#[test]
+fn pairs_are_as_far_apart_as_the_puzzle_says() {
+    let space_map = expanded_example();
+
+    // The puzzle numbers galaxies from 1.
+    assert_eq!(space_map.distance_between(4, 8).unwrap(), 9);
+    assert_eq!(space_map.distance_between(0, 6).unwrap(), 15);
+    assert_eq!(space_map.distance_between(2, 5).unwrap(), 17);
+    assert_eq!(space_map.distance_between(7, 8).unwrap(), 5);
+    assert_eq!(space_map.distance_between(8, 7).unwrap(), 5);
+}
+
+#[test]
+fn positions_move_with_expansion() {
+    let space_map = SpaceMap::parse("input/input_11_test.txt").unwrap();
+    assert_eq!(space_map.galaxy_count(), 9);
+    assert_eq!(space_map.position_of(8).unwrap(), (9, 4));
+    assert_eq!(expanded_example().position_of(8).unwrap(), (11, 5));
+    assert!(space_map.position_of(9).is_err());
+    assert!(space_map.distance_between(0, 9).is_err());
+}
+
+#[test]
+fn galaxies_are_found_by_rows_and_columns() {
+    let space_map = SpaceMap::parse("input/input_11_test.txt").unwrap();
+    assert_eq!(space_map.galaxies_in(0 .. 3, ..), [0, 1, 2]);
+    assert_eq!(space_map.galaxies_in(.., 0 ..= 1), [2, 4, 7]);
+    assert_eq!(space_map.galaxies_in(4 .., 4 .. 8), [3, 6, 8]);
+    assert!(space_map.galaxies_in(3 ..= 3, ..).is_empty());
+}
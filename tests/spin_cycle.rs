@@ -0,0 +1,34 @@
+// Spinning the problem14 example platform by segments, against sliding the
+// whole platform.
+
+use rook_aoc_2023::problems::problem14::{MirrorPlatform, SegmentSpinner, SpinTiltSolver};
+
+fn example() -> MirrorPlatform {
+    MirrorPlatform::parse("input/input_14_test.txt").unwrap()
+}
+
+#[test]
+fn load_after_a_billion_spins_is_as_the_puzzle_says() {
+    assert_eq!(SegmentSpinner::new(&example()).load_after(1_000_000_000).unwrap(), 64);
+
+    let mut solver = SpinTiltSolver::new(example());
+    solver.find_cycle().unwrap();
+    assert_eq!(solver.get_load(1_000_000_000).unwrap(), 64);
+}
+
+#[test]
+fn segments_spin_like_the_platform_slides() {
+    let mut solver = SpinTiltSolver::new(example());
+    for spins in 1 ..= 12 {
+        solver.run_cycle();
+        assert_eq!(SegmentSpinner::new(&example()).load_after(spins).unwrap(), solver.mirror_platform.calculate_load(), "{} spins", spins);
+    }
+}
+
+#[test]
+fn spins_keep_every_rock() {
+    let mut spinner = SegmentSpinner::new(&example());
+    for _ in 0 .. 5 {
+        assert_eq!(spinner.spin().iter().sum::<usize>(), 18);
+    }
+}
@@ -0,0 +1,55 @@
+// Counting spring arrangements bottom up, against the recursive search and
+// brute force.
+
+use rook_aoc_2023::problems::problem12::{SpringsCondition, SpringsConditionsSolver};
+
+fn example() -> Vec<SpringsCondition> {
+    std::fs::read_to_string("input/input_12_test.txt").unwrap()
+        .lines()
+        .map(|line| SpringsCondition::parse(line).unwrap())
+        .collect()
+}
+
+fn bottom_up(springs_condition: &SpringsCondition) -> u64 {
+    SpringsConditionsSolver::new(springs_condition).solve_bottom_up()
+}
+
+fn recursive(springs_condition: &SpringsCondition) -> u64 {
+    SpringsConditionsSolver::new(springs_condition).solve()
+}
+
+#[test]
+fn rows_count_as_the_puzzle_says() {
+    let counts: Vec<u64> = example().iter().map(bottom_up).collect();
+    assert_eq!(counts, vec![1, 4, 1, 1, 4, 10]);
+
+    let unfolded: Vec<u64> = example().iter().map(|row| bottom_up(&row.expand(4))).collect();
+    assert_eq!(unfolded, vec![1, 16384, 1, 16, 2500, 506250]);
+}
+
+#[test]
+fn bottom_up_agrees_with_brute_force() {
+    for row in example() {
+        for extra in 0 ..= 1 {
+            let row = row.expand(extra);
+            assert_eq!(bottom_up(&row), row.brute_force_count(), "{:?}", row);
+        }
+    }
+}
+
+#[test]
+fn bottom_up_agrees_with_recursion_as_rows_unfold() {
+    for row in example() {
+        for extra in 0 ..= 6 {
+            let row = row.expand(extra);
+            assert_eq!(bottom_up(&row), recursive(&row), "{:?}", row);
+        }
+    }
+}
+
+#[test]
+fn edge_rows() {
+    for (line, expected) in [("# 1", 1), (". 1", 0), ("? 1", 1), ("??? 1,1", 1), ("#.# 1,1", 1), ("## 1", 0), ("?#? 3", 1)] {
+        assert_eq!(bottom_up(&SpringsCondition::parse(line).unwrap()), expected, "{}", line);
+    }
+}
@@ -0,0 +1,44 @@
+// The random inputs from gen, and each day's strategies agreeing on them.
+
+use rook_aoc_2023::settings;
+use rook_aoc_2023::stress::{self, StressTest};
+
+fn stress_test(day: u32) -> StressTest {
+    stress::stress_test_for(day).unwrap()
+}
+
+#[test]
+fn seeds_give_the_same_input_each_time() {
+    for stress_test in stress::stress_tests() {
+        assert_eq!(stress_test.generate(7), stress_test.generate(7), "problem{}", stress_test.day);
+        assert_ne!(stress_test.generate(7), stress_test.generate(8), "problem{}", stress_test.day);
+    }
+}
+
+#[test]
+fn strategies_agree_on_random_inputs() {
+    for day in [5, 7, 8, 14, 16, 17, 22, 23] {
+        let stress_test = stress_test(day);
+        let mut solved = 0;
+
+        for seed in 0 .. 20 {
+            assert!(stress_test.check(seed).unwrap().is_none(), "problem{} seed {}", day, seed);
+
+            // Most inputs should have an answer, or there's not much being checked.
+            let (part, strategies) = stress_test.parts[0];
+            let answers = stress_test.answers(part, strategies, &stress_test.generate(seed)).unwrap();
+            if answers.iter().all(|(_, answer)| answer.is_ok()) {
+                solved += 1;
+            }
+        }
+        assert!(solved >= 5, "problem{} solved only {} of 20", day, solved);
+    }
+
+    // The forced strategies don't stick around afterwards.
+    assert_eq!(settings::strategy(), None);
+}
+
+#[test]
+fn days_without_strategies_cant_be_stressed() {
+    assert!(stress::stress_test_for(1).is_err());
+}
@@ -0,0 +1,40 @@
+// Chain reactions in the problem22 example, from the support graph and from
+// lowering what's left after disintegrating a piece.
+
+use rook_aoc_2023::problems::problem22::Pieces;
+
+fn settled_example() -> Pieces {
+    let mut pieces = Pieces::parse("input/input_22_test.txt").unwrap();
+    pieces.lower();
+    pieces
+}
+
+#[test]
+fn chain_reactions_are_as_the_puzzle_says() {
+    let pieces = settled_example();
+
+    // Pieces are numbered from 1, so A is 1 and F is 6.
+    assert_eq!(pieces.count_chain_reaction(1).unwrap(), 6);
+    assert_eq!(pieces.count_chain_reaction(6).unwrap(), 1);
+    assert_eq!(pieces.count_chain_reaction(7).unwrap(), 0);
+
+    let support_graph = pieces.support_graph();
+    assert_eq!(support_graph.piece_count(), 7);
+    let total: usize = (1 ..= 7).map(|id| support_graph.count_chain_reaction(id).unwrap()).sum();
+    assert_eq!(total, 7);
+}
+
+#[test]
+fn graph_agrees_with_lowering() {
+    let pieces = settled_example();
+    for id in 1 ..= 7 {
+        let mut without = pieces.clone();
+        without.disintegrate(id);
+        assert_eq!(pieces.count_chain_reaction(id).unwrap(), without.lower() as usize, "piece {}", id);
+    }
+}
+
+#[test]
+fn unknown_pieces_are_an_error() {
+    assert!(settled_example().count_chain_reaction(8).is_err());
+}
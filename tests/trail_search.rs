@@ -0,0 +1,13 @@
+// Every problem23 search gives the example's longest hikes.
+
+use rook_aoc_2023::problems::problem23::TrailSearch;
+
+#[test]
+fn searches_agree_on_the_example() {
+    for (slopes_matter, longest) in [(true, "94"), (false, "154")] {
+        let trail_search = TrailSearch::new("input/input_23_test.txt", slopes_matter).unwrap();
+        assert_eq!(trail_search.solve_exhaustive().unwrap(), longest);
+        assert_eq!(trail_search.solve_bounded().unwrap(), longest);
+        assert_eq!(trail_search.solve_bitmask().unwrap(), longest);
+    }
+}
@@ -0,0 +1,280 @@
+// Pictures of the example inputs, and PNGs and GIFs read back by hand to
+// check they're put together the way the formats say.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use rook_aoc_2023::problems::{problem10, problem18};
+use rook_aoc_2023::visualize::{self, Animation, Color, FrameSink, GifSink, Picture, Visualizable};
+
+// Each chunk's type and data, after checking the signature.
+fn png_chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+    assert_eq!(&png[.. 8], b"\x89PNG\r\n\x1a\n");
+
+    let mut chunks: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut at = 8;
+    while at < png.len() {
+        let length = u32::from_be_bytes(png[at .. at + 4].try_into().unwrap()) as usize;
+        let kind = String::from_utf8(png[at + 4 .. at + 8].to_vec()).unwrap();
+        chunks.push((kind, png[at + 8 .. at + 8 + length].to_vec()));
+        at += 12 + length;
+    }
+    chunks
+}
+
+// The image data back out of zlib stored blocks.
+fn unstore(zlib: &[u8]) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+    let mut at = 2;
+    loop {
+        let last = zlib[at] & 1 == 1;
+        let length = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]) as usize;
+        assert_eq!(!u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]) as usize, length);
+        data.extend_from_slice(&zlib[at + 5 .. at + 5 + length]);
+        at += 5 + length;
+        if last {
+            break;
+        }
+    }
+    assert_eq!(at + 4, zlib.len());
+    data
+}
+
+#[test]
+fn png_holds_the_pixels() {
+    let pixels: Vec<u8> = (0 .. 3 * 2 * 3).map(|i| i as u8).collect();
+    let png = visualize::png(3, 2, &pixels);
+    let chunks = png_chunks(&png);
+
+    let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+    assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+    assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+    let mut rows: Vec<u8> = vec![0];
+    rows.extend_from_slice(&pixels[.. 9]);
+    rows.push(0);
+    rows.extend_from_slice(&pixels[9 ..]);
+    assert_eq!(unstore(&chunks[1].1), rows);
+
+    // IEND is always the same, CRC included.
+    assert_eq!(&png[png.len() - 12 ..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+}
+
+#[test]
+fn big_images_are_split_into_blocks() {
+    let picture = Picture::new(200, 200);
+    let (width, height, pixels) = picture.to_pixels();
+    let chunks = png_chunks(&picture.to_png());
+
+    let data = unstore(&chunks[1].1);
+    assert!(data.len() > 0xffff);
+    assert_eq!(data.len(), (width * 3 + 1) * height);
+    assert_eq!(data[1 .. width * 3 + 1], pixels[.. width * 3]);
+}
+
+#[test]
+fn paths_are_drawn_over_cells() {
+    let mut picture = Picture::new(4, 4);
+    picture.set_cell(0, 0, visualize::STONE);
+    picture.add_cell_path(&[(2, 0), (2, 3)], visualize::HIGHLIGHT, false);
+
+    let (width, _, pixels) = picture.to_pixels();
+    let at = |py: usize, px: usize| {
+        let i = (py * width + px) * 3;
+        Color::rgb(pixels[i], pixels[i + 1], pixels[i + 2])
+    };
+    let scale = width / 4;
+
+    assert_eq!(at(0, 0), visualize::STONE);
+    assert_eq!(at(scale * 2 + scale / 2, scale * 2), visualize::HIGHLIGHT);
+    assert_eq!(at(scale * 3 + scale / 2, scale * 2), visualize::BACKGROUND);
+
+    let svg = picture.to_svg();
+    assert!(svg.contains("<polyline points=\"0.5,2.5 3.5,2.5\""), "{}", svg);
+    assert!(svg.contains(&format!("fill=\"{}\"", visualize::STONE.hex())), "{}", svg);
+}
+
+#[test]
+fn stacked_pictures_keep_their_paths() {
+    let mut top = Picture::new(2, 3);
+    top.set_cell(0, 0, visualize::STONE);
+    let mut bottom = Picture::new(5, 2);
+    bottom.set_cell(1, 4, visualize::WARM);
+    bottom.add_cell_path(&[(0, 0), (1, 0)], visualize::ACCENT, false);
+
+    let stacked = Picture::stacked(&[top, bottom], 1);
+    assert_eq!((stacked.width(), stacked.height()), (5, 6));
+    assert_eq!(stacked.cell(0, 0), visualize::STONE);
+    assert_eq!(stacked.cell(5, 4), visualize::WARM);
+    assert_eq!(stacked.paths()[0].points[0].y(), 4.5);
+}
+
+#[test]
+fn pipe_loop_is_drawn() {
+    let picture = problem10::PipeMap::parse("input/input_10_test3.txt").unwrap().picture().unwrap();
+    assert_eq!(picture.paths().len(), 1);
+    assert_eq!(picture.paths()[0].color, visualize::HIGHLIGHT);
+
+    // The example encloses 10 tiles.
+    let enclosed = (0 .. picture.height())
+        .flat_map(|y| (0 .. picture.width()).map(move |x| (y, x)))
+        .filter(|(y, x)| picture.cell(*y, *x) == visualize::ACCENT)
+        .count();
+    assert_eq!(enclosed, 10);
+}
+
+#[test]
+fn lagoon_is_filled_in() {
+    let picture = problem18::DigSite::parse("input/input_18_test.txt", false).unwrap().picture().unwrap();
+    let filled = (0 .. picture.height())
+        .flat_map(|y| (0 .. picture.width()).map(move |x| (y, x)))
+        .filter(|(y, x)| picture.cell(*y, *x) == visualize::ACCENT)
+        .count();
+
+    // The part 1 answer for the example.
+    assert_eq!(filled, 62);
+}
+
+// GIF's LZW, read the way a decoder does, a code behind the encoder.
+fn unlzw(data: &[u8]) -> Vec<u8> {
+    let (clear, end) = (256usize, 257usize);
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let mut size = 9;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut out: Vec<u8> = Vec::new();
+
+    let mut at = 0;
+    loop {
+        let code = (0 .. size).fold(0, |code, bit| {
+            let i = at + bit;
+            code | (((data[i / 8] >> (i % 8)) & 1) as usize) << bit
+        });
+        at += size;
+
+        if code == clear {
+            table = (0 .. 256).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+            size = 9;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            return out;
+        }
+
+        let entry = match (&previous, table.get(code)) {
+            (_, Some(entry)) => entry.clone(),
+            (Some(previous), None) => {
+                assert_eq!(code, table.len());
+                let mut entry = previous.clone();
+                entry.push(previous[0]);
+                entry
+            },
+            (None, None) => panic!("Code {} before any other", code),
+        };
+        if let Some(previous) = &previous {
+            let mut added = previous.clone();
+            added.push(entry[0]);
+            table.push(added);
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        out.extend_from_slice(&entry);
+        previous = Some(entry);
+    }
+}
+
+// The palette and each frame's palette indexes.
+fn gif_frames(gif: &[u8]) -> ((usize, usize), Vec<Color>, Vec<Vec<u8>>) {
+    assert_eq!(&gif[.. 6], b"GIF89a");
+    let width = u16::from_le_bytes([gif[6], gif[7]]) as usize;
+    let height = u16::from_le_bytes([gif[8], gif[9]]) as usize;
+    assert_eq!(gif[10], 0xf7);
+    let palette: Vec<Color> = gif[13 .. 13 + 768].chunks(3).map(|c| Color::rgb(c[0], c[1], c[2])).collect();
+
+    let mut frames: Vec<Vec<u8>> = Vec::new();
+    let mut at = 13 + 768;
+    loop {
+        match gif[at] {
+            0x3b => break,
+            0x21 => {
+                at += 2;
+                while gif[at] != 0 {
+                    at += gif[at] as usize + 1;
+                }
+                at += 1;
+            },
+            0x2c => {
+                assert_eq!(gif[at + 10], 8);
+                at += 11;
+                let mut data: Vec<u8> = Vec::new();
+                while gif[at] != 0 {
+                    data.extend_from_slice(&gif[at + 1 .. at + 1 + gif[at] as usize]);
+                    at += gif[at] as usize + 1;
+                }
+                at += 1;
+                frames.push(unlzw(&data));
+            },
+            other => panic!("Unexpected block {:x}", other),
+        }
+    }
+    assert_eq!(at + 1, gif.len());
+
+    ((width, height), palette, frames)
+}
+
+#[test]
+fn lzw_round_trips_past_a_full_table() {
+    let mut rng = StdRng::seed_from_u64(22);
+    for length in [0, 1, 2, 255, 256, 5_000, 200_000] {
+        let indexes: Vec<u8> = (0 .. length).map(|_| if rng.gen_bool(0.5) { 0 } else { rng.gen() }).collect();
+        assert_eq!(unlzw(&visualize::lzw(&indexes)), indexes, "length {}", length);
+    }
+
+    // Runs of one index fill the table with ever longer runs.
+    let same = vec![7u8; 3_000_000];
+    assert_eq!(unlzw(&visualize::lzw(&same)), same);
+}
+
+#[test]
+fn gif_frames_hold_the_pictures() {
+    let path = std::env::temp_dir().join(format!("rook_aoc_2023_visualize_{}.gif", std::process::id()));
+
+    let mut pictures: Vec<Picture> = Vec::new();
+    for step in 0 .. 3 {
+        let mut picture = Picture::new(5, 4);
+        picture.set_cell(step, step, visualize::WARM);
+        picture.set_cell(3, 4, visualize::STONE);
+        pictures.push(picture);
+    }
+
+    let mut animation = Animation::with_sink(Box::new(GifSink::new(&path)), None);
+    for picture in &pictures[.. 2] {
+        animation.frame(|| Ok(picture.clone())).unwrap();
+    }
+    animation.finish(|| Ok(pictures[2].clone())).unwrap();
+
+    let gif = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let ((width, height), palette, frames) = gif_frames(&gif);
+    assert_eq!(frames.len(), 3);
+
+    for (picture, frame) in pictures.iter().zip(&frames) {
+        let scale = width / picture.width();
+        let (pixel_width, pixel_height, pixels) = picture.pixels_at(scale);
+        assert_eq!((pixel_width, pixel_height), (width, height));
+
+        let colors: Vec<Color> = frame.iter().map(|index| palette[*index as usize]).collect();
+        let expected: Vec<Color> = pixels.chunks(3).map(|c| Color::rgb(c[0], c[1], c[2])).collect();
+        assert!(colors == expected);
+    }
+}
+
+#[test]
+fn frames_of_different_sizes_are_an_error() {
+    let mut sink = GifSink::new(std::env::temp_dir().join("rook_aoc_2023_never_written.gif"));
+    sink.add(&Picture::new(3, 3), false).unwrap();
+    assert!(sink.add(&Picture::new(4, 3), false).is_err());
+}
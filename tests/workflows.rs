@@ -0,0 +1,79 @@
+// problem19's workflows built in code, against the same ones parsed from the
+// example worksheet.
+
+use rook_aoc_2023::problems::problem19::{self, accept, goto, reject, CompiledWorkflows, Part, PartAttributeCombination, Workflow, WorkflowResult, Workflows};
+use rook_aoc_2023::problems::problem19::PartAttribute::*;
+
+fn example_workflows() -> Workflows {
+    Workflows::new()
+        .with(Workflow::builder("px").lt(Aerodynamic, 2006, goto("qkq")).gt(Musical, 2090, accept()).otherwise(goto("rfg")))
+        .with(Workflow::builder("pv").gt(Aerodynamic, 1716, reject()).otherwise(accept()))
+        .with(Workflow::builder("lnx").gt(Musical, 1548, accept()).otherwise(accept()))
+        .with(Workflow::builder("rfg").lt(Shiny, 537, goto("gd")).gt(Cool, 2440, reject()).otherwise(accept()))
+        .with(Workflow::builder("qs").gt(Shiny, 3448, accept()).otherwise(goto("lnx")))
+        .with(Workflow::builder("qkq").lt(Cool, 1416, accept()).otherwise(goto("crn")))
+        .with(Workflow::builder("crn").gt(Cool, 2662, accept()).otherwise(reject()))
+        .with(Workflow::builder("in").lt(Shiny, 1351, goto("px")).otherwise(goto("qqz")))
+        .with(Workflow::builder("qqz").gt(Shiny, 2770, goto("qs")).lt(Musical, 1801, goto("hdj")).otherwise(reject()))
+        .with(Workflow::builder("gd").gt(Aerodynamic, 3333, reject()).otherwise(reject()))
+        .with(Workflow::builder("hdj").gt(Musical, 838, accept()).otherwise(goto("pv")))
+}
+
+fn accepted(workflows: &Workflows) -> i64 {
+    workflows
+        .get_accepted_combinations(&PartAttributeCombination::new(1, 4000))
+        .unwrap()
+        .iter()
+        .map(|combination| combination.get_combination_size())
+        .sum()
+}
+
+#[test]
+fn built_workflows_match_the_parsed_ones() {
+    let (parsed, _parts) = problem19::parse_worksheet("input/input_19_test.txt").unwrap();
+    let built = example_workflows();
+
+    assert_eq!(accepted(&built), 167409079868000);
+    assert_eq!(accepted(&built), accepted(&parsed));
+
+    let combinations = PartAttributeCombination::new(1, 4000);
+    assert_eq!(built.get_accept_step_counts(&combinations).unwrap(), parsed.get_accept_step_counts(&combinations).unwrap());
+}
+
+fn is_accepted(workflows: &Workflows, part: &Part) -> bool {
+    matches!(workflows.process(part).unwrap(), WorkflowResult::Accept)
+}
+
+#[test]
+fn compiled_workflows_accept_what_the_workflows_do() {
+    let (workflows, parts) = problem19::parse_worksheet("input/input_19_test.txt").unwrap();
+    let compiled = CompiledWorkflows::compile(&workflows).unwrap();
+
+    let accepted: Vec<bool> = parts.iter().map(|part| compiled.is_accepted(part)).collect();
+    assert_eq!(accepted, [true, false, true, false, true]);
+    for part in &parts {
+        assert_eq!(compiled.is_accepted(part), is_accepted(&workflows, part), "{:?}", part);
+    }
+
+    // Either side of every threshold in the example, and outside 1 to 4000.
+    let values = [-5, 0, 1, 537, 538, 838, 839, 1350, 1351, 1415, 1416, 1548, 1549, 1716, 1717, 1800, 1801,
+        2005, 2006, 2090, 2091, 2440, 2441, 2662, 2663, 2770, 2771, 3333, 3334, 3448, 3449, 4000, 4001];
+    for (i, cool) in values.iter().enumerate() {
+        for (j, musical) in values.iter().enumerate() {
+            for shiny in values.iter().skip((i + j) % 3).step_by(3) {
+                for aerodynamic in values.iter().skip((i * j) % 4).step_by(4) {
+                    let part = Part { cool: *cool, musical: *musical, aerodynamic: *aerodynamic, shiny: *shiny };
+                    assert_eq!(compiled.is_accepted(&part), is_accepted(&workflows, &part), "{:?}", part);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn looping_workflows_are_an_error() {
+    let looping = Workflows::new()
+        .with(Workflow::builder("in").lt(Shiny, 10, goto("a")).otherwise(reject()))
+        .with(Workflow::builder("a").otherwise(goto("in")));
+    assert!(CompiledWorkflows::compile(&looping).is_err());
+}